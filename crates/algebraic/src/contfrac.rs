@@ -0,0 +1,183 @@
+//! Continued fractions of quadratic elements `a + b√d`.
+//!
+//! A real quadratic irrational has an eventually periodic continued fraction
+//! (Lagrange); `Quad::continued_fraction` finds the pre-period and period by
+//! iterating the complete quotients `(P + √D) / Q` exactly until a state
+//! repeats. Rational elements give a finite expansion with an empty period.
+
+use crate::Quad;
+use arith::{best_rational_by, cf_expand_q, BigInt, BigRat, Q};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// `[head; period, period, ...]`: `head` holds the terms before the repeating
+/// block (including a0). An empty `period` means the expansion is finite.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeriodicCf {
+    pub head: Vec<i64>,
+    pub period: Vec<i64>,
+}
+
+impl PeriodicCf {
+    /// All terms in order (infinite unless the period is empty).
+    pub fn terms(&self) -> impl Iterator<Item = i64> + '_ {
+        let cycle = std::iter::repeat(&self.period).take_while(|p| !p.is_empty()).flatten();
+        self.head.iter().chain(cycle).copied()
+    }
+}
+
+/// Largest r with r^2 <= n (n >= 0).
+fn isqrt(n: i128) -> i128 {
+    (n as u128).isqrt() as i128
+}
+
+impl Quad {
+    /// `√d` when `d` is a perfect square.
+    fn sqrt_d(&self) -> Option<i64> {
+        if self.d < 0 {
+            return None;
+        }
+        let r = isqrt(self.d as i128) as i64;
+        (r * r == self.d).then_some(r)
+    }
+
+    /// The value as a rational when `b = 0` or `d` is a perfect square.
+    fn rational_value(&self) -> Option<Q> {
+        if self.is_rational() {
+            return Some(self.a);
+        }
+        let r = &BigRat::from(self.b) * &BigRat::from(self.sqrt_d()?);
+        (&BigRat::from(self.a) + &r).to_q()
+    }
+
+    /// Sign of `self - m` for a real element (`d >= 0`), computed exactly.
+    pub fn cmp_q(&self, m: Q) -> Option<Ordering> {
+        if self.d < 0 && !self.is_rational() {
+            return None;
+        }
+        // self - m = u + b√d
+        let u = &BigRat::from(self.a) - &BigRat::from(m);
+        let b = BigRat::from(self.b);
+        let (su, sb) = (sign(&u), sign(&b));
+        if sb == Ordering::Equal || self.d == 0 {
+            return Some(su);
+        }
+        if su == Ordering::Equal || su == sb {
+            return Some(sb);
+        }
+        // Opposite signs: the larger of u^2 and b^2 d wins
+        let u2 = &u * &u;
+        let bd = &(&b * &b) * &BigRat::from(self.d);
+        Some(match u2.cmp(&bd) {
+            Ordering::Greater => su,
+            Ordering::Less => sb,
+            Ordering::Equal => Ordering::Equal,
+        })
+    }
+
+    /// Continued fraction of a real element; None if `d < 0` (with `b != 0`) or
+    /// an intermediate value overflows i128.
+    pub fn continued_fraction(&self) -> Option<PeriodicCf> {
+        if let Some(q) = self.rational_value() {
+            return Some(PeriodicCf { head: cf_expand_q(q), period: Vec::new() });
+        }
+        if self.d < 0 {
+            return None;
+        }
+        // self = (A + B√d) / L with integers, then = (P + √D) / Qn with Qn | D - P^2
+        let (a, b) = (self.a, self.b);
+        let l = lcm(a.1 as i128, b.1 as i128)?;
+        let big_a = (a.0 as i128).checked_mul(l / a.1 as i128)?;
+        let big_b = (b.0 as i128).checked_mul(l / b.1 as i128)?;
+        let s = big_b.signum();
+        let (mut p, mut qn) = (s * big_a, s * l);
+        let mut dd = big_b.checked_mul(big_b)?.checked_mul(self.d as i128)?;
+        if (dd - p.checked_mul(p)?) % qn != 0 {
+            let m = qn.abs();
+            p = p.checked_mul(m)?;
+            dd = dd.checked_mul(m)?.checked_mul(m)?;
+            qn = qn.checked_mul(m)?;
+        }
+        let r = isqrt(dd);
+        let mut seen: HashMap<(i128, i128), usize> = HashMap::new();
+        let mut terms = Vec::new();
+        loop {
+            if let Some(&start) = seen.get(&(p, qn)) {
+                let period = terms.split_off(start);
+                return Some(PeriodicCf { head: terms, period });
+            }
+            seen.insert((p, qn), terms.len());
+            // floor((P + √D) / Qn); √D is irrational, so for Qn < 0 round P + r up
+            let num = p.checked_add(r)?.checked_add(i128::from(qn < 0))?;
+            let t = num.div_euclid(qn) - i128::from(qn < 0 && num.rem_euclid(qn) != 0);
+            terms.push(i64::try_from(t).ok()?);
+            p = t.checked_mul(qn)?.checked_sub(p)?;
+            qn = (dd.checked_sub(p.checked_mul(p)?)?) / qn;
+        }
+    }
+
+    /// Closest rational with denominator at most `max_den` to a real element.
+    pub fn best_rational(&self, max_den: i64) -> Option<Q> {
+        let cf = self.continued_fraction()?;
+        best_rational_by(cf.terms(), max_den, |m| self.cmp_q(m).expect("real element"))
+    }
+}
+
+fn sign(r: &BigRat) -> Ordering {
+    if r.is_zero() {
+        Ordering::Equal
+    } else if r.is_negative() {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+fn lcm(a: i128, b: i128) -> Option<i128> {
+    let g = BigInt::from(a).gcd(&BigInt::from(b)).to_i128()?;
+    (a / g).checked_mul(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad(a: (i64, i64), b: (i64, i64), d: i64) -> Quad {
+        Quad::new(Q::new(a.0, a.1), Q::new(b.0, b.1), d)
+    }
+
+    #[test]
+    fn periodic_expansions() {
+        // √2 = [1; 2, 2, ...]
+        let cf = quad((0, 1), (1, 1), 2).continued_fraction().unwrap();
+        assert_eq!(cf, PeriodicCf { head: vec![1], period: vec![2] });
+        // Golden ratio (1 + √5)/2 = [1; 1, 1, ...]
+        let cf = quad((1, 2), (1, 2), 5).continued_fraction().unwrap();
+        assert_eq!(cf, PeriodicCf { head: vec![], period: vec![1] });
+        // √7 = [2; 1, 1, 1, 4, ...]
+        let cf = quad((0, 1), (1, 1), 7).continued_fraction().unwrap();
+        assert_eq!(cf, PeriodicCf { head: vec![2], period: vec![1, 1, 1, 4] });
+        // -√2 = [-2; 1, 1, 2, 2, ...]
+        let cf = quad((0, 1), (-1, 1), 2).continued_fraction().unwrap();
+        assert_eq!(cf.terms().take(6).collect::<Vec<_>>(), vec![-2, 1, 1, 2, 2, 2]);
+        // 1/3 + √2/5 ≈ 0.61618...
+        let cf = quad((1, 3), (1, 5), 2).continued_fraction().unwrap();
+        let x = 1.0 / 3.0 + 2f64.sqrt() / 5.0;
+        assert_eq!(cf.terms().take(6).collect::<Vec<_>>(), arith::cf_expand_f64(x, 6));
+        // Rational elements: finite expansion
+        let cf = quad((3, 2), (1, 1), 4).continued_fraction().unwrap();
+        assert_eq!(cf, PeriodicCf { head: vec![3, 2], period: vec![] });
+        assert_eq!(quad((0, 1), (1, 1), -1).continued_fraction(), None);
+    }
+
+    #[test]
+    fn surd_approximations() {
+        let sqrt2 = quad((0, 1), (1, 1), 2);
+        assert_eq!(sqrt2.best_rational(10), Some(Q(7, 5)));
+        assert_eq!(sqrt2.best_rational(100), Some(Q(140, 99)));
+        assert_eq!(sqrt2.cmp_q(Q(7, 5)), Some(Ordering::Greater));
+        assert_eq!(sqrt2.cmp_q(Q(3, 2)), Some(Ordering::Less));
+        let phi = quad((1, 2), (1, 2), 5);
+        assert_eq!(phi.best_rational(100), Some(Q(144, 89)));
+    }
+}
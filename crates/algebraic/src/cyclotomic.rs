@@ -3,7 +3,7 @@
 //! This module provides support for cyclotomic extensions Q(ζ_n) where ζ_n is a primitive nth root of unity.
 //! ζ_n = e^(2πi/n)
 
-use arith::Q;
+use arith::{add_q, checked_add_q, checked_mul_q, mul_q, ArithError, Q};
 use std::f64::consts::PI;
 
 /// Represents an element in Q(ζ_n) as a polynomial in ζ_n
@@ -102,7 +102,34 @@ impl Cyclotomic {
     pub fn trace(&self) -> Q {
         // Simplified: for ζ_n, trace is φ(n) times the constant term
         let phi = euler_phi(self.n);
-        Q(self.coeffs[0].0 * phi as i64, self.coeffs[0].1)
+        mul_q(self.coeffs[0], Q(phi as i64, 1))
+    }
+
+    /// Like `+`, but reports coefficient overflow instead of saturating.
+    pub fn checked_add(&self, rhs: &Self) -> Result<Self, ArithError> {
+        assert_eq!(self.n, rhs.n, "Cannot add elements from different cyclotomic fields");
+        let coeffs = self
+            .coeffs
+            .iter()
+            .zip(rhs.coeffs.iter())
+            .map(|(a, b)| checked_add_q(*a, *b))
+            .collect::<Result<_, _>>()?;
+        Ok(Cyclotomic { coeffs, n: self.n })
+    }
+
+    /// Like `*`, but reports coefficient overflow instead of saturating.
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self, ArithError> {
+        assert_eq!(self.n, rhs.n, "Cannot multiply elements from different cyclotomic fields");
+        let mut result = vec![Q::zero(); euler_phi(self.n)];
+        for (i, a) in self.coeffs.iter().enumerate() {
+            for (j, b) in rhs.coeffs.iter().enumerate() {
+                // Same reduction as `Mul`: ζ^n = 1, higher powers dropped
+                if let Some(slot) = result.get_mut((i + j) % self.n) {
+                    *slot = checked_add_q(*slot, checked_mul_q(*a, *b)?)?;
+                }
+            }
+        }
+        Ok(Cyclotomic { coeffs: result, n: self.n })
     }
 }
 
@@ -113,7 +140,7 @@ impl std::ops::Add for Cyclotomic {
         assert_eq!(self.n, rhs.n, "Cannot add elements from different cyclotomic fields");
 
         let coeffs: Vec<Q> =
-            self.coeffs.iter().zip(rhs.coeffs.iter()).map(|(a, b)| arith::add_q(*a, *b)).collect();
+            self.coeffs.iter().zip(rhs.coeffs.iter()).map(|(a, b)| add_q(*a, *b)).collect();
 
         Cyclotomic { coeffs, n: self.n }
     }
@@ -131,17 +158,17 @@ impl std::ops::Mul for Cyclotomic {
         // Multiply polynomials and reduce modulo Φ_n(x)
         for (i, a) in self.coeffs.iter().enumerate() {
             for (j, b) in rhs.coeffs.iter().enumerate() {
-                let coeff = arith::mul_q(*a, *b);
+                let coeff = mul_q(*a, *b);
                 let power = (i + j) % self.n;
 
                 // Reduce using ζ^n = 1
                 if power < phi_n {
-                    result[power] = arith::add_q(result[power], coeff);
+                    result[power] = add_q(result[power], coeff);
                 } else {
                     // ζ^n = 1, so ζ^{n+k} = ζ^k
                     let reduced_power = power % self.n;
                     if reduced_power < phi_n {
-                        result[reduced_power] = arith::add_q(result[reduced_power], coeff);
+                        result[reduced_power] = add_q(result[reduced_power], coeff);
                     }
                 }
             }
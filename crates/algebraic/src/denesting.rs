@@ -22,33 +22,30 @@ use arith::Q;
 /// For denesting to work, a² - b²c must be a perfect square
 pub fn denest_sqrt(a: i64, b: i64, c: i64) -> Option<(i64, i64)> {
     // Check if b²c is divisible by 4
-    let b2c = b * b * c;
+    let b2c = b.checked_mul(b)?.checked_mul(c)?;
     if b2c % 4 != 0 {
         return None;
     }
 
     // Compute discriminant: a² - b²c
-    let discriminant = a * a - b2c;
+    let discriminant = a.checked_mul(a)?.checked_sub(b2c)?;
 
     if discriminant < 0 {
         return None;
     }
 
     // Check if discriminant is a perfect square
-    let sqrt_disc = (discriminant as f64).sqrt() as i64;
-    if sqrt_disc * sqrt_disc != discriminant {
-        return None;
-    }
+    let sqrt_disc = isqrt_exact(discriminant)?;
 
     // Compute x and y
-    let x = (a + sqrt_disc) / 2;
-    let y = (a - sqrt_disc) / 2;
+    let x = a.checked_add(sqrt_disc)? / 2;
+    let y = a.checked_sub(sqrt_disc)? / 2;
 
     // Verify: x + y = a and 4xy = b²c
-    if x + y != a {
+    if x.checked_add(y)? != a {
         return None;
     }
-    if 4 * x * y != b2c {
+    if x.checked_mul(y)?.checked_mul(4)? != b2c {
         return None;
     }
 
@@ -73,9 +70,9 @@ pub fn denest_sqrt_rational(a: Q, b: Q, c: i64) -> Option<(i64, i64, i64)> {
     let b_den = b.1;
 
     // Common denominator
-    let common_den = a_den * b_den;
-    let new_a = a_num * b_den;
-    let new_b = b_num * a_den;
+    let common_den = a_den.checked_mul(b_den)?;
+    let new_a = a_num.checked_mul(b_den)?;
+    let new_b = b_num.checked_mul(a_den)?;
 
     // Try to denest √(new_a + new_b√c)
     if let Some((x, y)) = denest_sqrt(new_a, new_b, c) {
@@ -88,11 +85,14 @@ pub fn denest_sqrt_rational(a: Q, b: Q, c: i64) -> Option<(i64, i64, i64)> {
 
 /// Check if a number is a perfect square
 pub fn is_perfect_square(n: i64) -> bool {
-    if n < 0 {
-        return false;
-    }
-    let sqrt_n = (n as f64).sqrt() as i64;
-    sqrt_n * sqrt_n == n
+    n >= 0 && isqrt_exact(n).is_some()
+}
+
+/// `√n` when `n` is a perfect square. The f64 estimate can be off by one for
+/// large `n`, so its neighbours are checked exactly.
+fn isqrt_exact(n: i64) -> Option<i64> {
+    let r = (n as f64).sqrt() as i64;
+    (r.saturating_sub(1)..=r.saturating_add(1)).find(|&i| i >= 0 && i.checked_mul(i) == Some(n))
 }
 
 /// Simplify √n by extracting perfect square factors
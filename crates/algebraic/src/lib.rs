@@ -8,13 +8,17 @@
 //! - Minimal polynomial computation
 //! - Galois group computation
 //! - Field extension towers
+//! - Periodic continued fractions of quadratic irrationals
 
+pub mod contfrac;
 pub mod cyclotomic;
 pub mod denesting;
 pub mod galois;
 pub mod minimal_poly;
 
-use arith::{add_q, mul_q, sub_q, Q};
+use arith::{
+    add_q, checked_add_q, checked_div_q, checked_mul_q, checked_sub_q, mul_q, sub_q, ArithError, Q,
+};
 use std::ops::{Add, Mul, Neg, Sub};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -39,33 +43,66 @@ impl Quad {
         let a2 = mul_q(self.a, self.a);
         let b2 = mul_q(self.b, self.b);
         // b2 * d
-        let bd = Q(b2.0 * self.d, b2.1);
+        let bd = mul_q(b2, Q(self.d, 1));
         sub_q(a2, bd)
     }
 
-    /// Multiplicative inverse: 1/(a + b√d) = (a - b√d)/(a² - b²d)
+    /// Like [`Quad::norm`], but reports overflow instead of saturating.
+    pub fn checked_norm(&self) -> Result<Q, ArithError> {
+        let a2 = checked_mul_q(self.a, self.a)?;
+        let bd = checked_mul_q(checked_mul_q(self.b, self.b)?, Q(self.d, 1))?;
+        checked_sub_q(a2, bd)
+    }
+
+    /// Multiplicative inverse: 1/(a + b√d) = (a - b√d)/(a² - b²d).
+    /// `None` for zero and when the result does not fit in `Q`.
     pub fn inv(&self) -> Option<Self> {
-        let n = self.norm();
+        let n = self.checked_norm().ok()?;
         if n.0 == 0 {
             return None; // Division by zero
         }
         let conj = self.conj();
         // Divide conjugate by norm
-        let a_inv = Q(conj.a.0 * n.1, conj.a.1 * n.0);
-        let b_inv = Q(conj.b.0 * n.1, conj.b.1 * n.0);
+        let a_inv = checked_div_q(conj.a, n).ok()?;
+        let b_inv = checked_div_q(conj.b, n).ok()?;
         Some(Quad::new(a_inv, b_inv, self.d))
     }
 
     /// Division: self / rhs
     pub fn div(&self, rhs: &Quad) -> Option<Self> {
         let rhs_inv = rhs.inv()?;
-        Some(*self * rhs_inv)
+        self.checked_mul(&rhs_inv).ok()
     }
 
     /// Trace: Tr(a + b√d) = 2a
     pub fn trace(&self) -> Q {
-        let (num, den) = arith::normalize_rat(self.a.0 * 2, self.a.1);
-        Q(num, den)
+        mul_q(self.a, Q(2, 1))
+    }
+
+    /// Like [`Quad::trace`], but reports overflow instead of saturating.
+    pub fn checked_trace(&self) -> Result<Q, ArithError> {
+        checked_mul_q(self.a, Q(2, 1))
+    }
+
+    /// Like `+`, but reports overflow instead of saturating.
+    pub fn checked_add(&self, rhs: &Quad) -> Result<Self, ArithError> {
+        assert_eq!(self.d, rhs.d, "incompatible extensions: different d");
+        Ok(Quad { a: checked_add_q(self.a, rhs.a)?, b: checked_add_q(self.b, rhs.b)?, d: self.d })
+    }
+
+    /// Like `-`, but reports overflow instead of saturating.
+    pub fn checked_sub(&self, rhs: &Quad) -> Result<Self, ArithError> {
+        assert_eq!(self.d, rhs.d, "incompatible extensions: different d");
+        Ok(Quad { a: checked_sub_q(self.a, rhs.a)?, b: checked_sub_q(self.b, rhs.b)?, d: self.d })
+    }
+
+    /// Like `*`, but reports overflow instead of saturating.
+    pub fn checked_mul(&self, rhs: &Quad) -> Result<Self, ArithError> {
+        assert_eq!(self.d, rhs.d, "incompatible extensions: different d");
+        let be_d = checked_mul_q(checked_mul_q(self.b, rhs.b)?, Q(self.d, 1))?;
+        let real = checked_add_q(checked_mul_q(self.a, rhs.a)?, be_d)?;
+        let imag = checked_add_q(checked_mul_q(self.a, rhs.b)?, checked_mul_q(self.b, rhs.a)?)?;
+        Ok(Quad { a: real, b: imag, d: self.d })
     }
 
     /// Check if element is in base field Q (i.e., b = 0)
@@ -113,7 +150,7 @@ impl Mul for Quad {
         // (a + b√d)(c + e√d) = (ac + be d) + (ae + bc)√d
         let ac = mul_q(self.a, rhs.a);
         let be = mul_q(self.b, rhs.b);
-        let be_d = Q(be.0 * self.d, be.1);
+        let be_d = mul_q(be, Q(self.d, 1));
         let ae = mul_q(self.a, rhs.b);
         let bc = mul_q(self.b, rhs.a);
         let real = add_q(ac, be_d);
@@ -273,4 +310,18 @@ mod tests {
         assert_eq!(lhs.a, rhs.a);
         assert_eq!(lhs.b, rhs.b);
     }
+
+    #[test]
+    fn test_overflow_is_reported() {
+        let x = Quad::new(q(i64::MAX, 1), q(1, 1), 2);
+        assert!(x.checked_norm().is_err());
+        assert!(x.checked_mul(&x).is_err());
+        assert!(x.checked_add(&x).is_err());
+        assert!(x.checked_trace().is_err());
+        assert_eq!(x.inv(), None);
+
+        let y = Quad::new(q(1, 1), q(1, 1), 2);
+        assert_eq!(y.checked_mul(&y), Ok(y * y));
+        assert_eq!(y.inv(), Some(Quad::new(q(-1, 1), q(1, 1), 2)));
+    }
 }
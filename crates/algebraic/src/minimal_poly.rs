@@ -2,7 +2,7 @@
 //!
 //! This module provides functions to compute minimal polynomials of algebraic numbers
 
-use arith::Q;
+use arith::{add_q, checked_add_q, checked_div_q, checked_mul_q, div_q, mul_q, ArithError, Q};
 
 /// Represents a polynomial with rational coefficients
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -48,11 +48,19 @@ impl RationalPoly {
         // Horner's method
         let mut result = self.coeffs[self.coeffs.len() - 1];
         for i in (0..self.coeffs.len() - 1).rev() {
-            result = arith::add_q(arith::mul_q(result, x), self.coeffs[i]);
+            result = add_q(mul_q(result, x), self.coeffs[i]);
         }
         result
     }
 
+    /// Like [`RationalPoly::eval`], but reports overflow instead of saturating.
+    pub fn checked_eval(&self, x: Q) -> Result<Q, ArithError> {
+        self.coeffs
+            .iter()
+            .rev()
+            .try_fold(Q::zero(), |acc, &c| checked_add_q(checked_mul_q(acc, x)?, c))
+    }
+
     /// Check if polynomial is monic (leading coefficient is 1)
     pub fn is_monic(&self) -> bool {
         if self.coeffs.is_empty() {
@@ -73,20 +81,21 @@ impl RationalPoly {
         }
 
         // Divide each coefficient by leading coefficient
-        let coeffs: Vec<Q> = self
-            .coeffs
-            .iter()
-            .map(|c| {
-                // c / leading = c * (1/leading) = (c.0 * leading.1) / (c.1 * leading.0)
-                let num = c.0 * leading.1;
-                let den = c.1 * leading.0;
-                let (n, d) = arith::normalize_rat(num, den);
-                Q(n, d)
-            })
-            .collect();
+        let coeffs: Vec<Q> = self.coeffs.iter().map(|&c| div_q(c, leading)).collect();
 
         RationalPoly::new(coeffs)
     }
+
+    /// Like [`RationalPoly::make_monic`], but reports overflow instead of saturating.
+    pub fn checked_make_monic(&self) -> Result<Self, ArithError> {
+        let leading = match self.coeffs.last() {
+            Some(&l) if l != Q::zero() && l != Q::new(1, 1) => l,
+            _ => return Ok(self.clone()),
+        };
+        let coeffs =
+            self.coeffs.iter().map(|&c| checked_div_q(c, leading)).collect::<Result<_, _>>()?;
+        Ok(RationalPoly::new(coeffs))
+    }
 }
 
 /// Compute minimal polynomial of √d over Q
@@ -163,7 +172,7 @@ fn has_rational_root(poly: &RationalPoly) -> bool {
     for num in -10..=10 {
         for den in 1..=5 {
             let x = Q::new(num, den);
-            if poly.eval(x) == Q::zero() {
+            if poly.checked_eval(x) == Ok(Q::zero()) {
                 return true;
             }
         }
@@ -10,7 +10,7 @@ mod python_bindings {
 
     use calculus::{diff, integrate};
     use evalf::{eval, EvalContext};
-    use expr_core::{ExprId, Op, Payload, Store};
+    use expr_core::{ExprId, Store};
     use io::{to_latex, to_sexpr};
     use pattern::subst_symbol;
     use plot::{plot_svg, PlotConfig};
@@ -187,7 +187,7 @@ mod python_bindings {
                     .into_iter()
                     .map(|root_id| {
                         let mut new_store = Store::new();
-                        let new_id = Self::rebuild_expr(&store, root_id, &mut new_store);
+                        let new_id = new_store.import(&store, root_id);
                         Expr { store: new_store, id: new_id }
                     })
                     .collect()),
@@ -206,61 +206,7 @@ mod python_bindings {
     impl Expr {
         /// Helper to rebuild this expression in a new store
         fn rebuild_in(&self, target: &mut Store) -> ExprId {
-            Self::rebuild_expr(&self.store, self.id, target)
-        }
-
-        /// Recursively rebuild an expression from one store into another
-        fn rebuild_expr(src: &Store, id: ExprId, target: &mut Store) -> ExprId {
-            let node = src.get(id);
-            match &node.op {
-                Op::Integer => match &node.payload {
-                    Payload::Int(i) => target.int(*i),
-                    _ => target.int(0),
-                },
-                Op::Rational => match &node.payload {
-                    Payload::Rat(n, d) => target.rat(*n, *d),
-                    _ => target.int(0),
-                },
-                Op::Symbol => match &node.payload {
-                    Payload::Sym(s) => target.sym(s),
-                    _ => target.sym("x"),
-                },
-                Op::Add => {
-                    let children: Vec<ExprId> =
-                        node.children.iter().map(|&c| Self::rebuild_expr(src, c, target)).collect();
-                    target.add(children)
-                }
-                Op::Mul => {
-                    let children: Vec<ExprId> =
-                        node.children.iter().map(|&c| Self::rebuild_expr(src, c, target)).collect();
-                    target.mul(children)
-                }
-                Op::Pow => {
-                    let base = Self::rebuild_expr(src, node.children[0], target);
-                    let exp = Self::rebuild_expr(src, node.children[1], target);
-                    target.pow(base, exp)
-                }
-                Op::Function => {
-                    let fname = match &node.payload {
-                        Payload::Func(s) => s.clone(),
-                        _ => "f".to_string(),
-                    };
-                    let children: Vec<ExprId> =
-                        node.children.iter().map(|&c| Self::rebuild_expr(src, c, target)).collect();
-                    target.func(fname, children)
-                }
-                Op::Piecewise => {
-                    let children: Vec<ExprId> =
-                        node.children.iter().map(|&c| Self::rebuild_expr(src, c, target)).collect();
-                    let mut pairs = Vec::new();
-                    for chunk in children.chunks(2) {
-                        if chunk.len() == 2 {
-                            pairs.push((chunk[0], chunk[1]));
-                        }
-                    }
-                    target.piecewise(pairs)
-                }
-            }
+            target.import(&self.store, self.id)
         }
     }
 
@@ -0,0 +1,740 @@
+//! Arbitrary-precision integers and rationals.
+//! - `BigInt`: sign + magnitude, little-endian base 2^32 limbs
+//! - `BigRat`: normalized fraction of two `BigInt`s (den>0, gcd=1)
+//!
+//! Kept dependency-free like the rest of `arith`; algorithms are schoolbook
+//! (O(n^2) multiplication, shift-subtract division) which is adequate for the
+//! coefficient sizes met in symbolic work.
+
+use crate::Q;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+// ---------- BigInt ----------
+
+/// Arbitrary-precision signed integer.
+///
+/// Invariants: `mag` has no trailing zero limbs; zero is `neg == false, mag == []`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    neg: bool,
+    mag: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+    pub fn one() -> Self {
+        Self::from(1i64)
+    }
+    pub fn is_zero(&self) -> bool {
+        self.mag.is_empty()
+    }
+    pub fn is_one(&self) -> bool {
+        !self.neg && self.mag == [1]
+    }
+    pub fn is_negative(&self) -> bool {
+        self.neg
+    }
+    /// -1, 0 or 1
+    pub fn signum(&self) -> i32 {
+        if self.is_zero() {
+            0
+        } else if self.neg {
+            -1
+        } else {
+            1
+        }
+    }
+    pub fn abs(&self) -> Self {
+        Self { neg: false, mag: self.mag.clone() }
+    }
+    pub fn is_even(&self) -> bool {
+        self.mag.first().is_none_or(|l| l & 1 == 0)
+    }
+    /// Little-endian base 2^32 limbs of |self|.
+    pub fn magnitude(&self) -> &[u32] {
+        &self.mag
+    }
+    /// Number of significant bits in |self| (0 for zero).
+    pub fn bits(&self) -> u64 {
+        mag_bits(&self.mag)
+    }
+
+    fn from_parts(neg: bool, mut mag: Vec<u32>) -> Self {
+        while mag.last() == Some(&0) {
+            mag.pop();
+        }
+        let neg = neg && !mag.is_empty();
+        Self { neg, mag }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        let v = self.to_i128()?;
+        i64::try_from(v).ok()
+    }
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.mag.len() > 4 {
+            return None;
+        }
+        let mut m: u128 = 0;
+        for (i, &l) in self.mag.iter().enumerate() {
+            m |= (l as u128) << (32 * i);
+        }
+        if self.neg {
+            if m <= i128::MAX as u128 + 1 {
+                Some((m as i128).wrapping_neg())
+            } else {
+                None
+            }
+        } else {
+            i128::try_from(m).ok()
+        }
+    }
+    /// Nearest f64 (may be infinite for huge values).
+    pub fn to_f64(&self) -> f64 {
+        let mut acc = 0.0f64;
+        for &l in self.mag.iter().rev() {
+            acc = acc * 4294967296.0 + l as f64;
+        }
+        if self.neg {
+            -acc
+        } else {
+            acc
+        }
+    }
+
+    pub fn pow(&self, mut e: u32) -> Self {
+        let mut base = self.clone();
+        let mut acc = Self::one();
+        while e > 0 {
+            if e & 1 == 1 {
+                acc = &acc * &base;
+            }
+            e >>= 1;
+            if e > 0 {
+                base = &base * &base;
+            }
+        }
+        acc
+    }
+
+    /// self * 2^k
+    pub fn shl(&self, k: u64) -> Self {
+        Self::from_parts(self.neg, mag_shl(&self.mag, k))
+    }
+    /// |self| / 2^k truncated, keeping the sign of self.
+    pub fn shr(&self, k: u64) -> Self {
+        let limbs = (k / 32) as usize;
+        if limbs >= self.mag.len() {
+            return Self::zero();
+        }
+        let bits = (k % 32) as u32;
+        let mut mag: Vec<u32> = self.mag[limbs..].to_vec();
+        if bits > 0 {
+            let mut carry = 0u32;
+            for l in mag.iter_mut().rev() {
+                let nl = (*l >> bits) | carry;
+                carry = *l << (32 - bits);
+                *l = nl;
+            }
+        }
+        Self::from_parts(self.neg, mag)
+    }
+    /// Number of trailing zero bits (0 for zero).
+    pub fn trailing_zeros(&self) -> u64 {
+        let mut n = 0u64;
+        for &l in &self.mag {
+            if l == 0 {
+                n += 32;
+            } else {
+                return n + l.trailing_zeros() as u64;
+            }
+        }
+        0
+    }
+
+    /// Truncated division: returns (q, r) with self = q*rhs + r and |r| < |rhs|,
+    /// r having the sign of self. Panics on division by zero.
+    pub fn div_rem(&self, rhs: &Self) -> (Self, Self) {
+        assert!(!rhs.is_zero(), "division by zero");
+        let (q, r) = mag_div_rem(&self.mag, &rhs.mag);
+        (Self::from_parts(self.neg != rhs.neg, q), Self::from_parts(self.neg, r))
+    }
+
+    /// Non-negative greatest common divisor; gcd(0, 0) = 0.
+    pub fn gcd(&self, other: &Self) -> Self {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        while !b.is_zero() {
+            if let (Some(x), Some(y)) = (a.to_i128(), b.to_i128()) {
+                return Self::from(gcd_u128(x.unsigned_abs(), y.unsigned_abs()));
+            }
+            let (_, r) = a.div_rem(&b);
+            a = b;
+            b = r;
+        }
+        a
+    }
+}
+
+fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+impl From<i64> for BigInt {
+    fn from(v: i64) -> Self {
+        Self::from(v as i128)
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(v: i128) -> Self {
+        let neg = v < 0;
+        let mut m = v.unsigned_abs();
+        let mut mag = Vec::new();
+        while m != 0 {
+            mag.push(m as u32);
+            m >>= 32;
+        }
+        Self { neg, mag }
+    }
+}
+
+impl From<u128> for BigInt {
+    fn from(mut m: u128) -> Self {
+        let mut mag = Vec::new();
+        while m != 0 {
+            mag.push(m as u32);
+            m >>= 32;
+        }
+        Self { neg: false, mag }
+    }
+}
+
+// ---- magnitude helpers (little-endian u32 limbs, no sign) ----
+
+fn mag_cmp(a: &[u32], b: &[u32]) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+    for (x, y) in a.iter().rev().zip(b.iter().rev()) {
+        match x.cmp(y) {
+            Ordering::Equal => continue,
+            o => return o,
+        }
+    }
+    Ordering::Equal
+}
+
+fn mag_add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut out = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &l) in long.iter().enumerate() {
+        let s = l as u64 + short.get(i).copied().unwrap_or(0) as u64 + carry;
+        out.push(s as u32);
+        carry = s >> 32;
+    }
+    if carry != 0 {
+        out.push(carry as u32);
+    }
+    out
+}
+
+/// a - b, requires |a| >= |b|
+fn mag_sub(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &l) in a.iter().enumerate() {
+        let mut d = l as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        if d < 0 {
+            d += 1 << 32;
+            borrow = 1;
+        } else {
+            borrow = 0;
+        }
+        out.push(d as u32);
+    }
+    while out.last() == Some(&0) {
+        out.pop();
+    }
+    out
+}
+
+fn mag_mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        if x == 0 {
+            continue;
+        }
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let t = x as u64 * y as u64 + out[i + j] as u64 + carry;
+            out[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        out[i + b.len()] = carry as u32;
+    }
+    while out.last() == Some(&0) {
+        out.pop();
+    }
+    out
+}
+
+fn mag_bits(a: &[u32]) -> u64 {
+    match a.last() {
+        None => 0,
+        Some(&top) => (a.len() as u64 - 1) * 32 + (32 - top.leading_zeros()) as u64,
+    }
+}
+
+fn mag_shl(a: &[u32], shift: u64) -> Vec<u32> {
+    if a.is_empty() {
+        return Vec::new();
+    }
+    let limbs = (shift / 32) as usize;
+    let bits = (shift % 32) as u32;
+    let mut out = vec![0u32; limbs];
+    if bits == 0 {
+        out.extend_from_slice(a);
+    } else {
+        let mut carry = 0u32;
+        for &l in a {
+            out.push((l << bits) | carry);
+            carry = l >> (32 - bits);
+        }
+        if carry != 0 {
+            out.push(carry);
+        }
+    }
+    out
+}
+
+fn mag_div_small(a: &[u32], d: u32) -> (Vec<u32>, u32) {
+    let mut q = vec![0u32; a.len()];
+    let mut rem = 0u64;
+    for i in (0..a.len()).rev() {
+        let cur = (rem << 32) | a[i] as u64;
+        q[i] = (cur / d as u64) as u32;
+        rem = cur % d as u64;
+    }
+    while q.last() == Some(&0) {
+        q.pop();
+    }
+    (q, rem as u32)
+}
+
+fn mag_div_rem(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if mag_cmp(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if b.len() == 1 {
+        let (q, r) = mag_div_small(a, b[0]);
+        let r = if r == 0 { Vec::new() } else { vec![r] };
+        return (q, r);
+    }
+    // Shift-subtract long division over the bit-length difference
+    let shift = mag_bits(a) - mag_bits(b);
+    let mut rem = a.to_vec();
+    let mut q = vec![0u32; (shift / 32) as usize + 1];
+    let mut d = mag_shl(b, shift);
+    for s in (0..=shift).rev() {
+        if mag_cmp(&rem, &d) != Ordering::Less {
+            rem = mag_sub(&rem, &d);
+            q[(s / 32) as usize] |= 1 << (s % 32);
+        }
+        if s > 0 {
+            // d >>= 1
+            let mut carry = 0u32;
+            for l in d.iter_mut().rev() {
+                let nl = (*l >> 1) | carry;
+                carry = *l << 31;
+                *l = nl;
+            }
+            while d.last() == Some(&0) {
+                d.pop();
+            }
+        }
+    }
+    while q.last() == Some(&0) {
+        q.pop();
+    }
+    (q, rem)
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.neg, other.neg) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => mag_cmp(&self.mag, &other.mag),
+            (true, true) => mag_cmp(&other.mag, &self.mag),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn add(self, rhs: &BigInt) -> BigInt {
+        if self.neg == rhs.neg {
+            return BigInt::from_parts(self.neg, mag_add(&self.mag, &rhs.mag));
+        }
+        match mag_cmp(&self.mag, &rhs.mag) {
+            Ordering::Equal => BigInt::zero(),
+            Ordering::Greater => BigInt::from_parts(self.neg, mag_sub(&self.mag, &rhs.mag)),
+            Ordering::Less => BigInt::from_parts(rhs.neg, mag_sub(&rhs.mag, &self.mag)),
+        }
+    }
+}
+
+impl Sub<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn sub(self, rhs: &BigInt) -> BigInt {
+        self + &(-rhs)
+    }
+}
+
+impl Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn mul(self, rhs: &BigInt) -> BigInt {
+        BigInt::from_parts(self.neg != rhs.neg, mag_mul(&self.mag, &rhs.mag))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.neg, self.mag.clone())
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.neg, self.mag)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(v) = self.to_i128() {
+            return write!(f, "{v}");
+        }
+        // Peel off base-10^9 chunks
+        let mut chunks: Vec<u32> = Vec::new();
+        let mut m = self.mag.clone();
+        while !m.is_empty() {
+            let (q, r) = mag_div_small(&m, 1_000_000_000);
+            chunks.push(r);
+            m = q;
+        }
+        let mut s = String::new();
+        if self.neg {
+            s.push('-');
+        }
+        s.push_str(&chunks.last().copied().unwrap_or(0).to_string());
+        for c in chunks.iter().rev().skip(1) {
+            s.push_str(&format!("{c:09}"));
+        }
+        f.write_str(&s)
+    }
+}
+
+/// Error returned when parsing a `BigInt` or `BigRat` from a string fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseBigError;
+
+impl fmt::Display for ParseBigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid big number literal")
+    }
+}
+
+impl std::error::Error for ParseBigError {}
+
+impl FromStr for BigInt {
+    type Err = ParseBigError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (neg, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseBigError);
+        }
+        let mut mag: Vec<u32> = Vec::new();
+        let bytes = digits.as_bytes();
+        let head = bytes.len() % 9;
+        let mut chunks: Vec<&[u8]> = Vec::new();
+        if head > 0 {
+            chunks.push(&bytes[..head]);
+        }
+        chunks.extend(bytes[head..].chunks(9));
+        for chunk in chunks {
+            let v: u32 = std::str::from_utf8(chunk).unwrap().parse().unwrap();
+            let scale = 10u32.pow(chunk.len() as u32);
+            // mag = mag * scale + v
+            let mut carry = v as u64;
+            for l in mag.iter_mut() {
+                let t = *l as u64 * scale as u64 + carry;
+                *l = t as u32;
+                carry = t >> 32;
+            }
+            if carry != 0 {
+                mag.push(carry as u32);
+            }
+        }
+        Ok(Self::from_parts(neg, mag))
+    }
+}
+
+// ---------- BigRat ----------
+
+/// Arbitrary-precision rational, always normalized with den>0 and gcd(|num|, den)=1.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigRat {
+    num: BigInt,
+    den: BigInt,
+}
+
+impl BigRat {
+    /// Normalize num/den. Panics on a zero denominator, like `normalize_rat`.
+    pub fn new(num: BigInt, den: BigInt) -> Self {
+        assert!(!den.is_zero(), "zero denominator");
+        if num.is_zero() {
+            return Self::zero();
+        }
+        let g = num.gcd(&den);
+        let (mut n, _) = num.div_rem(&g);
+        let (mut d, _) = den.div_rem(&g);
+        if d.is_negative() {
+            n = -n;
+            d = -d;
+        }
+        Self { num: n, den: d }
+    }
+    pub fn from_integer(n: BigInt) -> Self {
+        Self { num: n, den: BigInt::one() }
+    }
+    pub fn zero() -> Self {
+        Self::from_integer(BigInt::zero())
+    }
+    pub fn one() -> Self {
+        Self::from_integer(BigInt::one())
+    }
+    pub fn numer(&self) -> &BigInt {
+        &self.num
+    }
+    pub fn denom(&self) -> &BigInt {
+        &self.den
+    }
+    pub fn is_zero(&self) -> bool {
+        self.num.is_zero()
+    }
+    pub fn is_one(&self) -> bool {
+        self.num.is_one() && self.den.is_one()
+    }
+    pub fn is_integer(&self) -> bool {
+        self.den.is_one()
+    }
+    pub fn is_negative(&self) -> bool {
+        self.num.is_negative()
+    }
+    pub fn abs(&self) -> Self {
+        Self { num: self.num.abs(), den: self.den.clone() }
+    }
+    /// Multiplicative inverse; None for zero.
+    pub fn recip(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        Some(Self::new(self.den.clone(), self.num.clone()))
+    }
+    /// Exact quotient; None when dividing by zero.
+    pub fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        rhs.recip().map(|r| self * &r)
+    }
+    pub fn pow(&self, e: i32) -> Option<Self> {
+        let base = if e < 0 { self.recip()? } else { self.clone() };
+        let k = e.unsigned_abs();
+        Some(Self { num: base.num.pow(k), den: base.den.pow(k) })
+    }
+    /// Convert to the i64 fast-path representation if both parts fit.
+    pub fn to_q(&self) -> Option<Q> {
+        Some(Q(self.num.to_i64()?, self.den.to_i64()?))
+    }
+    /// Correctly rounded nearest f64.
+    pub fn to_f64(&self) -> f64 {
+        crate::Float::from_bigrat(self, crate::DEFAULT_PREC).to_f64()
+    }
+}
+
+impl From<Q> for BigRat {
+    fn from(q: Q) -> Self {
+        Self::new(BigInt::from(q.0), BigInt::from(q.1))
+    }
+}
+
+impl From<BigInt> for BigRat {
+    fn from(n: BigInt) -> Self {
+        Self::from_integer(n)
+    }
+}
+
+impl From<i64> for BigRat {
+    fn from(n: i64) -> Self {
+        Self::from_integer(BigInt::from(n))
+    }
+}
+
+impl Ord for BigRat {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.num * &other.den).cmp(&(&other.num * &self.den))
+    }
+}
+
+impl PartialOrd for BigRat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add<&BigRat> for &BigRat {
+    type Output = BigRat;
+    fn add(self, rhs: &BigRat) -> BigRat {
+        if self.den == rhs.den {
+            return BigRat::new(&self.num + &rhs.num, self.den.clone());
+        }
+        BigRat::new(&(&self.num * &rhs.den) + &(&rhs.num * &self.den), &self.den * &rhs.den)
+    }
+}
+
+impl Sub<&BigRat> for &BigRat {
+    type Output = BigRat;
+    fn sub(self, rhs: &BigRat) -> BigRat {
+        self + &(-rhs)
+    }
+}
+
+impl Mul<&BigRat> for &BigRat {
+    type Output = BigRat;
+    fn mul(self, rhs: &BigRat) -> BigRat {
+        BigRat::new(&self.num * &rhs.num, &self.den * &rhs.den)
+    }
+}
+
+impl Neg for &BigRat {
+    type Output = BigRat;
+    fn neg(self) -> BigRat {
+        BigRat { num: -&self.num, den: self.den.clone() }
+    }
+}
+
+impl fmt::Display for BigRat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl FromStr for BigRat {
+    type Err = ParseBigError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((n, d)) => {
+                let den: BigInt = d.trim().parse()?;
+                if den.is_zero() {
+                    return Err(ParseBigError);
+                }
+                Ok(Self::new(n.trim().parse()?, den))
+            }
+            None => Ok(Self::from_integer(s.trim().parse()?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn factorial_30() {
+        let mut acc = BigInt::one();
+        for k in 1..=30i64 {
+            acc = &acc * &BigInt::from(k);
+        }
+        assert_eq!(acc.to_string(), "265252859812191058636308480000000");
+        assert_eq!(acc.to_i64(), None);
+    }
+
+    #[test]
+    fn parse_display_roundtrip() {
+        for s in ["0", "-1", "18446744073709551616", "-340282366920938463463374607431768211457"] {
+            assert_eq!(big(s).to_string(), s);
+        }
+        assert!("".parse::<BigInt>().is_err());
+        assert!("12a".parse::<BigInt>().is_err());
+    }
+
+    #[test]
+    fn div_rem_truncates() {
+        let a = big("-100000000000000000000000000007");
+        let b = big("1000000000000000000003");
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(&(&q * &b) + &r, a);
+        assert!(r.is_negative() && r.abs() < b.abs());
+    }
+
+    #[test]
+    fn gcd_large() {
+        let p = big("170141183460469231731687303715884105727"); // 2^127 - 1
+        let a = &p * &BigInt::from(6i64);
+        let b = &p * &BigInt::from(10i64);
+        assert_eq!(a.gcd(&b), &p * &BigInt::from(2i64));
+    }
+
+    #[test]
+    fn bigrat_normalizes() {
+        let r = BigRat::new(BigInt::from(6i64), BigInt::from(-4i64));
+        assert_eq!(r.to_q(), Some(Q(-3, 2)));
+        let half = BigRat::from(Q(1, 2));
+        let third = BigRat::from(Q(1, 3));
+        assert_eq!((&half + &third).to_q(), Some(Q(5, 6)));
+        assert_eq!((&half - &half), BigRat::zero());
+        assert_eq!("-10/4".parse::<BigRat>().unwrap().to_string(), "-5/2");
+    }
+
+    #[test]
+    fn bigrat_beyond_i64() {
+        let m = BigRat::from(i64::MAX);
+        let sum = &m + &m;
+        assert_eq!(sum.to_q(), None);
+        assert_eq!(sum.to_string(), "18446744073709551614");
+        assert!((sum.to_f64() - 1.8446744073709552e19).abs() < 1e5);
+    }
+}
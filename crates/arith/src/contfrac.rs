@@ -0,0 +1,186 @@
+//! Continued fractions and best rational approximation.
+//!
+//! - `cf_expand_q` / `cf_expand_f64`: simple continued fraction `[a0; a1, a2, ...]`
+//!   (a0 = floor, later terms >= 1). An f64 is expanded exactly, as the dyadic
+//!   rational it stores.
+//! - `cf_convergents`: the convergents `p_k / q_k` of a term sequence.
+//! - `best_rational_q` / `best_rational_f64`: the closest rational with
+//!   denominator at most a bound (convergents and semiconvergents).
+//!   `best_rational_by` does the same for any real given its terms and a
+//!   comparison against rationals (used for quadratic surds in `algebraic`).
+
+use crate::{BigInt, BigRat, Float, Q};
+use std::cmp::Ordering;
+
+/// Continued fraction of `q`; the last term is > 1 unless it is the only one.
+pub fn cf_expand_q(q: Q) -> Vec<i64> {
+    let (mut n, mut d) = (q.0 as i128, q.1 as i128);
+    let mut terms = Vec::new();
+    while d != 0 {
+        let a = n.div_euclid(d);
+        // |a| <= |n| and after the first step n < d, so `a` fits in i64
+        terms.push(a as i64);
+        (n, d) = (d, n - a * d);
+    }
+    terms
+}
+
+/// Exact continued fraction of the value stored in `x`, cut off at `max_terms`
+/// or before the first term that does not fit in i64. Empty for NaN/inf.
+pub fn cf_expand_f64(x: f64, max_terms: usize) -> Vec<i64> {
+    expand_f64(x, max_terms).0
+}
+
+/// `cf_expand_f64` and whether the expansion ended (rather than being cut off).
+fn expand_f64(x: f64, max_terms: usize) -> (Vec<i64>, bool) {
+    let Some(f) = Float::from_f64(x) else {
+        return (Vec::new(), true);
+    };
+    let r: BigRat = f.to_bigrat();
+    let (mut n, mut d) = (r.numer().clone(), r.denom().clone());
+    let mut terms = Vec::new();
+    while !d.is_zero() {
+        if terms.len() == max_terms {
+            return (terms, false);
+        }
+        // Floor division (d > 0 throughout)
+        let (mut a, mut rem) = n.div_rem(&d);
+        if rem.is_negative() {
+            a = &a - &BigInt::one();
+            rem = &rem + &d;
+        }
+        match a.to_i64() {
+            Some(t) => terms.push(t),
+            None => return (terms, false),
+        }
+        (n, d) = (d, rem);
+    }
+    (terms, true)
+}
+
+/// Convergents `p_k / q_k` of `[a0; a1, ...]`, stopping before the first one
+/// that overflows i64.
+pub fn cf_convergents(terms: &[i64]) -> Vec<Q> {
+    let mut out = Vec::with_capacity(terms.len());
+    let (mut p0, mut q0, mut p1, mut q1) = (0i64, 1i64, 1i64, 0i64);
+    for &a in terms {
+        let next = |x1: i64, x0: i64| a.checked_mul(x1).and_then(|v| v.checked_add(x0));
+        let (Some(p), Some(q)) = (next(p1, p0), next(q1, q0)) else {
+            break;
+        };
+        (p0, q0, p1, q1) = (p1, q1, p, q);
+        out.push(Q(p, q));
+    }
+    out
+}
+
+/// Closest rational to `q` with denominator at most `max_den` (>= 1); ties go
+/// to the smaller denominator.
+pub fn best_rational_q(q: Q, max_den: i64) -> Q {
+    best_rational_by(cf_expand_q(q), max_den, |m| q.cmp(&m)).expect("nonempty expansion")
+}
+
+/// Closest rational to `x` with denominator at most `max_den` (>= 1); None if
+/// `x` is not finite or its integer part does not fit in i64.
+pub fn best_rational_f64(x: f64, max_den: i64) -> Option<Q> {
+    // Denominators of 93 convergents exceed i64 (they grow like Fibonacci numbers)
+    let (mut terms, ended) = expand_f64(x, 93);
+    if terms.is_empty() {
+        return None;
+    }
+    if !ended {
+        // Any term this large pushes the next denominator past the bound
+        terms.push(i64::MAX);
+    }
+    best_rational_by(terms, max_den, |m| {
+        let m = BigRat::new(BigInt::from(m.0), BigInt::from(m.1));
+        Float::from_f64(x).expect("finite").to_bigrat().cmp(&m)
+    })
+}
+
+/// Best rational approximation with denominator at most `max_den` of the real
+/// `x` whose continued fraction terms are `terms` and where `cmp(m)` compares
+/// `x` with a rational `m`. If the terms run out first, their value is exact.
+/// None if `terms` is empty.
+pub fn best_rational_by<I, F>(terms: I, max_den: i64, cmp: F) -> Option<Q>
+where
+    I: IntoIterator<Item = i64>,
+    F: Fn(Q) -> Ordering,
+{
+    assert!(max_den >= 1, "denominator bound must be positive");
+    let mut it = terms.into_iter();
+    let (mut p0, mut q0, mut p1, mut q1) = (1i64, 0i64, it.next()?, 1i64);
+    for a in it {
+        let next = |x1: i64, x0: i64| a.checked_mul(x1).and_then(|v| v.checked_add(x0));
+        match (next(p1, p0), next(q1, q0)) {
+            (Some(p), Some(q)) if q <= max_den => (p0, q0, p1, q1) = (p1, q1, p, q),
+            _ => {
+                // Largest semiconvergent (p0 + t p1)/(q0 + t q1) within the bound
+                let conv = Q(p1, q1);
+                let t = (max_den - q0) / q1;
+                let semi =
+                    t.checked_mul(p1).and_then(|v| v.checked_add(p0)).map(|p| Q(p, q0 + t * q1));
+                let Some(semi) = semi.filter(|_| t > 0) else {
+                    return Some(conv);
+                };
+                return Some(closer(conv, semi, &cmp));
+            }
+        }
+    }
+    Some(Q(p1, q1))
+}
+
+/// Which of `conv` and `semi` (on opposite sides of x) is closer to x;
+/// `conv`, having the smaller denominator, wins ties.
+fn closer<F: Fn(Q) -> Ordering>(conv: Q, semi: Q, cmp: &F) -> Q {
+    let (lo, hi) = if conv < semi { (conv, semi) } else { (semi, conv) };
+    // Midpoint (lo + hi) / 2 compared exactly via BigRat
+    let mid = &BigRat::from(lo) + &BigRat::from(hi);
+    let mid = &mid * &BigRat::new(BigInt::one(), BigInt::from(2i64));
+    let Some(mid) = mid.to_q() else {
+        return conv;
+    };
+    match cmp(mid) {
+        Ordering::Less => lo,
+        Ordering::Greater => hi,
+        Ordering::Equal => conv,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expansions_and_convergents() {
+        assert_eq!(cf_expand_q(Q(415, 93)), vec![4, 2, 6, 7]);
+        assert_eq!(cf_expand_q(Q(-7, 3)), vec![-3, 1, 2]);
+        assert_eq!(cf_expand_q(Q(5, 1)), vec![5]);
+        let conv = cf_convergents(&[4, 2, 6, 7]);
+        assert_eq!(conv, vec![Q(4, 1), Q(9, 2), Q(58, 13), Q(415, 93)]);
+        // 0.5 and 0.75 are stored exactly
+        assert_eq!(cf_expand_f64(0.75, 10), vec![0, 1, 3]);
+        assert_eq!(cf_expand_f64(-0.5, 10), vec![-1, 2]);
+        assert!(cf_expand_f64(f64::NAN, 10).is_empty());
+        let pi = cf_expand_f64(std::f64::consts::PI, 5);
+        assert_eq!(pi, vec![3, 7, 15, 1, 292]);
+        // Convergents stop before overflowing
+        assert_eq!(cf_convergents(&[0, i64::MAX, i64::MAX]).len(), 2);
+    }
+
+    #[test]
+    fn best_approximations() {
+        let pi = std::f64::consts::PI;
+        assert_eq!(best_rational_f64(pi, 10), Some(Q(22, 7)));
+        assert_eq!(best_rational_f64(pi, 100), Some(Q(311, 99)));
+        assert_eq!(best_rational_f64(pi, 1000), Some(Q(355, 113)));
+        assert_eq!(best_rational_f64(0.1, 1000), Some(Q(1, 10)));
+        assert_eq!(best_rational_f64(-0.333333333333, 100), Some(Q(-1, 3)));
+        assert_eq!(best_rational_f64(f64::INFINITY, 10), None);
+        assert_eq!(best_rational_q(Q(415, 93), 1000), Q(415, 93));
+        assert_eq!(best_rational_q(Q(415, 93), 12), Q(49, 11));
+        // Exactly between 0 and 1/2: the smaller denominator wins
+        assert_eq!(best_rational_q(Q(1, 4), 3), Q(1, 3));
+        assert_eq!(best_rational_q(Q(1, 4), 2), Q(0, 1));
+    }
+}
@@ -0,0 +1,636 @@
+//! Arbitrary-precision binary floating point.
+//! A `Float` is `mant * 2^exp` rounded to `prec` significant bits
+//! (round-half-to-even). `prec = 53` matches IEEE f64.
+//!
+//! Values are normalized (odd mantissa or zero) so structural equality and
+//! hashing agree with numeric equality at a given precision.
+
+use crate::{BigInt, BigRat, ParseBigError};
+use std::fmt;
+use std::str::FromStr;
+
+/// Precision (in bits) of floats created from f64 or parsed without a tag.
+pub const DEFAULT_PREC: u32 = 53;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Float {
+    mant: BigInt,
+    exp: i64,
+    prec: u32,
+}
+
+impl Float {
+    pub fn zero(prec: u32) -> Self {
+        Self { mant: BigInt::zero(), exp: 0, prec: prec.max(1) }
+    }
+
+    /// Round `mant * 2^exp` to `prec` bits. `sticky` marks discarded nonzero
+    /// bits below `mant` (used to break ties correctly after division).
+    fn round(mant: BigInt, mut exp: i64, prec: u32, sticky: bool) -> Self {
+        let prec = prec.max(1);
+        if mant.is_zero() {
+            return Self::zero(prec);
+        }
+        let neg = mant.is_negative();
+        let mut m = mant.abs();
+        let bits = m.bits();
+        if bits > prec as u64 {
+            let drop = bits - prec as u64;
+            let lower = &m - &m.shr(drop).shl(drop);
+            m = m.shr(drop);
+            exp += drop as i64;
+            let half = BigInt::one().shl(drop - 1);
+            let round_up = match lower.cmp(&half) {
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Less => false,
+                std::cmp::Ordering::Equal => sticky || !m.is_even(),
+            };
+            if round_up {
+                m = &m + &BigInt::one();
+            }
+        }
+        let tz = m.trailing_zeros();
+        if tz > 0 {
+            m = m.shr(tz);
+            exp += tz as i64;
+        }
+        Self { mant: if neg { -m } else { m }, exp, prec }
+    }
+
+    /// Exact conversion from a finite f64 (53-bit precision); None for NaN/inf.
+    pub fn from_f64(x: f64) -> Option<Self> {
+        if !x.is_finite() {
+            return None;
+        }
+        if x == 0.0 {
+            return Some(Self::zero(DEFAULT_PREC));
+        }
+        let bits = x.to_bits();
+        let sign = bits >> 63 != 0;
+        let raw_exp = ((bits >> 52) & 0x7ff) as i64;
+        let frac = bits & ((1u64 << 52) - 1);
+        let (m, e) =
+            if raw_exp == 0 { (frac, -1074) } else { (frac | (1u64 << 52), raw_exp - 1075) };
+        let m = BigInt::from(m as i64);
+        Some(Self::round(if sign { -m } else { m }, e, DEFAULT_PREC, false))
+    }
+
+    /// Correctly rounded conversion of an exact rational.
+    pub fn from_bigrat(r: &BigRat, prec: u32) -> Self {
+        if r.is_zero() {
+            return Self::zero(prec);
+        }
+        let n = r.numer().abs();
+        let d = r.denom();
+        // Scale so the quotient carries at least prec + 2 bits
+        let shift = prec as i64 + 2 + d.bits() as i64 - n.bits() as i64;
+        let (num, den) =
+            if shift >= 0 { (n.shl(shift as u64), d.clone()) } else { (n, d.shl((-shift) as u64)) };
+        let (q, rem) = num.div_rem(&den);
+        let q = if r.is_negative() { -q } else { q };
+        Self::round(q, -shift, prec, !rem.is_zero())
+    }
+
+    /// Parse a decimal literal (`-12.5`, `1e-3`, `6.02E23`) at the given precision.
+    /// Literals scaled by more than `10^±100000` are rejected.
+    pub fn parse(s: &str, prec: u32) -> Result<Self, ParseBigError> {
+        Ok(Self::from_bigrat(&parse_decimal(s)?, prec))
+    }
+
+    /// Normalized `(mant, exp)` with value `mant * 2^exp`.
+    pub fn parts(&self) -> (&BigInt, i64) {
+        (&self.mant, self.exp)
+    }
+    pub fn prec(&self) -> u32 {
+        self.prec
+    }
+    pub fn is_zero(&self) -> bool {
+        self.mant.is_zero()
+    }
+    pub fn is_negative(&self) -> bool {
+        self.mant.is_negative()
+    }
+    /// Round (or widen) to a different precision.
+    pub fn with_prec(&self, prec: u32) -> Self {
+        Self::round(self.mant.clone(), self.exp, prec, false)
+    }
+
+    /// Exact rational value of this float.
+    pub fn to_bigrat(&self) -> BigRat {
+        if self.exp >= 0 {
+            BigRat::from_integer(self.mant.shl(self.exp as u64))
+        } else {
+            BigRat::new(self.mant.clone(), BigInt::one().shl(self.exp.unsigned_abs()))
+        }
+    }
+    pub fn to_f64(&self) -> f64 {
+        if self.is_zero() {
+            return 0.0;
+        }
+        let f = Self::round(self.mant.clone(), self.exp, DEFAULT_PREC, false);
+        // |mant| < 2^53 is exact in f64; scale in steps that stay exact until the last
+        let mut x = f.mant.to_f64();
+        let mut e = f.exp.clamp(-4000, 4000);
+        while e != 0 {
+            let step = e.clamp(-1000, 1000);
+            x *= 2f64.powi(step as i32);
+            e -= step;
+        }
+        x
+    }
+
+    /// Result precision of a binary operation: the less precise operand wins.
+    fn join_prec(&self, other: &Self) -> u32 {
+        self.prec.min(other.prec)
+    }
+    pub fn add(&self, other: &Self) -> Self {
+        let e = self.exp.min(other.exp);
+        let a = self.mant.shl((self.exp - e) as u64);
+        let b = other.mant.shl((other.exp - e) as u64);
+        Self::round(&a + &b, e, self.join_prec(other), false)
+    }
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+    pub fn mul(&self, other: &Self) -> Self {
+        Self::round(&self.mant * &other.mant, self.exp + other.exp, self.join_prec(other), false)
+    }
+    /// Quotient; None when dividing by zero.
+    pub fn div(&self, other: &Self) -> Option<Self> {
+        let q = self.to_bigrat().checked_div(&other.to_bigrat())?;
+        Some(Self::from_bigrat(&q, self.join_prec(other)))
+    }
+    /// Integer power, rounded once at the end; None for 0^negative.
+    pub fn powi(&self, n: i32) -> Option<Self> {
+        Some(Self::from_bigrat(&self.to_bigrat().pow(n)?, self.prec))
+    }
+    pub fn neg(&self) -> Self {
+        Self { mant: -&self.mant, exp: self.exp, prec: self.prec }
+    }
+
+    /// Decimal string with `digits` significant digits (trailing zeros trimmed).
+    pub fn to_decimal(&self, digits: usize) -> String {
+        if self.is_zero() {
+            return "0.0".into();
+        }
+        let digits = digits.max(1);
+        let v = self.to_bigrat().abs();
+        // Estimate the decimal exponent, then correct it
+        let approx = (self.mant.bits() as f64 + self.exp as f64) * std::f64::consts::LOG10_2;
+        let mut e10 = approx.floor() as i64;
+        let ten = BigRat::from(10i64);
+        let (scaled, mut e10) = loop {
+            // scaled = v * 10^(digits-1-e10), want 10^(digits-1) <= scaled < 10^digits
+            let k = digits as i64 - 1 - e10;
+            let s = &v * &ten.pow(k as i32).unwrap();
+            let lo = BigRat::from_integer(BigInt::from(10i64).pow(digits as u32 - 1));
+            let hi = &lo * &ten;
+            if s < lo {
+                e10 -= 1;
+            } else if s >= hi {
+                e10 += 1;
+            } else {
+                break (s, e10);
+            }
+        };
+        // Round half-even to an integer with `digits` digits
+        let (q, r) = scaled.numer().div_rem(scaled.denom());
+        let twice_r = r.shl(1);
+        let mut q = match twice_r.cmp(scaled.denom()) {
+            std::cmp::Ordering::Greater => &q + &BigInt::one(),
+            std::cmp::Ordering::Equal if !q.is_even() => &q + &BigInt::one(),
+            _ => q,
+        };
+        let mut ds = q.to_string();
+        if ds.len() > digits {
+            // Rounded up to the next power of ten
+            e10 += 1;
+            q = q.div_rem(&BigInt::from(10i64)).0;
+            ds = q.to_string();
+        }
+        let ds = ds.trim_end_matches('0');
+        let sign = if self.is_negative() { "-" } else { "" };
+        let (int_part, frac_part) = if (-5..17).contains(&e10) {
+            if e10 >= 0 {
+                let e = e10 as usize;
+                if ds.len() > e + 1 {
+                    (ds[..e + 1].to_string(), ds[e + 1..].to_string())
+                } else {
+                    (format!("{}{}", ds, "0".repeat(e + 1 - ds.len())), String::new())
+                }
+            } else {
+                ("0".to_string(), format!("{}{}", "0".repeat((-e10 - 1) as usize), ds))
+            }
+        } else {
+            let frac = if ds.len() > 1 { ds[1..].to_string() } else { String::new() };
+            let frac = if frac.is_empty() { "0".to_string() } else { frac };
+            return format!("{sign}{}.{frac}e{e10}", &ds[..1]);
+        };
+        let frac_part = if frac_part.is_empty() { "0".to_string() } else { frac_part };
+        format!("{sign}{int_part}.{frac_part}")
+    }
+
+    /// Fewest significant digits whose decimal form parses back to this exact float.
+    pub fn decimal_digits(&self) -> usize {
+        // ceil(prec * log10 2) + 1 digits always suffice; round-tripping is monotone in digits
+        let (mut lo, mut hi) =
+            (1, (self.prec as f64 * std::f64::consts::LOG10_2).ceil() as usize + 1);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if Self::parse(&self.to_decimal(mid), self.prec).as_ref() == Ok(self) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        lo
+    }
+}
+
+// ---------- Elementary functions ----------
+//
+// Each kernel works at `prec + GUARD` bits (plus whatever its argument
+// reduction costs) and rounds once at the end, so results are accurate to a
+// few units in the last place; near a zero of the function the error is that
+// size relative to 1 rather than to the result.
+
+/// Extra bits carried through a kernel before the final rounding.
+const GUARD: u32 = 32;
+/// Halvings before a Taylor series (and doublings after it) in exp, sin, cos
+/// and atan.
+const HALVINGS: u32 = 8;
+
+impl Float {
+    fn int(k: i64, prec: u32) -> Self {
+        Self::round(BigInt::from(k), 0, prec, false)
+    }
+
+    /// `self * 2^k`, exactly.
+    fn mul_pow2(&self, k: i64) -> Self {
+        if self.is_zero() {
+            return self.clone();
+        }
+        Self { mant: self.mant.clone(), exp: self.exp + k, prec: self.prec }
+    }
+
+    /// `self / k` for a positive integer `k`, without going through `BigRat`.
+    fn div_int(&self, k: i64) -> Self {
+        let shift = self.prec as u64 + 66;
+        let (q, r) = self.mant.shl(shift).div_rem(&BigInt::from(k));
+        Self::round(q, self.exp - shift as i64, self.prec, !r.is_zero())
+    }
+
+    /// The `e` with `2^(e-1) <= |self| < 2^e`; None for zero.
+    pub fn exponent(&self) -> Option<i64> {
+        (!self.is_zero()).then(|| self.mant.bits() as i64 + self.exp)
+    }
+
+    /// Whether `|self| < 2^e` (true for zero).
+    fn below(&self, e: i64) -> bool {
+        self.exponent().is_none_or(|m| m <= e)
+    }
+
+    /// Nearest integer (ties in either direction).
+    fn round_int(&self) -> BigInt {
+        if self.exp >= 0 {
+            return self.mant.shl(self.exp as u64);
+        }
+        let k = self.exp.unsigned_abs();
+        let half = BigInt::one().shl(k - 1);
+        let shifted = if self.is_negative() { &self.mant - &half } else { &self.mant + &half };
+        shifted.shr(k)
+    }
+
+    /// `pi` to `prec` bits (Machin's formula).
+    pub fn pi(prec: u32) -> Self {
+        let w = prec + GUARD;
+        let pi = atan_inv(5, w).mul_pow2(4).sub(&atan_inv(239, w).mul_pow2(2));
+        pi.with_prec(prec)
+    }
+
+    /// Square root; None for negative numbers.
+    pub fn sqrt(&self) -> Option<Self> {
+        if self.is_negative() {
+            return None;
+        }
+        if self.is_zero() {
+            return Some(self.clone());
+        }
+        // mant * 2^exp with an even exponent and at least 2 * (prec + 2) bits
+        let mut shift = (2 * (self.prec as i64 + 2) - self.mant.bits() as i64).max(0);
+        if (self.exp - shift) % 2 != 0 {
+            shift += 1;
+        }
+        let m = self.mant.shl(shift as u64);
+        let r = isqrt(&m);
+        let exact = &r * &r == m;
+        Some(Self::round(r, (self.exp - shift) / 2, self.prec, !exact))
+    }
+
+    /// `e^self`; None when the result's exponent would overflow.
+    pub fn exp(&self) -> Option<Self> {
+        let prec = self.prec;
+        let Some(mag) = self.exponent() else {
+            return Some(Self::int(1, prec));
+        };
+        if mag > 40 {
+            return None;
+        }
+        // e^x = (e^(x / 2^k))^(2^k) with |x / 2^k| < 2^-HALVINGS
+        let k = (mag + HALVINGS as i64).max(0);
+        let w = prec + GUARD + k as u32;
+        let r = self.with_prec(w).mul_pow2(-k);
+        let mut sum = Self::int(1, w);
+        let mut term = Self::int(1, w);
+        for n in 1.. {
+            term = term.mul(&r).div_int(n);
+            if term.below(-(w as i64)) {
+                break;
+            }
+            sum = sum.add(&term);
+        }
+        for _ in 0..k {
+            sum = sum.mul(&sum);
+        }
+        Some(sum.with_prec(prec))
+    }
+
+    /// Natural logarithm; None unless positive.
+    pub fn ln(&self) -> Option<Self> {
+        if self.is_negative() || self.is_zero() {
+            return None;
+        }
+        let prec = self.prec;
+        let w = prec + GUARD;
+        let x = self.with_prec(w);
+        // f64 start from x = m 2^e, m in [1, 2), then Halley's iteration
+        // y += 2 (x - e^y) / (x + e^y), which triples the correct bits
+        let e = self.exponent()? - 1;
+        let m = x.mul_pow2(-e).to_f64();
+        let mut y = Self::from_f64(m.ln() + e as f64 * std::f64::consts::LN_2)?.with_prec(w);
+        let mut bits = 48;
+        while bits < 3 * w {
+            let ey = y.exp()?;
+            y = y.add(&x.sub(&ey).mul_pow2(1).div(&x.add(&ey))?);
+            bits *= 3;
+        }
+        Some(y.with_prec(prec))
+    }
+
+    /// `(sin self, cos self)`; None for arguments too large to reduce.
+    pub fn sin_cos(&self) -> Option<(Self, Self)> {
+        let prec = self.prec;
+        let Some(mag) = self.exponent() else {
+            return Some((self.clone(), Self::int(1, prec)));
+        };
+        if mag > 4096 {
+            return None;
+        }
+        let w = prec + GUARD + 2 * HALVINGS;
+        // Reduce by a multiple of 2 pi, carrying the bits the reduction cancels
+        let wide = w + mag.max(0) as u32;
+        let x = self.with_prec(wide);
+        let two_pi = Self::pi(wide).mul_pow2(1);
+        let n = x.div(&two_pi)?.round_int();
+        let r = x.sub(&Self::round(n, 0, wide, false).mul(&two_pi)).with_prec(w);
+        let t = r.mul_pow2(-(HALVINGS as i64));
+        let t2 = t.mul(&t);
+        let (mut s, mut c) = (t.clone(), Self::int(1, w));
+        let (mut sterm, mut cterm) = (t, Self::int(1, w));
+        for n in 1.. {
+            // sterm = (-1)^n t^(2n+1) / (2n+1)!, cterm = (-1)^n t^(2n) / (2n)!
+            cterm = cterm.mul(&t2).div_int((2 * n - 1) * (2 * n)).neg();
+            sterm = sterm.mul(&t2).div_int((2 * n) * (2 * n + 1)).neg();
+            if cterm.below(-(w as i64)) {
+                break;
+            }
+            c = c.add(&cterm);
+            s = s.add(&sterm);
+        }
+        for _ in 0..HALVINGS {
+            (s, c) = (s.mul(&c).mul_pow2(1), c.mul(&c).sub(&s.mul(&s)));
+        }
+        Some((s.with_prec(prec), c.with_prec(prec)))
+    }
+
+    /// Arctangent, in (-pi/2, pi/2).
+    pub fn atan(&self) -> Self {
+        let prec = self.prec;
+        if self.is_zero() {
+            return self.clone();
+        }
+        let w = prec + GUARD + HALVINGS;
+        let one = Self::int(1, w);
+        let x = self.with_prec(w);
+        // atan(x) = sign(x) pi/2 - atan(1/x) outside [-1, 1]
+        if x.abs_gt_one() {
+            let half_pi = Self::pi(w).mul_pow2(-1);
+            let half_pi = if x.is_negative() { half_pi.neg() } else { half_pi };
+            let inv = one.div(&x).expect("nonzero");
+            return half_pi.sub(&inv.atan()).with_prec(prec);
+        }
+        // atan(x) = 2 atan(x / (1 + sqrt(1 + x^2)))
+        let mut t = x;
+        for _ in 0..HALVINGS {
+            let root = one.add(&t.mul(&t)).sqrt().expect("positive");
+            t = t.div(&one.add(&root)).expect("positive");
+        }
+        let t2 = t.mul(&t);
+        let (mut sum, mut power) = (t.clone(), t);
+        for n in 1.. {
+            power = power.mul(&t2).neg();
+            let term = power.div_int(2 * n + 1);
+            if term.below(-(w as i64)) {
+                break;
+            }
+            sum = sum.add(&term);
+        }
+        sum.mul_pow2(HALVINGS as i64).with_prec(prec)
+    }
+
+    /// Whether `|self| > 1`.
+    fn abs_gt_one(&self) -> bool {
+        match self.exponent() {
+            Some(e) if e > 1 => true,
+            // In [1, 2): exactly 1 is the normalized mantissa 1 at exponent 0
+            Some(1) => !self.mant.abs().is_one() || self.exp != 0,
+            _ => false,
+        }
+    }
+}
+
+/// `atan(1/n)` at `w` bits: the sum of `(-1)^k / ((2k+1) n^(2k+1))`.
+fn atan_inv(n: i64, w: u32) -> Float {
+    let mut power = Float::int(1, w).div_int(n);
+    let mut sum = power.clone();
+    for k in 1.. {
+        power = power.div_int(n * n);
+        let term = power.div_int(2 * k + 1);
+        if term.below(-(w as i64) - 2) {
+            break;
+        }
+        sum = if k % 2 == 1 { sum.sub(&term) } else { sum.add(&term) };
+    }
+    sum
+}
+
+/// `floor(sqrt(n))` for `n >= 0` by Newton's iteration from above.
+fn isqrt(n: &BigInt) -> BigInt {
+    if n.is_zero() {
+        return BigInt::zero();
+    }
+    let mut x = BigInt::one().shl(n.bits().div_ceil(2));
+    loop {
+        let y = (&x + &n.div_rem(&x).0).shr(1);
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+impl fmt::Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.prec == DEFAULT_PREC {
+            // Same shortest digits as f64 formatting, unless f64 is subnormal here
+            let s = format!("{:?}", self.to_f64());
+            if Self::parse(&s, DEFAULT_PREC).as_ref() == Ok(self) {
+                return f.write_str(&s);
+            }
+        }
+        f.write_str(&self.to_decimal(self.decimal_digits()))
+    }
+}
+
+impl FromStr for Float {
+    type Err = ParseBigError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, DEFAULT_PREC)
+    }
+}
+
+/// Largest decimal exponent `parse` accepts (after moving the point to the end
+/// of the digits); `10^k` is computed exactly, so larger ones are rejected
+/// rather than left to run for minutes.
+const MAX_DECIMAL_EXP: i64 = 100_000;
+
+/// Exact rational value of a decimal literal.
+fn parse_decimal(s: &str) -> Result<BigRat, ParseBigError> {
+    let s = s.trim();
+    let (mantissa, exp10) = match s.find(['e', 'E']) {
+        Some(i) => (&s[..i], s[i + 1..].parse::<i32>().map_err(|_| ParseBigError)?),
+        None => (s, 0),
+    };
+    let (neg, body) = match mantissa.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+    };
+    let (ip, fp) = body.split_once('.').unwrap_or((body, ""));
+    if ip.is_empty() && fp.is_empty() {
+        return Err(ParseBigError);
+    }
+    let digits = format!("{ip}{fp}");
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseBigError);
+    }
+    let mut n: BigInt = digits.parse()?;
+    if neg {
+        n = -n;
+    }
+    let scale = exp10 as i64 - fp.len() as i64;
+    if scale.abs() > MAX_DECIMAL_EXP {
+        return Err(ParseBigError);
+    }
+    let ten = BigRat::from(10i64);
+    let p = ten.pow(scale as i32).ok_or(ParseBigError)?;
+    Ok(&BigRat::from_integer(n) * &p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f64_roundtrip_and_display() {
+        for x in [0.1, -2.71, 1e-300, 6.02e23, 5e-324, 1.0] {
+            let f = Float::from_f64(x).unwrap();
+            assert_eq!(f.to_f64(), x);
+            assert_eq!(f.to_string().parse::<Float>().unwrap(), f);
+        }
+        assert_eq!(Float::from_f64(0.1).unwrap().to_string(), "0.1");
+        assert!(Float::from_f64(f64::NAN).is_none());
+    }
+
+    #[test]
+    fn parse_matches_f64() {
+        for s in ["0.1", "2.71", "-2.5e-3", "1E10", "7"] {
+            let f: Float = s.parse().unwrap();
+            assert_eq!(f.to_f64(), s.parse::<f64>().unwrap(), "{s}");
+        }
+        assert!("1.2.3".parse::<Float>().is_err());
+        assert!("e5".parse::<Float>().is_err());
+        // Huge exponents are rejected up front instead of computing 10^k
+        assert!("1e-99999999".parse::<Float>().is_err());
+        assert!("1e99999999".parse::<Float>().is_err());
+        assert!("0.5e-100000".parse::<Float>().is_err());
+        assert!("1e-300".parse::<Float>().is_ok());
+    }
+
+    #[test]
+    fn arithmetic_rounds_like_f64() {
+        let a = Float::from_f64(0.1).unwrap();
+        let b = Float::from_f64(0.2).unwrap();
+        assert_eq!(a.add(&b).to_f64(), 0.1 + 0.2);
+        assert_eq!(a.mul(&b).to_f64(), 0.1 * 0.2);
+        assert_eq!(a.div(&b).unwrap().to_f64(), 0.1 / 0.2);
+        assert_eq!(a.sub(&a), Float::zero(53));
+        assert!(a.div(&Float::zero(53)).is_none());
+    }
+
+    #[test]
+    fn high_precision_decimal() {
+        let third = Float::from_bigrat(&BigRat::new(BigInt::one(), BigInt::from(3i64)), 200);
+        let s = third.to_string();
+        assert!(s.starts_with("0.3333333333333333333333333333333333333333333333333333333333"));
+        assert_eq!(Float::parse(&s, 200).unwrap(), third);
+        // Lower precision operand determines the result precision
+        let half = Float::from_f64(0.5).unwrap();
+        assert_eq!(third.add(&half).prec(), 53);
+        let big = Float::parse("1.5e40", 80).unwrap();
+        assert_eq!(big.to_string(), "1.5e40");
+    }
+
+    #[test]
+    fn elementary_functions() {
+        // Known digits at 200 bits (about 60 decimal digits)
+        let pi = Float::pi(200).to_decimal(50);
+        assert_eq!(pi, "3.1415926535897932384626433832795028841971693993751");
+        let one = Float::from_bigrat(&BigRat::one(), 200);
+        let e = one.exp().unwrap().to_decimal(50);
+        assert_eq!(e, "2.7182818284590452353602874713526624977572470937");
+        let two = Float::from_bigrat(&BigRat::from(2i64), 200);
+        let sqrt2 = two.sqrt().unwrap().to_decimal(50);
+        assert_eq!(sqrt2, "1.4142135623730950488016887242096980785696718753769");
+        // Identities hold to nearly the working precision
+        let tiny = |x: Float| x.below(-190);
+        assert!(tiny(two.ln().unwrap().exp().unwrap().sub(&two)));
+        assert!(tiny(one.atan().mul_pow2(2).sub(&Float::pi(200))));
+        let x = Float::from_bigrat(&BigRat::from(1000i64), 200);
+        let (s, c) = x.sin_cos().unwrap();
+        assert!(tiny(s.mul(&s).add(&c.mul(&c)).sub(&one)));
+        // At 53 bits they agree with libm to within an ulp or so
+        for v in [0.3, -1.7, 2.5, 12.0, 1e-5] {
+            let f = Float::from_f64(v).unwrap();
+            let close = |a: f64, b: f64| (a - b).abs() <= 4.0 * f64::EPSILON * b.abs().max(1.0);
+            assert!(close(f.exp().unwrap().to_f64(), v.exp()), "exp {v}");
+            let (s, c) = f.sin_cos().unwrap();
+            assert!(close(s.to_f64(), v.sin()) && close(c.to_f64(), v.cos()), "sin_cos {v}");
+            assert!(close(f.atan().to_f64(), v.atan()), "atan {v}");
+            if v > 0.0 {
+                assert!(close(f.ln().unwrap().to_f64(), v.ln()), "ln {v}");
+                assert_eq!(f.sqrt().unwrap().to_f64(), v.sqrt(), "sqrt {v}");
+            }
+        }
+        assert!(two.neg().ln().is_none() && two.neg().sqrt().is_none());
+    }
+}
@@ -0,0 +1,355 @@
+//! Closed intervals `[lo, hi]` that are guaranteed to enclose a result.
+//!
+//! - `Interval<Q>` is exact; an endpoint that outgrows i64 is reported as
+//!   `ArithError::Overflow` rather than rounded.
+//! - `Interval<f64>` rounds every lower endpoint down and every upper endpoint
+//!   up (using the exact error of each IEEE operation), so the true value of an
+//!   operation on real numbers inside the operands always lies inside the result.
+//!
+//! Operations follow the usual interval rules; division fails with
+//! `ArithError::DivisionByZero` when the divisor contains zero.
+
+use crate::{checked_add_q, checked_div_q, checked_mul_q, ArithError, Q};
+use std::fmt;
+
+/// Endpoint type of an `Interval`: arithmetic rounded in a chosen direction.
+pub trait Bound: Copy + PartialOrd + fmt::Debug {
+    fn zero() -> Self;
+    fn one() -> Self;
+    /// `a + b` rounded up if `up`, down otherwise (exact types ignore `up`).
+    fn add_rounded(a: Self, b: Self, up: bool) -> Result<Self, ArithError>;
+    fn mul_rounded(a: Self, b: Self, up: bool) -> Result<Self, ArithError>;
+    fn div_rounded(a: Self, b: Self, up: bool) -> Result<Self, ArithError>;
+    /// `-a` (always exact for floats).
+    fn negate(a: Self) -> Result<Self, ArithError>;
+    /// `q` rounded up if `up`, down otherwise.
+    fn from_q(q: Q, up: bool) -> Self;
+}
+
+impl Bound for Q {
+    fn zero() -> Self {
+        Q::zero()
+    }
+    fn one() -> Self {
+        Q::one()
+    }
+    fn add_rounded(a: Self, b: Self, _up: bool) -> Result<Self, ArithError> {
+        checked_add_q(a, b)
+    }
+    fn mul_rounded(a: Self, b: Self, _up: bool) -> Result<Self, ArithError> {
+        checked_mul_q(a, b)
+    }
+    fn div_rounded(a: Self, b: Self, _up: bool) -> Result<Self, ArithError> {
+        checked_div_q(a, b)
+    }
+    fn negate(a: Self) -> Result<Self, ArithError> {
+        a.0.checked_neg().map(|n| Q(n, a.1)).ok_or(ArithError::Overflow)
+    }
+    fn from_q(q: Q, _up: bool) -> Self {
+        q
+    }
+}
+
+/// Round the computed `r` outward given the sign of the exact error `err`
+/// (true result = r + err). Non-finite values are always stepped.
+fn round_f64(r: f64, err: f64, up: bool) -> f64 {
+    if !r.is_finite() || !err.is_finite() {
+        return if up { r.next_up() } else { r.next_down() };
+    }
+    if up && err > 0.0 {
+        r.next_up()
+    } else if !up && err < 0.0 {
+        r.next_down()
+    } else {
+        r
+    }
+}
+
+impl Bound for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+    fn one() -> Self {
+        1.0
+    }
+    fn add_rounded(a: Self, b: Self, up: bool) -> Result<Self, ArithError> {
+        // TwoSum: err is the exact rounding error of a + b
+        let s = a + b;
+        let bb = s - a;
+        let err = (a - (s - bb)) + (b - bb);
+        Ok(round_f64(s, err, up))
+    }
+    fn mul_rounded(a: Self, b: Self, up: bool) -> Result<Self, ArithError> {
+        let p = a * b;
+        Ok(round_f64(p, a.mul_add(b, -p), up))
+    }
+    fn div_rounded(a: Self, b: Self, up: bool) -> Result<Self, ArithError> {
+        if b == 0.0 {
+            return Err(ArithError::DivisionByZero);
+        }
+        let q = a / b;
+        // a = q*b + rem exactly, so a/b = q + rem/b
+        let rem = (-q).mul_add(b, a);
+        Ok(round_f64(q, rem * b.signum(), up))
+    }
+    fn negate(a: Self) -> Result<Self, ArithError> {
+        Ok(-a)
+    }
+    fn from_q(q: Q, up: bool) -> Self {
+        const EXACT: i64 = 1 << 53;
+        if q.0.unsigned_abs() <= EXACT as u64 && q.1 <= EXACT {
+            return f64::div_rounded(q.0 as f64, q.1 as f64, up).expect("nonzero denominator");
+        }
+        // Three roundings of at most half an ulp each: two ulps cover them
+        let x = q.0 as f64 / q.1 as f64;
+        if up {
+            x.next_up().next_up()
+        } else {
+            x.next_down().next_down()
+        }
+    }
+}
+
+/// The closed interval `[lo, hi]` with `lo <= hi`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval<T> {
+    lo: T,
+    hi: T,
+}
+
+pub type IntervalQ = Interval<Q>;
+pub type IntervalF64 = Interval<f64>;
+
+fn min<T: PartialOrd>(a: T, b: T) -> T {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+fn max<T: PartialOrd>(a: T, b: T) -> T {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+impl<T: Bound> Interval<T> {
+    /// `[lo, hi]`, or None unless `lo <= hi` (in particular for NaN endpoints).
+    pub fn new(lo: T, hi: T) -> Option<Self> {
+        if lo <= hi {
+            Some(Self { lo, hi })
+        } else {
+            None
+        }
+    }
+    pub fn point(x: T) -> Self {
+        Self { lo: x, hi: x }
+    }
+    /// Smallest interval of endpoints `T` containing the rational `q`.
+    pub fn from_q(q: Q) -> Self {
+        Self { lo: T::from_q(q, false), hi: T::from_q(q, true) }
+    }
+    pub fn lo(&self) -> T {
+        self.lo
+    }
+    pub fn hi(&self) -> T {
+        self.hi
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+    /// Whether `other` lies entirely inside `self`.
+    pub fn encloses(&self, other: &Self) -> bool {
+        self.lo <= other.lo && other.hi <= self.hi
+    }
+    pub fn contains_zero(&self) -> bool {
+        self.contains(T::zero())
+    }
+    /// Every point is > 0.
+    pub fn is_positive(&self) -> bool {
+        self.lo > T::zero()
+    }
+    /// Every point is < 0.
+    pub fn is_negative(&self) -> bool {
+        self.hi < T::zero()
+    }
+
+    /// Smallest interval containing both.
+    pub fn hull(&self, other: &Self) -> Self {
+        Self { lo: min(self.lo, other.lo), hi: max(self.hi, other.hi) }
+    }
+    /// Common part, or None if disjoint.
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        Self::new(max(self.lo, other.lo), min(self.hi, other.hi))
+    }
+
+    pub fn neg(&self) -> Result<Self, ArithError> {
+        Ok(Self { lo: T::negate(self.hi)?, hi: T::negate(self.lo)? })
+    }
+    pub fn add(&self, rhs: &Self) -> Result<Self, ArithError> {
+        Ok(Self {
+            lo: T::add_rounded(self.lo, rhs.lo, false)?,
+            hi: T::add_rounded(self.hi, rhs.hi, true)?,
+        })
+    }
+    pub fn sub(&self, rhs: &Self) -> Result<Self, ArithError> {
+        self.add(&rhs.neg()?)
+    }
+    pub fn mul(&self, rhs: &Self) -> Result<Self, ArithError> {
+        let ends = [(self.lo, rhs.lo), (self.lo, rhs.hi), (self.hi, rhs.lo), (self.hi, rhs.hi)];
+        let mut lo = T::mul_rounded(ends[0].0, ends[0].1, false)?;
+        let mut hi = T::mul_rounded(ends[0].0, ends[0].1, true)?;
+        for &(a, b) in &ends[1..] {
+            lo = min(lo, T::mul_rounded(a, b, false)?);
+            hi = max(hi, T::mul_rounded(a, b, true)?);
+        }
+        Ok(Self { lo, hi })
+    }
+    /// `1 / self`; fails if `self` contains zero.
+    pub fn recip(&self) -> Result<Self, ArithError> {
+        if self.contains_zero() {
+            return Err(ArithError::DivisionByZero);
+        }
+        Ok(Self {
+            lo: T::div_rounded(T::one(), self.hi, false)?,
+            hi: T::div_rounded(T::one(), self.lo, true)?,
+        })
+    }
+    /// `self / rhs`; fails if `rhs` contains zero.
+    pub fn div(&self, rhs: &Self) -> Result<Self, ArithError> {
+        if rhs.contains_zero() {
+            return Err(ArithError::DivisionByZero);
+        }
+        let ends = [(self.lo, rhs.lo), (self.lo, rhs.hi), (self.hi, rhs.lo), (self.hi, rhs.hi)];
+        let mut lo = T::div_rounded(ends[0].0, ends[0].1, false)?;
+        let mut hi = T::div_rounded(ends[0].0, ends[0].1, true)?;
+        for &(a, b) in &ends[1..] {
+            lo = min(lo, T::div_rounded(a, b, false)?);
+            hi = max(hi, T::div_rounded(a, b, true)?);
+        }
+        Ok(Self { lo, hi })
+    }
+
+    /// `{|x| : x in self}`.
+    pub fn abs(&self) -> Result<Self, ArithError> {
+        if self.lo >= T::zero() {
+            Ok(*self)
+        } else if self.hi <= T::zero() {
+            self.neg()
+        } else {
+            Ok(Self { lo: T::zero(), hi: max(T::negate(self.lo)?, self.hi) })
+        }
+    }
+
+    /// `{x^n : x in self}`: tight for even powers of intervals around zero
+    /// (unlike repeated `mul`); negative `n` fails if `self` contains zero.
+    pub fn pow(&self, n: i64) -> Result<Self, ArithError> {
+        let m = self.pow_unsigned(n.unsigned_abs())?;
+        if n < 0 {
+            m.recip()
+        } else {
+            Ok(m)
+        }
+    }
+
+    /// `{x^n : x in self}` for `n >= 0`.
+    fn pow_unsigned(&self, n: u64) -> Result<Self, ArithError> {
+        if n == 0 {
+            return Ok(Self::point(T::one()));
+        }
+        // Signed power of one endpoint, rounded in direction `up`
+        let end = |x: T, up: bool| -> Result<T, ArithError> {
+            if x >= T::zero() {
+                pow_nonneg(x, n, up)
+            } else {
+                // x^n = -(|x|^n) for odd n
+                T::negate(pow_nonneg(T::negate(x)?, n, !up)?)
+            }
+        };
+        if n % 2 == 1 {
+            return Ok(Self { lo: end(self.lo, false)?, hi: end(self.hi, true)? });
+        }
+        let a = self.abs()?;
+        Ok(Self { lo: pow_nonneg(a.lo, n, false)?, hi: pow_nonneg(a.hi, n, true)? })
+    }
+}
+
+/// `x^n` for `x >= 0` by repeated squaring, each step rounded in direction
+/// `up` (monotone on non-negative values, so the bound is preserved).
+fn pow_nonneg<T: Bound>(x: T, mut n: u64, up: bool) -> Result<T, ArithError> {
+    let mut base = x;
+    let mut acc = T::one();
+    while n > 0 {
+        if n & 1 == 1 {
+            acc = T::mul_rounded(acc, base, up)?;
+        }
+        n >>= 1;
+        if n > 0 {
+            base = T::mul_rounded(base, base, up)?;
+        }
+    }
+    Ok(acc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iq(a: (i64, i64), b: (i64, i64)) -> IntervalQ {
+        Interval::new(Q::new(a.0, a.1), Q::new(b.0, b.1)).unwrap()
+    }
+
+    #[test]
+    fn rational_intervals_are_exact() {
+        let x = iq((-1, 2), (3, 1));
+        let y = iq((1, 1), (2, 1));
+        assert_eq!(x.add(&y).unwrap(), iq((1, 2), (5, 1)));
+        assert_eq!(x.sub(&y).unwrap(), iq((-5, 2), (2, 1)));
+        assert_eq!(x.mul(&y).unwrap(), iq((-1, 1), (6, 1)));
+        assert_eq!(x.div(&y).unwrap(), iq((-1, 2), (3, 1)));
+        assert_eq!(y.div(&x), Err(ArithError::DivisionByZero));
+        // Even powers around zero are tight: [-1/2, 3]^2 = [0, 9], not [-3/2, 9]
+        assert_eq!(x.pow(2).unwrap(), iq((0, 1), (9, 1)));
+        assert_eq!(x.pow(3).unwrap(), iq((-1, 8), (27, 1)));
+        assert_eq!(y.pow(-1).unwrap(), iq((1, 2), (1, 1)));
+        // i64::MIN has no positive counterpart; its magnitude is used directly
+        let one = iq((1, 1), (1, 1));
+        assert_eq!(one.pow(i64::MIN).unwrap(), one);
+        assert_eq!(y.pow(i64::MIN), Err(ArithError::Overflow));
+        assert!(x.contains(Q::zero()) && x.encloses(&iq((0, 1), (1, 1))));
+        assert!(y.is_positive() && !x.is_positive());
+        assert_eq!(x.intersect(&y), Some(iq((1, 1), (2, 1))));
+        assert_eq!(iq((5, 1), (6, 1)).intersect(&y), None);
+        assert_eq!(x.hull(&iq((5, 1), (6, 1))), iq((-1, 2), (6, 1)));
+        let big = Interval::point(Q(i64::MAX, 1));
+        assert_eq!(big.add(&big), Err(ArithError::Overflow));
+    }
+
+    #[test]
+    fn float_intervals_round_outward() {
+        let tenth = IntervalF64::from_q(Q(1, 10));
+        assert!(tenth.lo() < tenth.hi() && tenth.contains(0.1));
+        // 0.1 + 0.2 is inexact in f64: the sum must straddle the exact 3/10
+        let s = tenth.add(&IntervalF64::from_q(Q(1, 5))).unwrap();
+        assert!(s.encloses(&IntervalF64::from_q(Q(3, 10))));
+        assert!(s.lo() < s.hi());
+        // Exact operations stay points
+        let two = Interval::point(2.0);
+        assert_eq!(two.mul(&two).unwrap(), Interval::point(4.0));
+        assert_eq!(two.add(&two).unwrap(), Interval::point(4.0));
+        let third = Interval::point(1.0).div(&Interval::point(3.0)).unwrap();
+        assert!(third.lo() < third.hi() && third.hi() == third.lo().next_up());
+        assert!(third.mul(&Interval::point(3.0)).unwrap().contains(1.0));
+        let x = Interval::new(-2.0, 1.0).unwrap();
+        assert_eq!(x.pow(2).unwrap(), Interval::new(0.0, 4.0).unwrap());
+        assert_eq!(x.abs().unwrap(), Interval::new(0.0, 2.0).unwrap());
+        assert_eq!(Interval::new(1.0, f64::NAN), None);
+        // Overflow widens to infinity instead of failing
+        let huge = Interval::point(f64::MAX).add(&Interval::point(f64::MAX)).unwrap();
+        assert_eq!((huge.lo(), huge.hi()), (f64::MAX, f64::INFINITY));
+    }
+}
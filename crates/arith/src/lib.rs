@@ -1,11 +1,61 @@
 //! arith: shared small rational arithmetic utilities over i64.
 //! - Tuple-based rationals: (num, den) with helpers q_* and rat_*
-//! - Newtype `Q(i64, i64)` for use in polynomial code
+//! - `checked_*` variants that report overflow as an `ArithError`
+//! - Newtype `Q(i64, i64)` for use in polynomial code; it stays i64-only (no
+//!   big fallback), so values past i64 need `BigRat`
+//! - Arbitrary-precision `BigInt`/`BigRat` for values that outgrow i64
+//! - `Float`: binary floating point with an explicit precision in bits
+//! - Continued fractions, convergents and best rational approximation
+//! - `Interval`: guaranteed enclosures over `Q` (exact) or f64 (outward rounding)
 //!
 //!   All rationals are normalized with den>0 and gcd(|num|, den)=1.
 
 #![deny(warnings)]
 
+mod bigint;
+mod contfrac;
+mod float;
+mod interval;
+
+pub use bigint::{BigInt, BigRat, ParseBigError};
+pub use contfrac::{
+    best_rational_by, best_rational_f64, best_rational_q, cf_convergents, cf_expand_f64,
+    cf_expand_q,
+};
+pub use float::{Float, DEFAULT_PREC};
+pub use interval::{Bound, Interval, IntervalF64, IntervalQ};
+
+use std::fmt;
+
+/// Failure of a checked rational operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArithError {
+    /// The exact result does not fit in i64.
+    Overflow,
+    DivisionByZero,
+}
+
+impl fmt::Display for ArithError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str((*self).into())
+    }
+}
+
+impl std::error::Error for ArithError {}
+
+/// For callers whose errors are plain messages (`matrix`, `polys`).
+impl From<ArithError> for &'static str {
+    fn from(e: ArithError) -> Self {
+        match e {
+            ArithError::Overflow => "numeric overflow",
+            ArithError::DivisionByZero => "division by zero",
+        }
+    }
+}
+
+/// A reduced rational over i64. There is no big-number fallback: `add_q` and
+/// friends saturate, `checked_add_q` and friends report overflow, and values
+/// that outgrow i64 belong in `BigRat`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Q(pub i64, pub i64);
 
@@ -59,39 +109,101 @@ pub fn gcd_i64(mut a: i64, mut b: i64) -> i64 {
     a.abs()
 }
 
+/// `gcd_i64` that fails instead of overflowing (only `gcd = 2^63` can).
+pub fn checked_gcd_i64(a: i64, b: i64) -> Result<i64, ArithError> {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    i64::try_from(a).map_err(|_| ArithError::Overflow)
+}
+
+/// Reduce an i128 fraction to lowest terms with den > 0 (den must be non-zero).
+fn reduce_wide(num: i128, den: i128) -> (i128, i128) {
+    let (mut a, mut b) = (num.unsigned_abs(), den.unsigned_abs());
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    // Products of two i64 stay below 2^126, so g and the quotients fit in i128
+    let g = a.max(1) as i128;
+    let s = den.signum();
+    (s * (num / g), s * (den / g))
+}
+
+/// Exact i128 fraction narrowed to i64 parts.
+fn fit_wide(num: i128, den: i128) -> Result<(i64, i64), ArithError> {
+    if den == 0 {
+        return Err(ArithError::DivisionByZero);
+    }
+    let (n, d) = reduce_wide(num, den);
+    match (i64::try_from(n), i64::try_from(d)) {
+        (Ok(n), Ok(d)) => Ok((n, d)),
+        _ => Err(ArithError::Overflow),
+    }
+}
+
+/// Exact i128 fraction with each part clamped into i64 if it does not fit.
+fn saturate_wide(num: i128, den: i128) -> (i64, i64) {
+    assert!(den != 0, "zero denominator");
+    let (n, d) = reduce_wide(num, den);
+    let clamp = |v: i128| v.clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+    (clamp(n), clamp(d).max(1))
+}
+
 // ---------- Tuple rational helpers (aliases provided for compatibility) ----------
 /// Normalize (num, den) to gcd-reduced with den>0
 pub fn normalize_rat(num: i64, den: i64) -> (i64, i64) {
-    assert!(den != 0, "zero denominator");
-    let mut n = num;
-    let mut d = den;
-    if d < 0 {
-        n = -n;
-        d = -d;
-    }
-    if n == 0 {
-        return (0, 1);
-    }
-    let g = gcd_i64(n.abs(), d);
-    (n / g, d / g)
+    saturate_wide(num as i128, den as i128)
 }
-/// Add two rationals (num,den)
+/// Add two rationals (num,den).
+///
+/// Intermediates are exact; a result that does not fit in i64 saturates, so use
+/// `checked_rat_add` wherever that can happen.
 pub fn rat_add(a: (i64, i64), b: (i64, i64)) -> (i64, i64) {
-    // Use saturating arithmetic to prevent overflow panics
-    let num = a.0.saturating_mul(b.1).saturating_add(b.0.saturating_mul(a.1));
-    let den = a.1.saturating_mul(b.1);
-    normalize_rat(num, den)
+    let (n, d) = add_wide(a, b, 1);
+    saturate_wide(n, d)
 }
-/// Multiply two rationals (num,den)
+/// Multiply two rationals (num,den); saturates like `rat_add`.
 pub fn rat_mul(a: (i64, i64), b: (i64, i64)) -> (i64, i64) {
-    // Use saturating arithmetic to prevent overflow panics
-    let num = a.0.saturating_mul(b.0);
-    let den = a.1.saturating_mul(b.1);
-    normalize_rat(num, den)
+    saturate_wide(a.0 as i128 * b.0 as i128, a.1 as i128 * b.1 as i128)
 }
 /// Subtract two rationals (num,den)
 pub fn rat_sub(a: (i64, i64), b: (i64, i64)) -> (i64, i64) {
-    rat_add(a, (-b.0, b.1))
+    let (n, d) = add_wide(a, b, -1);
+    saturate_wide(n, d)
+}
+
+/// a + sign * b as an unreduced i128 fraction (exact for any i64 inputs).
+fn add_wide(a: (i64, i64), b: (i64, i64), sign: i128) -> (i128, i128) {
+    let num = a.0 as i128 * b.1 as i128 + sign * (b.0 as i128 * a.1 as i128);
+    (num, a.1 as i128 * b.1 as i128)
+}
+
+/// `normalize_rat` reporting a zero denominator or an unrepresentable result.
+pub fn checked_normalize_rat(num: i64, den: i64) -> Result<(i64, i64), ArithError> {
+    fit_wide(num as i128, den as i128)
+}
+/// `rat_add` reporting overflow instead of saturating.
+pub fn checked_rat_add(a: (i64, i64), b: (i64, i64)) -> Result<(i64, i64), ArithError> {
+    let (n, d) = add_wide(a, b, 1);
+    fit_wide(n, d)
+}
+/// `rat_sub` reporting overflow instead of saturating.
+pub fn checked_rat_sub(a: (i64, i64), b: (i64, i64)) -> Result<(i64, i64), ArithError> {
+    let (n, d) = add_wide(a, b, -1);
+    fit_wide(n, d)
+}
+/// `rat_mul` reporting overflow instead of saturating.
+pub fn checked_rat_mul(a: (i64, i64), b: (i64, i64)) -> Result<(i64, i64), ArithError> {
+    fit_wide(a.0 as i128 * b.0 as i128, a.1 as i128 * b.1 as i128)
+}
+/// `q_div` reporting overflow and division by zero instead of panicking.
+pub fn checked_q_div(a: (i64, i64), b: (i64, i64)) -> Result<(i64, i64), ArithError> {
+    fit_wide(a.0 as i128 * b.1 as i128, a.1 as i128 * b.0 as i128)
 }
 
 // Prefer q_* naming in calculus; keep both for ergonomic use.
@@ -108,7 +220,7 @@ pub fn q_mul(a: (i64, i64), b: (i64, i64)) -> (i64, i64) {
     rat_mul(a, b)
 }
 pub fn q_div(a: (i64, i64), b: (i64, i64)) -> (i64, i64) {
-    normalize_rat(a.0 * b.1, a.1 * b.0)
+    saturate_wide(a.0 as i128 * b.1 as i128, a.1 as i128 * b.0 as i128)
 }
 
 // ---------- Q arithmetic helpers ----------
@@ -128,6 +240,18 @@ pub fn div_q(a: Q, b: Q) -> Q {
     let (n, d) = q_div((a.0, a.1), (b.0, b.1));
     Q(n, d)
 }
+pub fn checked_add_q(a: Q, b: Q) -> Result<Q, ArithError> {
+    checked_rat_add((a.0, a.1), (b.0, b.1)).map(|(n, d)| Q(n, d))
+}
+pub fn checked_sub_q(a: Q, b: Q) -> Result<Q, ArithError> {
+    checked_rat_sub((a.0, a.1), (b.0, b.1)).map(|(n, d)| Q(n, d))
+}
+pub fn checked_mul_q(a: Q, b: Q) -> Result<Q, ArithError> {
+    checked_rat_mul((a.0, a.1), (b.0, b.1)).map(|(n, d)| Q(n, d))
+}
+pub fn checked_div_q(a: Q, b: Q) -> Result<Q, ArithError> {
+    checked_q_div((a.0, a.1), (b.0, b.1)).map(|(n, d)| Q(n, d))
+}
 
 #[cfg(test)]
 mod tests {
@@ -170,4 +294,26 @@ mod tests {
         assert!(Q::zero().is_zero());
         assert_eq!(Q::one(), Q(1, 1));
     }
+
+    #[test]
+    fn checked_ops_report_overflow() {
+        let big = (i64::MAX, 1);
+        assert_eq!(checked_rat_add(big, (1, 1)), Err(ArithError::Overflow));
+        assert_eq!(checked_rat_mul(big, (2, 1)), Err(ArithError::Overflow));
+        assert_eq!(checked_rat_sub((i64::MIN, 1), (1, 1)), Err(ArithError::Overflow));
+        assert_eq!(checked_q_div((1, 1), (0, 1)), Err(ArithError::DivisionByZero));
+        assert_eq!(checked_gcd_i64(i64::MIN, 0), Err(ArithError::Overflow));
+        assert_eq!(checked_normalize_rat(i64::MIN, -1), Err(ArithError::Overflow));
+        // Large intermediates that cancel are fine
+        assert_eq!(checked_rat_mul(big, (1, i64::MAX)), Ok((1, 1)));
+        assert_eq!(checked_rat_add((1, i64::MAX), (-1, i64::MAX)), Ok((0, 1)));
+        assert_eq!(checked_mul_q(Q(i64::MAX, 3), Q(3, i64::MAX)), Ok(Q::one()));
+        assert_eq!(checked_div_q(Q(1, 2), Q(1, 4)), Ok(Q(2, 1)));
+        assert_eq!(ArithError::Overflow.to_string(), "numeric overflow");
+        let msg: &'static str = ArithError::Overflow.into();
+        assert_eq!(msg, "numeric overflow");
+        // Unchecked helpers stay exact whenever the result fits
+        assert_eq!(mul_q(Q(i64::MAX, 2), Q(2, i64::MAX)), Q::one());
+        assert_eq!(q_div((i64::MAX, 1), (i64::MAX, 1)), (1, 1));
+    }
 }
@@ -1,6 +1,6 @@
 //! Property-based tests for arith
 
-use arith::{add_q, div_q, gcd_i64, mul_q, normalize_rat, sub_q, Q};
+use arith::{add_q, div_q, gcd_i64, mul_q, normalize_rat, sub_q, BigInt, Q};
 use proptest::prelude::*;
 
 fn small_int() -> impl Strategy<Value = i64> {
@@ -110,4 +110,31 @@ proptest! {
             prop_assert_eq!(gcd_i64(q.0.abs(), q.1), 1);
         }
     }
+
+    #[test]
+    fn prop_bigint_matches_i128(a in any::<i64>(), b in any::<i64>()) {
+        let (ba, bb) = (BigInt::from(a), BigInt::from(b));
+        let (wa, wb) = (a as i128, b as i128);
+        prop_assert_eq!((&ba + &bb).to_i128(), Some(wa + wb));
+        prop_assert_eq!((&ba - &bb).to_i128(), Some(wa - wb));
+        prop_assert_eq!((&ba * &bb).to_i128(), Some(wa * wb));
+        if b != 0 {
+            let (q, r) = ba.div_rem(&bb);
+            prop_assert_eq!(q.to_i128(), Some(wa / wb));
+            prop_assert_eq!(r.to_i128(), Some(wa % wb));
+        }
+        prop_assert_eq!(ba.cmp(&bb), a.cmp(&b));
+    }
+
+    #[test]
+    fn prop_bigint_div_rem_identity(a in any::<i128>(), b in any::<i64>(), c in 1i64..1000) {
+        // Multi-limb divisor exercises the shift-subtract path
+        let ba = &BigInt::from(a) * &BigInt::from(a);
+        let bb = &BigInt::from(b) * &BigInt::from(c);
+        if !bb.is_zero() {
+            let (q, r) = ba.div_rem(&bb);
+            prop_assert_eq!(&(&q * &bb) + &r, ba);
+            prop_assert!(r.abs() < bb.abs());
+        }
+    }
 }
@@ -2,6 +2,7 @@
 //! Phase I implementation: domain-aware assumptions with negative properties.
 #![deny(warnings)]
 
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -83,6 +84,51 @@ impl Context {
 
 // Default is derived; `new()` is provided for explicit construction convenience.
 
+/// Anything that answers property queries: a `Context`, or a `Recorder`
+/// around one.
+pub trait Facts {
+    fn has(&self, sym: &str, prop: Prop) -> Truth;
+}
+
+impl Facts for Context {
+    fn has(&self, sym: &str, prop: Prop) -> Truth {
+        Context::has(self, sym, prop)
+    }
+}
+
+/// Answers queries from a `Context` and logs the `(symbol, property)` facts
+/// that answered True, to report which assumptions a computation relied on.
+/// The log belongs to the recorder, so the context stays shared and unchanged.
+#[derive(Debug)]
+pub struct Recorder<'a> {
+    ctx: &'a Context,
+    log: RefCell<Vec<(String, Prop)>>,
+}
+
+impl<'a> Recorder<'a> {
+    pub fn new(ctx: &'a Context) -> Self {
+        Recorder { ctx, log: RefCell::new(Vec::new()) }
+    }
+
+    /// The facts that answered True, in first-query order without duplicates.
+    pub fn into_facts(self) -> Vec<(String, Prop)> {
+        self.log.into_inner()
+    }
+}
+
+impl Facts for Recorder<'_> {
+    fn has(&self, sym: &str, prop: Prop) -> Truth {
+        let t = self.ctx.has(sym, prop);
+        if t == Truth::True {
+            let mut log = self.log.borrow_mut();
+            if !log.iter().any(|(s, p)| s == sym && *p == prop) {
+                log.push((sym.to_string(), prop));
+            }
+        }
+        t
+    }
+}
+
 fn derive_props(base: &HashSet<Prop>) -> HashSet<Prop> {
     let mut out = base.clone();
     let mut changed = true;
@@ -203,4 +249,18 @@ mod tests {
         assert!(matches!(ctx.has("x", Prop::Nonzero), Truth::True));
         assert!(matches!(ctx.has("x", Prop::Real), Truth::True));
     }
+
+    #[test]
+    fn recorder_logs_true_answers() {
+        let mut ctx = Context::new();
+        ctx.assume("x", Prop::Positive);
+        let rec = Recorder::new(&ctx);
+        // Queries on the context itself are not logged
+        assert!(matches!(ctx.has("x", Prop::Real), Truth::True));
+        assert!(matches!(Facts::has(&rec, "x", Prop::Nonzero), Truth::True));
+        assert!(matches!(Facts::has(&rec, "y", Prop::Real), Truth::Unknown));
+        assert!(matches!(Facts::has(&rec, "x", Prop::Nonzero), Truth::True));
+        assert_eq!(rec.into_facts(), vec![("x".to_string(), Prop::Nonzero)]);
+        assert!(Recorder::new(&ctx).into_facts().is_empty());
+    }
 }
@@ -13,7 +13,7 @@
 use crate::evaluate::fold_constants;
 use crate::integrate::integrate;
 use crate::limit::{limit, LimitPoint as LimitPt, LimitResult as LimitRes};
-use expr_core::{ExprId, Op, Payload, Store};
+use expr_core::{ExprId, Store};
 use simplify::simplify;
 
 /// Represents the bounds of a definite integral
@@ -132,11 +132,10 @@ pub fn definite_integrate(
 
             match (limit_upper, limit_lower) {
                 (LimitRes::Finite(v1), LimitRes::Finite(v2)) => {
-                    use arith::q_sub;
-                    let diff = q_sub(v1, v2);
-                    let result_expr =
-                        if diff.1 == 1 { store.int(diff.0) } else { store.rat(diff.0, diff.1) };
-                    Some(DefiniteResult::Symbolic(result_expr))
+                    use arith::{BigRat, Q};
+                    // Exact difference: two in-range limits can still differ by more than i64
+                    let diff = &BigRat::from(Q(v1.0, v1.1)) - &BigRat::from(Q(v2.0, v2.1));
+                    Some(DefiniteResult::Symbolic(store.bigrat(diff)))
                 }
                 (LimitRes::PositiveInfinity, _) | (_, LimitRes::NegativeInfinity) => {
                     Some(DefiniteResult::Divergent)
@@ -151,40 +150,12 @@ pub fn definite_integrate(
 /// Substitutes a value for a variable in an expression
 ///
 /// This is a helper for evaluating definite integrals at bounds.
-/// Creates a new expression with all occurrences of `var` replaced by `value`.
-fn substitute(store: &mut Store, expr: ExprId, var: &str, value: ExprId) -> ExprId {
-    match (&store.get(expr).op, &store.get(expr).payload) {
-        (Op::Symbol, Payload::Sym(s)) if s == var => value,
-        (Op::Integer, _) | (Op::Rational, _) => expr,
-        _ => {
-            // Recursively substitute in children
-            let old_children = store.get(expr).children.clone();
-            let children: Vec<ExprId> =
-                old_children.iter().map(|&child| substitute(store, child, var, value)).collect();
-
-            // Rebuild expression with substituted children
-            match &store.get(expr).op {
-                Op::Add => store.add(children),
-                Op::Mul => store.mul(children),
-                Op::Pow => {
-                    if children.len() == 2 {
-                        store.pow(children[0], children[1])
-                    } else {
-                        expr
-                    }
-                }
-                Op::Function => {
-                    let fname = if let Payload::Func(fname) = &store.get(expr).payload {
-                        fname.clone()
-                    } else {
-                        return expr;
-                    };
-                    store.func(&fname, children)
-                }
-                _ => expr,
-            }
-        }
-    }
+/// Creates a new expression with all free occurrences of `var` replaced by `value`.
+/// Occurrences bound by a held sum, product, definite integral or lambda are left
+/// alone; a held derivative (or indefinite integral) in `var` itself, evaluated at a
+/// non-symbol, becomes `Subs(expr, var, value)` (see `Store::subs`).
+pub(crate) fn substitute(store: &mut Store, expr: ExprId, var: &str, value: ExprId) -> ExprId {
+    store.subs(expr, var, value)
 }
 
 /// Checks if a definite integral is improper (has infinite bounds or discontinuities)
@@ -203,6 +174,7 @@ pub fn is_improper(lower: &Bound, upper: &Bound) -> bool {
 mod tests {
     use super::*;
     use crate::evaluate::try_eval_constant;
+    use expr_core::{Op, Payload};
 
     #[test]
     fn test_definite_integral_polynomial() {
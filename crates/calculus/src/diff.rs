@@ -3,6 +3,7 @@
 use expr_core::{ExprId, Op, Payload, Store};
 use simplify::simplify;
 use special::SpecialFunction;
+use std::collections::BTreeSet;
 
 /// Differentiate expression `id` with respect to symbol `var`.
 /// Supported: Add (linearity), Mul (product rule), Pow with integer exponent (chain rule).
@@ -23,7 +24,7 @@ pub fn diff(store: &mut Store, id: ExprId, var: &str) -> ExprId {
 /// Internal implementation of differentiation (without caching).
 fn diff_impl(store: &mut Store, id: ExprId, var: &str) -> ExprId {
     match store.get(id).op {
-        Op::Integer | Op::Rational => store.int(0),
+        Op::Integer | Op::Rational | Op::Float | Op::Constant => store.int(0),
         Op::Symbol => match &store.get(id).payload {
             Payload::Sym(s) if s == var => store.int(1),
             _ => store.int(0),
@@ -168,8 +169,11 @@ fn diff_impl(store: &mut Store, id: ExprId, var: &str) -> ExprId {
                 return simplify(store, out);
             }
 
+            if fname == "Subs" && args.len() == 3 {
+                return diff_subs(store, id, var);
+            }
             if args.len() != 1 {
-                return store.int(0);
+                return diff_applied(store, id, var);
             }
             let u = args[0];
             let du = diff(store, u, var);
@@ -292,7 +296,33 @@ fn diff_impl(store: &mut Store, id: ExprId, var: &str) -> ExprId {
                         store.int(0)
                     }
                 }
-                _ => store.int(0),
+                // The variable is real, so the parts commute with d/dx
+                "re" | "im" | "conjugate" => store.func(&fname, vec![du]),
+                "abs" => {
+                    // d/dx |u| = (re(u) re(u') + im(u) im(u')) / |u|
+                    let (a, b, da, db) = complex_parts(store, u, du);
+                    let t1 = store.mul(vec![a, da]);
+                    let t2 = store.mul(vec![b, db]);
+                    let num = store.add(vec![t1, t2]);
+                    let abs_u = store.func("abs", vec![u]);
+                    let minus_one = store.int(-1);
+                    let inv = store.pow(abs_u, minus_one);
+                    store.mul(vec![num, inv])
+                }
+                "arg" => {
+                    // d/dx arg(u) = (re(u) im(u') - im(u) re(u')) / |u|^2
+                    let (a, b, da, db) = complex_parts(store, u, du);
+                    let t1 = store.mul(vec![a, db]);
+                    let minus_one = store.int(-1);
+                    let t2 = store.mul(vec![minus_one, b, da]);
+                    let num = store.add(vec![t1, t2]);
+                    let abs_u = store.func("abs", vec![u]);
+                    let minus_two = store.int(-2);
+                    let inv = store.pow(abs_u, minus_two);
+                    store.mul(vec![num, inv])
+                }
+                // Undefined function: the derivative stays symbolic
+                _ => return diff_applied(store, id, var),
             };
             simplify(store, out)
         }
@@ -311,6 +341,151 @@ fn diff_impl(store: &mut Store, id: ExprId, var: &str) -> ExprId {
             let pw = store.piecewise(pairs);
             simplify(store, pw)
         }
+        // Truth values are locally constant wherever they are defined
+        Op::Eq | Op::Lt | Op::Le | Op::And | Op::Or | Op::Not => store.int(0),
+        Op::Derivative | Op::Integral | Op::Sum | Op::Product => diff_held(store, id, var),
+        // Pointwise: d/dx (t -> f(t, x)) = t -> df/dx (the bound name never equals `var`)
+        Op::Lambda => match store.lambda_parts(id) {
+            Some((t, body)) => {
+                let t = t.to_string();
+                let d = diff(store, body, var);
+                store.lambda(&t, d)
+            }
+            None => store.int(0),
+        },
+    }
+}
+
+/// Chain rule for an applied undefined function `f(a1, ..., an)`.
+///
+/// An argument that is the variable itself (and occurs once) gives the partial
+/// derivative `Derivative(f(..., x, ...), x)`; any other argument `a` depending on
+/// `var` gives `Subs(Derivative(f(..., _xi_i, ...), _xi_i), _xi_i, a) * a'`,
+/// with `_xi_i` renamed if the arguments already use it.
+fn diff_applied(store: &mut Store, id: ExprId, var: &str) -> ExprId {
+    if !store.has_free_symbol(id, var) {
+        return store.int(0);
+    }
+    let n = store.get(id);
+    let (name, args) = match &n.payload {
+        Payload::Func(s) => (s.clone(), n.children.clone()),
+        _ => return store.int(0),
+    };
+    let x = store.sym(var);
+    let taken = store.free_symbols(id);
+    let mut terms = Vec::new();
+    for (i, &a) in args.iter().enumerate() {
+        if !store.has_free_symbol(a, var) {
+            continue;
+        }
+        if a == x && args.iter().filter(|&&b| b == x).count() == 1 {
+            terms.push(store.derivative(id, var, 1));
+            continue;
+        }
+        let xi_name = fresh_name(&format!("_xi_{}", i + 1), &taken);
+        let xi = store.sym(xi_name.as_str());
+        let mut at_xi = args.clone();
+        at_xi[i] = xi;
+        let f_xi = store.func(name.as_str(), at_xi);
+        let d = store.derivative(f_xi, &xi_name, 1);
+        let outer = store.func("Subs", vec![d, xi, a]);
+        let da = diff(store, a, var);
+        terms.push(store.mul(vec![outer, da]));
+    }
+    let sum = store.add(terms);
+    simplify(store, sum)
+}
+
+/// `base`, or `base_1`, `base_2`, ... : the first name not in `taken`.
+pub(crate) fn fresh_name(base: &str, taken: &BTreeSet<String>) -> String {
+    std::iter::once(base.to_string())
+        .chain((1..).map(|i| format!("{base}_{i}")))
+        .find(|s| !taken.contains(s))
+        .expect("unbounded supply of names")
+}
+
+/// re(u), im(u), re(u'), im(u') for the `abs` and `arg` rules.
+fn complex_parts(store: &mut Store, u: ExprId, du: ExprId) -> (ExprId, ExprId, ExprId, ExprId) {
+    (
+        store.func("re", vec![u]),
+        store.func("im", vec![u]),
+        store.func("re", vec![du]),
+        store.func("im", vec![du]),
+    )
+}
+
+/// d/dvar Subs(e, x, p) = Subs(de/dx, x, p) * p' (+ Subs(de/dvar, x, p) when
+/// `var` also occurs free in `e`).
+fn diff_subs(store: &mut Store, id: ExprId, var: &str) -> ExprId {
+    let args = store.get(id).children.clone();
+    let (e, xs, p) = (args[0], args[1], args[2]);
+    let x = match &store.get(xs).payload {
+        Payload::Sym(s) => s.clone(),
+        _ => return store.derivative(id, var, 1),
+    };
+    let mut terms = Vec::new();
+    if store.has_free_symbol(p, var) {
+        let de = diff(store, e, &x);
+        let outer = store.func("Subs", vec![de, xs, p]);
+        let dp = diff(store, p, var);
+        terms.push(store.mul(vec![outer, dp]));
+    }
+    if x != var && store.has_free_symbol(e, var) {
+        let de = diff(store, e, var);
+        terms.push(store.func("Subs", vec![de, xs, p]));
+    }
+    let sum = store.add(terms);
+    simplify(store, sum)
+}
+
+/// Differentiate a held node, staying held where no rule applies.
+fn diff_held(store: &mut Store, id: ExprId, var: &str) -> ExprId {
+    if !store.has_free_symbol(id, var) {
+        return store.int(0);
+    }
+    let n = store.get(id);
+    let (op, children) = (n.op.clone(), n.children.clone());
+    let x = store.held_var(id).map(str::to_string);
+    match op {
+        // d/dx Integral(f, x) = f; otherwise differentiate under the integral sign
+        Op::Integral if children.len() == 2 => {
+            if x.as_deref() == Some(var) {
+                return children[0];
+            }
+            let df = diff(store, children[0], var);
+            let x = x.unwrap_or_default();
+            store.integral(df, &x, None)
+        }
+        // Leibniz rule: f(b) b' - f(a) a' + Integral(df/dvar, (x, a, b))
+        Op::Integral => {
+            let (f, a, b) = (children[0], children[2], children[3]);
+            let x = x.unwrap_or_default();
+            let mut terms = Vec::new();
+            for (bound, sign) in [(b, 1), (a, -1)] {
+                let db = diff(store, bound, var);
+                if store.get(db).payload != Payload::Int(0) {
+                    let fb = crate::definite::substitute(store, f, &x, bound);
+                    let s = store.int(sign);
+                    terms.push(store.mul(vec![s, fb, db]));
+                }
+            }
+            if x != var && store.has_free_symbol(f, var) {
+                let df = diff(store, f, var);
+                terms.push(store.integral(df, &x, Some((a, b))));
+            }
+            let sum = store.add(terms);
+            simplify(store, sum)
+        }
+        // Sums with fixed limits differentiate termwise
+        Op::Sum
+            if !store.has_free_symbol(children[2], var)
+                && !store.has_free_symbol(children[3], var) =>
+        {
+            let dt = diff(store, children[0], var);
+            let k = x.unwrap_or_default();
+            store.sum(dt, &k, children[2], children[3])
+        }
+        _ => store.derivative(id, var, 1),
     }
 }
 
@@ -369,7 +544,7 @@ mod tests {
         let x = st.sym("x");
         let fx = st.func("unknown", vec![x]);
         let d = diff(&mut st, fx, "x");
-        assert_eq!(d, st.int(0));
+        assert_eq!(d, st.derivative(fx, "x", 1));
     }
 
     #[test]
@@ -379,7 +554,7 @@ mod tests {
         let y = st.sym("y");
         let f = st.func("f", vec![x, y]);
         let d = diff(&mut st, f, "x");
-        assert_eq!(d, st.int(0));
+        assert_eq!(d, st.derivative(f, "x", 1));
     }
 
     #[test]
@@ -618,4 +793,89 @@ mod tests {
         let result = st.to_string(d);
         assert!(result.contains("sinh"));
     }
+
+    #[test]
+    fn diff_complex_parts() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let z = st.sym("z");
+        let i = st.constant(expr_core::Constant::I);
+        // re(x + I z) differentiates to re(1) = 1
+        let iz = st.mul(vec![i, z]);
+        let w = st.add(vec![x, iz]);
+        let re = st.func("re", vec![w]);
+        assert_eq!(diff(&mut st, re, "x"), st.int(1));
+        let im = st.func("im", vec![w]);
+        assert_eq!(diff(&mut st, im, "x"), st.int(0));
+        // d/dx |w| = re(w) / |w| with w = x + I z
+        let abs = st.func("abs", vec![w]);
+        let d = diff(&mut st, abs, "x");
+        let m_one = st.int(-1);
+        let inv = st.pow(abs, m_one);
+        let expected = st.mul(vec![re, inv]);
+        let expected = simplify(&mut st, expected);
+        assert_eq!(d, expected);
+        // d/dx arg(w) = -im(w) / |w|^2
+        let arg = st.func("arg", vec![w]);
+        let d = diff(&mut st, arg, "x");
+        let m_two = st.int(-2);
+        let inv2 = st.pow(abs, m_two);
+        let expected = st.mul(vec![m_one, im, inv2]);
+        let expected = simplify(&mut st, expected);
+        assert_eq!(d, expected);
+    }
+
+    #[test]
+    fn diff_undefined_function() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        // f'(x) stays symbolic and higher derivatives merge
+        let fx = st.func("f", vec![x]);
+        let d = diff(&mut st, fx, "x");
+        assert_eq!(d, st.derivative(fx, "x", 1));
+        let d2 = diff(&mut st, d, "x");
+        assert_eq!(d2, st.derivative(fx, "x", 2));
+        // Partial derivatives of f(x, y)
+        let fxy = st.func("f", vec![x, y]);
+        let dy = diff(&mut st, fxy, "y");
+        assert_eq!(st.to_string(dy), "Derivative(f(x, y), y)");
+        assert_eq!(diff(&mut st, fxy, "z"), st.int(0));
+    }
+
+    #[test]
+    fn diff_undefined_chain_rule() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let two = st.int(2);
+        let x2 = st.pow(x, two);
+        // d/dx f(x^2) = Subs(Derivative(f(_xi_1), _xi_1), _xi_1, x^2) * 2x
+        let f = st.func("f", vec![x2]);
+        let d = diff(&mut st, f, "x");
+        let xi = st.sym("_xi_1");
+        let fxi = st.func("f", vec![xi]);
+        let dxi = st.derivative(fxi, "_xi_1", 1);
+        let outer = st.func("Subs", vec![dxi, xi, x2]);
+        let two_x = st.mul(vec![two, x]);
+        let expected = st.mul(vec![outer, two_x]);
+        assert_eq!(simplify(&mut st, expected), d);
+        // f(g(x)) keeps g'(x) symbolic too
+        let gx = st.func("g", vec![x]);
+        let fg = st.func("f", vec![gx]);
+        let d = diff(&mut st, fg, "x");
+        let s = st.to_string(d);
+        assert!(s.contains("Subs(Derivative(f(_xi_1), _xi_1), _xi_1, g(x))"));
+        assert!(s.contains("Derivative(g(x), x)"));
+        // Evaluating the Subs at a concrete function: f = sin
+        let sin_xi = st.func("sin", vec![xi]);
+        let dsin = st.derivative(sin_xi, "_xi_1", 1);
+        let at = st.func("Subs", vec![dsin, xi, x2]);
+        let cos_x2 = st.func("cos", vec![x2]);
+        assert_eq!(crate::doit(&mut st, at), cos_x2);
+        // A user symbol named _xi_1 is not captured by the dummy
+        let h = st.func("h", vec![x2, xi]);
+        let d = diff(&mut st, h, "x");
+        let s = st.to_string(d);
+        assert!(s.contains("Subs(Derivative(h(_xi_1_1, _xi_1), _xi_1_1), _xi_1_1, x^2)"), "{s}");
+    }
 }
@@ -0,0 +1,261 @@
+//! Evaluation of held Derivative/Integral/Sum/Product nodes and of
+//! `Subs(e, x, p)` (the value of `e` at `x = p`).
+//!
+//! `doit` evaluates held nodes bottom-up. A node whose evaluation fails
+//! (no closed-form antiderivative, unknown sum) stays held, with its
+//! children evaluated, so nothing is lost.
+
+use crate::definite::{definite_integrate, substitute, Bound, DefiniteResult};
+use crate::diff::diff;
+use crate::integrate::integrate;
+use expr_core::{Constant, ExprId, Op, Payload, Store};
+use simplify::simplify;
+
+/// Sums and products over at most this many integers are expanded term by term.
+const MAX_EXPLICIT_TERMS: i64 = 1000;
+
+/// Evaluate every held node in `id`, keeping those that cannot be evaluated.
+/// Shared subtrees are evaluated once.
+pub fn doit(store: &mut Store, id: ExprId) -> ExprId {
+    store.map_bottom_up(id, eval_node)
+}
+
+/// Evaluate `id` itself if it is held; its children are already evaluated.
+fn eval_node(store: &mut Store, id: ExprId) -> ExprId {
+    let n = store.get(id);
+    let (op, payload, children) = (n.op.clone(), n.payload.clone(), n.children.clone());
+    let var = store.held_var(id).map(str::to_string);
+    match (op, var) {
+        (Op::Derivative, Some(x)) => {
+            let Payload::Int(order) = store.get(children[2]).payload else {
+                return id;
+            };
+            let mut f = children[0];
+            for _ in 0..order {
+                f = diff(store, f, &x);
+            }
+            simplify(store, f)
+        }
+        (Op::Integral, Some(x)) => {
+            let bounds = (children.len() == 4).then(|| (children[2], children[3]));
+            integrate_or_hold(store, children[0], &x, bounds)
+        }
+        (Op::Sum, Some(k)) => eval_sum(store, children[0], &k, children[2], children[3])
+            .unwrap_or_else(|| store.sum(children[0], &k, children[2], children[3])),
+        (Op::Product, Some(k)) => eval_product(store, children[0], &k, children[2], children[3])
+            .unwrap_or_else(|| store.product(children[0], &k, children[2], children[3])),
+        (Op::Function, _) if payload == Payload::Func("Subs".into()) && children.len() == 3 => {
+            match store.get(children[1]).payload.clone() {
+                Payload::Sym(x) => {
+                    let e = substitute(store, children[0], &x, children[2]);
+                    simplify(store, e)
+                }
+                _ => id,
+            }
+        }
+        _ => id,
+    }
+}
+
+/// Integrate `f` in `x` (over `bounds` if given), returning a held
+/// `Integral` when no closed form is found.
+pub fn integrate_or_hold(
+    store: &mut Store,
+    f: ExprId,
+    x: &str,
+    bounds: Option<(ExprId, ExprId)>,
+) -> ExprId {
+    let Some((a, b)) = bounds else {
+        return integrate(store, f, x).unwrap_or_else(|| store.integral(f, x, None));
+    };
+    let to_bound = |st: &Store, e: ExprId| match st.as_constant(e) {
+        Some(Constant::Infinity) => Bound::PosInfinity,
+        Some(Constant::NegativeInfinity) => Bound::NegInfinity,
+        _ => Bound::Finite(e),
+    };
+    let (lower, upper) = (to_bound(store, a), to_bound(store, b));
+    match definite_integrate(store, f, x, lower, upper) {
+        Some(DefiniteResult::Symbolic(v)) => v,
+        _ => store.integral(f, x, Some((a, b))),
+    }
+}
+
+/// Integer value of a limit, if it is a small integer literal.
+fn int_value(store: &Store, id: ExprId) -> Option<i64> {
+    match store.get(id).payload {
+        Payload::Int(k) => Some(k),
+        _ => None,
+    }
+}
+
+/// Terms `t(k)` for `k = a..=b` when both limits are integers and the range is small.
+fn explicit_terms(
+    store: &mut Store,
+    t: ExprId,
+    k: &str,
+    a: ExprId,
+    b: ExprId,
+) -> Option<Vec<ExprId>> {
+    let (a, b) = (int_value(store, a)?, int_value(store, b)?);
+    if b.checked_sub(a)? >= MAX_EXPLICIT_TERMS {
+        return None;
+    }
+    Some(
+        (a..=b)
+            .map(|i| {
+                let v = store.int(i);
+                substitute(store, t, k, v)
+            })
+            .collect(),
+    )
+}
+
+fn eval_sum(store: &mut Store, t: ExprId, k: &str, a: ExprId, b: ExprId) -> Option<ExprId> {
+    if let Some(terms) = explicit_terms(store, t, k, a, b) {
+        let s = store.add(terms);
+        return Some(simplify(store, s));
+    }
+    let one = store.int(1);
+    let m1 = store.int(-1);
+    let neg_a = store.mul(vec![m1, a]);
+    // Number of terms b - a + 1
+    let count = store.add(vec![b, neg_a, one]);
+    if !store.has_free_symbol(t, k) {
+        let s = store.mul(vec![count, t]);
+        return Some(simplify(store, s));
+    }
+    // Shift the index so the sum starts at 0: t(k + a) for k = 0..=b - a
+    let ks = store.sym(k);
+    let shifted_k = store.add(vec![ks, a]);
+    let shifted = substitute(store, t, k, shifted_k);
+    let shifted = simplify(store, shifted);
+    let upper = store.add(vec![b, neg_a]);
+    let upper = simplify(store, upper);
+    let zero = store.int(0);
+    let s = summation::sum(store, shifted, k, zero, upper)?;
+    Some(simplify(store, s))
+}
+
+fn eval_product(store: &mut Store, t: ExprId, k: &str, a: ExprId, b: ExprId) -> Option<ExprId> {
+    if let Some(factors) = explicit_terms(store, t, k, a, b) {
+        let p = store.mul(factors);
+        return Some(simplify(store, p));
+    }
+    if !store.has_free_symbol(t, k) {
+        let one = store.int(1);
+        let m1 = store.int(-1);
+        let neg_a = store.mul(vec![m1, a]);
+        let count = store.add(vec![b, neg_a, one]);
+        let count = simplify(store, count);
+        let p = store.pow(t, count);
+        return Some(simplify(store, p));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doit_evaluates_held_nodes() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let three = st.int(3);
+        let x3 = st.pow(x, three);
+        // Derivative(x^3, x, 2) = 6x
+        let d = st.derivative(x3, "x", 2);
+        let six = st.int(6);
+        let six_x = st.mul(vec![six, x]);
+        assert_eq!(doit(&mut st, d), six_x);
+        // Integral(x^3, (x, 0, 2)) = 4
+        let zero = st.int(0);
+        let two = st.int(2);
+        let i = st.integral(x3, "x", Some((zero, two)));
+        assert_eq!(doit(&mut st, i), st.int(4));
+        // Sum(k, (k, 1, 10)) = 55 and Product(k, (k, 1, 5)) = 120
+        let k = st.sym("k");
+        let one = st.int(1);
+        let ten = st.int(10);
+        let s = st.sum(k, "k", one, ten);
+        assert_eq!(doit(&mut st, s), st.int(55));
+        let five = st.int(5);
+        let p = st.product(k, "k", one, five);
+        assert_eq!(doit(&mut st, p), st.int(120));
+    }
+
+    #[test]
+    fn doit_symbolic_limits() {
+        let mut st = Store::new();
+        let k = st.sym("k");
+        let n = st.sym("n");
+        let one = st.int(1);
+        // Sum(k, (k, 1, n)) = n(n+1)/2, checked at n = 7
+        let s = st.sum(k, "k", one, n);
+        let closed = doit(&mut st, s);
+        assert!(!st.is_held(closed));
+        let seven = st.int(7);
+        let at7 = substitute(&mut st, closed, "n", seven);
+        assert_eq!(simplify(&mut st, at7), st.int(28));
+        // Product of a constant is a power
+        let c = st.sym("c");
+        let p = st.product(c, "k", one, n);
+        assert_eq!(doit(&mut st, p), st.pow(c, n));
+    }
+
+    #[test]
+    fn failures_stay_held() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        // exp(x^2) has no elementary antiderivative
+        let two = st.int(2);
+        let x2 = st.pow(x, two);
+        let e = st.func("exp", vec![x2]);
+        let i = st.integral(e, "x", None);
+        assert_eq!(doit(&mut st, i), i);
+        assert_eq!(integrate_or_hold(&mut st, e, "x", None), i);
+        // Held pieces inside larger expressions survive, the rest is evaluated
+        let d = st.derivative(x2, "x", 1);
+        let sum = st.add(vec![i, d]);
+        let out = doit(&mut st, sum);
+        let two_x = st.mul(vec![two, x]);
+        assert_eq!(out, st.add(vec![i, two_x]));
+        // d/dx of the held integral recovers the integrand
+        assert_eq!(diff(&mut st, i, "x"), e);
+    }
+
+    #[test]
+    fn shared_subtrees_are_evaluated_once() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let two = st.int(2);
+        let x2 = st.pow(x, two);
+        // f(f(...f(d, d)..., ...), ...) with d = Derivative(x^2, x): 2^64 paths
+        // to the leaf, 65 distinct nodes
+        let mut e = st.derivative(x2, "x", 1);
+        let mut expected = st.mul(vec![two, x]);
+        for _ in 0..64 {
+            e = st.func("f", vec![e, e]);
+            expected = st.func("f", vec![expected, expected]);
+        }
+        assert_eq!(doit(&mut st, e), expected);
+    }
+
+    #[test]
+    fn diff_of_held_definite_integral() {
+        let mut st = Store::new();
+        let t = st.sym("t");
+        let x = st.sym("x");
+        let two = st.int(2);
+        let t2 = st.pow(t, two);
+        let e = st.func("exp", vec![t2]);
+        let zero = st.int(0);
+        // d/dx Integral(exp(t^2), (t, 0, x)) = exp(x^2)
+        let i = st.integral(e, "t", Some((zero, x)));
+        let d = diff(&mut st, i, "x");
+        let x2 = st.pow(x, two);
+        assert_eq!(d, st.func("exp", vec![x2]));
+        // Bound variables are not differentiated
+        assert_eq!(diff(&mut st, i, "t"), zero);
+    }
+}
@@ -4,7 +4,7 @@
 //! to their concrete numeric values. This is essential for definite
 //! integrals and numerical computations.
 
-use arith::{q_add, q_mul};
+use arith::{checked_rat_add, checked_rat_mul};
 use expr_core::{ExprId, Op, Payload, Store};
 
 /// Attempts to evaluate an expression to a rational constant
@@ -27,7 +27,7 @@ pub fn try_eval_constant(store: &Store, expr: ExprId) -> Option<(i64, i64)> {
             let mut sum = (0i64, 1i64);
             for &child in children {
                 let val = try_eval_constant(store, child)?;
-                sum = q_add(sum, val);
+                sum = checked_rat_add(sum, val).ok()?;
             }
             Some(sum)
         }
@@ -36,7 +36,7 @@ pub fn try_eval_constant(store: &Store, expr: ExprId) -> Option<(i64, i64)> {
             let mut product = (1i64, 1i64);
             for &child in children {
                 let val = try_eval_constant(store, child)?;
-                product = q_mul(product, val);
+                product = checked_rat_mul(product, val).ok()?;
             }
             Some(product)
         }
@@ -72,18 +72,14 @@ fn eval_pow(base: (i64, i64), exp: i64) -> Option<(i64, i64)> {
 
     if exp < 0 {
         // base^(-n) = 1 / base^n
-        let pos_pow = eval_pow(base, -exp)?;
+        let pos_pow = eval_pow(base, exp.checked_neg()?)?;
         return Some((pos_pow.1, pos_pow.0)); // Flip numerator and denominator
     }
 
     // Positive integer exponent: multiply base by itself exp times
     let mut result = (1i64, 1i64);
     for _ in 0..exp {
-        result = q_mul(result, base);
-        // Check for overflow (saturating arithmetic in q_mul protects this)
-        if result.0 == i64::MAX || result.1 == i64::MAX {
-            return None; // Overflow
-        }
+        result = checked_rat_mul(result, base).ok()?;
     }
     Some(result)
 }
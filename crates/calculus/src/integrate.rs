@@ -1,7 +1,7 @@
 //! Integration rules (v1, conservative + Phase J: integration by parts).
 
 use crate::diff::diff;
-use arith::{q_div, q_mul, q_sub, Q};
+use arith::{checked_q_div, checked_rat_mul, checked_rat_sub, Q};
 use expr_core::{ExprId, Op, Payload, Store};
 use polys::{expr_to_unipoly, partial_fractions_simple, UniPoly};
 use simplify::simplify;
@@ -31,9 +31,9 @@ fn integrate_impl(store: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
             _ => st.get(id).children.iter().any(|&c| depends_on_var(st, c, var)),
         }
     }
-    // helper: extract numeric coefficient and rest from a product
-    fn split_coeff_mul(st: &mut Store, id: ExprId) -> ((i64, i64), ExprId) {
-        match (&st.get(id).op, &st.get(id).payload) {
+    // helper: extract numeric coefficient and rest from a product (None on overflow)
+    fn split_coeff_mul(st: &mut Store, id: ExprId) -> Option<((i64, i64), ExprId)> {
+        Some(match (&st.get(id).op, &st.get(id).payload) {
             (Op::Integer, Payload::Int(k)) => ((*k, 1), st.int(1)),
             (Op::Rational, Payload::Rat(n, d)) => ((*n, *d), st.int(1)),
             (Op::Mul, _) => {
@@ -43,10 +43,10 @@ fn integrate_impl(store: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
                 for f in children {
                     match (&st.get(f).op, &st.get(f).payload) {
                         (Op::Integer, Payload::Int(k)) => {
-                            coeff = q_mul(coeff, (*k, 1));
+                            coeff = checked_rat_mul(coeff, (*k, 1)).ok()?;
                         }
                         (Op::Rational, Payload::Rat(n, d)) => {
-                            coeff = q_mul(coeff, (*n, *d));
+                            coeff = checked_rat_mul(coeff, (*n, *d)).ok()?;
                         }
                         _ => rest.push(f),
                     }
@@ -55,7 +55,7 @@ fn integrate_impl(store: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
                 (coeff, rest_id)
             }
             _ => ((1, 1), id),
-        }
+        })
     }
     // helper: build coeff * expr
     fn with_coeff(st: &mut Store, coeff: (i64, i64), expr: ExprId) -> ExprId {
@@ -86,6 +86,10 @@ fn integrate_impl(store: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
                 None
             }
         }
+        Op::Float | Op::Constant => {
+            let x = store.sym(var);
+            Some(store.mul(vec![id, x]))
+        }
         Op::Symbol => match &store.get(id).payload {
             Payload::Sym(s) if s == var => {
                 // ∫ x dx = x^2/2
@@ -136,7 +140,7 @@ fn integrate_impl(store: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
                 return Some(res);
             }
             // factor out numeric coefficient
-            let (coeff, rest) = split_coeff_mul(store, id);
+            let (coeff, rest) = split_coeff_mul(store, id)?;
             // f'/f pattern: look for a factor u^{-1} and check remaining equals u' up to numeric factor
             if store.get(rest).op == Op::Mul {
                 let factors = store.get(rest).children.clone();
@@ -166,11 +170,11 @@ fn integrate_impl(store: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
                                 };
                                 // compare to u' up to numeric coefficient
                                 let du = diff(store, u, var);
-                                let (coeff_o, rest_o) = split_coeff_mul(store, others_id);
-                                let (coeff_d, rest_d) = split_coeff_mul(store, du);
+                                let (coeff_o, rest_o) = split_coeff_mul(store, others_id)?;
+                                let (coeff_d, rest_d) = split_coeff_mul(store, du)?;
                                 if rest_o == rest_d {
-                                    let scale = q_div(coeff_o, coeff_d);
-                                    let total = q_mul(coeff, scale);
+                                    let scale = checked_q_div(coeff_o, coeff_d).ok()?;
+                                    let total = checked_rat_mul(coeff, scale).ok()?;
                                     let ln_u = store.func("ln", vec![u]);
                                     return Some(with_coeff(store, total, ln_u));
                                 }
@@ -229,9 +233,10 @@ fn integrate_impl(store: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
                             return Some(ln);
                         } else {
                             // x^(k+1)/(k+1)
-                            let k1 = store.int(k + 1);
-                            let xkp1 = store.pow(base, k1);
-                            let coeff = q_div((1, 1), (k + 1, 1));
+                            let k1 = k.checked_add(1)?;
+                            let k1_expr = store.int(k1);
+                            let xkp1 = store.pow(base, k1_expr);
+                            let coeff = checked_q_div((1, 1), (k1, 1)).ok()?;
                             return Some(with_coeff(store, coeff, xkp1));
                         }
                     }
@@ -284,7 +289,7 @@ fn integrate_impl(store: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
             if a == (0, 1) {
                 return None;
             }
-            let inv_a = q_div((1, 1), a);
+            let inv_a = checked_q_div((1, 1), a).ok()?;
             let res = match fname.as_str() {
                 "exp" => id,
                 "sin" => {
@@ -325,6 +330,25 @@ fn integrate_impl(store: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
             let pw = store.piecewise(pairs);
             Some(simplify(store, pw))
         }
+        // Relations and connectives are not integrands
+        Op::Eq | Op::Lt | Op::Le | Op::And | Op::Or | Op::Not => None,
+        Op::Derivative | Op::Integral | Op::Sum | Op::Product => {
+            if !store.has_free_symbol(id, var) {
+                let x = store.sym(var);
+                return Some(store.mul(vec![id, x]));
+            }
+            // ∫ Derivative(f, x, n) dx = Derivative(f, x, n - 1)
+            let n = store.get(id);
+            if n.op == Op::Derivative && store.held_var(id) == Some(var) {
+                if let Payload::Int(k) = store.get(n.children[2]).payload {
+                    let f = n.children[0];
+                    return Some(store.derivative(f, var, k as u32 - 1));
+                }
+            }
+            None
+        }
+        // A lambda is a function value, not an integrand
+        Op::Lambda => None,
     }
 }
 
@@ -550,9 +574,9 @@ fn integrate_rational(st: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
     let (q, terms) = partial_fractions_simple(&num, &den)?;
 
     // Integrate polynomial quotient q(x) term-wise to expression
-    fn poly_integral_expr(st: &mut Store, p: &UniPoly) -> ExprId {
+    fn poly_integral_expr(st: &mut Store, p: &UniPoly) -> Option<ExprId> {
         if p.is_zero() {
-            return st.int(0);
+            return Some(st.int(0));
         }
         let x = st.sym(&p.var);
         let mut terms_expr: Vec<ExprId> = Vec::new();
@@ -562,7 +586,7 @@ fn integrate_rational(st: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
             }
             // ∫ c x^k dx = c * x^{k+1}/(k+1)
             let k1 = (k as i64) + 1;
-            let coeff = q_div((c.0, c.1), (k1, 1));
+            let coeff = checked_q_div((c.0, c.1), (k1, 1)).ok()?;
             let k1_expr = st.int(k1);
             let pow = st.pow(x, k1_expr);
             let term = if coeff.1 == 1 {
@@ -574,11 +598,11 @@ fn integrate_rational(st: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
             };
             terms_expr.push(term);
         }
-        st.add(terms_expr)
+        Some(st.add(terms_expr))
     }
 
     let mut parts: Vec<ExprId> = Vec::new();
-    let poly_int = poly_integral_expr(st, &q);
+    let poly_int = poly_integral_expr(st, &q)?;
     if !matches!((&st.get(poly_int).op, &st.get(poly_int).payload), (Op::Integer, Payload::Int(0)))
     {
         parts.push(poly_int);
@@ -624,8 +648,8 @@ fn try_u_substitution(st: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
     let children = st.get(id).children.clone();
 
     // Helper: extract numeric coefficient from product
-    fn split_coeff(st: &mut Store, id: ExprId) -> ((i64, i64), ExprId) {
-        match (&st.get(id).op, &st.get(id).payload) {
+    fn split_coeff(st: &mut Store, id: ExprId) -> Option<((i64, i64), ExprId)> {
+        Some(match (&st.get(id).op, &st.get(id).payload) {
             (Op::Integer, Payload::Int(k)) => ((*k, 1), st.int(1)),
             (Op::Rational, Payload::Rat(n, d)) => ((*n, *d), st.int(1)),
             (Op::Mul, _) => {
@@ -633,8 +657,12 @@ fn try_u_substitution(st: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
                 let mut rest = Vec::new();
                 for &c in &st.get(id).children {
                     match (&st.get(c).op, &st.get(c).payload) {
-                        (Op::Integer, Payload::Int(k)) => coeff = q_mul(coeff, (*k, 1)),
-                        (Op::Rational, Payload::Rat(n, d)) => coeff = q_mul(coeff, (*n, *d)),
+                        (Op::Integer, Payload::Int(k)) => {
+                            coeff = checked_rat_mul(coeff, (*k, 1)).ok()?
+                        }
+                        (Op::Rational, Payload::Rat(n, d)) => {
+                            coeff = checked_rat_mul(coeff, (*n, *d)).ok()?
+                        }
                         _ => rest.push(c),
                     }
                 }
@@ -642,7 +670,7 @@ fn try_u_substitution(st: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
                 (coeff, rest_id)
             }
             _ => ((1, 1), id),
-        }
+        })
     }
 
     // Pattern 1: ∫ u^n * u' dx where u is a compound expression
@@ -675,8 +703,8 @@ fn try_u_substitution(st: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
                     if other_factors.len() == 1 { other_factors[0] } else { st.mul(other_factors) };
 
                 // Check if others equals c * du for some constant c
-                let (c_others, rest_others) = split_coeff(st, others);
-                let (c_du, rest_du) = split_coeff(st, du);
+                let (c_others, rest_others) = split_coeff(st, others)?;
+                let (c_du, rest_du) = split_coeff(st, du)?;
 
                 if rest_others == rest_du && c_du.0 != 0 {
                     // Found pattern: ∫ u^n * (c * u') dx
@@ -688,10 +716,14 @@ fn try_u_substitution(st: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
 
                     if let Some(n) = exp_val {
                         if n != -1 {
-                            let n_plus_1_val = n + 1;
+                            let n_plus_1_val = n.checked_add(1)?;
                             let n_plus_1 = st.int(n_plus_1_val);
                             let u_np1 = st.pow(u_base, n_plus_1);
-                            let coeff = q_div(c_others, q_mul(c_du, (n_plus_1_val, 1)));
+                            let coeff = checked_q_div(
+                                c_others,
+                                checked_rat_mul(c_du, (n_plus_1_val, 1)).ok()?,
+                            )
+                            .ok()?;
                             let result = if coeff.1 == 1 {
                                 let c_int = st.int(coeff.0);
                                 st.mul(vec![c_int, u_np1])
@@ -797,12 +829,10 @@ fn try_trig_power_general(st: &mut Store, id: ExprId, var: &str) -> Option<ExprI
     ) -> bool {
         match (&st.get(f).op, &st.get(f).payload) {
             (Op::Integer, Payload::Int(k)) => {
-                *coeff = q_mul(*coeff, (*k, 1));
-                true
+                checked_rat_mul(*coeff, (*k, 1)).map(|c| *coeff = c).is_ok()
             }
             (Op::Rational, Payload::Rat(a, b)) => {
-                *coeff = q_mul(*coeff, (*a, *b));
-                true
+                checked_rat_mul(*coeff, (*a, *b)).map(|c| *coeff = c).is_ok()
             }
             (Op::Function, Payload::Func(_)) => {
                 if is_sin_of_var(st, f, var) {
@@ -823,11 +853,9 @@ fn try_trig_power_general(st: &mut Store, id: ExprId, var: &str) -> Option<ExprI
                         return false;
                     }
                     if is_sin_of_var(st, base, var) {
-                        *m += *k;
-                        true
+                        m.checked_add(*k).map(|v| *m = v).is_some()
                     } else if is_cos_of_var(st, base, var) {
-                        *n += *k;
-                        true
+                        n.checked_add(*k).map(|v| *n = v).is_some()
                     } else {
                         false
                     }
@@ -870,14 +898,14 @@ fn try_trig_power_general(st: &mut Store, id: ExprId, var: &str) -> Option<ExprI
             let bin = binom_u64(k as u64, j as u64)?;
             // Coefficient: (-1)^{j+1} * bin / (n + 2j + 1)
             let sign: i64 = if (j % 2) == 0 { -1 } else { 1 };
-            let mut term_c = q_mul(coeff, (sign * bin, 1));
-            let denom = n + 2 * j + 1;
+            let mut term_c = checked_rat_mul(coeff, (sign * bin, 1)).ok()?;
+            let denom = n.checked_add(2 * j + 1)?;
             if denom == 0 {
                 return None;
             }
-            term_c = q_div(term_c, (denom, 1));
+            term_c = checked_q_div(term_c, (denom, 1)).ok()?;
 
-            let exp_e = st.int(n + 2 * j + 1);
+            let exp_e = st.int(denom);
             let pow_u = st.pow(cosx, exp_e);
             let term = if term_c == (1, 1) {
                 pow_u
@@ -904,14 +932,14 @@ fn try_trig_power_general(st: &mut Store, id: ExprId, var: &str) -> Option<ExprI
             let bin = binom_u64(l as u64, j as u64)?;
             // Coefficient: (-1)^j * bin / (m + 2j + 1)
             let sign: i64 = if (j % 2) == 0 { 1 } else { -1 };
-            let mut term_c = q_mul(coeff, (sign * bin, 1));
-            let denom = m + 2 * j + 1;
+            let mut term_c = checked_rat_mul(coeff, (sign * bin, 1)).ok()?;
+            let denom = m.checked_add(2 * j + 1)?;
             if denom == 0 {
                 return None;
             }
-            term_c = q_div(term_c, (denom, 1));
+            term_c = checked_q_div(term_c, (denom, 1)).ok()?;
 
-            let exp_e = st.int(m + 2 * j + 1);
+            let exp_e = st.int(denom);
             let pow_u = st.pow(sinx, exp_e);
             let term = if term_c == (1, 1) {
                 pow_u
@@ -1037,10 +1065,10 @@ fn try_trig_even_even_product(st: &mut Store, id: ExprId, var: &str) -> Option<E
     for &child in &children {
         match (&st.get(child).op, &st.get(child).payload) {
             (Op::Integer, Payload::Int(n)) => {
-                coeff = q_mul(coeff, (*n, 1));
+                coeff = checked_rat_mul(coeff, (*n, 1)).ok()?;
             }
             (Op::Rational, Payload::Rat(p, q)) => {
-                coeff = q_mul(coeff, (*p, *q));
+                coeff = checked_rat_mul(coeff, (*p, *q)).ok()?;
             }
             (Op::Pow, _) => {
                 let base = st.get(child).children[0];
@@ -1310,12 +1338,12 @@ fn try_general_weierstrass(
     }
 
     // Compute a² - b²
-    let a_sq = q_mul(a, a);
-    let b_sq = q_mul(b, b);
-    let discriminant = q_sub(a_sq, b_sq);
+    let a_sq = checked_rat_mul(a, a).ok()?;
+    let b_sq = checked_rat_mul(b, b).ok()?;
+    let discriminant = checked_rat_sub(a_sq, b_sq).ok()?;
 
     // Only handle a² > b² case (discriminant > 0)
-    if discriminant.0 * discriminant.1 <= 0 {
+    if discriminant.0 <= 0 {
         return None;
     }
 
@@ -1425,8 +1453,10 @@ fn try_atan_pattern(st: &mut Store, id: ExprId, var: &str) -> Option<ExprId> {
         let mut pow_term = None;
         for &child in mul_children {
             match (&st.get(child).op, &st.get(child).payload) {
-                (Op::Integer, Payload::Int(n)) => coef = q_mul(coef, (*n, 1)),
-                (Op::Rational, Payload::Rat(p, q)) => coef = q_mul(coef, (*p, *q)),
+                (Op::Integer, Payload::Int(n)) => coef = checked_rat_mul(coef, (*n, 1)).ok()?,
+                (Op::Rational, Payload::Rat(p, q)) => {
+                    coef = checked_rat_mul(coef, (*p, *q)).ok()?
+                }
                 (Op::Pow, _) => pow_term = Some(child),
                 _ => return None,
             }
@@ -1519,23 +1549,22 @@ fn extract_coeff_and_trig(st: &Store, id: ExprId, var: &str) -> Option<((i64, i6
             for &child in children {
                 match (&st.get(child).op, &st.get(child).payload) {
                     (Op::Integer, Payload::Int(n)) => {
-                        coeff = q_mul(coeff, (*n, 1));
+                        coeff = checked_rat_mul(coeff, (*n, 1)).ok()?;
                     }
                     (Op::Rational, Payload::Rat(p, q)) => {
-                        coeff = q_mul(coeff, (*p, *q));
+                        coeff = checked_rat_mul(coeff, (*p, *q)).ok()?;
                     }
-                    (Op::Function, Payload::Func(fname)) => {
-                        if (fname == "cos" || fname == "sin") && st.get(child).children.len() == 1 {
-                            let arg = st.get(child).children[0];
-                            if matches!((&st.get(arg).op, &st.get(arg).payload), (Op::Symbol, Payload::Sym(s)) if s == var)
-                            {
-                                if trig_fn.is_some() {
-                                    return None; // Multiple trig functions
-                                }
-                                trig_fn = Some(fname.clone());
-                            } else {
-                                return None;
+                    (Op::Function, Payload::Func(fname))
+                        if (fname == "cos" || fname == "sin")
+                            && st.get(child).children.len() == 1 =>
+                    {
+                        let arg = st.get(child).children[0];
+                        if matches!((&st.get(arg).op, &st.get(arg).payload), (Op::Symbol, Payload::Sym(s)) if s == var)
+                        {
+                            if trig_fn.is_some() {
+                                return None; // Multiple trig functions
                             }
+                            trig_fn = Some(fname.clone());
                         } else {
                             return None;
                         }
@@ -1,8 +1,10 @@
 //! Calculus v1 (minimal): structural differentiation for Add/Mul/Pow.
 //! Phase 3: definite integrals, limit evaluation, constant folding, and special functions
 //! Phase 4: symbolic simplification and advanced features
+//! Held Derivative/Integral/Sum/Product nodes are evaluated by `doit`
 mod definite;
 pub mod diff;
+mod doit;
 mod evaluate;
 pub mod integrate;
 pub mod limit;
@@ -13,10 +15,11 @@ mod symbolic_simplify;
 
 pub use definite::{definite_integrate, Bound, DefiniteResult};
 pub use diff::diff;
+pub use doit::{doit, integrate_or_hold};
 pub use evaluate::{fold_constants, try_eval_constant, try_eval_float};
 pub use integrate::integrate;
 pub use limit::{limit, LimitPoint as LimitPointCalc, LimitResult as LimitResultCalc};
-pub use ode::solve_ode_first_order;
+pub use ode::{dsolve, solve_ode_first_order};
 pub use risch::{
     build_tower, detect_extension, is_exponential, is_logarithm, logarithmic_derivative,
     try_integrate_logarithmic, ExtensionType, TowerElement,
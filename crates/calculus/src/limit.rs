@@ -4,7 +4,7 @@
 //! specific values or infinity. Essential for improper integrals and asymptotic analysis.
 
 use crate::evaluate::try_eval_constant;
-use expr_core::{ExprId, Op, Payload, Store};
+use expr_core::{Constant, ExprId, Op, Payload, Store};
 
 /// Point at which to evaluate a limit
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -17,6 +17,18 @@ pub enum LimitPoint {
     NegativeInfinity,
 }
 
+impl LimitPoint {
+    /// Limit point named by an expression: an integer, `oo` or `-oo`.
+    pub fn from_expr(store: &Store, id: ExprId) -> Option<Self> {
+        match (&store.get(id).payload, store.as_constant(id)) {
+            (Payload::Int(k), _) => Some(LimitPoint::Finite(*k)),
+            (_, Some(Constant::Infinity)) => Some(LimitPoint::PositiveInfinity),
+            (_, Some(Constant::NegativeInfinity)) => Some(LimitPoint::NegativeInfinity),
+            _ => None,
+        }
+    }
+}
+
 /// Result of a limit computation
 #[derive(Debug, Clone, PartialEq)]
 pub enum LimitResult {
@@ -30,6 +42,19 @@ pub enum LimitResult {
     Undefined,
 }
 
+impl LimitResult {
+    /// The limit as an expression (`oo`/`-oo` for the infinite cases),
+    /// or None if it is undefined.
+    pub fn to_expr(&self, store: &mut Store) -> Option<ExprId> {
+        match self {
+            LimitResult::Finite((n, d)) => Some(store.rat(*n, *d)),
+            LimitResult::PositiveInfinity => Some(store.constant(Constant::Infinity)),
+            LimitResult::NegativeInfinity => Some(store.constant(Constant::NegativeInfinity)),
+            LimitResult::Undefined => None,
+        }
+    }
+}
+
 /// Computes the limit of an expression as var approaches a point
 ///
 /// Uses algebraic techniques for polynomial and rational expressions.
@@ -52,8 +77,22 @@ pub fn limit(store: &Store, expr: ExprId, var: &str, point: LimitPoint) -> Limit
     }
 }
 
+/// Limit of a constant atom that is itself infinite
+fn constant_limit(store: &Store, expr: ExprId) -> LimitResult {
+    match store.as_constant(expr) {
+        Some(Constant::Infinity) => LimitResult::PositiveInfinity,
+        Some(Constant::NegativeInfinity) => LimitResult::NegativeInfinity,
+        // pi and E are finite but not rational, so there is no `Finite` value for them
+        _ => LimitResult::Undefined,
+    }
+}
+
 /// Computes limit as var → a for finite a
 fn limit_at_finite(store: &Store, expr: ExprId, _var: &str, _a: i64) -> LimitResult {
+    if store.get(expr).op == Op::Constant {
+        return constant_limit(store, expr);
+    }
+
     // For now, simple evaluation by substitution
     // Full implementation would need L'Hôpital's rule for indeterminate forms
 
@@ -178,15 +217,18 @@ fn limit_at_infinity(store: &Store, expr: ExprId, var: &str, positive: bool) ->
                 LimitResult::Undefined
             }
         }
+        Op::Constant => constant_limit(store, expr),
         _ => LimitResult::Undefined,
     }
 }
 
 /// Adds two limit results
 fn add_limits(a: LimitResult, b: LimitResult) -> LimitResult {
-    use arith::q_add;
+    use arith::checked_rat_add;
     match (a, b) {
-        (LimitResult::Finite(v1), LimitResult::Finite(v2)) => LimitResult::Finite(q_add(v1, v2)),
+        (LimitResult::Finite(v1), LimitResult::Finite(v2)) => {
+            checked_rat_add(v1, v2).map_or(LimitResult::Undefined, LimitResult::Finite)
+        }
         (LimitResult::PositiveInfinity, LimitResult::PositiveInfinity) => {
             LimitResult::PositiveInfinity
         }
@@ -206,9 +248,11 @@ fn add_limits(a: LimitResult, b: LimitResult) -> LimitResult {
 
 /// Multiplies two limit results
 fn mul_limits(a: LimitResult, b: LimitResult) -> LimitResult {
-    use arith::q_mul;
+    use arith::checked_rat_mul;
     match (a, b) {
-        (LimitResult::Finite(v1), LimitResult::Finite(v2)) => LimitResult::Finite(q_mul(v1, v2)),
+        (LimitResult::Finite(v1), LimitResult::Finite(v2)) => {
+            checked_rat_mul(v1, v2).map_or(LimitResult::Undefined, LimitResult::Finite)
+        }
         (LimitResult::PositiveInfinity, LimitResult::PositiveInfinity) => {
             LimitResult::PositiveInfinity
         }
@@ -251,11 +295,9 @@ fn pow_limit(base: LimitResult, exp: LimitResult) -> LimitResult {
             // For now, only handle integer exponents
             if e_d == 1 && (0..=10).contains(&e_n) {
                 // Small positive integer exponent
-                let mut result = (1i64, 1i64);
-                for _ in 0..e_n {
-                    result = arith::q_mul(result, (b_n, b_d));
-                }
-                LimitResult::Finite(result)
+                (0..e_n)
+                    .try_fold((1i64, 1i64), |acc, _| arith::checked_rat_mul(acc, (b_n, b_d)))
+                    .map_or(LimitResult::Undefined, LimitResult::Finite)
             } else {
                 LimitResult::Undefined
             }
@@ -299,6 +341,27 @@ mod tests {
         assert_eq!(result, LimitResult::Finite((5, 1)));
     }
 
+    #[test]
+    fn test_limit_with_infinity_atoms() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let oo = st.constant(Constant::Infinity);
+        let noo = st.constant(Constant::NegativeInfinity);
+        assert_eq!(LimitPoint::from_expr(&st, oo), Some(LimitPoint::PositiveInfinity));
+        assert_eq!(LimitPoint::from_expr(&st, noo), Some(LimitPoint::NegativeInfinity));
+        // lim[x→∞] (x + oo) = oo and the result converts back to the atom
+        let sum = st.add(vec![x, oo]);
+        let result = limit(&st, sum, "x", LimitPoint::PositiveInfinity);
+        assert_eq!(result, LimitResult::PositiveInfinity);
+        assert_eq!(result.to_expr(&mut st), Some(oo));
+        let m1 = st.int(-1);
+        let inv = st.pow(x, m1);
+        let result = limit(&st, inv, "x", LimitPoint::from_expr(&st, oo).unwrap());
+        let zero = st.int(0);
+        assert_eq!(result.to_expr(&mut st), Some(zero));
+        assert_eq!(limit(&st, noo, "x", LimitPoint::Finite(0)), LimitResult::NegativeInfinity);
+    }
+
     #[test]
     fn test_limit_variable_at_infinity() {
         let mut st = Store::new();
@@ -9,8 +9,16 @@
 //!
 //! Second-order ODEs:
 //! - Constant coefficients: ay'' + by' + cy = 0
+//!
+//! `dsolve` accepts equations written with an applied function `y(x)` and its held
+//! derivatives instead of a plain `y_var` symbol.
+//!
+//! When a separable or linear equation is recognized but an integral has no closed
+//! form, the solution carries it as a held `Integral` (see `calculus::doit`).
 
-use crate::diff::diff;
+use crate::definite::substitute;
+use crate::diff::{diff, fresh_name};
+use crate::doit::integrate_or_hold;
 use crate::integrate::integrate;
 use expr_core::{ExprId, Op, Payload, Store};
 use simplify::simplify;
@@ -29,12 +37,12 @@ pub fn solve_ode_first_order(
     }
 
     // Try separable form: dy/dx = f(x)g(y)
-    if let Some(solution) = try_separable(store, rhs, y_var, x_var) {
+    if let Some(solution) = try_separable(store, rhs, y_var, x_var, false) {
         return Some(solution);
     }
 
     // Try linear form: dy/dx = -p(x)y + q(x)
-    if let Some(solution) = try_linear(store, rhs, y_var, x_var) {
+    if let Some(solution) = try_linear(store, rhs, y_var, x_var, false) {
         return Some(solution);
     }
 
@@ -47,7 +55,146 @@ pub fn solve_ode_first_order(
         return Some(solution);
     }
 
-    None
+    // No closed form: keep the integrals of a separable or linear equation held
+    if let Some(solution) = try_separable(store, rhs, y_var, x_var, true) {
+        return Some(solution);
+    }
+    try_linear(store, rhs, y_var, x_var, true)
+}
+
+/// Solve an ODE in the unknown function `y` (an applied function such as `y(x)`).
+///
+/// `eq` is an `Eq` node or an expression equal to zero, written in terms of `y(x)`
+/// and `Derivative(y(x), x, n)`. First-order equations that are linear in `y'` go
+/// through `solve_ode_first_order`; second-order linear equations with constant
+/// coefficients through `solve_ode_second_order_constant_coeff`. The solution is
+/// returned in terms of `y(x)`, explicit or implicit as the underlying solver gives it.
+pub fn dsolve(store: &mut Store, eq: ExprId, y: ExprId) -> Option<ExprId> {
+    let yn = store.get(y);
+    let (name, x) = match (&yn.op, &yn.payload, yn.children.as_slice()) {
+        (Op::Function, Payload::Func(f), &[arg]) => match &store.get(arg).payload {
+            Payload::Sym(x) => (f.clone(), x.clone()),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let n = store.get(eq);
+    let expr = if n.op == Op::Eq {
+        let (lhs, rhs) = (n.children[0], n.children[1]);
+        let m1 = store.int(-1);
+        let neg_rhs = store.mul(vec![m1, rhs]);
+        store.add(vec![lhs, neg_rhs])
+    } else {
+        eq
+    };
+    // Placeholders for y, y' and y'' that the equation does not already use
+    let mut taken = store.free_symbols(expr);
+    taken.insert(x.clone());
+    let ys_name = fresh_name("_y", &taken);
+    let d1_name = fresh_name("_dy1", &taken);
+    let d2_name = fresh_name("_dy2", &taken);
+    let names = [ys_name.as_str(), d1_name.as_str(), d2_name.as_str()];
+    let mut order = 0;
+    let expr = replace_unknown(store, expr, y, &x, names, &mut order)?;
+    let expr = simplify(store, expr);
+    // Any other occurrence of the function (e.g. y(2x)) is out of scope
+    let is_unknown = |n: ExprId| matches!(&store.get(n).payload, Payload::Func(f) if *f == name);
+    if store.preorder(expr).any(is_unknown) {
+        return None;
+    }
+
+    let solution = match order {
+        1 => {
+            let a = diff(store, expr, &d1_name);
+            if store.has_free_symbol(a, &d1_name) || a == store.int(0) {
+                return None;
+            }
+            let zero = store.int(0);
+            let b = substitute(store, expr, &d1_name, zero);
+            // y' = -b / a
+            let m1 = store.int(-1);
+            let rhs = if a == store.int(1) {
+                store.mul(vec![m1, b])
+            } else {
+                let inv_a = store.pow(a, m1);
+                store.mul(vec![m1, b, inv_a])
+            };
+            let rhs = simplify(store, rhs);
+            solve_ode_first_order(store, rhs, &ys_name, &x)?
+        }
+        2 => {
+            let a = diff(store, expr, &d2_name);
+            let b = diff(store, expr, &d1_name);
+            let c = diff(store, expr, &ys_name);
+            for coeff in [a, b, c] {
+                if names.into_iter().chain([x.as_str()]).any(|v| store.has_free_symbol(coeff, v)) {
+                    return None;
+                }
+            }
+            // The equation must be exactly a*y'' + b*y' + c*y (homogeneous)
+            let (ys, d1, d2) = (store.sym(&ys_name), store.sym(&d1_name), store.sym(&d2_name));
+            let m1 = store.int(-1);
+            let ta = store.mul(vec![m1, a, d2]);
+            let tb = store.mul(vec![m1, b, d1]);
+            let tc = store.mul(vec![m1, c, ys]);
+            let rest = store.add(vec![expr, ta, tb, tc]);
+            if simplify(store, rest) != store.int(0) {
+                return None;
+            }
+            solve_ode_second_order_constant_coeff(store, a, b, c, &x)?
+        }
+        _ => return None,
+    };
+    let out = substitute(store, solution, &ys_name, y);
+    Some(simplify(store, out))
+}
+
+/// Replace `y` by the symbol `names[0]` and `Derivative(y, x, n)` by `names[n]`,
+/// recording the highest order seen. Fails on derivatives in another variable
+/// or of order above two.
+fn replace_unknown(
+    store: &mut Store,
+    id: ExprId,
+    y: ExprId,
+    x: &str,
+    names: [&str; 3],
+    order: &mut i64,
+) -> Option<ExprId> {
+    if id == y {
+        return Some(store.sym(names[0]));
+    }
+    let n = store.get(id);
+    if n.op == Op::Derivative && n.children[0] == y {
+        if store.held_var(id) != Some(x) {
+            return None;
+        }
+        let Payload::Int(k @ 1..=2) = store.get(n.children[2]).payload else {
+            return None;
+        };
+        *order = (*order).max(k);
+        return Some(store.sym(names[k as usize]));
+    }
+    if n.children.is_empty() {
+        return Some(id);
+    }
+    let (op, payload, old) = (n.op.clone(), n.payload.clone(), n.children.clone());
+    let mut children = Vec::with_capacity(old.len());
+    for c in &old {
+        children.push(replace_unknown(store, *c, y, x, names, order)?);
+    }
+    if children == old {
+        return Some(id);
+    }
+    Some(store.build(op, payload, children))
+}
+
+/// Antiderivative of `f` in `var`; with `hold`, a held integral instead of None.
+fn antiderivative(store: &mut Store, f: ExprId, var: &str, hold: bool) -> Option<ExprId> {
+    if hold {
+        Some(integrate_or_hold(store, f, var, None))
+    } else {
+        integrate(store, f, var)
+    }
 }
 
 /// Try to solve homogeneous ODE: dy/dx = f(y/x)
@@ -161,11 +308,11 @@ fn try_bernoulli(store: &mut Store, rhs: ExprId, y_var: &str, x_var: &str) -> Op
         if let Some(n) = is_power_of_var(store, child, y_var) {
             if n == 1 {
                 // This is the linear term
-                linear_coeff = Some(extract_coeff_of_power(store, child, y_var, 1));
+                linear_coeff = Some(polys::coeff(store, child, y_var, 1));
             } else if n > 1 {
                 // This is the y^n term
                 power_n = Some(n);
-                power_coeff = Some(extract_coeff_of_power(store, child, y_var, n));
+                power_coeff = Some(polys::coeff(store, child, y_var, n));
             }
         }
     }
@@ -207,7 +354,7 @@ fn try_bernoulli(store: &mut Store, rhs: ExprId, y_var: &str, x_var: &str) -> Op
     let linear_rhs = store.add(vec![neg_new_p_v, new_q]);
 
     // Solve linear ODE for v
-    let v_solution = try_linear(store, linear_rhs, &v_var, x_var)?;
+    let v_solution = try_linear(store, linear_rhs, &v_var, x_var, false)?;
 
     // Transform back: y = v^(1/(1-n))
     let exponent = if one_minus_n != 0 {
@@ -260,49 +407,20 @@ fn is_power_of_var(store: &Store, expr: ExprId, var: &str) -> Option<i64> {
     }
 }
 
-/// Extract coefficient of y^n from expression
-fn extract_coeff_of_power(store: &mut Store, expr: ExprId, var: &str, n: i64) -> ExprId {
-    match store.get(expr).op {
-        Op::Symbol => {
-            if matches!(&store.get(expr).payload, Payload::Sym(s) if s == var) && n == 1 {
-                store.int(1)
-            } else {
-                expr
-            }
-        }
-        Op::Pow => {
-            // If this is y^n, coefficient is 1
-            store.int(1)
-        }
-        Op::Mul => {
-            // Extract all factors except y^n
-            let children = &store.get(expr).children;
-            let mut coeff_parts = Vec::new();
-
-            for &child in children {
-                if is_power_of_var(store, child, var).is_none() {
-                    coeff_parts.push(child);
-                }
-            }
-
-            if coeff_parts.is_empty() {
-                store.int(1)
-            } else {
-                store.mul(coeff_parts)
-            }
-        }
-        _ => store.int(1),
-    }
-}
-
 /// Try to solve separable ODE: dy/dx = f(x)g(y)
 /// Solution: ∫ dy/g(y) = ∫ f(x) dx + C
-fn try_separable(store: &mut Store, rhs: ExprId, y_var: &str, x_var: &str) -> Option<ExprId> {
+fn try_separable(
+    store: &mut Store,
+    rhs: ExprId,
+    y_var: &str,
+    x_var: &str,
+    hold: bool,
+) -> Option<ExprId> {
     // Try to factor rhs into f(x) * g(y)
     let (f_x, g_y) = extract_separable_factors(store, rhs, y_var, x_var)?;
 
     // Integrate f(x) with respect to x
-    let integral_f = integrate(store, f_x, x_var)?;
+    let integral_f = antiderivative(store, f_x, x_var, hold)?;
 
     // For g(y), we need 1/g(y) integrated with respect to y
     // This is complex in general, so we handle simple cases
@@ -310,7 +428,7 @@ fn try_separable(store: &mut Store, rhs: ExprId, y_var: &str, x_var: &str) -> Op
     let inv_g = store.pow(g_y, neg_one);
 
     // Try to integrate 1/g(y) with respect to y
-    let integral_inv_g = integrate(store, inv_g, y_var)?;
+    let integral_inv_g = antiderivative(store, inv_g, y_var, hold)?;
 
     // Solution: integral_inv_g = integral_f + C
     // For now, return implicit form: integral_inv_g - integral_f = C
@@ -322,19 +440,25 @@ fn try_separable(store: &mut Store, rhs: ExprId, y_var: &str, x_var: &str) -> Op
 
 /// Try to solve linear ODE: dy/dx + p(x)y = q(x)
 /// Solution: y = e^{-∫p dx} [∫ q e^{∫p dx} dx + C]
-fn try_linear(store: &mut Store, rhs: ExprId, y_var: &str, x_var: &str) -> Option<ExprId> {
+fn try_linear(
+    store: &mut Store,
+    rhs: ExprId,
+    y_var: &str,
+    x_var: &str,
+    hold: bool,
+) -> Option<ExprId> {
     // rhs should be of form: -p(x)y + q(x)
     // We need to extract p(x) and q(x)
 
     let (p_x, q_x) = extract_linear_coefficients(store, rhs, y_var, x_var)?;
 
     // Compute integrating factor: μ = e^{∫p dx}
-    let integral_p = integrate(store, p_x, x_var)?;
+    let integral_p = antiderivative(store, p_x, x_var, hold)?;
     let mu = store.func("exp", vec![integral_p]);
 
     // Compute ∫ q·μ dx
     let q_mu = store.mul(vec![q_x, mu]);
-    let integral_q_mu = integrate(store, q_mu, x_var)?;
+    let integral_q_mu = antiderivative(store, q_mu, x_var, hold)?;
 
     // Solution: y = (∫ q·μ dx + C) / μ
     // For now, omit constant C
@@ -355,11 +479,11 @@ fn extract_separable_factors(
     // Simple case: expr is a product
     if store.get(expr).op != Op::Mul {
         // Check if it's purely a function of x or y
-        if !contains_var(store, expr, y_var) {
+        if !store.has_free_symbol(expr, y_var) {
             // f(x) * 1
             return Some((expr, store.int(1)));
         }
-        if !contains_var(store, expr, x_var) {
+        if !store.has_free_symbol(expr, x_var) {
             // 1 * g(y)
             return Some((store.int(1), expr));
         }
@@ -371,8 +495,8 @@ fn extract_separable_factors(
     let mut y_factors = Vec::new();
 
     for &child in children {
-        let has_x = contains_var(store, child, x_var);
-        let has_y = contains_var(store, child, y_var);
+        let has_x = store.has_free_symbol(child, x_var);
+        let has_y = store.has_free_symbol(child, y_var);
 
         if has_x && has_y {
             // Mixed term - not separable
@@ -401,110 +525,19 @@ fn extract_linear_coefficients(
     y_var: &str,
     _x_var: &str,
 ) -> Option<(ExprId, ExprId)> {
-    // expr should be of form: -p(x)y + q(x)
-    // We look for terms with y and without y
-
-    if store.get(expr).op != Op::Add {
-        // Single term - check if it's p(x)y or q(x)
-        if contains_var(store, expr, y_var) {
-            // Extract coefficient of y
-            let p = extract_coefficient_of_var(store, expr, y_var)?;
-            let neg_one = store.int(-1);
-            let neg_p = store.mul(vec![neg_one, p]);
-            return Some((neg_p, store.int(0)));
-        } else {
-            // It's q(x)
-            return Some((store.int(0), expr));
-        }
-    }
-
-    let children = store.get(expr).children.clone();
-    let mut p_terms = Vec::new();
-    let mut q_terms = Vec::new();
-
-    for child in children {
-        if contains_var(store, child, y_var) {
-            // This is a p(x)y term
-            if let Some(coeff) = extract_coefficient_of_var(store, child, y_var) {
-                p_terms.push(coeff);
-            } else {
-                return None;
-            }
-        } else {
-            // This is a q(x) term
-            q_terms.push(child);
-        }
-    }
-
-    if p_terms.len() != 1 {
-        // Linear ODE should have exactly one y term
+    // expr should be a polynomial of degree at most 1 in y
+    let poly = polys::as_poly_in(store, expr, &[y_var])?;
+    if poly.keys().any(|k| k[0] > 1) {
         return None;
     }
-
-    let p_x = p_terms[0];
+    let zero = store.int(0);
+    let p_x = poly.get(&vec![1]).copied().unwrap_or(zero);
+    let q_x = poly.get(&vec![0]).copied().unwrap_or(zero);
     let neg_one = store.int(-1);
     let neg_p = store.mul(vec![neg_one, p_x]);
-
-    let q_x = if q_terms.is_empty() { store.int(0) } else { store.add(q_terms) };
-
     Some((neg_p, q_x))
 }
 
-/// Extract coefficient of a variable from an expression
-/// For example: 3xy -> 3x (coefficient of y)
-fn extract_coefficient_of_var(store: &mut Store, expr: ExprId, var: &str) -> Option<ExprId> {
-    match store.get(expr).op {
-        Op::Symbol => {
-            if matches!(&store.get(expr).payload, Payload::Sym(s) if s == var) {
-                Some(store.int(1))
-            } else {
-                None
-            }
-        }
-        Op::Mul => {
-            let children = &store.get(expr).children;
-            let mut coeff_parts = Vec::new();
-            let mut found_var = false;
-
-            for &child in children {
-                if !found_var
-                    && matches!((&store.get(child).op, &store.get(child).payload), (Op::Symbol, Payload::Sym(ref s)) if s == var)
-                {
-                    found_var = true;
-                } else {
-                    coeff_parts.push(child);
-                }
-            }
-
-            if !found_var {
-                return None;
-            }
-
-            if coeff_parts.is_empty() {
-                Some(store.int(1))
-            } else {
-                Some(store.mul(coeff_parts))
-            }
-        }
-        _ => None,
-    }
-}
-
-fn contains_var(store: &Store, id: ExprId, var: &str) -> bool {
-    match (&store.get(id).op, &store.get(id).payload) {
-        (Op::Symbol, Payload::Sym(s)) => s == var,
-        (Op::Add, _) | (Op::Mul, _) => {
-            store.get(id).children.iter().any(|&c| contains_var(store, c, var))
-        }
-        (Op::Pow, _) => {
-            let n = store.get(id);
-            contains_var(store, n.children[0], var) || contains_var(store, n.children[1], var)
-        }
-        (Op::Function, _) => store.get(id).children.iter().any(|&c| contains_var(store, c, var)),
-        _ => false,
-    }
-}
-
 /// Solve second-order linear ODE with constant coefficients:
 /// a*y'' + b*y' + c*y = 0
 ///
@@ -649,4 +682,67 @@ mod tests {
         assert!(st.to_string(f_x).contains("x"));
         assert!(st.to_string(g_y).contains("y"));
     }
+
+    #[test]
+    fn test_separable_keeps_held_integral() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+
+        // dy/dx = exp(x^2): no elementary antiderivative
+        let two = st.int(2);
+        let x2 = st.pow(x, two);
+        let rhs = st.func("exp", vec![x2]);
+
+        let solution = solve_ode_first_order(&mut st, rhs, "y", "x").expect("held solution");
+
+        // Implicit form y - Integral(exp(x^2), x)
+        let held = st.integral(rhs, "x", None);
+        let neg_one = st.int(-1);
+        let neg_held = st.mul(vec![neg_one, held]);
+        let expected = st.add(vec![y, neg_held]);
+        assert_eq!(solution, expected);
+    }
+
+    #[test]
+    fn dsolve_in_terms_of_applied_function() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.func("y", vec![x]);
+        let dy = st.derivative(y, "x", 1);
+
+        // Eq(y'(x), exp(x^2)): implicit form y(x) - Integral(exp(x^2), x)
+        let two = st.int(2);
+        let x2 = st.pow(x, two);
+        let e = st.func("exp", vec![x2]);
+        let eq = st.eq(dy, e);
+        let solution = dsolve(&mut st, eq, y).expect("held solution");
+        let held = st.integral(e, "x", None);
+        let neg_one = st.int(-1);
+        let neg_held = st.mul(vec![neg_one, held]);
+        let expected = st.add(vec![y, neg_held]);
+        assert_eq!(solution, expected);
+
+        // y'' - 3y' + 2y = 0
+        let d2 = st.derivative(y, "x", 2);
+        let m3 = st.int(-3);
+        let t1 = st.mul(vec![m3, dy]);
+        let t2 = st.mul(vec![two, y]);
+        let ode2 = st.add(vec![d2, t1, t2]);
+        let sol2 = dsolve(&mut st, ode2, y).expect("constant coefficients");
+        let s = st.to_string(sol2);
+        assert!(s.contains("C1") && s.contains("C2") && s.contains("exp"));
+
+        // The unknown at another point is not supported
+        let y2x = st.func("y", vec![x2]);
+        let bad = st.add(vec![dy, y2x]);
+        assert!(dsolve(&mut st, bad, y).is_none());
+
+        // A parameter named like a placeholder stays a parameter: y' = _y
+        let param = st.sym("_y");
+        let eq = st.eq(dy, param);
+        let solution = dsolve(&mut st, eq, y).expect("linear");
+        let s = st.to_string(solution);
+        assert!(s.contains("_y") && !s.contains("_y_1") && !s.contains("exp"), "{s}");
+    }
 }
@@ -46,17 +46,12 @@ pub fn detect_extension(store: &Store, expr: ExprId, var: &str) -> ExtensionType
                 if children.len() == 1 {
                     let arg = children[0];
                     match fname.as_str() {
-                        "exp" => {
-                            // Check if argument depends on var
-                            if depends_on_var(store, arg, var) {
-                                return ExtensionType::Exponential(arg);
-                            }
+                        // Only a genuine extension if the argument depends on var
+                        "exp" if depends_on_var(store, arg, var) => {
+                            return ExtensionType::Exponential(arg);
                         }
-                        "ln" | "log" => {
-                            // Check if argument depends on var
-                            if depends_on_var(store, arg, var) {
-                                return ExtensionType::Logarithmic(arg);
-                            }
+                        "ln" | "log" if depends_on_var(store, arg, var) => {
+                            return ExtensionType::Logarithmic(arg);
                         }
                         _ => {}
                     }
@@ -1,6 +1,9 @@
 //! Power series utilities and Maclaurin expansions.
 
-use arith::{q_add, q_div, q_mul, q_norm, q_sub};
+use arith::{
+    checked_q_div, checked_rat_add, checked_rat_mul, checked_rat_sub, q_add, q_mul, q_norm, q_sub,
+    ArithError,
+};
 use expr_core::{ExprId, Op, Payload, Store};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -77,7 +80,7 @@ impl Series {
         Self { coeffs: out }
     }
 
-    // Compose s(inner): requires inner.c0 == 0
+    // Compose s(inner): requires inner.c0 == 0; `None` also on coefficient overflow
     pub fn compose(&self, inner: &Self, order: usize) -> Option<Self> {
         if inner.coeffs.first().copied().unwrap_or((0, 1)) != (0, 1) {
             return None;
@@ -89,10 +92,10 @@ impl Series {
         for k in 0..n {
             let a_k = self.coeffs.get(k).copied().unwrap_or((0, 1));
             if a_k.0 != 0 {
-                let term = p.scale(a_k, order);
-                out = out.add(&term, order);
+                let term = p.checked_scale(a_k, order).ok()?;
+                out = out.checked_add(&term, order).ok()?;
             }
-            p = p.mul(inner, order);
+            p = p.checked_mul(inner, order).ok()?;
         }
         Some(out)
     }
@@ -105,6 +108,52 @@ impl Series {
         }
         Self { coeffs: out }
     }
+
+    /// Like [`Series::add`], but reports coefficient overflow instead of saturating.
+    pub fn checked_add(&self, rhs: &Self, order: usize) -> Result<Self, ArithError> {
+        self.checked_zip(rhs, order, checked_rat_add)
+    }
+
+    /// Like [`Series::sub`], but reports coefficient overflow instead of saturating.
+    pub fn checked_sub(&self, rhs: &Self, order: usize) -> Result<Self, ArithError> {
+        self.checked_zip(rhs, order, checked_rat_sub)
+    }
+
+    /// Like [`Series::mul`], but reports coefficient overflow instead of saturating.
+    pub fn checked_mul(&self, rhs: &Self, order: usize) -> Result<Self, ArithError> {
+        let n = order + 1;
+        let mut out = vec![(0, 1); n];
+        for (i, out_i) in out.iter_mut().enumerate() {
+            for j in 0..=i {
+                let a = self.coeffs.get(j).copied().unwrap_or((0, 1));
+                let b = rhs.coeffs.get(i - j).copied().unwrap_or((0, 1));
+                *out_i = checked_rat_add(*out_i, checked_rat_mul(a, b)?)?;
+            }
+        }
+        Ok(Self { coeffs: out })
+    }
+
+    /// Like [`Series::scale`], but reports coefficient overflow instead of saturating.
+    pub fn checked_scale(&self, q: (i64, i64), order: usize) -> Result<Self, ArithError> {
+        let coeffs = (0..=order)
+            .map(|k| checked_rat_mul(self.coeffs.get(k).copied().unwrap_or((0, 1)), q))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { coeffs })
+    }
+
+    fn checked_zip<F>(&self, rhs: &Self, order: usize, op: F) -> Result<Self, ArithError>
+    where
+        F: Fn((i64, i64), (i64, i64)) -> Result<(i64, i64), ArithError>,
+    {
+        let coeffs = (0..=order)
+            .map(|k| {
+                let a = self.coeffs.get(k).copied().unwrap_or((0, 1));
+                let b = rhs.coeffs.get(k).copied().unwrap_or((0, 1));
+                op(a, b)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { coeffs })
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -141,7 +190,7 @@ pub fn limit_poly(store: &Store, id: ExprId, var: &str, point: LimitPoint) -> Li
                 let mut acc = (0, 1);
                 for &c in &store.get(id).children {
                     let ct = const_term(store, c, var)?;
-                    acc = q_add(acc, ct);
+                    acc = checked_rat_add(acc, ct).ok()?;
                 }
                 Some(acc)
             }
@@ -149,7 +198,7 @@ pub fn limit_poly(store: &Store, id: ExprId, var: &str, point: LimitPoint) -> Li
                 let mut acc = (1, 1);
                 for &f in &store.get(id).children {
                     let ct = const_term(store, f, var)?;
-                    acc = q_mul(acc, ct);
+                    acc = checked_rat_mul(acc, ct).ok()?;
                 }
                 Some(acc)
             }
@@ -163,7 +212,7 @@ pub fn limit_poly(store: &Store, id: ExprId, var: &str, point: LimitPoint) -> Li
                         // ct^k
                         let mut acc = (1, 1);
                         for _ in 0..(*k as usize) {
-                            acc = q_mul(acc, ct);
+                            acc = checked_rat_mul(acc, ct).ok()?;
                         }
                         Some(acc)
                     }
@@ -272,7 +321,7 @@ pub fn maclaurin(store: &Store, id: ExprId, var: &str, order: usize) -> Option<S
             let mut acc = Series::zero(order);
             for &c in &store.get(id).children {
                 let sc = maclaurin(store, c, var, order)?;
-                acc = acc.add(&sc, order);
+                acc = acc.checked_add(&sc, order).ok()?;
             }
             Some(acc)
         }
@@ -280,7 +329,7 @@ pub fn maclaurin(store: &Store, id: ExprId, var: &str, order: usize) -> Option<S
             let mut prod = Series::one(order);
             for &f in &store.get(id).children {
                 let sf = maclaurin(store, f, var, order)?;
-                prod = prod.mul(&sf, order);
+                prod = prod.checked_mul(&sf, order).ok()?;
             }
             Some(prod)
         }
@@ -294,7 +343,7 @@ pub fn maclaurin(store: &Store, id: ExprId, var: &str, order: usize) -> Option<S
             let mut s = Series::one(order);
             let b = maclaurin(store, base, var, order)?;
             for _ in 0..k {
-                s = s.mul(&b, order);
+                s = s.checked_mul(&b, order).ok()?;
             }
             Some(s)
         }
@@ -317,9 +366,8 @@ pub fn maclaurin(store: &Store, id: ExprId, var: &str, order: usize) -> Option<S
                     }
                     let mut base = Series::zero(order);
                     base.coeffs = (0..=order)
-                        .map(|k| (1i64, factorial(k as u32) as i64))
-                        .map(|(n, d)| q_div((n, 1), (d, 1)))
-                        .collect();
+                        .map(|k| checked_q_div((1, 1), (factorial(k as u32)?, 1)).ok())
+                        .collect::<Option<_>>()?;
                     base.compose(&su, order)
                 }
                 "sin" => {
@@ -334,7 +382,8 @@ pub fn maclaurin(store: &Store, id: ExprId, var: &str, order: usize) -> Option<S
                             break;
                         }
                         let sign = if m % 2 == 0 { 1 } else { -1 };
-                        base.coeffs[p] = q_div((sign, 1), (factorial(p as u32) as i64, 1));
+                        base.coeffs[p] =
+                            checked_q_div((sign, 1), (factorial(p as u32)?, 1)).ok()?;
                     }
                     base.compose(&su, order)
                 }
@@ -354,7 +403,8 @@ pub fn maclaurin(store: &Store, id: ExprId, var: &str, order: usize) -> Option<S
                             break;
                         }
                         let sign = if m % 2 == 0 { 1 } else { -1 };
-                        base.coeffs[p] = q_div((sign, 1), (factorial(p as u32) as i64, 1));
+                        base.coeffs[p] =
+                            checked_q_div((sign, 1), (factorial(p as u32)?, 1)).ok()?;
                     }
                     base.compose(&su, order)
                 }
@@ -363,14 +413,15 @@ pub fn maclaurin(store: &Store, id: ExprId, var: &str, order: usize) -> Option<S
                         return None;
                     }
                     let one = Series::one(order);
-                    let v = su.sub(&one, order);
+                    let v = su.checked_sub(&one, order).ok()?;
                     let mut out = Series::zero(order);
                     let mut pow = Series::one(order);
                     for k in 1..=order {
-                        pow = if k == 1 { v.clone() } else { pow.mul(&v, order) };
+                        pow = if k == 1 { v.clone() } else { pow.checked_mul(&v, order).ok()? };
                         let sign = if k % 2 == 1 { 1 } else { -1 };
-                        let coeff = q_div((sign, 1), (k as i64, 1));
-                        out = out.add(&pow.scale(coeff, order), order);
+                        let coeff = checked_q_div((sign, 1), (k as i64, 1)).ok()?;
+                        out =
+                            out.checked_add(&pow.checked_scale(coeff, order).ok()?, order).ok()?;
                     }
                     Some(out)
                 }
@@ -381,11 +432,20 @@ pub fn maclaurin(store: &Store, id: ExprId, var: &str, order: usize) -> Option<S
             // Piecewise series expansion not supported - would require conditional series
             None
         }
+        // Series coefficients are exact rationals; floats go through nsimplify first
+        Op::Float => None,
+        // pi, E, I are not rational; infinities have no expansion
+        Op::Constant => None,
+        Op::Eq | Op::Lt | Op::Le | Op::And | Op::Or | Op::Not => None,
+        // Held nodes are expanded only after `doit`
+        Op::Derivative | Op::Integral | Op::Sum | Op::Product => None,
+        Op::Lambda => None,
     }
 }
 
-fn factorial(n: u32) -> u128 {
-    (1..=n as u128).product::<u128>().max(1)
+/// `n!`, or `None` once it no longer fits in an `i64` (past 20!).
+fn factorial(n: u32) -> Option<i64> {
+    (1..=n as i64).try_fold(1i64, |acc, k| acc.checked_mul(k))
 }
 
 #[cfg(test)]
@@ -504,4 +564,19 @@ mod tests {
         let l = limit_poly(&st, sinx, "x", LimitPoint::Zero);
         assert_eq!(l, LimitResult::Unsupported);
     }
+
+    #[test]
+    fn series_overflow_is_reported() {
+        let big = Series::const_q(i64::MAX, 1, 2);
+        assert!(big.checked_mul(&big, 2).is_err());
+        assert!(big.checked_add(&big, 2).is_err());
+        assert_eq!(big.checked_sub(&big, 2), Ok(Series::zero(2)));
+
+        // 21! no longer fits in an i64: no series rather than a wrapped coefficient
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let ex = st.func("exp", vec![x]);
+        assert!(maclaurin(&st, ex, "x", 20).is_some());
+        assert!(maclaurin(&st, ex, "x", 21).is_none());
+    }
 }
@@ -6,7 +6,7 @@
 //! - Logarithmic/exponential identities
 //! - Inverse function composition
 
-use expr_core::{ExprId, Op, Payload, Store};
+use expr_core::{Constant, ExprId, Op, Payload, Store};
 use simplify::simplify;
 
 /// Simplifies calculus-specific patterns in an expression
@@ -76,9 +76,10 @@ fn simplify_function(store: &mut Store, expr: ExprId) -> ExprId {
     }
 }
 
-/// Simplifies sqrt expressions: √4 → 2, √9 → 3, etc.
+/// Simplifies sqrt expressions: √4 → 2, √9 → 3, √-1 → I, etc.
 fn simplify_sqrt(store: &mut Store, arg: ExprId) -> ExprId {
     match (&store.get(arg).op, &store.get(arg).payload) {
+        (Op::Integer, Payload::Int(-1)) => store.constant(Constant::I),
         (Op::Integer, Payload::Int(n)) if *n >= 0 => {
             // Check if n is a perfect square
             let sqrt_n = (*n as f64).sqrt();
@@ -116,8 +117,10 @@ fn simplify_ln(store: &mut Store, arg: ExprId) -> ExprId {
         }
     }
 
-    // ln(e) → 1 (where e is represented as exp(1))
-    // For now, just return ln(arg)
+    // ln(e) → 1
+    if store.as_constant(arg) == Some(Constant::E) {
+        return store.int(1);
+    }
     store.func("ln", vec![arg])
 }
 
@@ -739,6 +742,20 @@ mod tests {
         assert_eq!(st.get(result).op, Op::Function);
     }
 
+    #[test]
+    fn test_simplify_sqrt_minus_one_and_ln_e() {
+        let mut st = Store::new();
+        let m1 = st.int(-1);
+        let sqrt_m1 = st.func("sqrt", vec![m1]);
+        let result = simplify_calculus(&mut st, sqrt_m1);
+        assert_eq!(st.as_constant(result), Some(Constant::I));
+
+        let e = st.constant(Constant::E);
+        let ln_e = st.func("ln", vec![e]);
+        let result = simplify_calculus(&mut st, ln_e);
+        assert_eq!(result, st.int(1));
+    }
+
     #[test]
     fn test_simplify_ln_exp() {
         let mut st = Store::new();
@@ -0,0 +1,321 @@
+//! Zero-equivalence: whether two expressions agree for every value of their
+//! free symbols allowed by the assumptions.
+//!
+//! `equals` first simplifies `a - b`, also after expansion; a zero difference
+//! proves equality. Otherwise both sides are evaluated at pseudo-random points
+//! of the assumption domain (fixed seed, so witnesses are reproducible). A
+//! point where they differ proves inequality when an interval enclosure of
+//! `a - b` there excludes zero, or when the f64 values are far apart for
+//! functions without enclosures. Points the f64 test cannot settle, including
+//! every point where the sides agree to f64 tolerance, are re-evaluated in
+//! `arith::Float` at growing precision: a difference that stays put as the
+//! precision grows is real, one that shrinks with it is rounding error.
+//! Agreement at every point is reported as equality, which (as for any
+//! random-point test) is evidence, not proof; it never rests on f64 alone.
+//! Points where either side cannot be evaluated are skipped; too few usable
+//! points, or a difference too small to call, give Unknown.
+//!
+//! The crate sits above both `simplify` and `evalf`, so that neither has to
+//! depend on the other.
+
+#![deny(warnings)]
+
+use arith::{Float, Interval};
+use assumptions::{Context, Prop, Truth};
+use evalf::{eval, eval_float, eval_interval, EvalContext};
+use expr_core::{ExprId, Payload, Store};
+use simplify::{expand_with, simplify_with, ExpandOptions};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Points tried before deciding.
+const SAMPLES: usize = 16;
+/// Points that must agree (with free symbols) to report equality.
+const MIN_AGREEING: usize = 4;
+/// Relative f64 difference that is worth checking at higher precision.
+const TOLERANCE: f64 = 1e-9;
+/// Relative difference counted as a real one without an interval enclosure.
+const SEPARATION: f64 = 1e-6;
+const SEED: u64 = 0x5EED_CAFE_F00D_D00D;
+/// Working precisions (bits) for points the f64 test cannot settle. At each,
+/// differences below half the bits of the larger side are rounding noise.
+const PRECISIONS: [u32; 4] = [64, 128, 256, 512];
+
+/// A point where the two sides differ.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Witness {
+    /// Symbol values; empty when the sides differ everywhere (their
+    /// difference simplified to a nonzero number).
+    pub point: BTreeMap<String, f64>,
+    /// `a - b` at `point`.
+    pub difference: f64,
+}
+
+/// Whether `a` and `b` are equal on the domain `ctx` allows.
+pub fn equals(store: &mut Store, a: ExprId, b: ExprId, ctx: &Context) -> Truth {
+    equals_witness(store, a, b, ctx).0
+}
+
+/// `equals`, with a point where the sides differ when the answer is False.
+pub fn equals_witness(
+    store: &mut Store,
+    a: ExprId,
+    b: ExprId,
+    ctx: &Context,
+) -> (Truth, Option<Witness>) {
+    if a == b {
+        return (Truth::True, None);
+    }
+    let m_one = store.int(-1);
+    let nb = store.mul(vec![m_one, b]);
+    let d = store.add(vec![a, nb]);
+    let d = simplify_with(store, d, ctx);
+    let expanded = expand_with(store, d, &ExpandOptions::default(), ctx);
+    let expanded = simplify_with(store, expanded, ctx);
+    for d in [d, expanded] {
+        let difference = match store.get(d).payload {
+            Payload::Int(0) => return (Truth::True, None),
+            Payload::Int(n) => n as f64,
+            Payload::Rat(n, q) => n as f64 / q as f64,
+            _ => continue,
+        };
+        return (Truth::False, Some(Witness { point: BTreeMap::new(), difference }));
+    }
+    numeric(store, a, b, ctx)
+}
+
+fn numeric(store: &mut Store, a: ExprId, b: ExprId, ctx: &Context) -> (Truth, Option<Witness>) {
+    let mut symbols: BTreeSet<String> = store.free_symbols(a);
+    symbols.extend(store.free_symbols(b));
+    let samples = if symbols.is_empty() { 1 } else { SAMPLES };
+    let needed = if symbols.is_empty() { 1 } else { MIN_AGREEING };
+    let mut rng = SplitMix64::new(SEED);
+    let (mut agreeing, mut doubtful) = (0, false);
+    for _ in 0..samples {
+        let point: BTreeMap<String, f64> =
+            symbols.iter().map(|s| (s.clone(), sample(&mut rng, ctx, s))).collect();
+        let mut env = EvalContext::new();
+        for (s, &v) in &point {
+            env.bind(s.as_str(), v);
+        }
+        let (Ok(va), Ok(vb)) = (eval(store, a, &env), eval(store, b, &env)) else {
+            continue;
+        };
+        let scale = va.abs().max(vb.abs()).max(1.0);
+        if (va - vb).abs() > TOLERANCE * scale {
+            let apart = match separated(store, a, b, &point) {
+                Some(known) => known,
+                None => (va - vb).abs() > SEPARATION * scale,
+            };
+            if apart {
+                return (Truth::False, Some(Witness { point, difference: va - vb }));
+            }
+        }
+        match refine(store, a, b, &point) {
+            Some(Refined::Agree) => agreeing += 1,
+            Some(Refined::Differ(difference)) => {
+                return (Truth::False, Some(Witness { point, difference }));
+            }
+            None => doubtful = true,
+        }
+    }
+    if doubtful || agreeing < needed {
+        (Truth::Unknown, None)
+    } else {
+        (Truth::True, None)
+    }
+}
+
+/// Outcome of re-evaluating a point at growing precision.
+enum Refined {
+    Agree,
+    /// The difference `a - b`, stable across two precisions.
+    Differ(f64),
+}
+
+/// Settle `a - b` at `point` in `Float`: a difference above the noise level
+/// that keeps its value at the next precision is real; one below the noise at
+/// the last precision is agreement. None if either side has no `Float`
+/// evaluation or the difference never settles.
+fn refine(store: &Store, a: ExprId, b: ExprId, point: &BTreeMap<String, f64>) -> Option<Refined> {
+    let env: HashMap<String, Float> = point
+        .iter()
+        .map(|(s, &v)| Some((s.clone(), Float::from_f64(v)?)))
+        .collect::<Option<_>>()?;
+    let mut previous: Option<Float> = None;
+    for prec in PRECISIONS {
+        let va = eval_float(store, a, &env, prec).ok()?;
+        let vb = eval_float(store, b, &env, prec).ok()?;
+        let d = va.sub(&vb);
+        let scale = [va.exponent(), vb.exponent(), Some(1)].into_iter().flatten().max()?;
+        let noise = scale - i64::from(prec / 2);
+        let Some(mag) = d.exponent().filter(|&e| e > noise) else {
+            previous = None;
+            continue;
+        };
+        if let Some(p) = &previous {
+            // Same value to 8 bits at both precisions
+            let drift = p.sub(&d).exponent();
+            if drift.is_none_or(|e| e <= mag - 8) {
+                return Some(Refined::Differ(d.to_f64()));
+            }
+        }
+        previous = Some(d);
+    }
+    previous.is_none().then_some(Refined::Agree)
+}
+
+/// Whether an enclosure of `a - b` at `point` excludes zero; None if either
+/// side has no enclosure.
+fn separated(store: &Store, a: ExprId, b: ExprId, point: &BTreeMap<String, f64>) -> Option<bool> {
+    let env: HashMap<String, Interval<f64>> =
+        point.iter().map(|(s, &v)| (s.clone(), Interval::point(v))).collect();
+    let ia = eval_interval(store, a, &env).ok()?;
+    let ib = eval_interval(store, b, &env).ok()?;
+    Some(ia.hi() < ib.lo() || ib.hi() < ia.lo())
+}
+
+/// A value for `sym` in its assumption domain: an integer in [-6, 6], or a
+/// real with magnitude in [1/4, 4], each restricted by the sign assumptions.
+fn sample(rng: &mut SplitMix64, ctx: &Context, sym: &str) -> f64 {
+    let holds = |p| ctx.has(sym, p) == Truth::True;
+    let (positive, negative) = (holds(Prop::Positive), holds(Prop::Negative));
+    let nonzero = positive || negative || holds(Prop::Nonzero);
+    let nonnegative = positive || holds(Prop::Nonnegative);
+    let sign = if positive || nonnegative {
+        1.0
+    } else if negative || rng.unit() < 0.5 {
+        -1.0
+    } else {
+        1.0
+    };
+    if holds(Prop::Integer) {
+        let lo: u64 = if nonzero { 1 } else { 0 };
+        let k = lo + rng.next_u64() % (7 - lo);
+        return sign * k as f64;
+    }
+    sign * (0.25 + 3.75 * rng.unit())
+}
+
+/// Small deterministic generator (SplitMix64).
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expr_core::Constant;
+
+    #[test]
+    fn identities_hold() {
+        let mut st = Store::new();
+        let ctx = Context::new();
+        let x = st.sym("x");
+        let one = st.int(1);
+        let two = st.int(2);
+        // (x + 1)^2 = x^2 + 2x + 1, by expansion
+        let s = st.add(vec![x, one]);
+        let sq = st.pow(s, two);
+        let x2 = st.pow(x, two);
+        let tx = st.mul(vec![two, x]);
+        let p = st.add(vec![x2, tx, one]);
+        assert_eq!(equals(&mut st, sq, p, &ctx), Truth::True);
+        // sin^2 + cos^2 = 1
+        let (sx, cx) = (st.func("sin", vec![x]), st.func("cos", vec![x]));
+        let (s2, c2) = (st.pow(sx, two), st.pow(cx, two));
+        let pyth = st.add(vec![s2, c2]);
+        assert_eq!(equals(&mut st, pyth, one, &ctx), Truth::True);
+        // cos(2 pi n) = 1 holds for integer n only
+        let n = st.sym("n");
+        let pi = st.constant(Constant::Pi);
+        let arg = st.mul(vec![two, pi, n]);
+        let c = st.func("cos", vec![arg]);
+        assert_eq!(equals(&mut st, c, one, &ctx), Truth::False);
+        let mut ints = Context::new();
+        ints.assume("n", Prop::Integer);
+        assert_eq!(equals(&mut st, c, one, &ints), Truth::True);
+        // sqrt(x^2) = x needs x >= 0
+        let half = st.rat(1, 2);
+        let r = st.pow(x2, half);
+        assert_eq!(equals(&mut st, r, x, &ctx), Truth::False);
+        let mut pos = Context::new();
+        pos.assume("x", Prop::Positive);
+        assert_eq!(equals(&mut st, r, x, &pos), Truth::True);
+    }
+
+    #[test]
+    fn witnesses_and_unknowns() {
+        let mut st = Store::new();
+        let ctx = Context::new();
+        let x = st.sym("x");
+        let one = st.int(1);
+        // exp(x) != 1 + x, and the witness point shows it
+        let e = st.func("exp", vec![x]);
+        let p = st.add(vec![one, x]);
+        let (t, w) = equals_witness(&mut st, e, p, &ctx);
+        assert_eq!(t, Truth::False);
+        let w = w.unwrap();
+        let v = w.point["x"];
+        assert!((w.difference - (v.exp() - (1.0 + v))).abs() < 1e-12);
+        // pi != 355/113 although they agree to 7 digits
+        let pi = st.constant(Constant::Pi);
+        let q = st.rat(355, 113);
+        let (t, w) = equals_witness(&mut st, pi, q, &ctx);
+        assert_eq!(t, Truth::False);
+        assert!(w.unwrap().point.is_empty());
+        // f(x) + 1 != f(x) symbolically, although f cannot be evaluated
+        let fx = st.func("f", vec![x]);
+        let f1 = st.add(vec![fx, one]);
+        let (t, w) = equals_witness(&mut st, f1, fx, &ctx);
+        assert_eq!(t, Truth::False);
+        assert_eq!(w, Some(Witness { point: BTreeMap::new(), difference: 1.0 }));
+        // f(x) vs g(x): nothing to go on
+        let gx = st.func("g", vec![x]);
+        assert_eq!(equals_witness(&mut st, fx, gx, &ctx), (Truth::Unknown, None));
+    }
+
+    #[test]
+    fn differences_below_f64_resolution() {
+        let mut st = Store::new();
+        let ctx = Context::new();
+        let x = st.sym("x");
+        let two = st.int(2);
+        let m40 = st.int(-40);
+        let tiny = st.func("exp", vec![m40]);
+        // 2 + e^-40 and 2 agree to 17 digits, still not equal
+        let near = st.add(vec![two, tiny]);
+        let (t, w) = equals_witness(&mut st, two, near, &ctx);
+        assert_eq!(t, Truth::False);
+        let d = w.unwrap().difference;
+        assert!((d + (-40f64).exp()).abs() < 1e-30, "{d}");
+        // Likewise with a free symbol
+        let e = st.func("exp", vec![x]);
+        let shifted = st.add(vec![e, tiny]);
+        assert_eq!(equals(&mut st, e, shifted, &ctx), Truth::False);
+        // A true identity the simplifier leaves alone still agrees at every precision
+        let half = st.rat(1, 2);
+        let sq = st.pow(e, half);
+        let ex2 = st.mul(vec![half, x]);
+        let e_half = st.func("exp", vec![ex2]);
+        assert_eq!(equals(&mut st, sq, e_half, &ctx), Truth::True);
+    }
+}
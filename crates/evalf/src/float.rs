@@ -0,0 +1,186 @@
+//! Evaluation in arbitrary-precision binary floating point (`arith::Float`).
+//!
+//! Covers numbers, `+`, `*`, powers, `pi`/`E` and the elementary functions
+//! `Float` implements (exp, ln, sqrt, sin/cos/tan, atan/asin/acos, the
+//! hyperbolic functions, abs). Each step works at the requested precision, so
+//! the result is accurate to about `prec` bits less whatever cancellation the
+//! expression itself causes; evaluating again at a higher precision tells the
+//! two apart.
+
+use crate::EvalError;
+use arith::{BigRat, Float, Q};
+use expr_core::{Constant, ExprId, Op, Payload, Store};
+use std::collections::HashMap;
+
+/// Extra bits for the intermediate products of an integer power.
+const POW_GUARD: u32 = 64;
+
+/// Value of `id` at `prec` bits, with each symbol bound in `env`. Literal
+/// floats and bound values are widened to `prec` (their value is exact).
+pub fn eval_float(
+    store: &Store,
+    id: ExprId,
+    env: &HashMap<String, Float>,
+    prec: u32,
+) -> Result<Float, EvalError> {
+    let node = store.get(id);
+    let rec = |c: ExprId| eval_float(store, c, env, prec);
+    let domain = |what: &str| EvalError::DomainError(what.to_string());
+    match (&node.op, &node.payload) {
+        (Op::Integer | Op::Rational, _) => {
+            let r = store.as_bigrat(id).ok_or_else(|| domain("malformed number"))?;
+            Ok(Float::from_bigrat(&r, prec))
+        }
+        (Op::Float, Payload::Float(f)) => Ok(f.with_prec(prec)),
+        (Op::Constant, Payload::Const(Constant::Pi)) => Ok(Float::pi(prec)),
+        (Op::Constant, Payload::Const(Constant::E)) => {
+            Float::from_bigrat(&BigRat::one(), prec).exp().ok_or(EvalError::NonFinite)
+        }
+        (Op::Constant, Payload::Const(c)) => Err(domain(&format!("{} is not real", c.name()))),
+        (Op::Symbol, Payload::Sym(name)) => env
+            .get(name)
+            .map(|v| v.with_prec(prec))
+            .ok_or_else(|| EvalError::UnboundVariable(name.clone())),
+        (Op::Add, _) => {
+            node.children.iter().try_fold(Float::zero(prec), |acc, &c| Ok(acc.add(&rec(c)?)))
+        }
+        (Op::Mul, _) => node
+            .children
+            .iter()
+            .try_fold(Float::from_bigrat(&BigRat::one(), prec), |acc, &c| Ok(acc.mul(&rec(c)?))),
+        (Op::Pow, _) => {
+            let base = rec(node.children[0])?;
+            let exp = store.get(node.children[1]);
+            if let (Op::Integer, Payload::Int(n)) = (&exp.op, &exp.payload) {
+                return pow_int(&base, *n, prec);
+            }
+            if let (Op::Rational, Payload::Rat(1, 2)) = (&exp.op, &exp.payload) {
+                return base.sqrt().ok_or_else(|| domain("sqrt of a negative number"));
+            }
+            let e = rec(node.children[1])?;
+            if base.is_zero() && !e.is_negative() && !e.is_zero() {
+                return Ok(base);
+            }
+            // b^e = exp(e ln b) for b > 0
+            let ln = base.ln().ok_or_else(|| domain("non-integer power of a non-positive base"))?;
+            e.mul(&ln).exp().ok_or(EvalError::NonFinite)
+        }
+        (Op::Function, Payload::Func(name)) if node.children.len() == 1 => {
+            apply(name, &rec(node.children[0])?)
+        }
+        (Op::Function, Payload::Func(name)) => Err(EvalError::UnknownFunction(name.clone())),
+        _ => Err(domain(&format!("cannot evaluate {:?} in Float", node.op))),
+    }
+}
+
+/// `b^n` by repeated squaring with guard bits, rounded once.
+fn pow_int(b: &Float, n: i64, prec: u32) -> Result<Float, EvalError> {
+    let w = prec + POW_GUARD;
+    let mut base = b.with_prec(w);
+    let mut acc = Float::from_bigrat(&BigRat::one(), w);
+    let mut k = n.unsigned_abs();
+    while k > 0 {
+        if k & 1 == 1 {
+            acc = acc.mul(&base);
+        }
+        k >>= 1;
+        if k > 0 {
+            base = base.mul(&base);
+        }
+    }
+    if n < 0 {
+        let one = Float::from_bigrat(&BigRat::one(), w);
+        acc =
+            one.div(&acc).ok_or_else(|| EvalError::DomainError("0 to a negative power".into()))?;
+    }
+    Ok(acc.with_prec(prec))
+}
+
+fn apply(name: &str, x: &Float) -> Result<Float, EvalError> {
+    let prec = x.prec();
+    let one = Float::from_bigrat(&BigRat::one(), prec);
+    let half = Float::from_bigrat(&BigRat::from(Q(1, 2)), prec);
+    let domain = |what: &str| EvalError::DomainError(format!("{name} requires {what}"));
+    let exp = |v: &Float| v.exp().ok_or(EvalError::NonFinite);
+    let sin_cos = |v: &Float| v.sin_cos().ok_or(EvalError::NonFinite);
+    match name {
+        "exp" => exp(x),
+        "ln" | "log" => x.ln().ok_or_else(|| domain("x > 0")),
+        "log10" | "log2" => {
+            let base =
+                Float::from_bigrat(&BigRat::from(if name == "log10" { 10 } else { 2 }), prec);
+            let ln = x.ln().ok_or_else(|| domain("x > 0"))?;
+            Ok(ln.div(&base.ln().expect("positive base")).expect("nonzero logarithm"))
+        }
+        "sqrt" => x.sqrt().ok_or_else(|| domain("x >= 0")),
+        "abs" => Ok(if x.is_negative() { x.neg() } else { x.clone() }),
+        "sin" => Ok(sin_cos(x)?.0),
+        "cos" => Ok(sin_cos(x)?.1),
+        "tan" => {
+            let (s, c) = sin_cos(x)?;
+            s.div(&c).ok_or(EvalError::NonFinite)
+        }
+        "atan" | "arctan" => Ok(x.atan()),
+        "asin" | "arcsin" | "acos" | "arccos" => {
+            // asin(x) = atan(x / sqrt(1 - x^2)) inside (-1, 1)
+            let rest = one.sub(&x.mul(x));
+            if rest.is_negative() {
+                return Err(domain("-1 <= x <= 1"));
+            }
+            let half_pi = Float::pi(prec).mul(&half);
+            let asin = match x.div(&rest.sqrt().expect("nonnegative")) {
+                Some(t) => t.atan(),
+                None if x.is_negative() => half_pi.neg(),
+                None => half_pi.clone(),
+            };
+            Ok(if name.ends_with("sin") { asin } else { half_pi.sub(&asin) })
+        }
+        "sinh" | "cosh" | "tanh" => {
+            let (ep, em) = (exp(x)?, exp(&x.neg())?);
+            Ok(match name {
+                "sinh" => ep.sub(&em).mul(&half),
+                "cosh" => ep.add(&em).mul(&half),
+                _ => ep.sub(&em).div(&ep.add(&em)).expect("positive denominator"),
+            })
+        }
+        "re" | "conjugate" => Ok(x.clone()),
+        "im" => Ok(Float::zero(prec)),
+        _ => Err(EvalError::UnknownFunction(name.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_f64_and_refines() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let sx = st.func("sin", vec![x]);
+        let two = st.int(2);
+        let s2 = st.pow(sx, two);
+        let cx = st.func("cos", vec![x]);
+        let c2 = st.pow(cx, two);
+        let pyth = st.add(vec![s2, c2]);
+        let env = HashMap::from([("x".to_string(), Float::from_f64(0.7).unwrap())]);
+        // sin^2 + cos^2 = 1 to nearly 300 bits
+        let v = eval_float(&st, pyth, &env, 300).unwrap();
+        let one = Float::from_bigrat(&BigRat::one(), 300);
+        assert!(v.sub(&one).exponent().is_none_or(|e| e < -280));
+        // exp(-40) is far below f64 resolution next to 2, but not at 128 bits
+        let m40 = st.int(-40);
+        let tiny = st.func("exp", vec![m40]);
+        let sum = st.add(vec![two, tiny]);
+        let d = eval_float(&st, sum, &env, 128)
+            .unwrap()
+            .sub(&Float::from_bigrat(&BigRat::from(2), 128));
+        assert!((d.to_f64() - (-40f64).exp()).abs() < 1e-30);
+        // Unsupported functions and domain errors are reported
+        let g = st.func("Gamma", vec![x]);
+        assert!(matches!(eval_float(&st, g, &env, 64), Err(EvalError::UnknownFunction(_))));
+        let m1 = st.int(-1);
+        let l = st.func("ln", vec![m1]);
+        assert!(matches!(eval_float(&st, l, &env, 64), Err(EvalError::DomainError(_))));
+    }
+}
@@ -0,0 +1,217 @@
+//! Interval evaluation: a guaranteed enclosure of an expression's values when
+//! its symbols range over given intervals.
+//!
+//! Works over any `arith::Bound`. Rational operations are available for both
+//! `Interval<Q>` (exact) and `Interval<f64>` (outward rounded); elementary
+//! functions and `pi`/`E` only over f64 (see `IntervalEval`).
+
+use crate::EvalError;
+use arith::{ArithError, Bound, Interval, Q};
+use expr_core::{Constant, ExprId, Op, Payload, Store};
+use std::collections::HashMap;
+use std::f64::consts::{E, FRAC_PI_2, PI};
+
+/// Endpoint types that `eval_interval` can evaluate functions over.
+pub trait IntervalEval: Bound {
+    /// Enclosure of `name(x)`; None if unsupported.
+    fn func(_name: &str, _x: &Interval<Self>) -> Option<Interval<Self>> {
+        None
+    }
+    /// Enclosure of a constant; None if unsupported.
+    fn constant(_c: Constant) -> Option<Interval<Self>> {
+        None
+    }
+}
+
+impl IntervalEval for Q {}
+
+/// One step outward on each side: covers a libm result within 1 ulp.
+fn widen(lo: f64, hi: f64) -> Interval<f64> {
+    Interval::new(lo.next_down(), hi.next_up()).expect("ordered endpoints")
+}
+
+/// Enclosure of `f(x)` for `f` = sin or cos, whose maxima lie at
+/// `peak + 2k*pi` and minima at `peak + pi + 2k*pi`.
+fn periodic_interval(x: &Interval<f64>, f: fn(f64) -> f64, peak: f64) -> Interval<f64> {
+    let unit = Interval::new(-1.0, 1.0).expect("ordered endpoints");
+    let (lo, hi) = (x.lo(), x.hi());
+    if (hi - lo).is_nan() || hi - lo >= 2.0 * PI {
+        return unit;
+    }
+    // Whether some `at + 2k*pi` lies in [lo, hi], erring on yes
+    let hits = |at: f64| {
+        let slack = 1e-9;
+        let t_lo = (lo - at) / (2.0 * PI) - slack;
+        let t_hi = (hi - at) / (2.0 * PI) + slack;
+        t_lo.ceil() <= t_hi.floor()
+    };
+    let (a, b) = (f(lo), f(hi));
+    let top = if hits(peak) { 1.0 } else { a.max(b) };
+    let bottom = if hits(peak + PI) { -1.0 } else { a.min(b) };
+    widen(bottom, top).intersect(&unit).expect("values within [-1, 1]")
+}
+
+impl IntervalEval for f64 {
+    fn func(name: &str, x: &Interval<f64>) -> Option<Interval<f64>> {
+        let (lo, hi) = (x.lo(), x.hi());
+        match name {
+            "exp" => {
+                Some(widen(lo.exp(), hi.exp()).intersect(&Interval::new(0.0, f64::INFINITY)?)?)
+            }
+            "ln" | "log" if lo > 0.0 => Some(widen(lo.ln(), hi.ln())),
+            "sqrt" if lo >= 0.0 => {
+                Some(widen(lo.sqrt(), hi.sqrt()).intersect(&Interval::new(0.0, f64::INFINITY)?)?)
+            }
+            "atan" | "arctan" => Some(widen(lo.atan(), hi.atan())),
+            "sinh" => Some(widen(lo.sinh(), hi.sinh())),
+            "tanh" => Some(widen(lo.tanh(), hi.tanh())),
+            "sin" => Some(periodic_interval(x, f64::sin, FRAC_PI_2)),
+            "cos" => Some(periodic_interval(x, f64::cos, 0.0)),
+            _ => None,
+        }
+    }
+    fn constant(c: Constant) -> Option<Interval<f64>> {
+        match c {
+            Constant::Pi => Some(widen(PI, PI)),
+            Constant::E => Some(widen(E, E)),
+            _ => None,
+        }
+    }
+}
+
+fn arith_err(e: ArithError) -> EvalError {
+    EvalError::DomainError(e.to_string())
+}
+
+/// Enclosure of the values of `id` when each symbol ranges over its interval
+/// in `env`. Supports numbers, `+`, `*`, integer powers (and other powers of
+/// positive bases via `exp`/`ln`), `abs` and the functions of `IntervalEval`.
+pub fn eval_interval<T: IntervalEval>(
+    store: &Store,
+    id: ExprId,
+    env: &HashMap<String, Interval<T>>,
+) -> Result<Interval<T>, EvalError> {
+    let node = store.get(id);
+    let rec = |c: ExprId| eval_interval(store, c, env);
+    match (&node.op, &node.payload) {
+        (Op::Integer, Payload::Int(k)) => Ok(Interval::from_q(Q(*k, 1))),
+        (Op::Rational, Payload::Rat(n, d)) => Ok(Interval::from_q(Q(*n, *d))),
+        (Op::Integer, Payload::BigInt(_)) | (Op::Rational, Payload::BigRat(_)) => {
+            Err(arith_err(ArithError::Overflow))
+        }
+        (Op::Constant, Payload::Const(c)) => T::constant(*c)
+            .ok_or_else(|| EvalError::DomainError(format!("no enclosure of {}", c.name()))),
+        (Op::Symbol, Payload::Sym(name)) => {
+            env.get(name).copied().ok_or_else(|| EvalError::UnboundVariable(name.clone()))
+        }
+        (Op::Add, _) => node
+            .children
+            .iter()
+            .try_fold(Interval::point(T::zero()), |acc, &c| acc.add(&rec(c)?).map_err(arith_err)),
+        (Op::Mul, _) => node
+            .children
+            .iter()
+            .try_fold(Interval::point(T::one()), |acc, &c| acc.mul(&rec(c)?).map_err(arith_err)),
+        (Op::Pow, _) => {
+            let base = rec(node.children[0])?;
+            let exp = store.get(node.children[1]);
+            if let (Op::Integer, Payload::Int(n)) = (&exp.op, &exp.payload) {
+                return base.pow(*n).map_err(arith_err);
+            }
+            if let (Op::Rational, Payload::Rat(1, 2)) = (&exp.op, &exp.payload) {
+                return apply::<T>("sqrt", &base);
+            }
+            // b^e = exp(e ln b) for b > 0
+            let ln = apply::<T>("ln", &base)?;
+            let e = rec(node.children[1])?;
+            apply::<T>("exp", &e.mul(&ln).map_err(arith_err)?)
+        }
+        (Op::Function, Payload::Func(name)) if node.children.len() == 1 => {
+            let x = rec(node.children[0])?;
+            if name == "abs" {
+                return x.abs().map_err(arith_err);
+            }
+            apply::<T>(name, &x)
+        }
+        (Op::Function, Payload::Func(name)) => Err(EvalError::UnknownFunction(name.clone())),
+        _ => Err(EvalError::DomainError(format!("cannot enclose {:?}", node.op))),
+    }
+}
+
+fn apply<T: IntervalEval>(name: &str, x: &Interval<T>) -> Result<Interval<T>, EvalError> {
+    T::func(name, x).ok_or_else(|| match name {
+        "ln" | "log" | "sqrt" => {
+            EvalError::DomainError(format!("{} over {:?} may leave its domain", name, x))
+        }
+        _ => EvalError::UnknownFunction(name.to_string()),
+    })
+}
+
+/// `eval_interval` with a single symbol.
+pub fn eval_interval_at<T: IntervalEval>(
+    store: &Store,
+    id: ExprId,
+    var: &str,
+    range: Interval<T>,
+) -> Result<Interval<T>, EvalError> {
+    let env = HashMap::from([(var.to_string(), range)]);
+    eval_interval(store, id, &env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rational_enclosures() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let one = st.int(1);
+        let m_one = st.int(-1);
+        // x^2 - x + 1 on [0, 1]: naive interval bound [0, 2], truly [3/4, 1]
+        let two = st.int(2);
+        let x2 = st.pow(x, two);
+        let mx = st.mul(vec![m_one, x]);
+        let p = st.add(vec![x2, mx, one]);
+        let unit = Interval::new(Q::zero(), Q::one()).unwrap();
+        let r = eval_interval_at(&st, p, "x", unit).unwrap();
+        assert_eq!(r, Interval::new(Q::zero(), Q(2, 1)).unwrap());
+        assert!(!r.contains(Q(-1, 1)));
+        // 1/x is rejected when the range contains 0
+        let inv = st.pow(x, m_one);
+        assert!(eval_interval_at(&st, inv, "x", unit).is_err());
+        let sx = st.func("sin", vec![x]);
+        assert_eq!(
+            eval_interval_at(&st, sx, "x", unit),
+            Err(EvalError::UnknownFunction("sin".into()))
+        );
+    }
+
+    #[test]
+    fn float_enclosures_contain_point_values() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let sx = st.func("sin", vec![x]);
+        let ex = st.func("exp", vec![x]);
+        let e = st.add(vec![sx, ex]);
+        let range = Interval::new(0.0, 2.0).unwrap();
+        let r = eval_interval_at(&st, e, "x", range).unwrap();
+        for k in 0..=20 {
+            let t = k as f64 / 10.0;
+            assert!(r.contains(t.sin() + t.exp()));
+        }
+        // sin reaches its maximum 1 inside [0, 2]
+        let s = eval_interval_at(&st, sx, "x", range).unwrap();
+        assert_eq!(s.hi(), 1.0);
+        // "is e positive on [0, 2]?" is decided by the enclosure
+        assert!(r.is_positive());
+        let pi = st.constant(Constant::Pi);
+        let c = st.func("cos", vec![pi]);
+        let r = eval_interval(&st, c, &HashMap::<String, Interval<f64>>::new()).unwrap();
+        assert!(r.contains(-1.0) && r.hi() < -0.99);
+        let half = st.rat(1, 2);
+        let sq = st.pow(x, half);
+        let m = Interval::new(-1.0, 1.0).unwrap();
+        assert!(matches!(eval_interval_at(&st, sq, "x", m), Err(EvalError::DomainError(_))));
+    }
+}
@@ -2,12 +2,20 @@
 //!
 //! This module provides arbitrary-precision floating-point evaluation of symbolic expressions.
 //! For now, uses f64 for simplicity. Future versions can add MPFR via feature flags.
+//! `eval_interval` computes guaranteed enclosures over `arith::Interval`.
+//! `eval_float` evaluates in `arith::Float` at a chosen precision.
 
 #![deny(warnings)]
 
 use expr_core::{ExprId, Op, Payload, Store};
 use std::collections::HashMap;
 
+mod float;
+mod interval;
+
+pub use float::eval_float;
+pub use interval::{eval_interval, eval_interval_at, IntervalEval};
+
 /// Evaluation context holding variable bindings
 #[derive(Default, Clone, Debug)]
 pub struct EvalContext {
@@ -74,6 +82,36 @@ pub fn eval(store: &Store, id: ExprId, ctx: &EvalContext) -> Result<f64, EvalErr
     }
 }
 
+/// Evaluate a condition: relations and connectives directly, any other
+/// expression as "nonzero". Used for piecewise branches.
+pub fn eval_bool(store: &Store, id: ExprId, ctx: &EvalContext) -> Result<bool, EvalError> {
+    let node = store.get(id);
+    let side = |i: usize| eval_recursive(store, node.children[i], ctx);
+    match node.op {
+        Op::Eq => Ok(side(0)? == side(1)?),
+        Op::Lt => Ok(side(0)? < side(1)?),
+        Op::Le => Ok(side(0)? <= side(1)?),
+        Op::And => {
+            for &c in &node.children {
+                if !eval_bool(store, c, ctx)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        Op::Or => {
+            for &c in &node.children {
+                if eval_bool(store, c, ctx)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+        Op::Not => Ok(!eval_bool(store, node.children[0], ctx)?),
+        _ => Ok(eval_recursive(store, id, ctx)? != 0.0),
+    }
+}
+
 /// Evaluate with a single variable binding (convenience function)
 pub fn eval_at(store: &Store, id: ExprId, var: &str, value: f64) -> Result<f64, EvalError> {
     let mut ctx = EvalContext::new();
@@ -90,6 +128,17 @@ fn eval_recursive(store: &Store, id: ExprId, ctx: &EvalContext) -> Result<f64, E
 
         (Op::Rational, Payload::Rat(num, den)) => Ok((*num as f64) / (*den as f64)),
 
+        (Op::Integer, Payload::BigInt(k)) => Ok(k.to_f64()),
+
+        (Op::Rational, Payload::BigRat(r)) => Ok(r.to_f64()),
+
+        (Op::Float, Payload::Float(f)) => Ok(f.to_f64()),
+
+        // pi, E and the real infinities (rejected later by `eval` as non-finite)
+        (Op::Constant, Payload::Const(c)) => {
+            c.to_f64().ok_or_else(|| EvalError::DomainError(format!("{} is not real", c.name())))
+        }
+
         // Symbols
         (Op::Symbol, Payload::Sym(name)) => {
             ctx.get(name).ok_or_else(|| EvalError::UnboundVariable(name.clone()))
@@ -123,10 +172,108 @@ fn eval_recursive(store: &Store, id: ExprId, ctx: &EvalContext) -> Result<f64, E
         // Functions
         (Op::Function, Payload::Func(name)) => eval_function(store, name, &node.children, ctx),
 
+        // Truth values as 1.0 / 0.0
+        (Op::Eq | Op::Lt | Op::Le | Op::And | Op::Or | Op::Not, _) => {
+            Ok(if eval_bool(store, id, ctx)? { 1.0 } else { 0.0 })
+        }
+
+        // Piecewise: first branch whose condition holds
+        (Op::Piecewise, _) => {
+            for pair in node.children.chunks(2) {
+                if pair.len() == 2 && eval_bool(store, pair[0], ctx)? {
+                    return eval_recursive(store, pair[1], ctx);
+                }
+            }
+            Err(EvalError::DomainError("no piecewise branch applies".into()))
+        }
+
+        // Held sums/products over integer ranges, definite integrals by quadrature
+        (Op::Sum | Op::Product, _) => eval_series(store, id, ctx),
+        (Op::Integral, _) if node.children.len() == 4 => eval_quadrature(store, id, ctx),
+
         _ => Err(EvalError::DomainError(format!("cannot evaluate {:?}", node.op))),
     }
 }
 
+/// Most terms a held sum or product is evaluated over.
+const MAX_SERIES_TERMS: f64 = 1e6;
+
+/// Bound variable and numeric limits of a held Sum/Product/Integral.
+fn held_limits<'a>(
+    store: &'a Store,
+    id: ExprId,
+    ctx: &EvalContext,
+) -> Result<(&'a str, f64, f64), EvalError> {
+    let node = store.get(id);
+    let var = store.held_var(id).ok_or_else(|| EvalError::DomainError("bad variable".into()))?;
+    let a = eval_recursive(store, node.children[2], ctx)?;
+    let b = eval_recursive(store, node.children[3], ctx)?;
+    Ok((var, a, b))
+}
+
+fn eval_series(store: &Store, id: ExprId, ctx: &EvalContext) -> Result<f64, EvalError> {
+    let (var, a, b) = held_limits(store, id, ctx)?;
+    if a.fract() != 0.0 || b.fract() != 0.0 {
+        return Err(EvalError::DomainError("summation limits must be integers".into()));
+    }
+    if b - a >= MAX_SERIES_TERMS {
+        return Err(EvalError::DomainError("too many terms".into()));
+    }
+    let is_sum = store.get(id).op == Op::Sum;
+    let body = store.get(id).children[0];
+    let mut inner = ctx.clone();
+    let mut acc = if is_sum { 0.0 } else { 1.0 };
+    let mut k = a;
+    while k <= b {
+        inner.bind(var, k);
+        let t = eval_recursive(store, body, &inner)?;
+        acc = if is_sum { acc + t } else { acc * t };
+        k += 1.0;
+    }
+    Ok(acc)
+}
+
+/// Definite integral over finite limits by adaptive Simpson quadrature.
+fn eval_quadrature(store: &Store, id: ExprId, ctx: &EvalContext) -> Result<f64, EvalError> {
+    let (var, a, b) = held_limits(store, id, ctx)?;
+    if !a.is_finite() || !b.is_finite() {
+        return Err(EvalError::DomainError("quadrature needs finite limits".into()));
+    }
+    let body = store.get(id).children[0];
+    let mut inner = ctx.clone();
+    let mut f = |x: f64| {
+        inner.bind(var, x);
+        eval_recursive(store, body, &inner)
+    };
+    let (fa, fb) = (f(a)?, f(b)?);
+    let m = 0.5 * (a + b);
+    let fm = f(m)?;
+    let whole = (b - a) / 6.0 * (fa + 4.0 * fm + fb);
+    simpson(&mut f, (a, fa), (m, fm), (b, fb), whole, 1e-10, 40)
+}
+
+fn simpson(
+    f: &mut dyn FnMut(f64) -> Result<f64, EvalError>,
+    (a, fa): (f64, f64),
+    (m, fm): (f64, f64),
+    (b, fb): (f64, f64),
+    whole: f64,
+    eps: f64,
+    depth: u32,
+) -> Result<f64, EvalError> {
+    let (lm, rm) = (0.5 * (a + m), 0.5 * (m + b));
+    let (flm, frm) = (f(lm)?, f(rm)?);
+    let left = (m - a) / 6.0 * (fa + 4.0 * flm + fm);
+    let right = (b - m) / 6.0 * (fm + 4.0 * frm + fb);
+    let delta = left + right - whole;
+    if depth == 0 || delta.abs() <= 15.0 * eps {
+        return Ok(left + right + delta / 15.0);
+    }
+    let l = simpson(f, (a, fa), (lm, flm), (m, fm), left, eps / 2.0, depth - 1)?;
+    let r = simpson(f, (m, fm), (rm, frm), (b, fb), right, eps / 2.0, depth - 1)?;
+    Ok(l + r)
+}
+
 fn eval_function(
     store: &Store,
     name: &str,
@@ -253,6 +400,24 @@ fn eval_function(
             Ok(x.abs())
         }
 
+        // Complex parts of a real value
+        "re" | "conjugate" => {
+            check_arity(name, args, 1)?;
+            eval_recursive(store, args[0], ctx)
+        }
+
+        "im" => {
+            check_arity(name, args, 1)?;
+            eval_recursive(store, args[0], ctx)?;
+            Ok(0.0)
+        }
+
+        "arg" => {
+            check_arity(name, args, 1)?;
+            let x = eval_recursive(store, args[0], ctx)?;
+            Ok(if x < 0.0 { std::f64::consts::PI } else { 0.0 })
+        }
+
         "floor" => {
             check_arity(name, args, 1)?;
             let x = eval_recursive(store, args[0], ctx)?;
@@ -436,7 +601,7 @@ fn check_arity(name: &str, args: &[ExprId], expected: usize) -> Result<(), EvalE
 #[cfg(test)]
 mod tests {
     use super::*;
-    use expr_core::Store;
+    use expr_core::{Constant, Store};
 
     #[test]
     fn eval_integer() {
@@ -585,6 +750,21 @@ mod tests {
         assert_eq!(eval(&st, expr, &ctx).unwrap(), 5.0);
     }
 
+    #[test]
+    fn eval_complex_parts_of_reals() {
+        let mut st = Store::new();
+        let neg_five = st.int(-5);
+        let ctx = EvalContext::new();
+        let re = st.func("re", vec![neg_five]);
+        assert_eq!(eval(&st, re, &ctx).unwrap(), -5.0);
+        let im = st.func("im", vec![neg_five]);
+        assert_eq!(eval(&st, im, &ctx).unwrap(), 0.0);
+        let conj = st.func("conjugate", vec![neg_five]);
+        assert_eq!(eval(&st, conj, &ctx).unwrap(), -5.0);
+        let arg = st.func("arg", vec![neg_five]);
+        assert_eq!(eval(&st, arg, &ctx).unwrap(), std::f64::consts::PI);
+    }
+
     #[test]
     fn eval_complex_expression() {
         let mut st = Store::new();
@@ -957,4 +1137,90 @@ mod tests {
         // erf(0.5) ≈ 0.5205
         assert!((result - 0.5205).abs() < 0.001);
     }
+
+    #[test]
+    fn eval_float_literal() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let c = st.float(0.1);
+        let expr = st.mul(vec![c, x]);
+        let mut ctx = EvalContext::new();
+        ctx.bind("x", 3.0);
+        assert_eq!(eval(&st, expr, &ctx).unwrap(), 0.1 * 3.0);
+    }
+
+    #[test]
+    fn eval_relations_and_piecewise() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let zero = st.int(0);
+        let one = st.int(1);
+        let neg = st.lt(x, zero);
+        let big = st.le(one, x);
+        let either = st.or(vec![neg, big]);
+        let inside = st.not(either);
+        let m1 = st.int(-1);
+        let minus_x = st.mul(vec![m1, x]);
+        let pw = st.piecewise(vec![(neg, minus_x), (big, one), (inside, x)]);
+        for (v, expected) in [(-2.0, 2.0), (0.25, 0.25), (3.0, 1.0)] {
+            assert_eq!(eval_at(&st, pw, "x", v).unwrap(), expected);
+        }
+        assert_eq!(eval_at(&st, inside, "x", 0.5).unwrap(), 1.0);
+        let mut ctx = EvalContext::new();
+        ctx.bind("x", 0.5);
+        assert!(!eval_bool(&st, either, &ctx).unwrap());
+        let only_neg = st.piecewise(vec![(neg, one)]);
+        assert!(eval_at(&st, only_neg, "x", 1.0).is_err());
+    }
+
+    #[test]
+    fn eval_constants() {
+        let mut st = Store::new();
+        let pi = st.constant(Constant::Pi);
+        let e = st.constant(Constant::E);
+        let x = st.sym("x");
+        let pix = st.mul(vec![pi, x]);
+        let expr = st.add(vec![pix, e]);
+        let v = eval_at(&st, expr, "x", 2.0).unwrap();
+        assert!((v - (2.0 * std::f64::consts::PI + std::f64::consts::E)).abs() < 1e-12);
+        let oo = st.constant(Constant::Infinity);
+        assert_eq!(eval(&st, oo, &EvalContext::new()), Err(EvalError::NonFinite));
+        let i = st.constant(Constant::I);
+        assert!(matches!(eval(&st, i, &EvalContext::new()), Err(EvalError::DomainError(_))));
+        // Conditions may compare against infinities
+        let cond = st.lt(x, oo);
+        let mut ctx = EvalContext::new();
+        ctx.bind("x", 1e300);
+        assert!(eval_bool(&st, cond, &ctx).unwrap());
+    }
+
+    #[test]
+    fn eval_held_sums_and_integrals() {
+        let mut st = Store::new();
+        let k = st.sym("k");
+        let n = st.sym("n");
+        let one = st.int(1);
+        let two = st.int(2);
+        // Sum(k^2, (k, 1, n)) at n = 4 is 30
+        let k2 = st.pow(k, two);
+        let s = st.sum(k2, "k", one, n);
+        assert_eq!(eval_at(&st, s, "n", 4.0).unwrap(), 30.0);
+        // Product(k, (k, 1, 5)) = 120; the index does not leak out
+        let five = st.int(5);
+        let p = st.product(k, "k", one, five);
+        assert_eq!(eval_at(&st, p, "k", 100.0).unwrap(), 120.0);
+        // Integral(exp(-x^2), (x, 0, 1)) ~ 0.746824132812427
+        let x = st.sym("x");
+        let m1 = st.int(-1);
+        let x2 = st.pow(x, two);
+        let neg = st.mul(vec![m1, x2]);
+        let g = st.func("exp", vec![neg]);
+        let zero = st.int(0);
+        let i = st.integral(g, "x", Some((zero, one)));
+        let v = eval(&st, i, &EvalContext::new()).unwrap();
+        assert!((v - 0.746824132812427).abs() < 1e-9);
+        // Derivatives and indefinite integrals have no numeric value
+        let d = st.derivative(g, "x", 1);
+        assert!(eval_at(&st, d, "x", 0.5).is_err());
+    }
 }
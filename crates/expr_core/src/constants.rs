@@ -0,0 +1,425 @@
+//! Named constants as atoms (`Op::Constant`) and their arithmetic.
+//!
+//! Rules applied by the canonical constructors:
+//! - `add`: `oo + c = oo` for finite `c`, `oo + -oo = nan`, `zoo` absorbs finite terms,
+//!   `zoo + zoo = nan`, and `nan` absorbs everything.
+//! - `mul`: infinities absorb positive/negative finite real factors (flipping sign as
+//!   needed), `0 * oo = nan`, and `I * I = -1`.
+//! - `pow`: `I^k` reduces mod 4, `(-1)^(1/2) = I`, and powers of/to infinities fold
+//!   where the limit is unambiguous (`2^oo = oo`, `oo^-1 = 0`, `1^oo = nan`).
+//!
+//! Symbolic terms are left next to an infinity (`x + oo` stays as is), since
+//! nothing is known about their size.
+
+use crate::{ExprId, NumAcc, Op, Payload, Store};
+use arith::BigRat;
+
+/// Mathematical constants with dedicated atoms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Constant {
+    Pi,
+    E,
+    /// Imaginary unit
+    I,
+    Infinity,
+    NegativeInfinity,
+    /// Unsigned infinity (e.g. `1/0` over the complex numbers)
+    ComplexInfinity,
+    NaN,
+}
+
+impl Constant {
+    pub const ALL: [Constant; 7] = [
+        Constant::Pi,
+        Constant::E,
+        Constant::I,
+        Constant::Infinity,
+        Constant::NegativeInfinity,
+        Constant::ComplexInfinity,
+        Constant::NaN,
+    ];
+
+    /// Printed name: `pi`, `E`, `I`, `oo`, `-oo`, `zoo`, `nan`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Constant::Pi => "pi",
+            Constant::E => "E",
+            Constant::I => "I",
+            Constant::Infinity => "oo",
+            Constant::NegativeInfinity => "-oo",
+            Constant::ComplexInfinity => "zoo",
+            Constant::NaN => "nan",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        Constant::ALL.into_iter().find(|c| c.name() == s)
+    }
+
+    /// Real value as an f64 (`None` for `I` and `zoo`).
+    pub fn to_f64(self) -> Option<f64> {
+        match self {
+            Constant::Pi => Some(std::f64::consts::PI),
+            Constant::E => Some(std::f64::consts::E),
+            Constant::Infinity => Some(f64::INFINITY),
+            Constant::NegativeInfinity => Some(f64::NEG_INFINITY),
+            Constant::NaN => Some(f64::NAN),
+            Constant::I | Constant::ComplexInfinity => None,
+        }
+    }
+
+    pub fn is_infinite(self) -> bool {
+        matches!(self, Constant::Infinity | Constant::NegativeInfinity | Constant::ComplexInfinity)
+    }
+}
+
+impl Store {
+    pub fn constant(&mut self, c: Constant) -> ExprId {
+        self.intern(Op::Constant, Payload::Const(c), vec![])
+    }
+
+    pub fn as_constant(&self, id: ExprId) -> Option<Constant> {
+        match self.get(id).payload {
+            Payload::Const(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Finite and real without any free symbols: numbers, `pi`, `E`, and
+    /// sums/products/integer powers of them.
+    fn is_finite_real_constant(&self, id: ExprId) -> bool {
+        let n = self.get(id);
+        match (&n.op, &n.payload) {
+            (Op::Integer | Op::Rational | Op::Float, _) => true,
+            (Op::Constant, Payload::Const(c)) => matches!(c, Constant::Pi | Constant::E),
+            (Op::Add | Op::Mul, _) => n.children.iter().all(|&c| self.is_finite_real_constant(c)),
+            (Op::Pow, _) => {
+                matches!(self.get(n.children[1]).payload, Payload::Int(k) if k >= 0)
+                    && self.is_finite_real_constant(n.children[0])
+            }
+            _ => false,
+        }
+    }
+
+    /// Constant rules for `add`, applied to the flattened non-numeric terms and
+    /// the folded numeric part. Returns the whole sum when it collapses.
+    pub(crate) fn fold_add_constants(
+        &mut self,
+        terms: &mut Vec<ExprId>,
+        num: &mut NumAcc,
+    ) -> Option<ExprId> {
+        let count = |c: Constant| terms.iter().filter(|&&t| self.as_constant(t) == Some(c)).count();
+        let (pos, neg, zoo) = (
+            count(Constant::Infinity),
+            count(Constant::NegativeInfinity),
+            count(Constant::ComplexInfinity),
+        );
+        if count(Constant::NaN) > 0 || (pos > 0 && neg > 0) || zoo > 1 || (zoo > 0 && pos + neg > 0)
+        {
+            return Some(self.constant(Constant::NaN));
+        }
+        let inf = if zoo > 0 {
+            Constant::ComplexInfinity
+        } else if pos > 0 {
+            Constant::Infinity
+        } else if neg > 0 {
+            Constant::NegativeInfinity
+        } else {
+            return None;
+        };
+        // The infinity absorbs finite constants (zoo also absorbs imaginary ones)
+        terms.retain(|&t| {
+            !(self.as_constant(t).is_some_and(Constant::is_infinite)
+                || self.is_finite_real_constant(t)
+                || (inf == Constant::ComplexInfinity && self.has_only_constants(t)))
+        });
+        *num = NumAcc::Small(0, 1);
+        let c = self.constant(inf);
+        terms.push(c);
+        None
+    }
+
+    fn has_only_constants(&self, id: ExprId) -> bool {
+        let n = self.get(id);
+        match n.op {
+            Op::Integer | Op::Rational | Op::Float => true,
+            Op::Constant => {
+                !matches!(n.payload, Payload::Const(c) if c.is_infinite() || c == Constant::NaN)
+            }
+            Op::Add | Op::Mul | Op::Pow => n.children.iter().all(|&c| self.has_only_constants(c)),
+            _ => false,
+        }
+    }
+
+    /// Constant rules for `mul`; `has_zero` says a numeric zero factor was seen.
+    /// Returns the whole product when it collapses.
+    pub(crate) fn fold_mul_constants(
+        &mut self,
+        factors: &mut Vec<ExprId>,
+        num: &mut NumAcc,
+        has_zero: bool,
+    ) -> Option<ExprId> {
+        let count =
+            |c: Constant| factors.iter().filter(|&&f| self.as_constant(f) == Some(c)).count();
+        let (pos, neg, zoo, imag) = (
+            count(Constant::Infinity),
+            count(Constant::NegativeInfinity),
+            count(Constant::ComplexInfinity),
+            count(Constant::I),
+        );
+        if count(Constant::NaN) > 0 || (has_zero && pos + neg + zoo > 0) {
+            return Some(self.constant(Constant::NaN));
+        }
+        if has_zero {
+            return None;
+        }
+        if pos + neg + zoo > 0 {
+            // Finite real factors of known sign are absorbed into the infinity
+            let known_sign = |st: &Store, f: ExprId| {
+                if st.is_finite_real_constant(f) {
+                    st.real_sign(f).filter(|&s| s != 0)
+                } else {
+                    None
+                }
+            };
+            let mut negative = neg % 2 == 1;
+            for &f in factors.iter() {
+                if known_sign(self, f) == Some(-1) {
+                    negative = !negative;
+                }
+            }
+            if num.is_negative() {
+                negative = !negative;
+            }
+            let inf = if zoo > 0 {
+                Constant::ComplexInfinity
+            } else if negative {
+                Constant::NegativeInfinity
+            } else {
+                Constant::Infinity
+            };
+            factors.retain(|&f| {
+                !(self.as_constant(f).is_some_and(Constant::is_infinite)
+                    || known_sign(self, f).is_some()
+                    || (zoo > 0 && self.as_constant(f) == Some(Constant::I)))
+            });
+            *num = NumAcc::Small(1, 1);
+            let c = self.constant(inf);
+            factors.push(c);
+            if zoo > 0 {
+                return None;
+            }
+        }
+        // I^2 = -1
+        if imag > 1 {
+            factors.retain(|&f| self.as_constant(f) != Some(Constant::I));
+            if imag % 4 >= 2 {
+                let acc = std::mem::replace(num, NumAcc::Small(1, 1));
+                *num = acc.mul(NumAcc::Small(-1, 1));
+            }
+            if imag % 2 == 1 {
+                let i = self.constant(Constant::I);
+                factors.push(i);
+            }
+        }
+        None
+    }
+
+    /// Sign of a finite real constant expression, when it can be read off cheaply.
+    fn real_sign(&self, id: ExprId) -> Option<i32> {
+        if let Some(v) = self.real_value(id) {
+            return Some(v.numer().signum());
+        }
+        let n = self.get(id);
+        match (&n.op, &n.payload) {
+            (Op::Constant, Payload::Const(Constant::Pi | Constant::E)) => Some(1),
+            (Op::Mul, _) => n.children.iter().try_fold(1, |s, &c| Some(s * self.real_sign(c)?)),
+            (Op::Pow, _) => {
+                match (self.real_sign(n.children[0])?, &self.get(n.children[1]).payload) {
+                    (1, _) => Some(1),
+                    (s, Payload::Int(k)) => Some(if k % 2 == 0 { 1 } else { s }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Constant rules for `pow` (after the `x^0`/`x^1` shortcuts).
+    pub(crate) fn fold_pow_constants(&mut self, base: ExprId, exp: ExprId) -> Option<ExprId> {
+        let (bc, ec) = (self.as_constant(base), self.as_constant(exp));
+        if bc == Some(Constant::NaN) || ec == Some(Constant::NaN) {
+            return Some(self.constant(Constant::NaN));
+        }
+        let exp_sign = self.real_value(exp).map(|v| v.numer().signum());
+        match (bc, ec) {
+            (Some(Constant::I), _) => {
+                if let Payload::Int(k) = self.get(exp).payload {
+                    return Some(match k.rem_euclid(4) {
+                        0 => self.int(1),
+                        1 => base,
+                        2 => self.int(-1),
+                        _ => {
+                            let m1 = self.int(-1);
+                            self.mul(vec![m1, base])
+                        }
+                    });
+                }
+                None
+            }
+            (Some(Constant::Infinity | Constant::ComplexInfinity), _)
+            | (Some(Constant::NegativeInfinity), _)
+                if exp_sign == Some(-1) =>
+            {
+                Some(self.int(0))
+            }
+            (Some(Constant::Infinity), _)
+                if exp_sign == Some(1) || ec == Some(Constant::Infinity) =>
+            {
+                Some(base)
+            }
+            (Some(Constant::ComplexInfinity), _) if exp_sign == Some(1) => Some(base),
+            (Some(Constant::NegativeInfinity), _) => match self.get(exp).payload {
+                Payload::Int(k) if k % 2 == 0 => Some(self.constant(Constant::Infinity)),
+                Payload::Int(_) => Some(base),
+                _ => None,
+            },
+            (_, Some(inf @ (Constant::Infinity | Constant::NegativeInfinity))) => {
+                // b^oo for a nonnegative real constant b
+                let b = match self.real_value(base) {
+                    Some(v) => v,
+                    None if matches!(bc, Some(Constant::Pi | Constant::E)) => BigRat::from(2),
+                    None => return None,
+                };
+                if b.is_negative() {
+                    return None;
+                }
+                let one = BigRat::from(1);
+                let grows = (b > one) == (inf == Constant::Infinity);
+                Some(if b == one {
+                    self.constant(Constant::NaN)
+                } else if b.is_zero() {
+                    if inf == Constant::Infinity {
+                        self.int(0)
+                    } else {
+                        self.constant(Constant::ComplexInfinity)
+                    }
+                } else if grows {
+                    self.constant(Constant::Infinity)
+                } else {
+                    self.int(0)
+                })
+            }
+            _ => {
+                // (-1)^(±1/2) = ±I
+                if let (Payload::Int(-1), Payload::Rat(n, 2)) =
+                    (&self.get(base).payload, &self.get(exp).payload)
+                {
+                    let n = *n;
+                    let i = self.constant(Constant::I);
+                    return Some(if n == 1 {
+                        i
+                    } else if n == -1 {
+                        let m1 = self.int(-1);
+                        self.mul(vec![m1, i])
+                    } else {
+                        return None;
+                    });
+                }
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imaginary_unit_powers_and_products() {
+        let mut st = Store::new();
+        let i = st.constant(Constant::I);
+        let two = st.int(2);
+        assert_eq!(st.pow(i, two), st.int(-1));
+        let three = st.int(3);
+        let i3 = st.pow(i, three);
+        assert_eq!(st.to_string(i3), "-1 * I");
+        let m5 = st.int(-5);
+        assert_eq!(st.pow(i, m5), i3);
+        assert_eq!(st.mul(vec![i, i]), st.int(-1));
+        let x = st.sym("x");
+        let prod = st.mul(vec![i, x, i, i]);
+        let m1 = st.int(-1);
+        assert_eq!(prod, st.mul(vec![m1, x, i]));
+        let m1 = st.int(-1);
+        let half = st.rat(1, 2);
+        assert_eq!(st.pow(m1, half), i);
+    }
+
+    #[test]
+    fn infinity_arithmetic() {
+        let mut st = Store::new();
+        let oo = st.constant(Constant::Infinity);
+        let noo = st.constant(Constant::NegativeInfinity);
+        let zoo = st.constant(Constant::ComplexInfinity);
+        let nan = st.constant(Constant::NaN);
+        let pi = st.constant(Constant::Pi);
+        let one = st.int(1);
+        let x = st.sym("x");
+        assert_eq!(st.add(vec![oo, one]), oo);
+        assert_eq!(st.add(vec![oo, pi, oo]), oo);
+        assert_eq!(st.add(vec![oo, noo]), nan);
+        assert_eq!(st.add(vec![zoo, one]), zoo);
+        assert_eq!(st.add(vec![zoo, oo]), nan);
+        let sum = st.add(vec![x, oo, one]);
+        let x_oo = st.add(vec![x, oo]);
+        assert_eq!(sum, x_oo);
+        let m2 = st.int(-2);
+        assert_eq!(st.mul(vec![m2, oo]), noo);
+        assert_eq!(st.mul(vec![noo, noo]), oo);
+        let mpi = st.mul(vec![m2, pi]);
+        assert_eq!(st.mul(vec![mpi, oo]), noo);
+        let zero = st.int(0);
+        assert_eq!(st.mul(vec![zero, oo]), nan);
+        assert_eq!(st.mul(vec![zoo, m2]), zoo);
+        assert_eq!(st.add(vec![nan, x]), nan);
+    }
+
+    #[test]
+    fn powers_with_infinities() {
+        let mut st = Store::new();
+        let oo = st.constant(Constant::Infinity);
+        let noo = st.constant(Constant::NegativeInfinity);
+        let nan = st.constant(Constant::NaN);
+        let two = st.int(2);
+        let three = st.int(3);
+        let half = st.rat(1, 2);
+        let one = st.int(1);
+        let m1 = st.int(-1);
+        let zero = st.int(0);
+        assert_eq!(st.pow(two, oo), oo);
+        assert_eq!(st.pow(half, oo), zero);
+        assert_eq!(st.pow(two, noo), zero);
+        assert_eq!(st.pow(one, oo), nan);
+        assert_eq!(st.pow(oo, m1), zero);
+        assert_eq!(st.pow(oo, half), oo);
+        assert_eq!(st.pow(noo, two), oo);
+        assert_eq!(st.pow(noo, three), noo);
+        assert_eq!(st.pow(oo, zero), one);
+        let e = st.constant(Constant::E);
+        assert_eq!(st.pow(e, one), e);
+        assert_eq!(st.pow(e, oo), oo);
+    }
+
+    #[test]
+    fn names_round_trip() {
+        let mut st = Store::new();
+        for c in Constant::ALL {
+            assert_eq!(Constant::from_name(c.name()), Some(c));
+            let id = st.constant(c);
+            assert_eq!(st.to_string(id), c.name());
+            assert_eq!(st.as_constant(id), Some(c));
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Held (unevaluated) calculus nodes: `Derivative`, `Integral`, `Sum`, `Product`.
+//!
+//! Children layouts:
+//! - `Derivative`: `[f, x, n]` — the n-th derivative of `f` in the symbol `x`.
+//! - `Integral`: `[f, x]` (indefinite) or `[f, x, a, b]` (definite).
+//! - `Sum` / `Product`: `[t, k, a, b]` over integers `k = a..=b`.
+//!
+//! The index of a sum/product and the variable of a definite integral are bound:
+//! they are not free symbols of the node. A derivative's variable and the variable
+//! of an indefinite integral stay free (the result is a function of them).
+//! Nested derivatives in the same variable merge; order 0 is the expression itself.
+//! Evaluation lives in `calculus::doit`.
+
+use crate::{ExprId, Op, Payload, Store};
+use std::collections::HashSet;
+
+impl Store {
+    /// Held `n`-th derivative of `f` with respect to `x`.
+    pub fn derivative(&mut self, f: ExprId, x: &str, n: u32) -> ExprId {
+        if n == 0 {
+            return f;
+        }
+        let xs = self.sym(x);
+        let inner = self.get(f);
+        if inner.op == Op::Derivative && inner.children[1] == xs {
+            if let Payload::Int(m) = self.get(inner.children[2]).payload {
+                let body = inner.children[0];
+                let order = self.int(m + n as i64);
+                return self.intern(Op::Derivative, Payload::None, vec![body, xs, order]);
+            }
+        }
+        let order = self.int(n as i64);
+        self.intern(Op::Derivative, Payload::None, vec![f, xs, order])
+    }
+
+    /// Held integral of `f` in `x`; `bounds = Some((a, b))` for a definite integral.
+    pub fn integral(&mut self, f: ExprId, x: &str, bounds: Option<(ExprId, ExprId)>) -> ExprId {
+        let xs = self.sym(x);
+        let children = match bounds {
+            Some((a, b)) => vec![f, xs, a, b],
+            None => vec![f, xs],
+        };
+        self.intern(Op::Integral, Payload::None, children)
+    }
+
+    /// Held sum of `t` for `k = a..=b`.
+    pub fn sum(&mut self, t: ExprId, k: &str, a: ExprId, b: ExprId) -> ExprId {
+        let ks = self.sym(k);
+        self.intern(Op::Sum, Payload::None, vec![t, ks, a, b])
+    }
+
+    /// Held product of `t` for `k = a..=b`.
+    pub fn product(&mut self, t: ExprId, k: &str, a: ExprId, b: ExprId) -> ExprId {
+        let ks = self.sym(k);
+        self.intern(Op::Product, Payload::None, vec![t, ks, a, b])
+    }
+
+    /// True for `Derivative`, `Integral`, `Sum` and `Product` nodes.
+    pub fn is_held(&self, id: ExprId) -> bool {
+        matches!(self.get(id).op, Op::Derivative | Op::Integral | Op::Sum | Op::Product)
+    }
+
+    /// Variable name of a held node (differentiation, integration or summation variable).
+    pub fn held_var(&self, id: ExprId) -> Option<&str> {
+        if !self.is_held(id) {
+            return None;
+        }
+        match &self.get(self.get(id).children[1]).payload {
+            Payload::Sym(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Variable bound by `id` itself, if any: the index of a sum/product, the
+    /// variable of a definite integral or of a lambda.
+    pub fn bound_var(&self, id: ExprId) -> Option<&str> {
+        let n = self.get(id);
+        match n.op {
+            Op::Sum | Op::Product => self.held_var(id),
+            Op::Integral if n.children.len() == 4 => self.held_var(id),
+            Op::Lambda => self.lambda_parts(id).map(|(x, _)| x),
+            _ => None,
+        }
+    }
+
+    /// Whether the symbol `name` occurs free in `id` (occurrences bound by a
+    /// sum, product, definite integral or lambda do not count; limits do).
+    pub fn has_free_symbol(&self, id: ExprId, name: &str) -> bool {
+        self.nodes_with_free_symbol(id, name).contains(&id)
+    }
+
+    /// The nodes of `id` (itself included) in which `name` occurs free, in one
+    /// pass that visits every distinct node once.
+    pub fn nodes_with_free_symbol(&self, id: ExprId, name: &str) -> HashSet<ExprId> {
+        let mut out = HashSet::new();
+        for m in self.postorder(id) {
+            let n = self.get(m);
+            let free = match (&n.op, &n.payload) {
+                (Op::Symbol, Payload::Sym(s)) => s == name,
+                _ if self.bound_var(m) == Some(name) => {
+                    n.children[2..].iter().any(|c| out.contains(c))
+                }
+                _ => n.children.iter().any(|c| out.contains(c)),
+            };
+            if free {
+                out.insert(m);
+            }
+        }
+        out
+    }
+
+    /// Canonical rebuild for the held ops (used by `build`).
+    pub(crate) fn build_held(&mut self, op: Op, children: Vec<ExprId>) -> ExprId {
+        if op == Op::Derivative {
+            let var = match &self.get(children[1]).payload {
+                Payload::Sym(s) => Some(s.clone()),
+                _ => None,
+            };
+            if let (Some(x), Payload::Int(n)) = (var, &self.get(children[2]).payload) {
+                if let Ok(n) = u32::try_from(*n) {
+                    return self.derivative(children[0], &x, n);
+                }
+            }
+        }
+        self.intern(op, Payload::None, children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derivatives_merge_and_print() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let f = st.func("f", vec![x]);
+        assert_eq!(st.derivative(f, "x", 0), f);
+        let d1 = st.derivative(f, "x", 1);
+        assert_eq!(st.to_string(d1), "Derivative(f(x), x)");
+        let d3 = st.derivative(d1, "x", 2);
+        assert_eq!(d3, st.derivative(f, "x", 3));
+        assert_eq!(st.to_string(d3), "Derivative(f(x), x, 3)");
+        // Different variables nest
+        let dy = st.derivative(d1, "y", 1);
+        assert_eq!(st.get(dy).children[0], d1);
+        // build goes through the canonical constructor
+        let three = st.int(3);
+        let xs = st.sym("x");
+        assert_eq!(
+            st.build(Op::Derivative, Payload::None, vec![d1, xs, three]),
+            st.derivative(f, "x", 4)
+        );
+    }
+
+    #[test]
+    fn bound_variables_are_not_free() {
+        let mut st = Store::new();
+        let k = st.sym("k");
+        let n = st.sym("n");
+        let one = st.int(1);
+        let s = st.sum(k, "k", one, n);
+        assert_eq!(st.to_string(s), "Sum(k, (k, 1, n))");
+        assert!(!st.has_free_symbol(s, "k"));
+        assert!(st.has_free_symbol(s, "n"));
+        assert_eq!(st.bound_var(s), Some("k"));
+        let p = st.product(k, "k", one, n);
+        assert_eq!(st.to_string(p), "Product(k, (k, 1, n))");
+        // The variable of an indefinite integral stays free
+        let x = st.sym("x");
+        let ix = st.integral(x, "x", None);
+        assert_eq!(st.to_string(ix), "Integral(x, x)");
+        assert!(st.has_free_symbol(ix, "x"));
+        assert_eq!(st.bound_var(ix), None);
+        let zero = st.int(0);
+        let dx = st.integral(x, "x", Some((zero, x)));
+        assert_eq!(st.to_string(dx), "Integral(x, (x, 0, x))");
+        // Bound in the body but free in the upper limit
+        assert!(st.has_free_symbol(dx, "x"));
+        assert!(st.is_held(dx));
+        assert_eq!(st.held_var(dx), Some("x"));
+        // Shared subterms are visited once: 2^64 paths, 65 nodes
+        let y = st.sym("y");
+        let mut e = st.add(vec![x, y]);
+        for _ in 0..64 {
+            e = st.func("f", vec![e, e]);
+        }
+        assert!(st.has_free_symbol(e, "y"));
+        assert!(!st.has_free_symbol(e, "z"));
+    }
+}
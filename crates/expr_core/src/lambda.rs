@@ -0,0 +1,204 @@
+//! Lambda (binder) nodes and capture-avoiding substitution.
+//!
+//! A `Lambda` has children `[body, var]`, the same layout as the binders in
+//! `held`, so `bound_var`, `has_free_symbol` and `free_symbols` treat all binders
+//! alike. On construction the bound variable is renamed to a canonical `#k`, where
+//! `k` is the first level above every lambda inside the body that is not
+//! free in it (a free `#`-symbol comes from an opened lambda body). Hence
+//! alpha-equivalent lambdas intern to the same node, and a nested lambda never
+//! reuses the name of an enclosing one. Symbol names starting with `#` are
+//! reserved for these variables.
+//!
+//! `subs` renames a bound variable whenever the replacement mentions it, so
+//! substituting into a sum over `k` (or a lambda) cannot capture a free `k`.
+
+use crate::{ExprId, Op, Payload, Store};
+use std::collections::HashMap;
+
+impl Store {
+    /// `var -> body` with the bound variable in canonical form.
+    pub fn lambda(&mut self, var: &str, body: ExprId) -> ExprId {
+        // Above every inner level, skipping free `#k` (other than `var`), so
+        // that renaming neither clashes with a binder nor captures
+        let level = self.preorder(body).filter_map(|n| self.lambda_level(n)).max();
+        let free = self.free_symbols(body);
+        let name = (level.map_or(0, |l| l + 1)..)
+            .map(|k| format!("#{k}"))
+            .find(|s| s == var || !free.contains(s))
+            .expect("unbounded range");
+        let v = self.sym(name.as_str());
+        let body = if var == name { body } else { self.subs(body, var, v) };
+        self.intern(Op::Lambda, Payload::None, vec![body, v])
+    }
+
+    /// Bound variable name and body of a lambda.
+    pub fn lambda_parts(&self, id: ExprId) -> Option<(&str, ExprId)> {
+        let n = self.get(id);
+        if n.op != Op::Lambda {
+            return None;
+        }
+        match &self.get(n.children[1]).payload {
+            Payload::Sym(s) => Some((s, n.children[0])),
+            _ => None,
+        }
+    }
+
+    /// Beta reduction: the body of `f` with its variable replaced by `arg`
+    /// (`None` if `f` is not a lambda).
+    pub fn apply_lambda(&mut self, f: ExprId, arg: ExprId) -> Option<ExprId> {
+        let (var, body) = self.lambda_parts(f)?;
+        let var = var.to_string();
+        Some(self.subs(body, &var, arg))
+    }
+
+    fn lambda_level(&self, id: ExprId) -> Option<usize> {
+        self.lambda_parts(id)?.0.strip_prefix('#')?.parse().ok()
+    }
+
+    /// Replace the free occurrences of the symbol `name` in `id` by `value`.
+    ///
+    /// Binders (lambdas, sums, products, definite integrals) that bind `name`
+    /// are only entered in their limits; a binder whose variable occurs free in
+    /// `value` is renamed first. A held derivative (or indefinite integral) in
+    /// `name` evaluated at a non-symbol becomes `Subs(expr, name, value)`.
+    pub fn subs(&mut self, id: ExprId, name: &str, value: ExprId) -> ExprId {
+        let mut memo = HashMap::new();
+        self.subs_rec(id, name, value, &mut memo)
+    }
+
+    fn subs_rec(
+        &mut self,
+        id: ExprId,
+        name: &str,
+        value: ExprId,
+        memo: &mut HashMap<ExprId, ExprId>,
+    ) -> ExprId {
+        if let Some(&r) = memo.get(&id) {
+            return r;
+        }
+        let out = match (&self.get(id).op, &self.get(id).payload) {
+            (Op::Symbol, Payload::Sym(s)) => {
+                if s == name {
+                    value
+                } else {
+                    id
+                }
+            }
+            _ if self.get(id).children.is_empty() => id,
+            (Op::Derivative | Op::Integral, _)
+                if self.bound_var(id).is_none()
+                    && self.held_var(id) == Some(name)
+                    && self.get(value).op != Op::Symbol =>
+            {
+                let x = self.sym(name);
+                self.func("Subs", vec![id, x, value])
+            }
+            _ => {
+                let n = self.get(id);
+                let (op, payload) = (n.op.clone(), n.payload.clone());
+                let old = n.children.clone();
+                let mut children = old.clone();
+                let mut from = 0;
+                if let Some(b) = self.bound_var(id).map(str::to_string) {
+                    if b == name {
+                        // Only the limits see the outer `name`
+                        from = 2;
+                    } else if self.has_free_symbol(value, &b)
+                        && self.has_free_symbol(children[0], name)
+                    {
+                        let fresh = self.fresh_name(&b, &[children[0], value]);
+                        let f = self.sym(fresh.as_str());
+                        children[0] = self.subs(children[0], &b, f);
+                        children[1] = f;
+                    }
+                }
+                for c in children.iter_mut().skip(from) {
+                    *c = self.subs_rec(*c, name, value, memo);
+                }
+                if children == old {
+                    id
+                } else {
+                    self.build(op, payload, children)
+                }
+            }
+        };
+        memo.insert(id, out);
+        out
+    }
+
+    /// `base_1`, `base_2`, ... : the first name free in none of `avoid`.
+    fn fresh_name(&self, base: &str, avoid: &[ExprId]) -> String {
+        (1..)
+            .map(|i| format!("{base}_{i}"))
+            .find(|s| avoid.iter().all(|&e| !self.has_free_symbol(e, s)))
+            .expect("unbounded supply of names")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alpha_equivalent_lambdas_intern_together() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let two = st.int(2);
+        let x2 = st.pow(x, two);
+        let y2 = st.pow(y, two);
+        let fx = st.lambda("x", x2);
+        assert_eq!(fx, st.lambda("y", y2));
+        assert_eq!(st.to_string(fx), "Lambda(#0, #0^2)");
+        assert!(st.free_symbols(fx).is_empty());
+        // Nested binders get distinct names
+        let xy = st.add(vec![x, y]);
+        let inner = st.lambda("y", xy);
+        let outer = st.lambda("x", inner);
+        assert_eq!(st.to_string(outer), "Lambda(#1, Lambda(#0, #1 + #0))");
+        // Beta reduction
+        let three = st.int(3);
+        let r = st.apply_lambda(fx, three).unwrap();
+        assert_eq!(st.to_string(r), "3^2");
+        assert_eq!(st.apply_lambda(x, three), None);
+        // Rebinding the open body of Lambda(#0, #0) must not capture its free #0
+        let id = st.lambda("x", x);
+        let (_, open) = st.lambda_parts(id).unwrap();
+        let t = st.sym("t");
+        let body = st.mul(vec![open, t]);
+        let f = st.lambda("t", body);
+        assert_eq!(st.to_string(f), "Lambda(#1, #1 * #0)");
+        assert_eq!(st.free_symbols(f).into_iter().collect::<Vec<_>>(), ["#0"]);
+    }
+
+    #[test]
+    fn substitution_avoids_capture() {
+        let mut st = Store::new();
+        let k = st.sym("k");
+        let n = st.sym("n");
+        let m = st.sym("m");
+        let one = st.int(1);
+        // Sum(k * n, (k, 1, m)) with n := k must not capture k
+        let kn = st.mul(vec![k, n]);
+        let s = st.sum(kn, "k", one, m);
+        let r = st.subs(s, "n", k);
+        assert_eq!(st.to_string(r), "Sum(k_1 * k, (k_1, 1, m))");
+        assert!(st.has_free_symbol(r, "k"));
+        // The bound index itself is untouched; limits are substituted
+        assert_eq!(st.subs(s, "k", one), s);
+        let r = st.subs(s, "m", n);
+        assert_eq!(r, st.sum(kn, "k", one, n));
+        // No renaming when the body does not mention the replaced symbol
+        let sk = st.sum(k, "k", one, m);
+        assert_eq!(st.subs(sk, "m", k), st.sum(k, "k", one, k));
+        // Lambdas: the free y is replaced, the bound variable is not
+        let y = st.sym("y");
+        let xy = st.mul(vec![k, y]);
+        let f = st.lambda("k", xy);
+        let g = st.subs(f, "y", k);
+        let kk = st.mul(vec![k, k]);
+        assert_ne!(g, st.lambda("k", kk));
+        let r = st.apply_lambda(g, n).unwrap();
+        assert_eq!(r, st.mul(vec![n, k]));
+    }
+}
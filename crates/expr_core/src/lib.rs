@@ -2,11 +2,40 @@
 //! expr_core: minimal immutable DAG expression kernel with hash-consing.
 //! - Op/Node/ExprId
 //! - Store with interning + canonical Add/Mul/Pow
-//! - Basic numeric payload (i64 integers; small rationals)
-//! - Deterministic digest (FNV-1a 64) for canonical ordering
-
-use arith::{normalize_rat, rat_add, rat_mul};
+//! - Numeric payload: i64 integers/small rationals as the fast path,
+//!   promoted to `BigInt`/`BigRat` only when a value does not fit
+//! - Float literals carry their own precision (bits) and fold with other numbers
+//! - Deterministic digest (FNV-1a 64) for canonical ordering; interning is keyed on
+//!   child ids, so digest collisions never merge distinct nodes
+//! - Bounded memo caches with hit/miss stats, and `compact` to reclaim unreachable nodes
+//! - `freeze` snapshots a store for threads; per-thread overlays `merge` back
+//! - Relational/boolean nodes (Eq, Lt, Le, And, Or, Not) with canonical forms
+//! - Constant atoms (pi, E, I, oo, -oo, zoo, nan) with arithmetic rules in add/mul/pow
+//! - Held Derivative/Integral/Sum/Product nodes with bound-variable semantics
+//! - Lambda binders interned up to alpha-equivalence; capture-avoiding `subs`
+//! - Traversal toolkit: pre/post-order walks, `free_symbols`, `map_bottom_up`, `replace_all`
+
+mod constants;
+mod held;
+mod lambda;
+mod logic;
+mod memo;
+mod shared;
+mod traverse;
+
+pub use constants::Constant;
+pub use memo::CacheStats;
+pub use shared::SharedStore;
+pub use traverse::{PostOrder, PreOrder};
+
+use arith::{
+    checked_normalize_rat, checked_rat_add, checked_rat_mul, ArithError, BigInt, BigRat, Float,
+};
+use memo::MemoCache;
+use shared::Frozen;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 // ---------- IDs & basic nodes ----------
 
@@ -23,6 +52,24 @@ pub enum Op {
     Rational,
     Function,
     Piecewise,
+    Float,
+    // Relations (children: [lhs, rhs]); `>`/`>=` are stored with sides swapped
+    Eq,
+    Lt,
+    Le,
+    // Boolean connectives; empty And is True, empty Or is False
+    And,
+    Or,
+    Not,
+    // Named constant atom (payload `Const`)
+    Constant,
+    // Held calculus nodes (see `held`): [f, x, n], [f, x] or [f, x, a, b], [t, k, a, b]
+    Derivative,
+    Integral,
+    Sum,
+    Product,
+    // Binder [body, var]; the variable is renamed canonically (see `lambda`)
+    Lambda,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -33,6 +80,13 @@ pub enum Payload {
     // Reduced fraction: den>0 and gcd(|num|, den)=1
     Rat(i64, i64),
     Func(String),
+    // Integer outside i64 range (never used for values that fit in `Int`)
+    BigInt(BigInt),
+    // Reduced fraction whose numerator or denominator does not fit in i64
+    BigRat(BigRat),
+    // Inexact binary float with a precision tag (bits)
+    Float(Float),
+    Const(Constant),
 }
 
 #[derive(Clone, Debug)]
@@ -40,31 +94,62 @@ pub struct Node {
     pub op: Op,
     pub payload: Payload,
     pub children: Vec<ExprId>,
-    pub digest: u64, // structural fingerprint for ordering
+    pub digest: u64, // structural fingerprint for ordering (not guaranteed unique)
 }
 
-// Key used for interning (avoid storing unstable child ids in key; use child digests)
+// Key used for interning. Children are already interned, so equal child ids mean
+// structurally equal subtrees; the key is exact regardless of digest collisions.
 #[derive(Hash, PartialEq, Eq)]
 struct NodeKey {
     op: Op,
     payload: Payload,
-    child_digests: Vec<u64>,
+    children: Vec<ExprId>,
+}
+
+/// Node digest function: `(op, payload, child digests) -> u64`.
+pub type DigestFn = fn(&Op, &Payload, &[u64]) -> u64;
+
+struct Digester(DigestFn);
+
+impl Default for Digester {
+    fn default() -> Self {
+        Self(default_digest)
+    }
 }
 
 // ---------- Store (arena + interner) ----------
 
 #[derive(Default)]
 pub struct Store {
+    /// Frozen layers shared with other stores (ids `0..base_len`)
+    base: Option<Arc<Frozen>>,
+    base_len: usize,
+    /// Nodes owned by this store (ids `base_len..`)
     nodes: Vec<Node>,
     interner: HashMap<NodeKey, ExprId>,
+    digester: Digester,
     /// Memoization cache for differentiation: (expr_id, variable_name) -> derivative
-    diff_cache: HashMap<(ExprId, String), ExprId>,
+    diff_cache: MemoCache<(ExprId, String), ExprId>,
     /// Memoization cache for simplification: expr_id -> simplified_expr
-    simplify_cache: HashMap<ExprId, ExprId>,
+    simplify_cache: MemoCache<ExprId, ExprId>,
     /// Memoization cache for integration: (expr_id, variable_name) -> `Option<ExprId>`
-    integrate_cache: HashMap<(ExprId, String), Option<ExprId>>,
+    integrate_cache: MemoCache<(ExprId, String), Option<ExprId>>,
     /// Memoization cache for substitution: (expr_id, symbol, replacement) -> result
-    subst_cache: HashMap<(ExprId, String, ExprId), ExprId>,
+    subst_cache: MemoCache<(ExprId, String, ExprId), ExprId>,
+}
+
+/// Selects one of the store's memoization caches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CacheKind {
+    Diff,
+    Simplify,
+    Integrate,
+    Subst,
+}
+
+impl CacheKind {
+    pub const ALL: [CacheKind; 4] =
+        [CacheKind::Diff, CacheKind::Simplify, CacheKind::Integrate, CacheKind::Subst];
 }
 
 impl Store {
@@ -72,15 +157,87 @@ impl Store {
         Self::default()
     }
 
+    /// Store whose node digests come from `f` instead of FNV-1a.
+    /// Digests only drive ordering, so any function (even a constant) yields
+    /// correct interning; this exists mainly to exercise collision handling.
+    pub fn with_digest_fn(f: DigestFn) -> Self {
+        Self { digester: Digester(f), ..Self::default() }
+    }
+
     pub fn get(&self, id: ExprId) -> &Node {
-        &self.nodes[id.0]
+        match id.0.checked_sub(self.base_len) {
+            Some(i) => &self.nodes[i],
+            None => self.base.as_ref().expect("base layer").get(id),
+        }
+    }
+
+    /// Number of interned nodes (including frozen ones).
+    pub fn node_count(&self) -> usize {
+        self.base_len + self.nodes.len()
+    }
+
+    /// Drop every node not reachable from `roots` and renumber the rest.
+    /// Returns the old -> new id mapping for surviving nodes; any other
+    /// `ExprId` from before the call is invalid afterwards. Cache entries that
+    /// mention only surviving nodes are kept (remapped), the rest are dropped.
+    /// Frozen layers are flattened: surviving shared nodes are copied in.
+    pub fn compact(&mut self, roots: &[ExprId]) -> HashMap<ExprId, ExprId> {
+        let mut live = vec![false; self.node_count()];
+        let mut stack: Vec<ExprId> = roots.to_vec();
+        while let Some(id) = stack.pop() {
+            if !std::mem::replace(&mut live[id.0], true) {
+                stack.extend(&self.get(id).children);
+            }
+        }
+
+        // Children are always interned before their parents, so one forward pass
+        // sees every child's new id before it is needed.
+        let base = self.base.take();
+        let base_len = std::mem::take(&mut self.base_len);
+        let local = std::mem::take(&mut self.nodes);
+        let shared = (0..base_len)
+            .map(|i| live[i].then(|| base.as_ref().expect("base layer").get(ExprId(i)).clone()));
+        let old_nodes = shared.chain(local.into_iter().map(Some));
+        let mut new_id: Vec<Option<ExprId>> = vec![None; live.len()];
+        self.interner.clear();
+        for (i, node) in old_nodes.enumerate() {
+            let Some(mut node) = node.filter(|_| live[i]) else {
+                continue;
+            };
+            for c in node.children.iter_mut() {
+                *c = new_id[c.0].expect("child of a live node is live");
+            }
+            let id = ExprId(self.nodes.len());
+            let key = NodeKey {
+                op: node.op.clone(),
+                payload: node.payload.clone(),
+                children: node.children.clone(),
+            };
+            self.interner.insert(key, id);
+            self.nodes.push(node);
+            new_id[i] = Some(id);
+        }
+
+        let m = |id: &ExprId| new_id[id.0];
+        self.diff_cache.remap(|(e, v), r| Some(((m(e)?, v.clone()), m(r)?)));
+        self.simplify_cache.remap(|e, r| Some((m(e)?, m(r)?)));
+        self.integrate_cache.remap(|(e, v), r| {
+            let r = match r {
+                Some(r) => Some(m(r)?),
+                None => None,
+            };
+            Some(((m(e)?, v.clone()), r))
+        });
+        self.subst_cache.remap(|(e, s, w), r| Some(((m(e)?, s.clone(), m(w)?), m(r)?)));
+
+        new_id.iter().enumerate().filter_map(|(i, n)| n.map(|n| (ExprId(i), n))).collect()
     }
 
     // ---- Memoization cache access ----
 
     /// Check if a differentiation result is cached
     pub fn get_diff_cached(&self, expr: ExprId, var: &str) -> Option<ExprId> {
-        self.diff_cache.get(&(expr, var.to_string())).copied()
+        self.diff_cache.get(&(expr, var.to_string()))
     }
 
     /// Store a differentiation result in the cache
@@ -90,7 +247,7 @@ impl Store {
 
     /// Check if a simplification result is cached
     pub fn get_simplify_cached(&self, expr: ExprId) -> Option<ExprId> {
-        self.simplify_cache.get(&expr).copied()
+        self.simplify_cache.get(&expr)
     }
 
     /// Store a simplification result in the cache
@@ -100,7 +257,7 @@ impl Store {
 
     /// Check if an integration result is cached
     pub fn get_integrate_cached(&self, expr: ExprId, var: &str) -> Option<Option<ExprId>> {
-        self.integrate_cache.get(&(expr, var.to_string())).copied()
+        self.integrate_cache.get(&(expr, var.to_string()))
     }
 
     /// Store an integration result in the cache
@@ -110,7 +267,7 @@ impl Store {
 
     /// Check if a substitution result is cached
     pub fn get_subst_cached(&self, expr: ExprId, sym: &str, replacement: ExprId) -> Option<ExprId> {
-        self.subst_cache.get(&(expr, sym.to_string(), replacement)).copied()
+        self.subst_cache.get(&(expr, sym.to_string(), replacement))
     }
 
     /// Store a substitution result in the cache
@@ -126,6 +283,42 @@ impl Store {
         self.subst_cache.clear();
     }
 
+    /// Cap one cache at `limit` entries (`None` = unbounded, `Some(0)` disables it).
+    /// When full, the least recently used entries are evicted first.
+    pub fn set_cache_limit(&mut self, kind: CacheKind, limit: Option<usize>) {
+        match kind {
+            CacheKind::Diff => self.diff_cache.set_limit(limit),
+            CacheKind::Simplify => self.simplify_cache.set_limit(limit),
+            CacheKind::Integrate => self.integrate_cache.set_limit(limit),
+            CacheKind::Subst => self.subst_cache.set_limit(limit),
+        }
+    }
+
+    /// Apply the same limit to every cache.
+    pub fn set_cache_limits(&mut self, limit: Option<usize>) {
+        for kind in CacheKind::ALL {
+            self.set_cache_limit(kind, limit);
+        }
+    }
+
+    /// Hit/miss/eviction counters and current size of one cache.
+    pub fn cache_stats(&self, kind: CacheKind) -> CacheStats {
+        match kind {
+            CacheKind::Diff => self.diff_cache.stats(),
+            CacheKind::Simplify => self.simplify_cache.stats(),
+            CacheKind::Integrate => self.integrate_cache.stats(),
+            CacheKind::Subst => self.subst_cache.stats(),
+        }
+    }
+
+    /// Zero the hit/miss/eviction counters of every cache (entries are kept).
+    pub fn reset_cache_stats(&mut self) {
+        self.diff_cache.reset_stats();
+        self.simplify_cache.reset_stats();
+        self.integrate_cache.reset_stats();
+        self.subst_cache.reset_stats();
+    }
+
     // ---- Constructors (atoms) ----
     pub fn sym<S: Into<String>>(&mut self, name: S) -> ExprId {
         self.intern(Op::Symbol, Payload::Sym(name.into()), vec![])
@@ -133,13 +326,78 @@ impl Store {
     pub fn int(&mut self, n: i64) -> ExprId {
         self.intern(Op::Integer, Payload::Int(n), vec![])
     }
+    /// Reduced `num/den`; `i64::MIN / -1` is promoted to a big integer.
     pub fn rat(&mut self, num: i64, den: i64) -> ExprId {
-        let (n, d) = normalize_rat(num, den);
+        let (n, d) = match checked_normalize_rat(num, den) {
+            Ok(nd) => nd,
+            Err(ArithError::Overflow) => {
+                return self.bigrat(BigRat::new(BigInt::from(num), BigInt::from(den)))
+            }
+            Err(ArithError::DivisionByZero) => panic!("zero denominator"),
+        };
         if d == 1 {
             return self.int(n);
         }
         self.intern(Op::Rational, Payload::Rat(n, d), vec![])
     }
+    /// Arbitrary-precision integer; values that fit in i64 use the `Int` fast path.
+    pub fn bigint(&mut self, n: BigInt) -> ExprId {
+        match n.to_i64() {
+            Some(k) => self.int(k),
+            None => self.intern(Op::Integer, Payload::BigInt(n), vec![]),
+        }
+    }
+    /// Arbitrary-precision rational; reduces to `int`/`rat`/`bigint` when possible.
+    pub fn bigrat(&mut self, r: BigRat) -> ExprId {
+        if r.is_integer() {
+            return self.bigint(r.numer().clone());
+        }
+        match r.to_q() {
+            Some(q) => self.rat(q.0, q.1),
+            None => self.intern(Op::Rational, Payload::BigRat(r), vec![]),
+        }
+    }
+    /// Exact value of a numeric atom (any of the four numeric payloads).
+    pub fn as_bigrat(&self, id: ExprId) -> Option<BigRat> {
+        match &self.get(id).payload {
+            Payload::Int(k) => Some(BigRat::from(*k)),
+            Payload::Rat(n, d) => Some(BigRat::from(arith::Q(*n, *d))),
+            Payload::BigInt(n) => Some(BigRat::from_integer(n.clone())),
+            Payload::BigRat(r) => Some(r.clone()),
+            _ => None,
+        }
+    }
+    /// Float literal from an f64 (53-bit precision). Panics on NaN or infinity.
+    pub fn float(&mut self, x: f64) -> ExprId {
+        let f = Float::from_f64(x).expect("float literal must be finite");
+        self.bigfloat(f)
+    }
+    /// Float literal at the precision carried by `f`.
+    pub fn bigfloat(&mut self, f: Float) -> ExprId {
+        self.intern(Op::Float, Payload::Float(f), vec![])
+    }
+    pub fn as_float(&self, id: ExprId) -> Option<&Float> {
+        match &self.get(id).payload {
+            Payload::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+    fn num_acc(&self, id: ExprId) -> Option<NumAcc> {
+        match &self.get(id).payload {
+            Payload::Float(f) => Some(NumAcc::Float(f.clone())),
+            Payload::Int(k) => Some(NumAcc::Small(*k, 1)),
+            Payload::Rat(n, d) => Some(NumAcc::Small(*n, *d)),
+            Payload::BigInt(_) | Payload::BigRat(_) => self.as_bigrat(id).map(NumAcc::Big),
+            _ => None,
+        }
+    }
+    fn num_acc_expr(&mut self, acc: NumAcc) -> ExprId {
+        match acc {
+            NumAcc::Small(n, d) => self.rat(n, d),
+            NumAcc::Big(r) => self.bigrat(r),
+            NumAcc::Float(f) => self.bigfloat(f),
+        }
+    }
     pub fn func<S: Into<String>>(&mut self, name: S, args: Vec<ExprId>) -> ExprId {
         // Functions are not canonicalized across args (order matters).
         self.intern(Op::Function, Payload::Func(name.into()), args)
@@ -161,10 +419,45 @@ impl Store {
         self.intern(Op::Piecewise, Payload::None, children)
     }
 
+    /// Rebuild a node from its parts through the canonical constructors
+    /// (e.g. after mapping over a node's children).
+    pub fn build(&mut self, op: Op, payload: Payload, children: Vec<ExprId>) -> ExprId {
+        match op {
+            Op::Add => self.add(children),
+            Op::Mul => self.mul(children),
+            Op::Pow => self.pow(children[0], children[1]),
+            Op::Piecewise => {
+                let pairs = children.chunks(2).map(|c| (c[0], c[1])).collect();
+                self.piecewise(pairs)
+            }
+            Op::Eq => self.eq(children[0], children[1]),
+            Op::Lt => self.lt(children[0], children[1]),
+            Op::Le => self.le(children[0], children[1]),
+            Op::And => self.and(children),
+            Op::Or => self.or(children),
+            Op::Not => self.not(children[0]),
+            Op::Integer | Op::Rational => match payload {
+                Payload::Int(k) => self.int(k),
+                Payload::Rat(n, d) => self.rat(n, d),
+                Payload::BigInt(n) => self.bigint(n),
+                Payload::BigRat(r) => self.bigrat(r),
+                other => self.intern(op, other, children),
+            },
+            Op::Symbol | Op::Function | Op::Float | Op::Constant => {
+                self.intern(op, payload, children)
+            }
+            Op::Derivative | Op::Integral | Op::Sum | Op::Product => self.build_held(op, children),
+            Op::Lambda => match self.get(children[1]).payload.clone() {
+                Payload::Sym(x) => self.lambda(&x, children[0]),
+                other => self.intern(op, other, children),
+            },
+        }
+    }
+
     // ---- Canonical combinators ----
     pub fn add<I: IntoIterator<Item = ExprId>>(&mut self, it: I) -> ExprId {
         let mut terms: Vec<ExprId> = Vec::new();
-        let mut num = (0i64, 1i64); // rational accumulator (num, den)
+        let mut num = NumAcc::Small(0, 1); // rational accumulator
 
         // Flatten and fold numeric terms
         for t in it {
@@ -174,23 +467,26 @@ impl Store {
                         terms.push(*c);
                     }
                 }
-                Op::Integer => {
-                    if let Payload::Int(k) = &self.get(t).payload {
-                        num = rat_add(num, (*k, 1));
-                    }
-                }
-                Op::Rational => {
-                    if let Payload::Rat(n, d) = &self.get(t).payload {
-                        num = rat_add(num, (*n, *d));
+                Op::Integer | Op::Rational | Op::Float => {
+                    if let Some(v) = self.num_acc(t) {
+                        num = num.add(v);
                     }
                 }
                 _ => terms.push(t),
             }
         }
 
-        // Push folded numeric if nonzero
-        if num.0 != 0 {
-            terms.push(self.rat(num.0, num.1));
+        if terms.iter().any(|&t| self.get(t).op == Op::Constant) {
+            if let Some(r) = self.fold_add_constants(&mut terms, &mut num) {
+                return r;
+            }
+        }
+
+        // Push folded numeric if nonzero; a float zero survives only on its own
+        if !num.is_zero() {
+            terms.push(self.num_acc_expr(num));
+        } else if terms.is_empty() && matches!(num, NumAcc::Float(_)) {
+            return self.num_acc_expr(num);
         }
 
         // Remove trivial zeros
@@ -207,14 +503,15 @@ impl Store {
         }
 
         // Deterministic order by digest to achieve canonical form
-        terms.sort_by_key(|id| self.get(*id).digest);
+        terms.sort_by(|&a, &b| self.canonical_cmp(a, b));
 
         self.intern(Op::Add, Payload::None, terms)
     }
 
     pub fn mul<I: IntoIterator<Item = ExprId>>(&mut self, it: I) -> ExprId {
         let mut factors: Vec<ExprId> = Vec::new();
-        let mut num = (1i64, 1i64); // rational product
+        let mut num = NumAcc::Small(1, 1); // rational product
+        let mut zero: Option<NumAcc> = None;
 
         for f in it {
             match self.get(f).op {
@@ -223,29 +520,31 @@ impl Store {
                         factors.push(*c);
                     }
                 }
-                Op::Integer => {
-                    if let Payload::Int(k) = &self.get(f).payload {
-                        if *k == 0 {
-                            return self.int(0);
-                        }
-                        num = rat_mul(num, (*k, 1));
-                    }
-                }
-                Op::Rational => {
-                    if let Payload::Rat(n, d) = &self.get(f).payload {
-                        if *n == 0 {
-                            return self.int(0);
+                Op::Integer | Op::Rational | Op::Float => {
+                    if let Some(v) = self.num_acc(f) {
+                        if v.is_zero() {
+                            zero = Some(v);
+                            continue;
                         }
-                        num = rat_mul(num, (*n, *d));
+                        num = num.mul(v);
                     }
                 }
                 _ => factors.push(f),
             }
         }
 
+        if factors.iter().any(|&f| self.get(f).op == Op::Constant) {
+            if let Some(r) = self.fold_mul_constants(&mut factors, &mut num, zero.is_some()) {
+                return r;
+            }
+        }
+        if let Some(z) = zero {
+            return self.num_acc_expr(z);
+        }
+
         // If numeric product != 1, include it
-        if !(num.0 == 1 && num.1 == 1) {
-            factors.push(self.rat(num.0, num.1));
+        if !num.is_one() {
+            factors.push(self.num_acc_expr(num));
         }
 
         // Remove multiplicative identity 1
@@ -262,7 +561,7 @@ impl Store {
         }
 
         // Deterministic order (by digest)
-        factors.sort_by_key(|id| self.get(*id).digest);
+        factors.sort_by(|&a, &b| self.canonical_cmp(a, b));
 
         self.intern(Op::Mul, Payload::None, factors)
     }
@@ -282,6 +581,22 @@ impl Store {
             }
             return self.int(1);
         }
+        if self.get(base).op == Op::Constant
+            || self.get(exp).op == Op::Constant
+            || self.get(base).payload == Payload::Int(-1)
+        {
+            if let Some(r) = self.fold_pow_constants(base, exp) {
+                return r;
+            }
+        }
+        // Float^integer folds at the float's precision
+        if let (Payload::Float(f), Payload::Int(k)) =
+            (&self.get(base).payload, &self.get(exp).payload)
+        {
+            if let Some(v) = i32::try_from(*k).ok().and_then(|k| f.powi(k)) {
+                return self.bigfloat(v);
+            }
+        }
         self.intern(Op::Pow, Payload::None, vec![base, exp])
     }
 
@@ -289,10 +604,13 @@ impl Store {
     pub fn to_string(&self, id: ExprId) -> String {
         fn prec(op: &Op) -> u8 {
             match op {
-                Op::Add => 1,
-                Op::Mul => 2,
-                Op::Pow => 3,
-                _ => 4,
+                Op::Or => 1,
+                Op::And => 2,
+                Op::Lt | Op::Le => 3,
+                Op::Add => 4,
+                Op::Mul => 5,
+                Op::Pow => 6,
+                _ => 7,
             }
         }
         fn go(st: &Store, id: ExprId, parent_prec: u8) -> String {
@@ -300,7 +618,11 @@ impl Store {
             let s = match (&n.op, &n.payload) {
                 (Op::Integer, Payload::Int(k)) => k.to_string(),
                 (Op::Rational, Payload::Rat(a, b)) => format!("{}/{}", a, b),
+                (Op::Integer, Payload::BigInt(k)) => k.to_string(),
+                (Op::Rational, Payload::BigRat(r)) => r.to_string(),
+                (Op::Float, Payload::Float(f)) => f.to_string(),
                 (Op::Symbol, Payload::Sym(name)) => name.clone(),
+                (Op::Constant, Payload::Const(c)) => c.name().into(),
                 (Op::Function, Payload::Func(name)) => {
                     let args =
                         n.children.iter().map(|c| go(st, *c, 0)).collect::<Vec<_>>().join(", ");
@@ -334,6 +656,47 @@ impl Store {
                     }
                     format!("piecewise({})", parts.join(", "))
                 }
+                (Op::And, _) if n.children.is_empty() => "True".into(),
+                (Op::Or, _) if n.children.is_empty() => "False".into(),
+                (Op::And | Op::Or, _) => {
+                    let sep = if n.op == Op::And { " & " } else { " | " };
+                    let p = prec(&n.op);
+                    n.children.iter().map(|c| go(st, *c, p + 1)).collect::<Vec<_>>().join(sep)
+                }
+                (Op::Lt | Op::Le, _) => {
+                    let sym = if n.op == Op::Lt { "<" } else { "<=" };
+                    let l = go(st, n.children[0], prec(&Op::Add));
+                    let r = go(st, n.children[1], prec(&Op::Add));
+                    format!("{l} {sym} {r}")
+                }
+                (Op::Eq, _) => {
+                    format!("Eq({}, {})", go(st, n.children[0], 0), go(st, n.children[1], 0))
+                }
+                (Op::Not, _) => format!("~{}", go(st, n.children[0], prec(&Op::Not))),
+                (Op::Derivative, _) => {
+                    let f = go(st, n.children[0], 0);
+                    let x = go(st, n.children[1], 0);
+                    match st.get(n.children[2]).payload {
+                        Payload::Int(1) => format!("Derivative({f}, {x})"),
+                        _ => format!("Derivative({f}, {x}, {})", go(st, n.children[2], 0)),
+                    }
+                }
+                (Op::Integral | Op::Sum | Op::Product, _) => {
+                    let name = match n.op {
+                        Op::Integral => "Integral",
+                        Op::Sum => "Sum",
+                        _ => "Product",
+                    };
+                    let parts: Vec<String> = n.children.iter().map(|c| go(st, *c, 0)).collect();
+                    if parts.len() == 4 {
+                        format!("{name}({}, ({}, {}, {}))", parts[0], parts[1], parts[2], parts[3])
+                    } else {
+                        format!("{name}({})", parts.join(", "))
+                    }
+                }
+                (Op::Lambda, _) => {
+                    format!("Lambda({}, {})", go(st, n.children[1], 0), go(st, n.children[0], 0))
+                }
                 _ => "<unknown>".into(),
             };
             if prec(&n.op) < parent_prec {
@@ -345,25 +708,50 @@ impl Store {
         go(self, id, 0)
     }
 
+    /// Total order used for Add/Mul children: by digest, with a structural
+    /// comparison breaking ties so colliding digests still sort deterministically.
+    fn canonical_cmp(&self, a: ExprId, b: ExprId) -> Ordering {
+        if a == b {
+            return Ordering::Equal;
+        }
+        let (na, nb) = (self.get(a), self.get(b));
+        na.digest
+            .cmp(&nb.digest)
+            .then_with(|| op_tag(&na.op).cmp(&op_tag(&nb.op)))
+            .then_with(|| payload_cmp(&na.payload, &nb.payload))
+            .then_with(|| na.children.len().cmp(&nb.children.len()))
+            .then_with(|| {
+                na.children
+                    .iter()
+                    .zip(&nb.children)
+                    .map(|(&ca, &cb)| self.canonical_cmp(ca, cb))
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+    }
+
     // ---- Interning ----
     fn intern(&mut self, op: Op, payload: Payload, children: Vec<ExprId>) -> ExprId {
-        // Compute child digests; some ops (Add/Mul) expect children sorted already
-        let child_digests: Vec<u64> = children.iter().map(|id| self.get(*id).digest).collect();
-        let key = NodeKey { op: op.clone(), payload: payload.clone(), child_digests };
+        // Some ops (Add/Mul) expect children sorted already
+        let key = NodeKey { op, payload, children };
 
         if let Some(&id) = self.interner.get(&key) {
             return id;
         }
+        if let Some(id) = self.base.as_ref().and_then(|b| b.lookup(&key)) {
+            return id;
+        }
 
         // Compute digest for this node deterministically
-        let digest = digest_node(
-            &op,
-            &payload,
-            &children.iter().map(|id| self.get(*id).digest).collect::<Vec<_>>(),
-        );
-
-        let id = ExprId(self.nodes.len());
-        self.nodes.push(Node { op, payload, children, digest });
+        let child_digests: Vec<u64> = key.children.iter().map(|id| self.get(*id).digest).collect();
+        let digest = (self.digester.0)(&key.op, &key.payload, &child_digests);
+        let id = ExprId(self.node_count());
+        self.nodes.push(Node {
+            op: key.op.clone(),
+            payload: key.payload.clone(),
+            children: key.children.clone(),
+            digest,
+        });
         self.interner.insert(key, id);
         id
     }
@@ -371,7 +759,8 @@ impl Store {
 
 // ---------- Deterministic digest (FNV-1a 64) ----------
 
-fn digest_node(op: &Op, payload: &Payload, child_digests: &[u64]) -> u64 {
+/// Default node digest: FNV-1a 64 over the op tag, payload and child digests.
+pub fn default_digest(op: &Op, payload: &Payload, child_digests: &[u64]) -> u64 {
     let mut h = Fnv64::new();
     h.write_u8(op_tag(op));
     match payload {
@@ -393,6 +782,26 @@ fn digest_node(op: &Op, payload: &Payload, child_digests: &[u64]) -> u64 {
             h.write_u8(4);
             h.write_bytes(s.as_bytes());
         }
+        Payload::BigInt(n) => {
+            h.write_u8(5);
+            write_bigint(&mut h, n);
+        }
+        Payload::BigRat(r) => {
+            h.write_u8(6);
+            write_bigint(&mut h, r.numer());
+            write_bigint(&mut h, r.denom());
+        }
+        Payload::Float(f) => {
+            h.write_u8(7);
+            let (mant, exp) = f.parts();
+            write_bigint(&mut h, mant);
+            h.write_i64(exp);
+            h.write_u64(f.prec() as u64);
+        }
+        Payload::Const(c) => {
+            h.write_u8(8);
+            h.write_u8(*c as u8);
+        }
     }
     for &cd in child_digests {
         h.write_u64(cd);
@@ -400,6 +809,44 @@ fn digest_node(op: &Op, payload: &Payload, child_digests: &[u64]) -> u64 {
     h.finish()
 }
 
+/// Deterministic order on payloads (variant first, then value).
+fn payload_cmp(a: &Payload, b: &Payload) -> Ordering {
+    fn rank(p: &Payload) -> u8 {
+        match p {
+            Payload::None => 0,
+            Payload::Int(_) => 1,
+            Payload::Rat(..) => 2,
+            Payload::Sym(_) => 3,
+            Payload::Func(_) => 4,
+            Payload::BigInt(_) => 5,
+            Payload::BigRat(_) => 6,
+            Payload::Float(_) => 7,
+            Payload::Const(_) => 8,
+        }
+    }
+    match (a, b) {
+        (Payload::Int(x), Payload::Int(y)) => x.cmp(y),
+        (Payload::Rat(n1, d1), Payload::Rat(n2, d2)) => (n1, d1).cmp(&(n2, d2)),
+        (Payload::Sym(x), Payload::Sym(y)) | (Payload::Func(x), Payload::Func(y)) => x.cmp(y),
+        (Payload::BigInt(x), Payload::BigInt(y)) => x.cmp(y),
+        (Payload::BigRat(x), Payload::BigRat(y)) => x.cmp(y),
+        (Payload::Const(x), Payload::Const(y)) => x.cmp(y),
+        (Payload::Float(x), Payload::Float(y)) => {
+            let ((mx, ex), (my, ey)) = (x.parts(), y.parts());
+            x.prec().cmp(&y.prec()).then(ex.cmp(&ey)).then_with(|| mx.cmp(my))
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn write_bigint(h: &mut Fnv64, n: &BigInt) {
+    h.write_u8(n.is_negative() as u8);
+    h.write_u64(n.magnitude().len() as u64);
+    for &limb in n.magnitude() {
+        h.write_u64(limb as u64);
+    }
+}
+
 fn op_tag(op: &Op) -> u8 {
     match op {
         Op::Add => 1,
@@ -410,6 +857,19 @@ fn op_tag(op: &Op) -> u8 {
         Op::Rational => 6,
         Op::Function => 7,
         Op::Piecewise => 8,
+        Op::Float => 9,
+        Op::Eq => 10,
+        Op::Lt => 11,
+        Op::Le => 12,
+        Op::And => 13,
+        Op::Or => 14,
+        Op::Not => 15,
+        Op::Constant => 16,
+        Op::Derivative => 17,
+        Op::Integral => 18,
+        Op::Sum => 19,
+        Op::Product => 20,
+        Op::Lambda => 21,
     }
 }
 
@@ -441,6 +901,86 @@ impl Fnv64 {
     }
 }
 
+// ---------- Numeric folding accumulator ----------
+
+/// Rational accumulator for constant folding in `add`/`mul`.
+/// Stays on (i64, i64) until an operation overflows, then continues exactly in `BigRat`.
+/// Once a float joins, the result is inexact and continues as a `Float`.
+enum NumAcc {
+    Small(i64, i64),
+    Big(BigRat),
+    Float(Float),
+}
+
+impl NumAcc {
+    fn into_big(self) -> BigRat {
+        match self {
+            NumAcc::Small(n, d) => BigRat::from(arith::Q(n, d)),
+            NumAcc::Big(r) => r,
+            NumAcc::Float(f) => f.to_bigrat(),
+        }
+    }
+    /// Value as a float at `prec` (exact values are rounded once).
+    fn into_float(self, prec: u32) -> Float {
+        match self {
+            NumAcc::Float(f) => f,
+            exact => Float::from_bigrat(&exact.into_big(), prec),
+        }
+    }
+    fn add(self, rhs: NumAcc) -> NumAcc {
+        let (lhs, rhs) = match (self, rhs) {
+            (NumAcc::Float(a), b) | (b, NumAcc::Float(a)) => {
+                let b = b.into_float(a.prec());
+                return NumAcc::Float(a.add(&b));
+            }
+            pair => pair,
+        };
+        if let (NumAcc::Small(a, b), NumAcc::Small(c, d)) = (&lhs, &rhs) {
+            if let Ok((n, d)) = checked_rat_add((*a, *b), (*c, *d)) {
+                return NumAcc::Small(n, d);
+            }
+        }
+        NumAcc::Big(&lhs.into_big() + &rhs.into_big())
+    }
+    fn mul(self, rhs: NumAcc) -> NumAcc {
+        let (lhs, rhs) = match (self, rhs) {
+            (NumAcc::Float(a), b) | (b, NumAcc::Float(a)) => {
+                let b = b.into_float(a.prec());
+                return NumAcc::Float(a.mul(&b));
+            }
+            pair => pair,
+        };
+        if let (NumAcc::Small(a, b), NumAcc::Small(c, d)) = (&lhs, &rhs) {
+            if let Ok((n, d)) = checked_rat_mul((*a, *b), (*c, *d)) {
+                return NumAcc::Small(n, d);
+            }
+        }
+        NumAcc::Big(&lhs.into_big() * &rhs.into_big())
+    }
+    fn is_zero(&self) -> bool {
+        match self {
+            NumAcc::Small(n, _) => *n == 0,
+            NumAcc::Big(r) => r.is_zero(),
+            NumAcc::Float(f) => f.is_zero(),
+        }
+    }
+    fn is_negative(&self) -> bool {
+        match self {
+            NumAcc::Small(n, _) => *n < 0,
+            NumAcc::Big(r) => r.is_negative(),
+            NumAcc::Float(f) => f.is_negative(),
+        }
+    }
+    fn is_one(&self) -> bool {
+        match self {
+            NumAcc::Small(n, d) => *n == 1 && *d == 1,
+            NumAcc::Big(r) => r.is_one(),
+            // 1.0 is kept as a marker that the product is inexact
+            NumAcc::Float(_) => false,
+        }
+    }
+}
+
 // rational helpers now sourced from `arith` crate
 
 #[cfg(test)]
@@ -591,10 +1131,21 @@ mod tests {
     #[should_panic(expected = "zero denominator")]
     fn test_rat_zero_denominator_panics() {
         let mut st = Store::new();
-        // This should panic due to assert! in normalize_rat
         let _ = st.rat(1, 0);
     }
 
+    #[test]
+    fn test_rat_overflow_promotes() {
+        let mut st = Store::new();
+        let r = st.rat(i64::MIN, -1);
+        assert_eq!(r, st.bigint(-BigInt::from(i64::MIN)));
+        // Folding past i64 switches to big rationals instead of wrapping
+        let a = st.rat(1, i64::MAX);
+        let b = st.rat(1, i64::MAX - 1);
+        let s = st.add(vec![a, b]);
+        assert_eq!(st.to_string(s), "18446744073709551613/85070591730234615838173535747377725442");
+    }
+
     #[test]
     fn test_add_empty() {
         let mut st = Store::new();
@@ -846,4 +1397,135 @@ mod tests {
         st.clear_caches();
         assert_eq!(st.get_subst_cached(x, "x", y_plus_1), None);
     }
+
+    #[test]
+    fn test_factorial_30_folds_to_bigint() {
+        let mut st = Store::new();
+        let factors: Vec<ExprId> = (1..=30).map(|k| st.int(k)).collect();
+        let f30 = st.mul(factors);
+        assert!(matches!(st.get(f30).payload, Payload::BigInt(_)));
+        assert_eq!(st.to_string(f30), "265252859812191058636308480000000");
+        // Same value built directly is hash-consed to the same node
+        let direct = st.bigint("265252859812191058636308480000000".parse().unwrap());
+        assert_eq!(f30, direct);
+    }
+
+    #[test]
+    fn test_add_overflow_promotes_instead_of_saturating() {
+        let mut st = Store::new();
+        let max = st.int(i64::MAX);
+        let sum = st.add(vec![max, max]);
+        assert_eq!(st.to_string(sum), "18446744073709551614");
+        // Folding back into range returns to the i64 fast path
+        let neg = st.int(-i64::MAX);
+        let back = st.add(vec![sum, neg]);
+        assert_eq!(back, max);
+        assert!(matches!(st.get(back).payload, Payload::Int(_)));
+    }
+
+    #[test]
+    fn test_bigrat_fast_path_and_printing() {
+        let mut st = Store::new();
+        let small = st.bigrat(BigRat::new(BigInt::from(2i64), BigInt::from(4i64)));
+        assert_eq!(small, st.rat(1, 2));
+        let huge_den = st.rat(1, i64::MAX);
+        let sq = st.mul(vec![huge_den, huge_den]);
+        assert!(matches!(st.get(sq).payload, Payload::BigRat(_)));
+        assert_eq!(st.to_string(sq), "1/85070591730234615847396907784232501249");
+        let v = st.as_bigrat(sq).unwrap();
+        assert_eq!(v.denom().to_string(), "85070591730234615847396907784232501249");
+    }
+
+    #[test]
+    fn test_float_folding_and_precision() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let a = st.float(0.1);
+        let b = st.float(0.2);
+        let half = st.rat(1, 2);
+        // Floats absorb exact numbers in the same sum
+        let s = st.add(vec![a, x, b, half]);
+        let folded = st.float(0.1 + 0.2 + 0.5);
+        assert_eq!(s, st.add(vec![x, folded]));
+        assert_eq!(st.to_string(a), "0.1");
+        // A float zero disappears next to symbols, but survives on its own
+        let z = st.float(0.0);
+        assert_eq!(st.add(vec![z, x]), x);
+        let minus_a = st.float(-0.1);
+        assert_eq!(st.add(vec![a, minus_a]), z);
+        // 1.0 stays as a marker that the product is inexact
+        let one = st.float(1.0);
+        let p = st.mul(vec![one, x]);
+        assert_eq!(st.get(p).op, Op::Mul);
+        // Float^integer folds; the precision tag is part of node identity
+        let two = st.int(2);
+        let sq = st.pow(b, two);
+        assert_eq!(st.as_float(sq).unwrap().to_f64(), 0.04000000000000001);
+        let b80 = st.bigfloat(st.as_float(b).unwrap().with_prec(80));
+        assert_ne!(b80, b);
+        let mixed = st.add(vec![b80, half]);
+        assert_eq!(st.as_float(mixed).unwrap().prec(), 80);
+    }
+
+    #[test]
+    fn test_compact_keeps_reachable_nodes_and_remaps() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let two = st.int(2);
+        let x2 = st.pow(x, two);
+        let keep = st.add(vec![x2, y]);
+        // Garbage: built but not rooted
+        for k in 0..50 {
+            let c = st.int(k + 100);
+            st.mul(vec![c, y]);
+        }
+        st.cache_simplify(keep, x2);
+        st.cache_diff(keep, "x".into(), x);
+        let dead = st.int(149);
+        st.cache_simplify(dead, dead);
+        let before = st.to_string(keep);
+        let n_before = st.node_count();
+
+        let map = st.compact(&[keep]);
+        assert!(st.node_count() < n_before);
+        assert_eq!(st.node_count(), map.len());
+        let keep2 = map[&keep];
+        assert_eq!(st.to_string(keep2), before);
+        // Interning still works after compaction
+        let (x_new, y_new) = (map[&x], map[&y]);
+        let two = st.int(2);
+        let x2_again = st.pow(x_new, two);
+        assert_eq!(st.add(vec![y_new, x2_again]), keep2);
+        // Caches follow the remap; entries with dead ids are gone
+        assert_eq!(st.get_simplify_cached(keep2), Some(map[&x2]));
+        assert_eq!(st.get_diff_cached(keep2, "x"), Some(x_new));
+        assert_eq!(st.cache_stats(CacheKind::Simplify).len, 1);
+    }
+
+    #[test]
+    fn test_cache_limits_and_stats() {
+        let mut st = Store::new();
+        st.set_cache_limit(CacheKind::Simplify, Some(8));
+        let ids: Vec<ExprId> = (0..20).map(|k| st.int(k)).collect();
+        for &id in &ids {
+            st.cache_simplify(id, id);
+        }
+        let stats = st.cache_stats(CacheKind::Simplify);
+        assert!(stats.len <= 8);
+        assert_eq!(stats.evictions, 20 - stats.len as u64);
+        // Most recent entry survives, the oldest is gone
+        assert_eq!(st.get_simplify_cached(ids[19]), Some(ids[19]));
+        assert_eq!(st.get_simplify_cached(ids[0]), None);
+        let stats = st.cache_stats(CacheKind::Simplify);
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        assert_eq!(stats.hit_rate(), 0.5);
+        st.reset_cache_stats();
+        assert_eq!(st.cache_stats(CacheKind::Simplify).hits, 0);
+        // Other caches stay unbounded
+        assert_eq!(st.cache_stats(CacheKind::Diff).limit, None);
+        st.set_cache_limits(Some(0));
+        st.cache_diff(ids[0], "x".into(), ids[1]);
+        assert_eq!(st.get_diff_cached(ids[0], "x"), None);
+    }
 }
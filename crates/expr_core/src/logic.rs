@@ -0,0 +1,225 @@
+//! Relational (`Eq`, `Lt`, `Le`) and boolean (`And`, `Or`, `Not`) nodes.
+//!
+//! Canonical forms:
+//! - `True` is the empty `And`, `False` the empty `Or`.
+//! - `gt`/`ge` are `lt`/`le` with swapped sides; `Eq` orders its sides canonically.
+//! - Relations between two numbers (or `oo`/`-oo`) fold to `True`/`False`; `Eq(a, a)` and
+//!   `Le(a, a)` are `True`; any relation involving `nan` is `False`.
+//! - `And`/`Or` flatten, drop identities, short-circuit on absorbing constants,
+//!   deduplicate and sort their operands.
+//! - `Not` folds constants and double negation, and flips strict/non-strict
+//!   comparisons: `Not(a < b) = b <= a`.
+
+use crate::{Constant, ExprId, Op, Payload, Store};
+use arith::BigRat;
+use std::cmp::Ordering;
+
+#[derive(PartialEq, PartialOrd)]
+enum Extended {
+    NegInf,
+    Finite(BigRat),
+    PosInf,
+}
+
+impl Store {
+    /// Boolean constant (`True` = empty `And`, `False` = empty `Or`).
+    pub fn boolean(&mut self, b: bool) -> ExprId {
+        let op = if b { Op::And } else { Op::Or };
+        self.intern(op, Payload::None, vec![])
+    }
+
+    /// `Some(b)` if `id` is the boolean constant `b`.
+    pub fn as_bool(&self, id: ExprId) -> Option<bool> {
+        let n = self.get(id);
+        match n.op {
+            Op::And if n.children.is_empty() => Some(true),
+            Op::Or if n.children.is_empty() => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Exact value of a numeric atom, floats included.
+    pub(crate) fn real_value(&self, id: ExprId) -> Option<BigRat> {
+        match self.as_float(id) {
+            Some(f) => Some(f.to_bigrat()),
+            None => self.as_bigrat(id),
+        }
+    }
+
+    /// Position on the extended real line of a numeric atom or `oo`/`-oo`.
+    fn extended_value(&self, id: ExprId) -> Option<Extended> {
+        match self.as_constant(id) {
+            Some(Constant::Infinity) => Some(Extended::PosInf),
+            Some(Constant::NegativeInfinity) => Some(Extended::NegInf),
+            _ => self.real_value(id).map(Extended::Finite),
+        }
+    }
+
+    fn is_nan(&self, id: ExprId) -> bool {
+        self.as_constant(id) == Some(Constant::NaN)
+    }
+
+    pub fn eq(&mut self, a: ExprId, b: ExprId) -> ExprId {
+        if self.is_nan(a) || self.is_nan(b) {
+            return self.boolean(false);
+        }
+        if a == b {
+            return self.boolean(true);
+        }
+        if let (Some(x), Some(y)) = (self.extended_value(a), self.extended_value(b)) {
+            return self.boolean(x == y);
+        }
+        let (l, r) = if self.canonical_cmp(a, b) == Ordering::Greater { (b, a) } else { (a, b) };
+        self.intern(Op::Eq, Payload::None, vec![l, r])
+    }
+
+    /// `a < b`
+    pub fn lt(&mut self, a: ExprId, b: ExprId) -> ExprId {
+        if a == b || self.is_nan(a) || self.is_nan(b) {
+            return self.boolean(false);
+        }
+        if let (Some(x), Some(y)) = (self.extended_value(a), self.extended_value(b)) {
+            return self.boolean(x < y);
+        }
+        self.intern(Op::Lt, Payload::None, vec![a, b])
+    }
+
+    /// `a <= b`
+    pub fn le(&mut self, a: ExprId, b: ExprId) -> ExprId {
+        if self.is_nan(a) || self.is_nan(b) {
+            return self.boolean(false);
+        }
+        if a == b {
+            return self.boolean(true);
+        }
+        if let (Some(x), Some(y)) = (self.extended_value(a), self.extended_value(b)) {
+            return self.boolean(x <= y);
+        }
+        self.intern(Op::Le, Payload::None, vec![a, b])
+    }
+
+    /// `a > b`, stored as `b < a`.
+    pub fn gt(&mut self, a: ExprId, b: ExprId) -> ExprId {
+        self.lt(b, a)
+    }
+
+    /// `a >= b`, stored as `b <= a`.
+    pub fn ge(&mut self, a: ExprId, b: ExprId) -> ExprId {
+        self.le(b, a)
+    }
+
+    pub fn and<I: IntoIterator<Item = ExprId>>(&mut self, it: I) -> ExprId {
+        self.junction(Op::And, it)
+    }
+
+    pub fn or<I: IntoIterator<Item = ExprId>>(&mut self, it: I) -> ExprId {
+        self.junction(Op::Or, it)
+    }
+
+    fn junction<I: IntoIterator<Item = ExprId>>(&mut self, op: Op, it: I) -> ExprId {
+        // And: True is the identity and False absorbs; Or the other way round
+        let identity = op == Op::And;
+        let mut args: Vec<ExprId> = Vec::new();
+        for a in it {
+            match self.as_bool(a) {
+                Some(b) if b == identity => {}
+                Some(_) => return self.boolean(!identity),
+                None if self.get(a).op == op => args.extend(self.get(a).children.iter()),
+                None => args.push(a),
+            }
+        }
+        args.sort_by(|&x, &y| self.canonical_cmp(x, y));
+        args.dedup();
+        match args.len() {
+            0 => self.boolean(identity),
+            1 => args[0],
+            _ => self.intern(op, Payload::None, args),
+        }
+    }
+
+    pub fn not(&mut self, a: ExprId) -> ExprId {
+        if let Some(b) = self.as_bool(a) {
+            return self.boolean(!b);
+        }
+        let n = self.get(a);
+        match n.op {
+            Op::Not => n.children[0],
+            Op::Lt => {
+                let (l, r) = (n.children[0], n.children[1]);
+                self.le(r, l)
+            }
+            Op::Le => {
+                let (l, r) = (n.children[0], n.children[1]);
+                self.lt(r, l)
+            }
+            _ => self.intern(Op::Not, Payload::None, vec![a]),
+        }
+    }
+
+    /// True for the relational ops and boolean connectives (constants included).
+    pub fn is_boolean(&self, id: ExprId) -> bool {
+        matches!(self.get(id).op, Op::Eq | Op::Lt | Op::Le | Op::And | Op::Or | Op::Not)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relations_fold_numbers_and_canonicalize() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let one = st.int(1);
+        let half = st.rat(1, 2);
+        let tru = st.boolean(true);
+        let fls = st.boolean(false);
+        assert_eq!(st.lt(half, one), tru);
+        assert_eq!(st.le(one, half), fls);
+        let h = st.float(0.5);
+        assert_eq!(st.eq(h, half), tru);
+        assert_eq!(st.eq(x, y), st.eq(y, x));
+        assert_eq!(st.gt(x, y), st.lt(y, x));
+        assert_eq!(st.le(x, x), tru);
+        assert_eq!(st.lt(x, x), fls);
+        assert_eq!(st.to_string(tru), "True");
+        let lt = st.lt(x, one);
+        assert_eq!(st.to_string(lt), "x < 1");
+        let oo = st.constant(Constant::Infinity);
+        let noo = st.constant(Constant::NegativeInfinity);
+        let nan = st.constant(Constant::NaN);
+        assert_eq!(st.lt(one, oo), tru);
+        assert_eq!(st.lt(noo, oo), tru);
+        assert_eq!(st.le(oo, half), fls);
+        assert_eq!(st.eq(nan, nan), fls);
+    }
+
+    #[test]
+    fn connectives_flatten_and_negate() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let zero = st.int(0);
+        let a = st.lt(x, zero);
+        let b = st.eq(y, zero);
+        let tru = st.boolean(true);
+        let fls = st.boolean(false);
+        assert_eq!(st.and(vec![a, tru]), a);
+        assert_eq!(st.and(vec![a, fls]), fls);
+        assert_eq!(st.or(vec![a, tru]), tru);
+        let ab = st.and(vec![a, b]);
+        assert_eq!(st.and(vec![b, a, a]), ab);
+        let c = st.le(y, x);
+        let nested = st.and(vec![ab, c]);
+        assert_eq!(st.get(nested).children.len(), 3);
+        // Not flips comparisons and cancels itself
+        assert_eq!(st.not(a), st.le(zero, x));
+        let nb = st.not(b);
+        assert_eq!(st.get(nb).op, Op::Not);
+        assert_eq!(st.not(nb), b);
+        assert_eq!(st.not(tru), fls);
+        let or = st.or(vec![a, nb]);
+        assert_eq!(st.to_string(or).matches(" | ").count(), 1);
+    }
+}
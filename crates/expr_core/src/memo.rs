@@ -0,0 +1,179 @@
+//! Bounded memoization cache with hit/miss statistics.
+//! Lookups take `&self` (recency and counters are atomics), so the store's
+//! `get_*_cached` accessors keep their shared-borrow signatures.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counters and size of one memo cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub len: usize,
+    /// Maximum number of entries (`None` = unbounded).
+    pub limit: Option<usize>,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that hit (0.0 when there were none).
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+pub(crate) struct MemoCache<K, V> {
+    map: HashMap<K, (V, AtomicU64)>,
+    limit: Option<usize>,
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: u64,
+}
+
+impl<K, V> Default for MemoCache<K, V> {
+    fn default() -> Self {
+        Self {
+            map: HashMap::new(),
+            limit: None,
+            clock: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: 0,
+        }
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> MemoCache<K, V> {
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub(crate) fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.map.get(key) {
+            Some((v, used)) => {
+                used.store(self.tick(), Ordering::Relaxed);
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(v.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        if self.limit == Some(0) {
+            return;
+        }
+        if let Some(limit) = self.limit {
+            if self.map.len() >= limit && !self.map.contains_key(&key) {
+                self.evict_to(limit - 1);
+            }
+        }
+        let t = self.tick();
+        self.map.insert(key, (value, AtomicU64::new(t)));
+    }
+
+    /// Drop least recently used entries until at most `target` remain.
+    /// Evicts at least a quarter of the cache at once so inserts stay amortized O(1).
+    fn evict_to(&mut self, target: usize) {
+        let len = self.map.len();
+        if len <= target {
+            return;
+        }
+        let n_evict = (len - target).max(len / 4).min(len);
+        let mut stamps: Vec<u64> =
+            self.map.values().map(|(_, used)| used.load(Ordering::Relaxed)).collect();
+        stamps.select_nth_unstable(n_evict - 1);
+        let cutoff = stamps[n_evict - 1];
+        // Stamps are unique, so exactly n_evict entries are at or below the cutoff
+        self.map.retain(|_, (_, used)| used.load(Ordering::Relaxed) > cutoff);
+        self.evictions += (len - self.map.len()) as u64;
+    }
+
+    pub(crate) fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+        if let Some(limit) = limit {
+            self.evict_to(limit);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Rewrite every entry, dropping those for which `f` returns None.
+    pub(crate) fn remap(&mut self, mut f: impl FnMut(&K, &V) -> Option<(K, V)>) {
+        let old = std::mem::take(&mut self.map);
+        self.map = old
+            .into_iter()
+            .filter_map(|(k, (v, used))| f(&k, &v).map(|(k2, v2)| (k2, (v2, used))))
+            .collect();
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions,
+            len: self.map.len(),
+            limit: self.limit,
+        }
+    }
+
+    pub(crate) fn reset_stats(&mut self) {
+        *self.hits.get_mut() = 0;
+        *self.misses.get_mut() = 0;
+        self.evictions = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut c: MemoCache<u32, u32> = MemoCache::default();
+        c.set_limit(Some(4));
+        for k in 0..4 {
+            c.insert(k, k * 10);
+        }
+        // Touch 0 so 1 becomes the oldest
+        assert_eq!(c.get(&0), Some(0));
+        c.insert(4, 40);
+        assert_eq!(c.stats().len, 4);
+        assert_eq!(c.get(&1), None);
+        assert_eq!(c.get(&0), Some(0));
+        assert_eq!(c.get(&4), Some(40));
+        let s = c.stats();
+        assert_eq!((s.hits, s.misses, s.evictions), (3, 1, 1));
+    }
+
+    #[test]
+    fn zero_limit_disables_and_shrinking_evicts() {
+        let mut c: MemoCache<u32, u32> = MemoCache::default();
+        for k in 0..100 {
+            c.insert(k, k);
+        }
+        c.set_limit(Some(10));
+        assert_eq!(c.stats().len, 10);
+        assert_eq!(c.get(&99), Some(99));
+        c.set_limit(Some(0));
+        c.insert(1000, 1);
+        assert_eq!(c.stats().len, 0);
+    }
+}
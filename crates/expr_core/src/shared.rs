@@ -0,0 +1,302 @@
+//! Frozen, shareable store snapshots for parallel work.
+//!
+//! `Store::freeze` moves the store's nodes into an immutable layer behind an
+//! `Arc` and keeps the store itself as an overlay on top of it. Any number of
+//! further overlays (`SharedStore::overlay`) can then be handed to threads:
+//! each one sees every frozen node under its original `ExprId` and interns new
+//! nodes locally. `Store::merge` brings an overlay's results back.
+//!
+//! The same machinery copies expressions between unrelated stores:
+//! `Store::import` for one expression, `merge` for several roots sharing one
+//! pass, and `absorb` for every node of another store.
+//!
+//! Digests depend only on structure, so canonical ordering is identical in
+//! every overlay and in the merged result.
+
+use crate::{DigestFn, ExprId, Node, NodeKey, Store};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// One immutable layer: nodes with ids `offset..offset + nodes.len()`.
+pub(crate) struct Frozen {
+    pub(crate) parent: Option<Arc<Frozen>>,
+    pub(crate) offset: usize,
+    pub(crate) nodes: Vec<Node>,
+    pub(crate) interner: HashMap<NodeKey, ExprId>,
+    pub(crate) digest_fn: DigestFn,
+}
+
+impl Frozen {
+    pub(crate) fn end(&self) -> usize {
+        self.offset + self.nodes.len()
+    }
+
+    pub(crate) fn get(&self, id: ExprId) -> &Node {
+        let mut layer = self;
+        while id.0 < layer.offset {
+            layer = layer.parent.as_deref().expect("id below the first layer");
+        }
+        &layer.nodes[id.0 - layer.offset]
+    }
+
+    pub(crate) fn lookup(&self, key: &NodeKey) -> Option<ExprId> {
+        let mut layer = Some(self);
+        while let Some(l) = layer {
+            if let Some(&id) = l.interner.get(key) {
+                return Some(id);
+            }
+            layer = l.parent.as_deref();
+        }
+        None
+    }
+
+    /// True if `other` is this layer or one of its ancestors.
+    pub(crate) fn contains_layer(self: &Arc<Self>, other: &Arc<Frozen>) -> bool {
+        let mut layer = Some(self);
+        while let Some(l) = layer {
+            if Arc::ptr_eq(l, other) {
+                return true;
+            }
+            layer = l.parent.as_ref();
+        }
+        false
+    }
+}
+
+/// Read-only, cheaply clonable snapshot of a store (`Send + Sync`).
+#[derive(Clone)]
+pub struct SharedStore(pub(crate) Arc<Frozen>);
+
+impl SharedStore {
+    /// Fresh mutable store layered on this snapshot. Ids from the snapshot
+    /// stay valid; new nodes get ids past `node_count()`. Caches start empty.
+    pub fn overlay(&self) -> Store {
+        Store::on_layer(Arc::clone(&self.0))
+    }
+
+    pub fn get(&self, id: ExprId) -> &Node {
+        self.0.get(id)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.0.end()
+    }
+}
+
+impl Store {
+    pub(crate) fn on_layer(base: Arc<Frozen>) -> Self {
+        let mut st = Store::with_digest_fn(base.digest_fn);
+        st.base_len = base.end();
+        st.base = Some(base);
+        st
+    }
+
+    /// Move all nodes into a shared immutable snapshot. This store keeps
+    /// working as an overlay on it: existing ids and caches remain valid.
+    pub fn freeze(&mut self) -> SharedStore {
+        let layer = Arc::new(Frozen {
+            parent: self.base.take(),
+            offset: self.base_len,
+            nodes: std::mem::take(&mut self.nodes),
+            interner: std::mem::take(&mut self.interner),
+            digest_fn: self.digester.0,
+        });
+        self.base_len = layer.end();
+        self.base = Some(Arc::clone(&layer));
+        SharedStore(layer)
+    }
+
+    /// Copy `roots` (and everything they reference) from `other` into this
+    /// store, returning their ids here. Nodes in a snapshot both stores share
+    /// keep their ids; everything else is re-interned, so equal expressions
+    /// from different overlays merge into the same node. Each distinct node is
+    /// copied once, however many roots reach it.
+    pub fn merge(&mut self, other: &Store, roots: &[ExprId]) -> Vec<ExprId> {
+        let mut memo: HashMap<ExprId, ExprId> = HashMap::new();
+        self.merge_with(other, roots, &mut memo)
+    }
+
+    /// Copy one expression from `other` into this store (see `merge`).
+    pub fn import(&mut self, other: &Store, id: ExprId) -> ExprId {
+        self.merge(other, &[id])[0]
+    }
+
+    /// Copy every node of `other` into this store and return the mapping from
+    /// `other`'s ids to ids here.
+    pub fn absorb(&mut self, other: &Store) -> HashMap<ExprId, ExprId> {
+        let ids: Vec<ExprId> = (0..other.node_count()).map(ExprId).collect();
+        let mut memo: HashMap<ExprId, ExprId> = HashMap::new();
+        let new_ids = self.merge_with(other, &ids, &mut memo);
+        ids.into_iter().zip(new_ids).collect()
+    }
+
+    fn merge_with(
+        &mut self,
+        other: &Store,
+        roots: &[ExprId],
+        memo: &mut HashMap<ExprId, ExprId>,
+    ) -> Vec<ExprId> {
+        // Ids below `shared` name the same node in both stores
+        let shared = match (&self.base, &other.base) {
+            (Some(mine), Some(theirs)) => {
+                let mut layer = Some(theirs);
+                let mut end = 0;
+                while let Some(l) = layer {
+                    if mine.contains_layer(l) {
+                        end = l.end();
+                        break;
+                    }
+                    layer = l.parent.as_ref();
+                }
+                end
+            }
+            _ => 0,
+        };
+        // With a different digest function the canonical order of Add/Mul
+        // children differs, so nodes are rebuilt instead of interned as-is
+        let same_order = std::ptr::fn_addr_eq(self.digester.0, other.digester.0);
+        roots.iter().map(|&r| self.merge_node(other, r, shared, same_order, memo)).collect()
+    }
+
+    fn merge_node(
+        &mut self,
+        other: &Store,
+        id: ExprId,
+        shared: usize,
+        same_order: bool,
+        memo: &mut HashMap<ExprId, ExprId>,
+    ) -> ExprId {
+        if id.0 < shared {
+            return id;
+        }
+        if let Some(&m) = memo.get(&id) {
+            return m;
+        }
+        let node = other.get(id);
+        let children: Vec<ExprId> = node
+            .children
+            .iter()
+            .map(|&c| self.merge_node(other, c, shared, same_order, memo))
+            .collect();
+        // Children are already in canonical order, and ordering depends only on
+        // structure, so the node can be interned as-is.
+        let out = if same_order {
+            self.intern(node.op.clone(), node.payload.clone(), children)
+        } else {
+            self.build(node.op.clone(), node.payload.clone(), children)
+        };
+        memo.insert(id, out);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn snapshot_types_are_thread_safe() {
+        assert_send_sync::<SharedStore>();
+        assert_send_sync::<Store>();
+    }
+
+    #[test]
+    fn overlays_share_ids_and_merge_back() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let xy = st.mul(vec![x, y]);
+        let snap = st.freeze();
+        assert_eq!(snap.node_count(), st.node_count());
+
+        let mut a = snap.overlay();
+        let mut b = snap.overlay();
+        // Existing structure is found, not duplicated
+        assert_eq!(a.mul(vec![y, x]), xy);
+        let one = a.int(1);
+        let ra = a.add(vec![xy, one]);
+        let one_b = b.int(1);
+        let rb = b.add(vec![one_b, xy]);
+        // Overlays allocate independently, so ids can clash; merging resolves it
+        assert_eq!(ra, rb);
+
+        let before = st.node_count();
+        let merged = st.merge(&a, &[ra]);
+        let merged_b = st.merge(&b, &[rb]);
+        assert_eq!(merged, merged_b);
+        assert_eq!(st.to_string(merged[0]), a.to_string(ra));
+        assert_eq!(st.node_count(), before + 2);
+        // Merged node is the canonical one
+        let one_main = st.int(1);
+        assert_eq!(st.add(vec![one_main, xy]), merged[0]);
+    }
+
+    #[test]
+    fn merge_between_unrelated_stores() {
+        let mut a = Store::new();
+        let x = a.sym("x");
+        let s = a.func("sin", vec![x]);
+        let mut b = Store::new();
+        b.sym("unrelated");
+        let m = b.merge(&a, &[s]);
+        assert_eq!(b.to_string(m[0]), "sin(x)");
+    }
+
+    #[test]
+    fn import_and_absorb() {
+        let mut a = Store::new();
+        let x = a.sym("x");
+        let y = a.sym("y");
+        let big = a.bigint(&arith::BigInt::from(i64::MAX) * &arith::BigInt::from(4i64));
+        let xy = a.mul(vec![x, y]);
+        let e = a.add(vec![xy, big]);
+        let mut b = Store::new();
+        let ib = b.import(&a, e);
+        assert_eq!(b.to_string(ib), a.to_string(e));
+        // Importing again finds the existing nodes
+        let n = b.node_count();
+        assert_eq!(b.import(&a, e), ib);
+        assert_eq!(b.node_count(), n);
+        // Absorbing maps every node, including ones not reachable from `e`
+        let z = a.sym("z");
+        let map = b.absorb(&a);
+        assert_eq!(map.len(), a.node_count());
+        assert_eq!(map[&e], ib);
+        assert_eq!(b.to_string(map[&z]), "z");
+    }
+
+    #[test]
+    fn import_across_digest_functions() {
+        fn reversed(op: &crate::Op, p: &crate::Payload, c: &[u64]) -> u64 {
+            !crate::default_digest(op, p, c)
+        }
+        let mut a = Store::with_digest_fn(reversed);
+        let x = a.sym("x");
+        let y = a.sym("y");
+        let s = a.add(vec![x, y]);
+        let mut b = Store::new();
+        let ib = b.import(&a, s);
+        // The sum is re-sorted for b's digest, so it is b's canonical node
+        let bx = b.sym("x");
+        let by = b.sym("y");
+        assert_eq!(b.add(vec![by, bx]), ib);
+    }
+
+    #[test]
+    fn repeated_freeze_and_compact() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        st.freeze();
+        let two = st.int(2);
+        let p = st.pow(x, two);
+        st.freeze();
+        let q = st.add(vec![p, x]);
+        assert_eq!(st.to_string(q), "x^2 + x");
+        // Compaction flattens the layers into a plain store
+        let map = st.compact(&[q]);
+        assert_eq!(st.to_string(map[&q]), "x^2 + x");
+        assert!(st.base.is_none());
+    }
+}
@@ -0,0 +1,256 @@
+//! Generic traversal over the expression DAG.
+//!
+//! - `preorder` / `postorder` iterate over the distinct nodes reachable from a root;
+//!   a subtree shared by several parents is visited once.
+//! - `free_symbols` and `has` answer the usual membership queries.
+//! - `map_bottom_up` and `replace_all` rebuild an expression through the canonical
+//!   constructors, memoized per node so shared subtrees are rewritten once.
+//! - `count_ops` measures the size of an expression as a tree of operations.
+
+use crate::{ExprId, Op, Payload, Store};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Parents before children, children left to right. See `Store::preorder`.
+pub struct PreOrder<'a> {
+    store: &'a Store,
+    stack: Vec<ExprId>,
+    seen: HashSet<ExprId>,
+}
+
+impl Iterator for PreOrder<'_> {
+    type Item = ExprId;
+
+    fn next(&mut self) -> Option<ExprId> {
+        while let Some(id) = self.stack.pop() {
+            if self.seen.insert(id) {
+                self.stack.extend(self.store.get(id).children.iter().rev());
+                return Some(id);
+            }
+        }
+        None
+    }
+}
+
+/// Children before parents, children left to right. See `Store::postorder`.
+pub struct PostOrder<'a> {
+    store: &'a Store,
+    // (node, children already pushed)
+    stack: Vec<(ExprId, bool)>,
+    seen: HashSet<ExprId>,
+}
+
+impl Iterator for PostOrder<'_> {
+    type Item = ExprId;
+
+    fn next(&mut self) -> Option<ExprId> {
+        while let Some((id, expanded)) = self.stack.pop() {
+            if expanded {
+                return Some(id);
+            }
+            if self.seen.insert(id) {
+                self.stack.push((id, true));
+                let children = &self.store.get(id).children;
+                self.stack.extend(children.iter().rev().map(|&c| (c, false)));
+            }
+        }
+        None
+    }
+}
+
+impl Store {
+    /// Distinct nodes reachable from `id`, each parent before its children.
+    pub fn preorder(&self, id: ExprId) -> PreOrder<'_> {
+        PreOrder { store: self, stack: vec![id], seen: HashSet::new() }
+    }
+
+    /// Distinct nodes reachable from `id`, each node after all of its children
+    /// (so `id` itself comes last).
+    pub fn postorder(&self, id: ExprId) -> PostOrder<'_> {
+        PostOrder { store: self, stack: vec![(id, false)], seen: HashSet::new() }
+    }
+
+    /// Names of the symbols occurring free in `id` (see `has_free_symbol`).
+    pub fn free_symbols(&self, id: ExprId) -> BTreeSet<String> {
+        let mut memo: HashMap<ExprId, BTreeSet<String>> = HashMap::new();
+        for n in self.postorder(id) {
+            let node = self.get(n);
+            let mut out = BTreeSet::new();
+            if let (Op::Symbol, Payload::Sym(s)) = (&node.op, &node.payload) {
+                out.insert(s.clone());
+            }
+            match self.bound_var(n) {
+                // The binder's body and variable are children 0 and 1
+                Some(x) => {
+                    out.extend(memo[&node.children[0]].iter().filter(|s| *s != x).cloned());
+                    for c in &node.children[2..] {
+                        out.extend(memo[c].iter().cloned());
+                    }
+                }
+                None => {
+                    for c in &node.children {
+                        out.extend(memo[c].iter().cloned());
+                    }
+                }
+            }
+            memo.insert(n, out);
+        }
+        memo.remove(&id).unwrap_or_default()
+    }
+
+    /// Whether `sub` occurs as a node of `id` (structurally: a subset of an
+    /// `Add`'s terms is not a node and does not count).
+    pub fn has(&self, id: ExprId, sub: ExprId) -> bool {
+        self.preorder(id).any(|n| n == sub)
+    }
+
+    /// Rebuild `id` bottom-up: each node's children are mapped first, the node is
+    /// rebuilt through `build` if any child changed, then `f` is applied to it.
+    /// Every distinct node is visited once.
+    pub fn map_bottom_up<F>(&mut self, id: ExprId, mut f: F) -> ExprId
+    where
+        F: FnMut(&mut Store, ExprId) -> ExprId,
+    {
+        let order: Vec<ExprId> = self.postorder(id).collect();
+        let mut memo: HashMap<ExprId, ExprId> = HashMap::with_capacity(order.len());
+        for n in order {
+            let children = self.get(n).children.iter().map(|c| memo[c]).collect();
+            let rebuilt = self.rebuild_with(n, children);
+            let out = f(self, rebuilt);
+            memo.insert(n, out);
+        }
+        memo[&id]
+    }
+
+    /// Replace every occurrence of each key of `map` in `id` by its value.
+    /// Matching is top-down: a replaced subtree is not searched further, and
+    /// replacements are inserted as-is.
+    pub fn replace_all(&mut self, id: ExprId, map: &HashMap<ExprId, ExprId>) -> ExprId {
+        fn go(
+            st: &mut Store,
+            id: ExprId,
+            map: &HashMap<ExprId, ExprId>,
+            memo: &mut HashMap<ExprId, ExprId>,
+        ) -> ExprId {
+            if let Some(&r) = map.get(&id).or_else(|| memo.get(&id)) {
+                return r;
+            }
+            let children = st.get(id).children.clone();
+            let mapped = children.into_iter().map(|c| go(st, c, map, memo)).collect();
+            let out = st.rebuild_with(id, mapped);
+            memo.insert(id, out);
+            out
+        }
+        if map.is_empty() {
+            return id;
+        }
+        go(self, id, map, &mut HashMap::new())
+    }
+
+    /// Number of operations in `id` viewed as a tree: an n-ary `Add`, `Mul`, `And`
+    /// or `Or` counts n - 1, every other non-atomic node counts 1, atoms count 0.
+    /// Shared subtrees count once per occurrence (computed once per distinct node).
+    pub fn count_ops(&self, id: ExprId) -> usize {
+        let mut memo: HashMap<ExprId, usize> = HashMap::new();
+        for n in self.postorder(id) {
+            let node = self.get(n);
+            let own = match node.op {
+                _ if node.children.is_empty() => 0,
+                Op::Add | Op::Mul | Op::And | Op::Or => node.children.len() - 1,
+                _ => 1,
+            };
+            let total = node.children.iter().fold(own, |acc, c| acc.saturating_add(memo[c]));
+            memo.insert(n, total);
+        }
+        memo[&id]
+    }
+
+    /// `id` with its children replaced by `children`; rebuilt only if they differ.
+    fn rebuild_with(&mut self, id: ExprId, children: Vec<ExprId>) -> ExprId {
+        let node = self.get(id);
+        if node.children == children {
+            return id;
+        }
+        let (op, payload) = (node.op.clone(), node.payload.clone());
+        self.build(op, payload, children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walks_visit_shared_nodes_once() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let two = st.int(2);
+        let x2 = st.pow(x, two);
+        let s = st.func("sin", vec![x2]);
+        let e = st.add(vec![x2, s]);
+        let pre: Vec<ExprId> = st.preorder(e).collect();
+        assert_eq!(pre.len(), 5);
+        assert_eq!(pre[0], e);
+        let post: Vec<ExprId> = st.postorder(e).collect();
+        assert_eq!(post.len(), 5);
+        assert_eq!(*post.last().unwrap(), e);
+        // Every child precedes its parent
+        let pos = |id| post.iter().position(|&p| p == id).unwrap();
+        assert!(pos(x) < pos(x2) && pos(x2) < pos(s));
+        assert!(st.has(e, x2));
+        let three = st.int(3);
+        assert!(!st.has(e, three));
+        // Tree count: Add(1) + Pow(1) + sin(1) + Pow(1)
+        assert_eq!(st.count_ops(e), 4);
+    }
+
+    #[test]
+    fn free_symbols_skip_bound_variables() {
+        let mut st = Store::new();
+        let k = st.sym("k");
+        let n = st.sym("n");
+        let a = st.sym("a");
+        let one = st.int(1);
+        let t = st.mul(vec![a, k]);
+        let s = st.sum(t, "k", one, n);
+        let expected: BTreeSet<String> = ["a", "n"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(st.free_symbols(s), expected);
+        // The same subtree outside the binder keeps k free
+        let e = st.add(vec![s, t]);
+        assert!(st.free_symbols(e).contains("k"));
+    }
+
+    #[test]
+    fn bottom_up_and_replace() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let two = st.int(2);
+        let x2 = st.pow(x, two);
+        let sx = st.func("sin", vec![x2]);
+        let e = st.add(vec![x2, sx]);
+        // Replace the shared subexpression x^2 by y
+        let map = HashMap::from([(x2, y)]);
+        let r = st.replace_all(e, &map);
+        let sy = st.func("sin", vec![y]);
+        assert_eq!(r, st.add(vec![y, sy]));
+        // sin -> cos everywhere, called once per distinct node
+        let mut calls = 0;
+        let m = st.map_bottom_up(e, |st, id| {
+            calls += 1;
+            match &st.get(id).payload {
+                Payload::Func(f) if f == "sin" => {
+                    let args = st.get(id).children.clone();
+                    st.func("cos", args)
+                }
+                _ => id,
+            }
+        });
+        assert_eq!(calls, 5);
+        let cx = st.func("cos", vec![x2]);
+        assert_eq!(m, st.add(vec![x2, cx]));
+        // Rebuilding goes through the canonical constructors
+        let z = st.int(0);
+        let zero_map = HashMap::from([(x2, z)]);
+        assert_eq!(st.replace_all(x2, &zero_map), z);
+    }
+}
@@ -0,0 +1,98 @@
+//! Interning under forced digest collisions.
+//! Stores built with a degenerate digest function must behave exactly like
+//! the default store: distinct expressions stay distinct and canonical
+//! ordering stays independent of construction order.
+
+use expr_core::{default_digest, ExprId, Op, Payload, Store};
+use proptest::prelude::*;
+
+/// Every node collides with every other node.
+fn constant_digest(_: &Op, _: &Payload, _: &[u64]) -> u64 {
+    0
+}
+
+/// Only two distinct digest values: frequent but not universal collisions.
+fn one_bit_digest(op: &Op, payload: &Payload, children: &[u64]) -> u64 {
+    default_digest(op, payload, children) & 1
+}
+
+const DIGESTS: [expr_core::DigestFn; 3] = [default_digest, constant_digest, one_bit_digest];
+
+/// Build sum_i c_i * x_i^e_i in `st`, with terms in the given order.
+fn build(st: &mut Store, terms: &[(i64, usize, i64)]) -> ExprId {
+    let mut parts = Vec::new();
+    for &(c, v, e) in terms {
+        let x = st.sym(format!("x{v}"));
+        let ee = st.int(e);
+        let p = st.pow(x, ee);
+        let cc = st.int(c);
+        parts.push(st.mul(vec![cc, p]));
+    }
+    st.add(parts)
+}
+
+/// Order-independent rendering (Add/Mul children sorted by their rendering).
+fn canon(st: &Store, id: ExprId) -> String {
+    let n = st.get(id);
+    let mut kids: Vec<String> = n.children.iter().map(|&c| canon(st, c)).collect();
+    if matches!(n.op, Op::Add | Op::Mul) {
+        kids.sort();
+    }
+    format!("{:?}{:?}[{}]", n.op, n.payload, kids.join(","))
+}
+
+#[test]
+fn distinct_atoms_are_not_merged() {
+    for f in DIGESTS {
+        let mut st = Store::with_digest_fn(f);
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let one = st.int(1);
+        let two = st.int(2);
+        assert_ne!(x, y);
+        assert_ne!(one, two);
+        let fx = st.func("f", vec![x]);
+        let fy = st.func("f", vec![y]);
+        assert_ne!(fx, fy);
+        assert_eq!(st.to_string(fy), "f(y)");
+        // Same structure still interns to one node
+        assert_eq!(st.func("f", vec![x]), fx);
+    }
+}
+
+#[test]
+fn canonical_order_survives_collisions() {
+    let mut st = Store::with_digest_fn(constant_digest);
+    let x = st.sym("x");
+    let y = st.sym("y");
+    let z = st.sym("z");
+    let a = st.add(vec![x, y, z]);
+    let b = st.add(vec![z, x, y]);
+    assert_eq!(a, b);
+    let m1 = st.mul(vec![y, x]);
+    let m2 = st.mul(vec![x, y]);
+    assert_eq!(m1, m2);
+    // x^y and y^x collide but must stay different
+    let p1 = st.pow(x, y);
+    let p2 = st.pow(y, x);
+    assert_ne!(p1, p2);
+}
+
+proptest! {
+    #[test]
+    fn prop_collisions_preserve_structure(
+        terms in prop::collection::vec((1i64..5, 0usize..3, 2i64..4), 1..5),
+    ) {
+        let mut reference = Store::new();
+        let expected = build(&mut reference, &terms);
+        for f in [constant_digest as expr_core::DigestFn, one_bit_digest] {
+            let mut st = Store::with_digest_fn(f);
+            let forward = build(&mut st, &terms);
+            let reversed: Vec<_> = terms.iter().rev().copied().collect();
+            let backward = build(&mut st, &reversed);
+            prop_assert_eq!(forward, backward);
+            // Same expression as the default store, up to child order
+            prop_assert_eq!(canon(&st, forward), canon(&reference, expected));
+        }
+    }
+}
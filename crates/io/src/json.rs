@@ -2,13 +2,23 @@
 //! Format is stable and minimal:
 //! - Integer: {"Integer": k}
 //! - Rational: {"Rational": {"num": n, "den": d}}
+//!   (integers of any size are written as plain JSON numbers)
+//! - Float: {"Float": {"value": "0.1", "prec": 53}} (decimal string, precision in bits)
 //! - Symbol: {"Symbol": "name"}
+//! - Constant: {"Constant": "pi"} (one of pi, E, I, oo, -oo, zoo, nan)
 //! - Function: {"Function": {"name": "f", "args": [ ... ]}}
 //! - Add: {"Add": [ ... ]}
 //! - Mul: {"Mul": [ ... ]}
 //! - Pow: {"Pow": {"base": ..., "exp": ...}}
+//! - Relations: {"Eq": [a, b]}, {"Lt": [a, b]}, {"Le": [a, b]}
+//! - Logic: {"And": [ ... ]}, {"Or": [ ... ]}, {"Not": ...}, {"True": null}, {"False": null}
+//! - Held: {"Derivative": {"expr": ..., "var": "x", "order": n}},
+//!   {"Integral": {"expr": ..., "var": "x"}} (plus "lower"/"upper" when definite),
+//!   {"Sum": {"expr": ..., "var": "k", "lower": ..., "upper": ...}}, same for "Product"
+//! - Lambda: {"Lambda": {"expr": ..., "var": "x"}}
 
-use expr_core::{ExprId, Op, Payload, Store};
+use arith::{BigInt, BigRat, Float};
+use expr_core::{Constant, ExprId, Op, Payload, Store};
 
 /// Serialize an expression to the stable JSON format described above.
 pub fn to_json(st: &Store, id: ExprId) -> String {
@@ -23,7 +33,17 @@ pub fn to_json(st: &Store, id: ExprId) -> String {
             (Op::Rational, Payload::Rat(a, b)) => {
                 format!("{{\"Rational\": {{\"num\": {a}, \"den\": {b}}}}}")
             }
+            (Op::Integer, Payload::BigInt(k)) => format!("{{\"Integer\": {k}}}"),
+            (Op::Rational, Payload::BigRat(r)) => {
+                let (a, b) = (r.numer(), r.denom());
+                format!("{{\"Rational\": {{\"num\": {a}, \"den\": {b}}}}}")
+            }
+            (Op::Float, Payload::Float(f)) => {
+                // Decimal string keeps every digit the precision tag promises
+                format!("{{\"Float\": {{\"value\": \"{f}\", \"prec\": {}}}}}", f.prec())
+            }
             (Op::Symbol, Payload::Sym(name)) => format!("{{\"Symbol\": \"{}\"}}", esc(name)),
+            (Op::Constant, Payload::Const(c)) => format!("{{\"Constant\": \"{}\"}}", c.name()),
             (Op::Function, Payload::Func(name)) => {
                 let args = n.children.iter().map(|c| go(st, *c)).collect::<Vec<_>>().join(", ");
                 format!("{{\"Function\": {{\"name\": \"{}\", \"args\": [{}]}}}}", esc(name), args)
@@ -41,9 +61,58 @@ pub fn to_json(st: &Store, id: ExprId) -> String {
                 let e = go(st, n.children[1]);
                 format!("{{\"Pow\": {{\"base\": {b}, \"exp\": {e}}}}}")
             }
+            (Op::And, _) if n.children.is_empty() => "{\"True\": null}".into(),
+            (Op::Or, _) if n.children.is_empty() => "{\"False\": null}".into(),
+            (Op::Eq | Op::Lt | Op::Le | Op::And | Op::Or, _) => {
+                let head = match n.op {
+                    Op::Eq => "Eq",
+                    Op::Lt => "Lt",
+                    Op::Le => "Le",
+                    Op::And => "And",
+                    _ => "Or",
+                };
+                let parts = n.children.iter().map(|c| go(st, *c)).collect::<Vec<_>>().join(", ");
+                format!("{{\"{head}\": [{parts}]}}")
+            }
+            (Op::Not, _) => format!("{{\"Not\": {}}}", go(st, n.children[0])),
+            (Op::Derivative, _) => {
+                let f = go(st, n.children[0]);
+                let x = esc(st.held_var(id).unwrap_or_default());
+                let k = go_order(st, n.children[2]);
+                format!("{{\"Derivative\": {{\"expr\": {f}, \"var\": \"{x}\", \"order\": {k}}}}}")
+            }
+            (Op::Integral | Op::Sum | Op::Product, _) => {
+                let head = match n.op {
+                    Op::Integral => "Integral",
+                    Op::Sum => "Sum",
+                    _ => "Product",
+                };
+                let f = go(st, n.children[0]);
+                let x = esc(st.held_var(id).unwrap_or_default());
+                let limits = if n.children.len() == 4 {
+                    let (a, b) = (go(st, n.children[2]), go(st, n.children[3]));
+                    format!(", \"lower\": {a}, \"upper\": {b}")
+                } else {
+                    String::new()
+                };
+                format!("{{\"{head}\": {{\"expr\": {f}, \"var\": \"{x}\"{limits}}}}}")
+            }
+            (Op::Lambda, _) => match st.lambda_parts(id) {
+                Some((x, body)) => {
+                    let f = go(st, body);
+                    format!("{{\"Lambda\": {{\"expr\": {f}, \"var\": \"{}\"}}}}", esc(x))
+                }
+                None => "{\"Unknown\": null}".into(),
+            },
             _ => "{\"Unknown\": null}".into(),
         }
     }
+    fn go_order(st: &Store, id: ExprId) -> String {
+        match &st.get(id).payload {
+            Payload::Int(k) => k.to_string(),
+            _ => "0".into(),
+        }
+    }
     go(st, id)
 }
 
@@ -54,7 +123,7 @@ pub fn from_json(st: &mut Store, input: &str) -> Result<ExprId, String> {
         Obj(Vec<(String, J)>),
         Arr(Vec<J>),
         Str(String),
-        Num(i64),
+        Num(BigInt),
         Null,
     }
     struct P<'a> {
@@ -113,7 +182,7 @@ pub fn from_json(st: &mut Store, input: &str) -> Result<ExprId, String> {
             }
             Err("unterminated string".into())
         }
-        fn parse_num(&mut self) -> Result<i64, String> {
+        fn parse_num(&mut self) -> Result<BigInt, String> {
             self.skip_ws();
             let start = self.i;
             if self.peek() == Some(b'-') {
@@ -132,7 +201,7 @@ pub fn from_json(st: &mut Store, input: &str) -> Result<ExprId, String> {
                 return Err("expected integer".into());
             }
             let s = std::str::from_utf8(&self.s[start..self.i]).unwrap();
-            s.parse::<i64>().map_err(|_| "invalid integer".into())
+            s.parse::<BigInt>().map_err(|_| "invalid integer".into())
         }
         fn parse_value(&mut self) -> Result<J, String> {
             self.skip_ws();
@@ -192,6 +261,10 @@ pub fn from_json(st: &mut Store, input: &str) -> Result<ExprId, String> {
                 }
                 Some(b'"') => Ok(J::Str(self.parse_str()?)),
                 Some(c) if c == b'-' || c.is_ascii_digit() => Ok(J::Num(self.parse_num()?)),
+                Some(b'n') if self.s[self.i..].starts_with(b"null") => {
+                    self.i += 4;
+                    Ok(J::Null)
+                }
                 _ => Err("unexpected token".into()),
             }
         }
@@ -207,7 +280,7 @@ pub fn from_json(st: &mut Store, input: &str) -> Result<ExprId, String> {
                 let (k, v) = fields.remove(0);
                 match k.as_str() {
                     "Integer" => match v {
-                        J::Num(k) => Ok(st.int(k)),
+                        J::Num(k) => Ok(st.bigint(k)),
                         _ => Err("Integer expects number".into()),
                     },
                     "Rational" => match v {
@@ -234,14 +307,47 @@ pub fn from_json(st: &mut Store, input: &str) -> Result<ExprId, String> {
                                 }
                             }
                             let (nn, dd) = (n.ok_or("missing num")?, d.ok_or("missing den")?);
-                            Ok(st.rat(nn, dd))
+                            if dd.is_zero() {
+                                return Err("zero denominator".into());
+                            }
+                            Ok(st.bigrat(BigRat::new(nn, dd)))
                         }
                         _ => Err("Rational expects object".into()),
                     },
+                    "Float" => match v {
+                        J::Obj(mut f2) => {
+                            let mut value = None;
+                            let mut prec = None;
+                            for (kk, vv) in f2.drain(..) {
+                                match (kk.as_str(), vv) {
+                                    ("value", J::Str(x)) => value = Some(x),
+                                    ("prec", J::Num(x)) => prec = Some(x),
+                                    ("value", _) => return Err("value must be string".into()),
+                                    ("prec", _) => return Err("prec must be number".into()),
+                                    _ => {}
+                                }
+                            }
+                            let value = value.ok_or("missing value")?;
+                            let prec = prec
+                                .and_then(|p| p.to_i64())
+                                .and_then(|p| u32::try_from(p).ok())
+                                .filter(|&p| p > 0)
+                                .ok_or("invalid prec")?;
+                            let f = Float::parse(&value, prec).map_err(|_| "invalid float")?;
+                            Ok(st.bigfloat(f))
+                        }
+                        _ => Err("Float expects object".into()),
+                    },
                     "Symbol" => match v {
                         J::Str(s) => Ok(st.sym(s)),
                         _ => Err("Symbol expects string".into()),
                     },
+                    "Constant" => match v {
+                        J::Str(s) => Constant::from_name(&s)
+                            .map(|c| st.constant(c))
+                            .ok_or_else(|| format!("unknown constant: {s}")),
+                        _ => Err("Constant expects string".into()),
+                    },
                     "Function" => match v {
                         J::Obj(mut f2) => {
                             let mut name = None;
@@ -312,6 +418,91 @@ pub fn from_json(st: &mut Store, input: &str) -> Result<ExprId, String> {
                         }
                         _ => Err("Pow expects object".into()),
                     },
+                    "True" => Ok(st.boolean(true)),
+                    "False" => Ok(st.boolean(false)),
+                    "Eq" | "Lt" | "Le" => match v {
+                        J::Arr(items) if items.len() == 2 => {
+                            let mut it = items.into_iter();
+                            let a = build_expr(st, it.next().unwrap())?;
+                            let b = build_expr(st, it.next().unwrap())?;
+                            Ok(match k.as_str() {
+                                "Eq" => st.eq(a, b),
+                                "Lt" => st.lt(a, b),
+                                _ => st.le(a, b),
+                            })
+                        }
+                        _ => Err("relation expects array of two".into()),
+                    },
+                    "And" | "Or" => match v {
+                        J::Arr(items) => {
+                            let mut ch = Vec::with_capacity(items.len());
+                            for it in items {
+                                ch.push(build_expr(st, it)?);
+                            }
+                            Ok(if k == "And" { st.and(ch) } else { st.or(ch) })
+                        }
+                        _ => Err("And/Or expects array".into()),
+                    },
+                    "Not" => {
+                        let a = build_expr(st, v)?;
+                        Ok(st.not(a))
+                    }
+                    "Lambda" => match v {
+                        J::Obj(f2) => {
+                            let (mut expr, mut var) = (None, None);
+                            for (kk, vv) in f2 {
+                                match (kk.as_str(), vv) {
+                                    ("expr", e) => expr = Some(build_expr(st, e)?),
+                                    ("var", J::Str(x)) => var = Some(x),
+                                    ("var", _) => return Err("var must be string".into()),
+                                    _ => {}
+                                }
+                            }
+                            let body = expr.ok_or("missing expr")?;
+                            let x = var.ok_or("missing var")?;
+                            Ok(st.lambda(&x, body))
+                        }
+                        _ => Err("Lambda expects object".into()),
+                    },
+                    "Derivative" | "Integral" | "Sum" | "Product" => match v {
+                        J::Obj(f2) => {
+                            let (mut expr, mut var, mut order) = (None, None, None);
+                            let (mut lower, mut upper) = (None, None);
+                            for (kk, vv) in f2 {
+                                match (kk.as_str(), vv) {
+                                    ("expr", e) => expr = Some(build_expr(st, e)?),
+                                    ("var", J::Str(x)) => var = Some(x),
+                                    ("var", _) => return Err("var must be string".into()),
+                                    ("order", J::Num(n)) => order = Some(n),
+                                    ("order", _) => return Err("order must be number".into()),
+                                    ("lower", e) => lower = Some(build_expr(st, e)?),
+                                    ("upper", e) => upper = Some(build_expr(st, e)?),
+                                    _ => {}
+                                }
+                            }
+                            let f = expr.ok_or("missing expr")?;
+                            let x = var.ok_or("missing var")?;
+                            let limits = match (lower, upper) {
+                                (Some(a), Some(b)) => Some((a, b)),
+                                (None, None) => None,
+                                _ => return Err("lower and upper go together".into()),
+                            };
+                            match (k.as_str(), limits) {
+                                ("Derivative", _) => {
+                                    let n = order
+                                        .and_then(|n| n.to_i64())
+                                        .and_then(|n| u32::try_from(n).ok())
+                                        .ok_or("invalid order")?;
+                                    Ok(st.derivative(f, &x, n))
+                                }
+                                ("Integral", limits) => Ok(st.integral(f, &x, limits)),
+                                ("Sum", Some((a, b))) => Ok(st.sum(f, &x, a, b)),
+                                ("Product", Some((a, b))) => Ok(st.product(f, &x, a, b)),
+                                _ => Err("Sum/Product expects lower and upper".into()),
+                            }
+                        }
+                        _ => Err("held operator expects object".into()),
+                    },
                     _ => Err("unknown head".into()),
                 }
             }
@@ -706,8 +897,12 @@ mod tests {
     #[test]
     fn json_parse_invalid_number() {
         let mut st = Store::new();
-        let json = r#"{"Integer": 999999999999999999999999999}"#;
+        let json = r#"{"Integer": -}"#;
         assert!(from_json(&mut st, json).is_err());
+        // Integers beyond i64 are valid and parse to a big integer atom
+        let json = r#"{"Integer": 999999999999999999999999999}"#;
+        let id = from_json(&mut st, json).unwrap();
+        assert_eq!(st.to_string(id), "999999999999999999999999999");
     }
 
     #[test]
@@ -739,4 +934,114 @@ mod tests {
         let json = to_json(&st, x);
         assert!(json.contains("Symbol"));
     }
+
+    #[test]
+    fn json_roundtrip_big_numbers() {
+        let mut st = Store::new();
+        let big = st.bigint("123456789012345678901234567890".parse().unwrap());
+        let x = st.sym("x");
+        let huge_den = st.rat(1, i64::MAX);
+        let tiny = st.mul(vec![huge_den, huge_den]);
+        let term = st.mul(vec![tiny, x]);
+        let expr = st.add(vec![big, term]);
+        let s = to_json(&st, expr);
+        assert!(s.contains("123456789012345678901234567890"));
+        let mut st2 = Store::new();
+        let parsed = from_json(&mut st2, &s).expect("parse");
+        assert_eq!(st.to_string(expr), st2.to_string(parsed));
+        assert!(from_json(&mut st2, "{\"Rational\": {\"num\": 1, \"den\": 0}}").is_err());
+    }
+
+    #[test]
+    fn json_roundtrip_floats() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let third = arith::Float::from_bigrat(&"1/3".parse().unwrap(), 120);
+        let c = st.bigfloat(third);
+        let expr = st.mul(vec![c, x]);
+        let s = to_json(&st, expr);
+        assert!(s.contains("\"prec\": 120"));
+        let mut st2 = Store::new();
+        let parsed = from_json(&mut st2, &s).expect("parse");
+        assert_eq!(to_json(&st2, parsed), s);
+        assert!(from_json(&mut st2, "{\"Float\": {\"value\": 0.5, \"prec\": 53}}").is_err());
+        assert!(from_json(&mut st2, "{\"Float\": {\"value\": \"0.5\"}}").is_err());
+    }
+
+    #[test]
+    fn json_roundtrip_relations() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let one = st.int(1);
+        let lt = st.lt(x, one);
+        let eq = st.eq(x, y);
+        let ne = st.not(eq);
+        let le = st.le(y, x);
+        let or = st.or(vec![le, ne]);
+        let expr = st.and(vec![lt, or]);
+        let s = to_json(&st, expr);
+        let mut st2 = Store::new();
+        let parsed = from_json(&mut st2, &s).expect("parse");
+        assert_eq!(to_json(&st2, parsed), s);
+        let t = from_json(&mut st2, "{\"True\": null}").unwrap();
+        assert_eq!(st2.as_bool(t), Some(true));
+        assert!(from_json(&mut st2, "{\"Lt\": [{\"Symbol\": \"x\"}]}").is_err());
+    }
+
+    #[test]
+    fn json_roundtrip_constants() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let pi = st.constant(Constant::Pi);
+        let i = st.constant(Constant::I);
+        let oo = st.constant(Constant::Infinity);
+        let pix = st.mul(vec![pi, x, i]);
+        let expr = st.add(vec![pix, oo]);
+        let s = to_json(&st, expr);
+        assert!(s.contains("{\"Constant\": \"pi\"}"));
+        let mut st2 = Store::new();
+        let parsed = from_json(&mut st2, &s).expect("parse");
+        assert_eq!(to_json(&st2, parsed), s);
+        let noo = from_json(&mut st2, "{\"Constant\": \"-oo\"}").unwrap();
+        assert_eq!(st2.as_constant(noo), Some(Constant::NegativeInfinity));
+        assert!(from_json(&mut st2, "{\"Constant\": \"tau\"}").is_err());
+    }
+
+    #[test]
+    fn json_roundtrip_held() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let k = st.sym("k");
+        let n = st.sym("n");
+        let one = st.int(1);
+        let fx = st.func("f", vec![x]);
+        let d = st.derivative(fx, "x", 2);
+        let ix = st.integral(d, "x", None);
+        let zero = st.int(0);
+        let ij = st.integral(x, "x", Some((zero, one)));
+        let s = st.sum(k, "k", one, n);
+        let p = st.product(k, "k", one, n);
+        let expr = st.add(vec![ix, ij, s, p]);
+        let out = to_json(&st, expr);
+        assert!(out.contains("\"order\": 2"));
+        let mut st2 = Store::new();
+        let parsed = from_json(&mut st2, &out).expect("parse");
+        assert_eq!(to_json(&st2, parsed), out);
+        assert!(from_json(&mut st2, "{\"Sum\": {\"expr\": {\"Symbol\": \"k\"}, \"var\": \"k\"}}")
+            .is_err());
+    }
+
+    #[test]
+    fn json_roundtrip_lambda() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let s = st.func("sin", vec![x]);
+        let f = st.lambda("x", s);
+        let out = to_json(&st, f);
+        assert!(out.contains("\"Lambda\"") && out.contains("\"var\": \"#0\""));
+        let mut st2 = Store::new();
+        let parsed = from_json(&mut st2, &out).expect("parse");
+        assert_eq!(to_json(&st2, parsed), out);
+    }
 }
@@ -1,24 +1,30 @@
 //! LaTeX printer for Symmetrica expressions.
 //! Minimal, deterministic, precedence-aware pretty printer.
 
-use expr_core::{ExprId, Op, Payload, Store};
+use expr_core::{Constant, ExprId, Op, Payload, Store};
 
 /// Convert an expression to a LaTeX string.
 pub fn to_latex(st: &Store, id: ExprId) -> String {
     fn prec(op: &Op) -> u8 {
         match op {
-            Op::Add => 1,
-            Op::Mul => 2,
-            Op::Pow => 3,
-            _ => 4,
+            Op::Lambda => 0,
+            Op::Or => 1,
+            Op::And => 2,
+            Op::Eq | Op::Lt | Op::Le => 3,
+            Op::Add => 4,
+            // Held operators bind like a product: parenthesized as a base, not in sums
+            Op::Mul | Op::Derivative | Op::Integral | Op::Sum | Op::Product => 5,
+            Op::Pow => 6,
+            _ => 7,
         }
     }
     fn needs_paren(child_op: &Op, parent_prec: u8) -> bool {
         prec(child_op) < parent_prec
     }
     fn esc_ident(s: &str) -> String {
-        // Minimal escaping for LaTeX: underscore is common in identifiers
-        s.replace('_', "\\_")
+        // Minimal escaping for LaTeX: underscore is common in identifiers, and `#`
+        // starts the names of lambda-bound variables
+        s.replace('_', "\\_").replace('#', "\\#")
     }
     fn mul_join(parts: Vec<String>) -> String {
         parts.join(" \\cdot ")
@@ -29,7 +35,28 @@ pub fn to_latex(st: &Store, id: ExprId) -> String {
         let s = match (&n.op, &n.payload) {
             (Op::Integer, Payload::Int(k)) => k.to_string(),
             (Op::Rational, Payload::Rat(a, b)) => format!("\\frac{{{}}}{{{}}}", a, b),
+            (Op::Integer, Payload::BigInt(k)) => k.to_string(),
+            (Op::Rational, Payload::BigRat(r)) => {
+                format!("\\frac{{{}}}{{{}}}", r.numer(), r.denom())
+            }
+            (Op::Float, Payload::Float(f)) => {
+                let s = f.to_string();
+                match s.split_once('e') {
+                    Some((m, e)) => format!("{m} \\times 10^{{{e}}}"),
+                    None => s,
+                }
+            }
             (Op::Symbol, Payload::Sym(name)) => esc_ident(name),
+            (Op::Constant, Payload::Const(c)) => match c {
+                Constant::Pi => "\\pi",
+                Constant::E => "e",
+                Constant::I => "i",
+                Constant::Infinity => "\\infty",
+                Constant::NegativeInfinity => "-\\infty",
+                Constant::ComplexInfinity => "\\tilde{\\infty}",
+                Constant::NaN => "\\mathrm{NaN}",
+            }
+            .into(),
             (Op::Function, Payload::Func(name)) => {
                 let head = match name.as_str() {
                     "sin" => "\\sin",
@@ -79,6 +106,58 @@ pub fn to_latex(st: &Store, id: ExprId) -> String {
                 let exp = go(st, e_id, 0);
                 format!("{}^{{{}}}", base, exp)
             }
+            (Op::And, _) if n.children.is_empty() => "\\text{True}".into(),
+            (Op::Or, _) if n.children.is_empty() => "\\text{False}".into(),
+            (Op::And | Op::Or, _) => {
+                let sep = if n.op == Op::And { " \\land " } else { " \\lor " };
+                let p = prec(&n.op);
+                n.children.iter().map(|c| go(st, *c, p + 1)).collect::<Vec<_>>().join(sep)
+            }
+            (Op::Eq | Op::Lt | Op::Le, _) => {
+                let rel = match n.op {
+                    Op::Eq => "=",
+                    Op::Lt => "<",
+                    _ => "\\leq",
+                };
+                let l = go(st, n.children[0], prec(&Op::Add));
+                let r = go(st, n.children[1], prec(&Op::Add));
+                format!("{l} {rel} {r}")
+            }
+            (Op::Not, _) => format!("\\neg {}", go(st, n.children[0], prec(&Op::Not))),
+            (Op::Derivative, _) => {
+                let body = go(st, n.children[0], prec(&Op::Mul));
+                let x = go(st, n.children[1], 0);
+                match st.get(n.children[2]).payload {
+                    Payload::Int(1) => format!("\\frac{{d}}{{d{x}}} {body}"),
+                    _ => {
+                        let k = go(st, n.children[2], 0);
+                        format!("\\frac{{d^{{{k}}}}}{{d{x}^{{{k}}}}} {body}")
+                    }
+                }
+            }
+            (Op::Integral, _) => {
+                let body = go(st, n.children[0], prec(&Op::Mul));
+                let x = go(st, n.children[1], 0);
+                if n.children.len() == 4 {
+                    let a = go(st, n.children[2], 0);
+                    let b = go(st, n.children[3], 0);
+                    format!("\\int_{{{a}}}^{{{b}}} {body} \\, d{x}")
+                } else {
+                    format!("\\int {body} \\, d{x}")
+                }
+            }
+            (Op::Sum | Op::Product, _) => {
+                let head = if n.op == Op::Sum { "\\sum" } else { "\\prod" };
+                let body = go(st, n.children[0], prec(&Op::Mul));
+                let k = go(st, n.children[1], 0);
+                let a = go(st, n.children[2], 0);
+                let b = go(st, n.children[3], 0);
+                format!("{head}_{{{k}={a}}}^{{{b}}} {body}")
+            }
+            (Op::Lambda, _) => match st.lambda_parts(id) {
+                Some((x, body)) => format!("{} \\mapsto {}", esc_ident(x), go(st, body, 0)),
+                None => "<unknown>".into(),
+            },
             _ => "<unknown>".into(),
         };
         if prec(&n.op) < parent_prec {
@@ -167,4 +246,87 @@ mod tests {
         let s = to_latex(&st, expr);
         assert!(s.contains("(2 \\cdot x)^{3}"));
     }
+
+    #[test]
+    fn latex_big_numbers() {
+        let mut st = Store::new();
+        let big = st.bigint("-100000000000000000000".parse().unwrap());
+        assert_eq!(to_latex(&st, big), "-100000000000000000000");
+        let r = st.bigrat("3/100000000000000000000".parse().unwrap());
+        assert_eq!(to_latex(&st, r), "\\frac{3}{100000000000000000000}");
+    }
+
+    #[test]
+    fn latex_floats() {
+        let mut st = Store::new();
+        let f = st.float(-0.25);
+        assert_eq!(to_latex(&st, f), "-0.25");
+        let big = st.float(6.02e23);
+        assert_eq!(to_latex(&st, big), "6.02 \\times 10^{23}");
+    }
+
+    #[test]
+    fn latex_relations() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let one = st.int(1);
+        let lt = st.lt(x, one);
+        assert_eq!(to_latex(&st, lt), "x < 1");
+        let eq = st.eq(x, one);
+        let ne = st.not(eq);
+        let expr = st.and(vec![lt, ne]);
+        let s = to_latex(&st, expr);
+        assert!(s.contains("\\land") && s.contains("\\neg"));
+        let t = st.boolean(true);
+        assert_eq!(to_latex(&st, t), "\\text{True}");
+    }
+
+    #[test]
+    fn latex_constants() {
+        let mut st = Store::new();
+        let two = st.int(2);
+        let pi = st.constant(Constant::Pi);
+        let two_pi = st.mul(vec![two, pi]);
+        assert_eq!(to_latex(&st, two_pi), "2 \\cdot \\pi");
+        let oo = st.constant(Constant::Infinity);
+        assert_eq!(to_latex(&st, oo), "\\infty");
+        let zoo = st.constant(Constant::ComplexInfinity);
+        assert_eq!(to_latex(&st, zoo), "\\tilde{\\infty}");
+    }
+
+    #[test]
+    fn latex_held_calculus() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let fx = st.func("f", vec![x]);
+        let d = st.derivative(fx, "x", 1);
+        assert_eq!(to_latex(&st, d), "\\frac{d}{dx} f(x)");
+        let d2 = st.derivative(fx, "x", 2);
+        assert_eq!(to_latex(&st, d2), "\\frac{d^{2}}{dx^{2}} f(x)");
+        let zero = st.int(0);
+        let one = st.int(1);
+        let xp1 = st.add(vec![x, one]);
+        let i = st.integral(xp1, "x", Some((zero, one)));
+        assert_eq!(to_latex(&st, i), "\\int_{0}^{1} (1 + x) \\, dx");
+        let k = st.sym("k");
+        let n = st.sym("n");
+        let s = st.sum(k, "k", one, n);
+        assert_eq!(to_latex(&st, s), "\\sum_{k=1}^{n} k");
+        let p = st.product(k, "k", one, n);
+        let two = st.int(2);
+        let p2 = st.pow(p, two);
+        assert_eq!(to_latex(&st, p2), "(\\prod_{k=1}^{n} k)^{2}");
+    }
+
+    #[test]
+    fn latex_lambda() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let two = st.int(2);
+        let x2 = st.pow(x, two);
+        let f = st.lambda("x", x2);
+        assert_eq!(to_latex(&st, f), "\\#0 \\mapsto \\#0^{2}");
+        let fs = st.func("F", vec![f]);
+        assert_eq!(to_latex(&st, fs), "F(\\#0 \\mapsto \\#0^{2})");
+    }
 }
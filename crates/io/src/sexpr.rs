@@ -1,13 +1,19 @@
 //! S-expression serializer and parser for Symmetrica expressions.
 //! Formats:
-//! - Atoms: (Int k), (Rat n d), (Sym name)
+//! - Atoms: (Int k), (Rat n d), (Float value prec), (Sym name), (Const name); integers may
+//!   have any number of digits and a bare decimal like 0.25 reads as a 53-bit float
 //! - Composite: (+ e1 e2 ...), (* e1 e2 ...), (^ base exp), (Fn name arg1 arg2 ...)
+//! - Logic: (= a b), (< a b), (<= a b), (And ...), (Or ...), (Not a), (True), (False)
+//! - Held: (Derivative f x n), (Integral f x), (Integral f x a b), (Sum t k a b),
+//!   (Product t k a b)
+//! - Lambda: (Lambda body x)
 //!
 //! Parser is minimal and conservative; it expects the above structured forms.
 //! Names in (Sym name) and (Fn name ...) accept bare tokens without spaces/parentheses; use
 //! double quotes to include spaces or special characters.
 
-use expr_core::{ExprId, Op, Payload, Store};
+use arith::{BigInt, BigRat, Float, DEFAULT_PREC};
+use expr_core::{Constant, ExprId, Op, Payload, Store};
 
 /// Convert an expression to an S-expression string.
 pub fn to_sexpr(st: &Store, id: ExprId) -> String {
@@ -24,7 +30,11 @@ pub fn to_sexpr(st: &Store, id: ExprId) -> String {
         match (&n.op, &n.payload) {
             (Op::Integer, Payload::Int(k)) => format!("(Int {k})"),
             (Op::Rational, Payload::Rat(a, b)) => format!("(Rat {a} {b})"),
+            (Op::Integer, Payload::BigInt(k)) => format!("(Int {k})"),
+            (Op::Rational, Payload::BigRat(r)) => format!("(Rat {} {})", r.numer(), r.denom()),
+            (Op::Float, Payload::Float(f)) => format!("(Float {f} {})", f.prec()),
             (Op::Symbol, Payload::Sym(name)) => format!("(Sym {})", esc_name(name)),
+            (Op::Constant, Payload::Const(c)) => format!("(Const {})", esc_name(c.name())),
             (Op::Function, Payload::Func(name)) => {
                 let args = n.children.iter().map(|c| go(st, *c)).collect::<Vec<_>>().join(" ");
                 format!("(Fn {} {})", esc_name(name), args)
@@ -42,6 +52,41 @@ pub fn to_sexpr(st: &Store, id: ExprId) -> String {
                 let e = go(st, n.children[1]);
                 format!("(^ {} {})", b, e)
             }
+            (Op::And, _) if n.children.is_empty() => "(True)".into(),
+            (Op::Or, _) if n.children.is_empty() => "(False)".into(),
+            (Op::Eq | Op::Lt | Op::Le | Op::And | Op::Or | Op::Not, _) => {
+                let head = match n.op {
+                    Op::Eq => "=",
+                    Op::Lt => "<",
+                    Op::Le => "<=",
+                    Op::And => "And",
+                    Op::Or => "Or",
+                    _ => "Not",
+                };
+                let parts = n.children.iter().map(|c| go(st, *c)).collect::<Vec<_>>().join(" ");
+                format!("({head} {parts})")
+            }
+            (Op::Derivative | Op::Integral | Op::Sum | Op::Product, _) => {
+                let head = match n.op {
+                    Op::Derivative => "Derivative",
+                    Op::Integral => "Integral",
+                    Op::Sum => "Sum",
+                    _ => "Product",
+                };
+                let mut parts = vec![go(st, n.children[0])];
+                parts.push(esc_name(st.held_var(id).unwrap_or_default()));
+                for &c in &n.children[2..] {
+                    parts.push(match (&st.get(c).op, &st.get(c).payload) {
+                        (Op::Integer, Payload::Int(k)) if n.op == Op::Derivative => k.to_string(),
+                        _ => go(st, c),
+                    });
+                }
+                format!("({head} {})", parts.join(" "))
+            }
+            (Op::Lambda, _) => match st.lambda_parts(id) {
+                Some((x, body)) => format!("(Lambda {} {})", go(st, body), esc_name(x)),
+                None => "(Unknown)".into(),
+            },
             _ => "(Unknown)".into(),
         }
     }
@@ -56,7 +101,9 @@ pub fn from_sexpr(st: &mut Store, input: &str) -> Result<ExprId, String> {
         RParen,
         Str(String),
         Sym(String),
-        Int(i64),
+        Int(BigInt),
+        // Decimal literal with a point or exponent, kept as text until the precision is known
+        Dec(String),
     }
     struct Lexer<'a> {
         s: &'a [u8],
@@ -126,9 +173,20 @@ pub fn from_sexpr(st: &mut Store, input: &str) -> Result<ExprId, String> {
                     Ok(Some(Tok::RParen))
                 }
                 Some(b'"') => Ok(Some(Tok::Str(self.read_string()?))),
-                Some(c) if c == b'-' || c.is_ascii_digit() => {
-                    let s = self.read_while(|ch| ch == b'-' || ch.is_ascii_digit());
-                    let k: i64 = s.parse().map_err(|_| format!("invalid int: {s}"))?;
+                // A leading '-' starts a number only before a digit or '.'; otherwise
+                // it is part of a name such as -oo
+                Some(c)
+                    if c.is_ascii_digit()
+                        || (c == b'-'
+                            && matches!(self.s.get(self.i + 1), Some(d) if d.is_ascii_digit() || *d == b'.')) =>
+                {
+                    let s = self.read_while(|ch| {
+                        ch.is_ascii_digit() || matches!(ch, b'-' | b'+' | b'.' | b'e' | b'E')
+                    });
+                    if s.contains(['.', 'e', 'E']) {
+                        return Ok(Some(Tok::Dec(s)));
+                    }
+                    let k: BigInt = s.parse().map_err(|_| format!("invalid int: {s}"))?;
                     Ok(Some(Tok::Int(k)))
                 }
                 Some(_) => {
@@ -177,10 +235,10 @@ pub fn from_sexpr(st: &mut Store, input: &str) -> Result<ExprId, String> {
                 _ => Err("expected symbol or string".into()),
             }
         }
-        fn expect_int(&mut self) -> Result<i64, String> {
+        fn expect_int(&mut self) -> Result<BigInt, String> {
             match self.peek() {
                 Some(Tok::Int(k)) => {
-                    let v = *k;
+                    let v = k.clone();
                     self.bump();
                     Ok(v)
                 }
@@ -226,19 +284,98 @@ pub fn from_sexpr(st: &mut Store, input: &str) -> Result<ExprId, String> {
                 let e = parse_any(st, cur)?;
                 st.pow(b, e)
             }
+            "=" | "<" | "<=" => {
+                let a = parse_any(st, cur)?;
+                let b = parse_any(st, cur)?;
+                match head.as_str() {
+                    "=" => st.eq(a, b),
+                    "<" => st.lt(a, b),
+                    _ => st.le(a, b),
+                }
+            }
+            "And" | "Or" => {
+                let mut args: Vec<ExprId> = Vec::new();
+                while !matches!(cur.peek(), Some(Tok::RParen)) {
+                    args.push(parse_any(st, cur)?);
+                }
+                if head == "And" {
+                    st.and(args)
+                } else {
+                    st.or(args)
+                }
+            }
+            "Not" => {
+                let a = parse_any(st, cur)?;
+                st.not(a)
+            }
+            "True" => st.boolean(true),
+            "False" => st.boolean(false),
             "Int" => {
                 let k = cur.expect_int()?;
-                st.int(k)
+                st.bigint(k)
             }
             "Rat" => {
                 let n = cur.expect_int()?;
                 let d = cur.expect_int()?;
-                st.rat(n, d)
+                if d.is_zero() {
+                    return Err("zero denominator".into());
+                }
+                st.bigrat(BigRat::new(n, d))
+            }
+            "Float" => {
+                let value = match cur.peek() {
+                    Some(Tok::Dec(s)) => s.clone(),
+                    Some(Tok::Int(k)) => k.to_string(),
+                    _ => return Err("expected decimal".into()),
+                };
+                cur.bump();
+                let prec = cur
+                    .expect_int()?
+                    .to_i64()
+                    .and_then(|p| u32::try_from(p).ok())
+                    .filter(|&p| p > 0)
+                    .ok_or("invalid prec")?;
+                let f =
+                    Float::parse(&value, prec).map_err(|_| format!("invalid float: {value}"))?;
+                st.bigfloat(f)
             }
             "Sym" => {
                 let name = cur.expect_sym()?;
                 st.sym(name)
             }
+            "Const" => {
+                let name = cur.expect_sym()?;
+                let c = Constant::from_name(&name)
+                    .ok_or_else(|| format!("unknown constant: {name}"))?;
+                st.constant(c)
+            }
+            "Derivative" | "Integral" | "Sum" | "Product" => {
+                let f = parse_any(st, cur)?;
+                let x = cur.expect_sym()?;
+                if head == "Derivative" {
+                    let n = cur
+                        .expect_int()?
+                        .to_i64()
+                        .and_then(|n| u32::try_from(n).ok())
+                        .ok_or("invalid order")?;
+                    st.derivative(f, &x, n)
+                } else if head == "Integral" && matches!(cur.peek(), Some(Tok::RParen)) {
+                    st.integral(f, &x, None)
+                } else {
+                    let a = parse_any(st, cur)?;
+                    let b = parse_any(st, cur)?;
+                    match head.as_str() {
+                        "Integral" => st.integral(f, &x, Some((a, b))),
+                        "Sum" => st.sum(f, &x, a, b),
+                        _ => st.product(f, &x, a, b),
+                    }
+                }
+            }
+            "Lambda" => {
+                let body = parse_any(st, cur)?;
+                let x = cur.expect_sym()?;
+                st.lambda(&x, body)
+            }
             "Fn" => {
                 let name = cur.expect_sym()?;
                 let mut args: Vec<ExprId> = Vec::new();
@@ -257,9 +394,14 @@ pub fn from_sexpr(st: &mut Store, input: &str) -> Result<ExprId, String> {
         match cur.peek() {
             Some(Tok::LParen) => parse_list(st, cur),
             Some(Tok::Int(k)) => {
-                let v = *k;
+                let v = k.clone();
+                cur.bump();
+                Ok(st.bigint(v))
+            }
+            Some(Tok::Dec(s)) => {
+                let f = Float::parse(s, DEFAULT_PREC).map_err(|_| format!("invalid float: {s}"))?;
                 cur.bump();
-                Ok(st.int(v))
+                Ok(st.bigfloat(f))
             }
             Some(Tok::Sym(s)) => {
                 // bare symbol token: interpret as (Sym s)
@@ -597,9 +739,10 @@ mod tests {
     }
 
     #[test]
-    fn sexpr_int_overflow() {
+    fn sexpr_int_beyond_i64() {
         let mut st = Store::new();
-        assert!(from_sexpr(&mut st, "(Int 99999999999999999999999999)").is_err());
+        let id = from_sexpr(&mut st, "(Int 99999999999999999999999999)").unwrap();
+        assert_eq!(st.to_string(id), "99999999999999999999999999");
     }
 
     #[test]
@@ -645,4 +788,112 @@ mod tests {
         let parsed = from_sexpr(&mut st2, &s).expect("parse");
         assert_eq!(st.to_string(outer), st2.to_string(parsed));
     }
+
+    #[test]
+    fn sexpr_roundtrip_big_numbers() {
+        let mut st = Store::new();
+        let s = "(* (Rat 1 100000000000000000000000) (Int -99999999999999999999) (Sym x))";
+        let id = from_sexpr(&mut st, s).expect("parse");
+        let out = to_sexpr(&st, id);
+        assert!(out.contains("(Rat -99999999999999999999 100000000000000000000000)"));
+        let small = from_sexpr(&mut st, "(Int 00042)").unwrap();
+        assert_eq!(small, st.int(42));
+        assert!(from_sexpr(&mut st, "(Rat 1 0)").is_err());
+    }
+
+    #[test]
+    fn sexpr_roundtrip_floats() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let f = st.float(0.1);
+        let g = st.bigfloat("1.5e40".parse::<arith::Float>().unwrap().with_prec(80));
+        let fx = st.mul(vec![f, x]);
+        let expr = st.add(vec![fx, g]);
+        let s = to_sexpr(&st, expr);
+        assert!(s.contains("(Float 0.1 53)"));
+        let mut st2 = Store::new();
+        let parsed = from_sexpr(&mut st2, &s).expect("parse");
+        assert_eq!(to_sexpr(&st2, parsed), s);
+        // Bare decimals read as 53-bit floats
+        let bare = from_sexpr(&mut st2, "(+ 2.5e-3 (Sym y))").unwrap();
+        assert_eq!(st2.to_string(bare), "0.0025 + y");
+        assert!(from_sexpr(&mut st2, "(Float 1.2.3 53)").is_err());
+        assert!(from_sexpr(&mut st2, "(Float 1.5 0)").is_err());
+    }
+
+    #[test]
+    fn sexpr_roundtrip_relations() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let zero = st.int(0);
+        let pos = st.lt(zero, x);
+        let eq = st.eq(x, zero);
+        let ne = st.not(eq);
+        let expr = st.or(vec![pos, ne]);
+        let s = to_sexpr(&st, expr);
+        let mut st2 = Store::new();
+        let parsed = from_sexpr(&mut st2, &s).expect("parse");
+        assert_eq!(to_sexpr(&st2, parsed), s);
+        let f = from_sexpr(&mut st2, "(And (<= (Sym x) (Int 1)) (False))").unwrap();
+        assert_eq!(st2.as_bool(f), Some(false));
+        assert!(from_sexpr(&mut st2, "(< (Sym x))").is_err());
+    }
+
+    #[test]
+    fn sexpr_roundtrip_constants() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let e = st.constant(Constant::E);
+        let noo = st.constant(Constant::NegativeInfinity);
+        let ex = st.pow(e, x);
+        let expr = st.add(vec![ex, noo]);
+        let s = to_sexpr(&st, expr);
+        assert!(s.contains("(Const E)") && s.contains("(Const -oo)"));
+        let mut st2 = Store::new();
+        let parsed = from_sexpr(&mut st2, &s).expect("parse");
+        assert_eq!(to_sexpr(&st2, parsed), s);
+        assert!(from_sexpr(&mut st2, "(Const tau)").is_err());
+    }
+
+    #[test]
+    fn sexpr_roundtrip_held() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let k = st.sym("k");
+        let n = st.sym("n");
+        let one = st.int(1);
+        let fx = st.func("f", vec![x]);
+        let d = st.derivative(fx, "x", 3);
+        assert_eq!(to_sexpr(&st, d), "(Derivative (Fn f (Sym x)) x 3)");
+        let ix = st.integral(fx, "x", None);
+        assert_eq!(to_sexpr(&st, ix), "(Integral (Fn f (Sym x)) x)");
+        let s = st.sum(k, "k", one, n);
+        assert_eq!(to_sexpr(&st, s), "(Sum (Sym k) k (Int 1) (Sym n))");
+        let zero = st.int(0);
+        let ij = st.integral(x, "x", Some((zero, one)));
+        let p = st.product(k, "k", one, n);
+        let expr = st.add(vec![d, ix, ij, s, p]);
+        let out = to_sexpr(&st, expr);
+        let mut st2 = Store::new();
+        let parsed = from_sexpr(&mut st2, &out).expect("parse");
+        assert_eq!(to_sexpr(&st2, parsed), out);
+        assert!(from_sexpr(&mut st2, "(Sum (Sym k) k 1)").is_err());
+    }
+
+    #[test]
+    fn sexpr_roundtrip_lambda() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let xy = st.mul(vec![x, y]);
+        let f = st.lambda("x", xy);
+        let s = to_sexpr(&st, f);
+        assert_eq!(s, "(Lambda (* (Sym \"#0\") (Sym y)) \"#0\")");
+        let mut st2 = Store::new();
+        let parsed = from_sexpr(&mut st2, &s).expect("parse");
+        assert_eq!(to_sexpr(&st2, parsed), s);
+        // Any bound name reads back as the same lambda
+        let alt = from_sexpr(&mut st2, "(Lambda (* (Sym t) (Sym y)) t)").expect("parse");
+        assert_eq!(alt, parsed);
+    }
 }
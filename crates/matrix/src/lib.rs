@@ -1,8 +1,15 @@
 //! Matrix/linear algebra module: exact matrices over Q and fraction-free methods.
 #![deny(warnings)]
 
-use arith::{add_q, div_q, mul_q, sub_q, Q};
-
+use arith::{
+    checked_add_q, checked_div_q, checked_mul_q, checked_sub_q, mul_q, ArithError, BigInt, BigRat,
+    Q,
+};
+
+/// Dense matrix of `Q` entries, i64-only with no big-number fallback.
+/// Fallible operations report overflow through their `Err`, `scalar_mul` and
+/// `nullspace` saturate (their `checked_*` siblings report it), and `det_big`
+/// returns the exact determinant as a `BigRat`.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MatrixQ {
     pub rows: usize,
@@ -41,6 +48,8 @@ impl MatrixQ {
 
     /// Compute determinant using the Bareiss fraction-free algorithm.
     /// Returns 0 for singular matrices. Requires square matrix.
+    /// Fails with "numeric overflow" when an entry outgrows i64; `det_big`
+    /// handles such matrices exactly.
     pub fn det_bareiss(&self) -> Result<Q, &'static str> {
         if self.rows != self.cols {
             return Err("determinant requires square matrix");
@@ -67,7 +76,7 @@ impl MatrixQ {
                     a.set(k, c, a.get(pr, c));
                     a.set(pr, c, t);
                 }
-                sign = mul_q(sign, Q(-1, 1));
+                sign = checked_mul_q(sign, Q(-1, 1))?;
             }
             // eliminate below
             let akk = a.get(k, k);
@@ -76,9 +85,9 @@ impl MatrixQ {
                 if aik.is_zero() {
                     continue;
                 }
-                let factor = div_q(aik, akk);
+                let factor = checked_div_q(aik, akk)?;
                 for j in k..n {
-                    let val = sub_q(a.get(i, j), mul_q(factor, a.get(k, j)));
+                    let val = checked_sub_q(a.get(i, j), checked_mul_q(factor, a.get(k, j))?)?;
                     a.set(i, j, val);
                 }
                 a.set(i, k, Q::zero());
@@ -87,11 +96,109 @@ impl MatrixQ {
         // determinant is sign * product of diagonal
         let mut det = sign;
         for i in 0..n {
-            det = mul_q(det, a.get(i, i));
+            det = checked_mul_q(det, a.get(i, i))?;
         }
         Ok(det)
     }
 
+    /// Determinant computed exactly over arbitrary-precision integers.
+    /// Rows are cleared of denominators and reduced with the fraction-free
+    /// Bareiss recurrence, so entries never overflow even when the i64
+    /// `det_bareiss` would.
+    pub fn det_big(&self) -> Result<BigRat, &'static str> {
+        if self.rows != self.cols {
+            return Err("determinant requires square matrix");
+        }
+        let n = self.rows;
+        if n == 0 {
+            return Ok(BigRat::one());
+        }
+        let (mut a, scale) = self.integer_rows();
+        let mut negate = false;
+        let mut prev = BigInt::one();
+        for k in 0..n - 1 {
+            if a[k][k].is_zero() {
+                match (k + 1..n).find(|&r| !a[r][k].is_zero()) {
+                    Some(pr) => {
+                        a.swap(k, pr);
+                        negate = !negate;
+                    }
+                    None => return Ok(BigRat::zero()),
+                }
+            }
+            for i in k + 1..n {
+                for j in k + 1..n {
+                    let num = &(&a[i][j] * &a[k][k]) - &(&a[i][k] * &a[k][j]);
+                    // Exact division guaranteed by Sylvester's identity
+                    a[i][j] = num.div_rem(&prev).0;
+                }
+                a[i][k] = BigInt::zero();
+            }
+            prev = a[k][k].clone();
+        }
+        let mut det = a[n - 1][n - 1].clone();
+        if negate {
+            det = -det;
+        }
+        Ok(BigRat::new(det, scale))
+    }
+
+    /// Scale each row to integers by the lcm of its denominators.
+    /// Returns the integer rows and the product of the scale factors.
+    fn integer_rows(&self) -> (Vec<Vec<BigInt>>, BigInt) {
+        let mut scale = BigInt::one();
+        let mut a: Vec<Vec<BigInt>> = Vec::with_capacity(self.rows);
+        for r in 0..self.rows {
+            let mut lcm = BigInt::one();
+            for c in 0..self.cols {
+                let d = BigInt::from(self.get(r, c).1);
+                let g = lcm.gcd(&d);
+                lcm = (&lcm * &d).div_rem(&g).0;
+            }
+            let row = (0..self.cols)
+                .map(|c| {
+                    let q = self.get(r, c);
+                    &BigInt::from(q.0) * &lcm.div_rem(&BigInt::from(q.1)).0
+                })
+                .collect();
+            a.push(row);
+            scale = &scale * &lcm;
+        }
+        (a, scale)
+    }
+
+    /// Fraction-free row echelon form over BigInt (rectangular Bareiss).
+    /// Returns the reduced rows and the pivot column of each leading row.
+    /// Row scaling and Bareiss steps preserve the row space, so the rank,
+    /// pivot columns and nullspace match those of the original matrix.
+    fn echelon_big(&self) -> (Vec<Vec<BigInt>>, Vec<usize>) {
+        let (mut a, _) = self.integer_rows();
+        let mut pivot_cols = Vec::new();
+        let mut prev = BigInt::one();
+        let mut r = 0;
+        for c in 0..self.cols {
+            if r >= self.rows {
+                break;
+            }
+            let Some(pr) = (r..self.rows).find(|&i| !a[i][c].is_zero()) else {
+                continue;
+            };
+            a.swap(r, pr);
+            for i in r + 1..self.rows {
+                for j in c + 1..self.cols {
+                    let num = &(&a[i][j] * &a[r][c]) - &(&a[i][c] * &a[r][j]);
+                    // Exact division: every entry is a minor of the input
+                    a[i][j] = num.div_rem(&prev).0;
+                }
+                a[i][c] = BigInt::zero();
+            }
+            prev = a[r][c].clone();
+            pivot_cols.push(c);
+            r += 1;
+        }
+        (a, pivot_cols)
+    }
+
     /// Solve A x = b using fraction-free Bareiss elimination.
     /// Returns Ok(Some(x)) if unique solution exists; Ok(None) if singular; Err on misuse.
     #[allow(clippy::needless_range_loop)]
@@ -118,26 +225,28 @@ impl MatrixQ {
                 a_col.set(r, col, br);
             }
             let det_i = a_col.det_bareiss()?;
-            x[col] = div_q(det_i, det_a);
+            x[col] = checked_div_q(det_i, det_a)?;
         }
         Ok(Some(x))
     }
 
-    /// Add two matrices element-wise. Returns Err if dimensions mismatch.
+    /// Add two matrices element-wise. Returns Err if dimensions mismatch or an entry overflows.
     pub fn add(&self, other: &MatrixQ) -> Result<MatrixQ, &'static str> {
         if self.rows != other.rows || self.cols != other.cols {
             return Err("matrix dimensions must match for addition");
         }
-        let data = self.data.iter().zip(&other.data).map(|(&a, &b)| add_q(a, b)).collect();
+        let data = self.data.iter().zip(&other.data).map(|(&a, &b)| checked_add_q(a, b));
+        let data = data.collect::<Result<_, _>>()?;
         Ok(MatrixQ::new(self.rows, self.cols, data))
     }
 
-    /// Subtract two matrices element-wise. Returns Err if dimensions mismatch.
+    /// Subtract two matrices element-wise. Returns Err if dimensions mismatch or an entry overflows.
     pub fn sub(&self, other: &MatrixQ) -> Result<MatrixQ, &'static str> {
         if self.rows != other.rows || self.cols != other.cols {
             return Err("matrix dimensions must match for subtraction");
         }
-        let data = self.data.iter().zip(&other.data).map(|(&a, &b)| sub_q(a, b)).collect();
+        let data = self.data.iter().zip(&other.data).map(|(&a, &b)| checked_sub_q(a, b));
+        let data = data.collect::<Result<_, _>>()?;
         Ok(MatrixQ::new(self.rows, self.cols, data))
     }
 
@@ -154,7 +263,7 @@ impl MatrixQ {
             for j in 0..n {
                 let mut sum = Q::zero();
                 for k in 0..p {
-                    sum = add_q(sum, mul_q(self.get(i, k), other.get(k, j)));
+                    sum = checked_add_q(sum, checked_mul_q(self.get(i, k), other.get(k, j))?)?;
                 }
                 data[i * n + j] = sum;
             }
@@ -182,6 +291,12 @@ impl MatrixQ {
         MatrixQ::new(self.rows, self.cols, data)
     }
 
+    /// `scalar_mul` that fails instead of overflowing.
+    pub fn checked_scalar_mul(&self, scalar: Q) -> Result<MatrixQ, ArithError> {
+        let data = self.data.iter().map(|&x| checked_mul_q(x, scalar)).collect::<Result<_, _>>()?;
+        Ok(MatrixQ::new(self.rows, self.cols, data))
+    }
+
     /// Compute the trace (sum of diagonal elements) of a square matrix.
     /// Returns Err if the matrix is not square.
     pub fn trace(&self) -> Result<Q, &'static str> {
@@ -190,7 +305,7 @@ impl MatrixQ {
         }
         let mut sum = Q::zero();
         for i in 0..self.rows {
-            sum = add_q(sum, self.get(i, i));
+            sum = checked_add_q(sum, self.get(i, i))?;
         }
         Ok(sum)
     }
@@ -265,7 +380,7 @@ impl MatrixQ {
             // Scale pivot row to make pivot = 1
             let pivot = aug.get(col, col);
             for j in 0..2 * n {
-                let val = div_q(aug.get(col, j), pivot);
+                let val = checked_div_q(aug.get(col, j), pivot)?;
                 aug.set(col, j, val);
             }
 
@@ -279,7 +394,8 @@ impl MatrixQ {
                     continue;
                 }
                 for j in 0..2 * n {
-                    let val = sub_q(aug.get(i, j), mul_q(factor, aug.get(col, j)));
+                    let val =
+                        checked_sub_q(aug.get(i, j), checked_mul_q(factor, aug.get(col, j))?)?;
                     aug.set(i, j, val);
                 }
             }
@@ -295,186 +411,57 @@ impl MatrixQ {
         Ok(Some(MatrixQ::new(n, n, inv_data)))
     }
 
-    /// Compute the rank of the matrix using row reduction.
+    /// Compute the rank of the matrix using fraction-free row reduction.
     /// Returns the number of linearly independent rows (or columns).
     pub fn rank(&self) -> usize {
         if self.rows == 0 || self.cols == 0 {
             return 0;
         }
-
-        // Create working copy for row reduction
-        let mut a = self.clone();
-        let mut rank = 0;
-        let mut pivot_col = 0;
-
-        // Row reduction to row echelon form
-        for pivot_row in 0..self.rows {
-            if pivot_col >= self.cols {
-                break;
-            }
-
-            // Find pivot in current column
-            let mut found_pivot = false;
-            for search_row in pivot_row..self.rows {
-                if !a.get(search_row, pivot_col).is_zero() {
-                    // Swap rows if needed
-                    if search_row != pivot_row {
-                        for c in 0..self.cols {
-                            let temp = a.get(pivot_row, c);
-                            a.set(pivot_row, c, a.get(search_row, c));
-                            a.set(search_row, c, temp);
-                        }
-                    }
-                    found_pivot = true;
-                    break;
-                }
-            }
-
-            if !found_pivot {
-                // No pivot in this column, move to next column
-                pivot_col += 1;
-                continue;
-            }
-
-            // We found a pivot at (pivot_row, pivot_col)
-            rank += 1;
-            let pivot_val = a.get(pivot_row, pivot_col);
-
-            // Eliminate below the pivot
-            for row in (pivot_row + 1)..self.rows {
-                let factor = div_q(a.get(row, pivot_col), pivot_val);
-                if factor.is_zero() {
-                    continue;
-                }
-                for col in pivot_col..self.cols {
-                    let val = sub_q(a.get(row, col), mul_q(factor, a.get(pivot_row, col)));
-                    a.set(row, col, val);
-                }
-            }
-
-            pivot_col += 1;
-        }
-
-        rank
+        self.echelon_big().1.len()
     }
 
     /// Compute a basis for the nullspace (kernel) of the matrix.
     /// Returns a list of column vectors that span the nullspace.
     /// For an m×n matrix A, the nullspace is {x ∈ ℚⁿ | Ax = 0}.
+    /// Elimination runs over BigInt; basis entries that do not fit in Q
+    /// saturate, and `checked_nullspace` reports them instead.
     pub fn nullspace(&self) -> Vec<Vec<Q>> {
-        if self.rows == 0 || self.cols == 0 {
-            return vec![];
-        }
-
-        // Reduce to row echelon form and track pivot columns
-        let mut a = self.clone();
-        let mut pivot_cols = Vec::new();
-        let mut pivot_row = 0;
-
-        // Forward elimination with pivot tracking
-        for col in 0..self.cols {
-            if pivot_row >= self.rows {
-                break;
-            }
-
-            // Find pivot in current column
-            let mut found_pivot = false;
-            for search_row in pivot_row..self.rows {
-                if !a.get(search_row, col).is_zero() {
-                    // Swap rows if needed
-                    if search_row != pivot_row {
-                        for c in 0..self.cols {
-                            let temp = a.get(pivot_row, c);
-                            a.set(pivot_row, c, a.get(search_row, c));
-                            a.set(search_row, c, temp);
-                        }
-                    }
-                    found_pivot = true;
-                    break;
-                }
-            }
-
-            if !found_pivot {
-                // No pivot in this column - it's a free variable
-                continue;
-            }
-
-            // Record this pivot column
-            pivot_cols.push(col);
-            let pivot_val = a.get(pivot_row, col);
-
-            // Eliminate below the pivot
-            for row in (pivot_row + 1)..self.rows {
-                let factor = div_q(a.get(row, col), pivot_val);
-                if factor.is_zero() {
-                    continue;
-                }
-                for c in col..self.cols {
-                    let val = sub_q(a.get(row, c), mul_q(factor, a.get(pivot_row, c)));
-                    a.set(row, c, val);
-                }
-            }
-
-            pivot_row += 1;
-        }
-
-        // Back-substitution to get reduced row echelon form
-        for i in (0..pivot_cols.len()).rev() {
-            let piv_row = i;
-            let piv_col = pivot_cols[i];
-            let piv_val = a.get(piv_row, piv_col);
-
-            // Scale pivot row to make pivot = 1
-            for c in 0..self.cols {
-                let val = div_q(a.get(piv_row, c), piv_val);
-                a.set(piv_row, c, val);
-            }
+        self.nullspace_big().iter().map(|x| x.iter().map(saturate).collect()).collect()
+    }
 
-            // Eliminate above the pivot
-            for row in 0..piv_row {
-                let factor = a.get(row, piv_col);
-                if factor.is_zero() {
-                    continue;
-                }
-                for c in 0..self.cols {
-                    let val = sub_q(a.get(row, c), mul_q(factor, a.get(piv_row, c)));
-                    a.set(row, c, val);
-                }
-            }
-        }
+    /// `nullspace` that fails if a basis entry does not fit in Q.
+    pub fn checked_nullspace(&self) -> Result<Vec<Vec<Q>>, ArithError> {
+        self.nullspace_big()
+            .into_iter()
+            .map(|x| x.iter().map(|v| v.to_q().ok_or(ArithError::Overflow)).collect())
+            .collect()
+    }
 
-        // Identify free variables (non-pivot columns)
-        let mut free_vars = Vec::new();
-        for col in 0..self.cols {
-            if !pivot_cols.contains(&col) {
-                free_vars.push(col);
-            }
+    /// Nullspace basis with one vector per free column, that column set to 1.
+    fn nullspace_big(&self) -> Vec<Vec<BigRat>> {
+        if self.rows == 0 || self.cols == 0 {
+            return vec![];
         }
+        let (a, pivot_cols) = self.echelon_big();
 
-        // Construct basis vectors for nullspace
+        // One basis vector per free (non-pivot) column
         let mut basis = Vec::new();
-        for &free_col in &free_vars {
-            let mut vec = vec![Q::zero(); self.cols];
-            vec[free_col] = Q::one(); // Set free variable to 1
+        for free_col in (0..self.cols).filter(|c| !pivot_cols.contains(c)) {
+            let mut x = vec![BigRat::zero(); self.cols];
+            x[free_col] = BigRat::one();
 
             // Back-substitute to find values of pivot variables
             for (i, &piv_col) in pivot_cols.iter().enumerate().rev() {
-                let piv_row = i;
-                let mut sum = Q::zero();
-
-                // Sum contributions from columns to the right
-                #[allow(clippy::needless_range_loop)]
+                let mut sum = BigRat::zero();
                 for c in (piv_col + 1)..self.cols {
-                    sum = add_q(sum, mul_q(a.get(piv_row, c), vec[c]));
+                    if !x[c].is_zero() {
+                        sum = &sum + &(&BigRat::from_integer(a[i][c].clone()) * &x[c]);
+                    }
                 }
-
-                // Pivot variable = -sum (since pivot is normalized to 1)
-                vec[piv_col] = Q(-sum.0, sum.1);
+                x[piv_col] = -&(&sum * &BigRat::new(BigInt::one(), a[i][piv_col].clone()));
             }
-
-            basis.push(vec);
+            basis.push(x);
         }
-
         basis
     }
 
@@ -533,11 +520,11 @@ impl MatrixQ {
 
             // Eliminate below pivot
             for i in (k + 1)..n {
-                let factor = div_q(u.get(i, k), u.get(k, k));
+                let factor = checked_div_q(u.get(i, k), u.get(k, k))?;
                 l.set(i, k, factor);
 
                 for j in k..n {
-                    let val = sub_q(u.get(i, j), mul_q(factor, u.get(k, j)));
+                    let val = checked_sub_q(u.get(i, j), checked_mul_q(factor, u.get(k, j))?)?;
                     u.set(i, j, val);
                 }
             }
@@ -584,7 +571,7 @@ impl MatrixQ {
         for i in 0..n {
             let mut sum = b_perm[i];
             for j in 0..i {
-                sum = sub_q(sum, mul_q(l.get(i, j), y[j]));
+                sum = checked_sub_q(sum, checked_mul_q(l.get(i, j), y[j])?)?;
             }
             y[i] = sum; // L has 1's on diagonal
         }
@@ -595,9 +582,9 @@ impl MatrixQ {
         for i in (0..n).rev() {
             let mut sum = y[i];
             for j in (i + 1)..n {
-                sum = sub_q(sum, mul_q(u.get(i, j), x[j]));
+                sum = checked_sub_q(sum, checked_mul_q(u.get(i, j), x[j])?)?;
             }
-            x[i] = div_q(sum, u.get(i, i));
+            x[i] = checked_div_q(sum, u.get(i, i))?;
         }
 
         Ok(Some(x))
@@ -613,76 +600,25 @@ impl MatrixQ {
         if self.rows == 0 || self.cols == 0 {
             return vec![];
         }
-
-        // Reduce to row echelon form and track pivot columns
-        let mut a = self.clone();
-        let mut pivot_cols = Vec::new();
-        let mut pivot_row = 0;
-
-        // Forward elimination with pivot tracking
-        for col in 0..self.cols {
-            if pivot_row >= self.rows {
-                break;
-            }
-
-            // Find pivot in current column
-            let mut found_pivot = false;
-            for search_row in pivot_row..self.rows {
-                if !a.get(search_row, col).is_zero() {
-                    // Swap rows if needed
-                    if search_row != pivot_row {
-                        for c in 0..self.cols {
-                            let temp = a.get(pivot_row, c);
-                            a.set(pivot_row, c, a.get(search_row, c));
-                            a.set(search_row, c, temp);
-                        }
-                    }
-                    found_pivot = true;
-                    break;
-                }
-            }
-
-            if !found_pivot {
-                // No pivot in this column - skip it
-                continue;
-            }
-
-            // Record this pivot column
-            pivot_cols.push(col);
-            let pivot_val = a.get(pivot_row, col);
-
-            // Eliminate below the pivot
-            for row in (pivot_row + 1)..self.rows {
-                let factor = div_q(a.get(row, col), pivot_val);
-                if factor.is_zero() {
-                    continue;
-                }
-                for c in col..self.cols {
-                    let val = sub_q(a.get(row, c), mul_q(factor, a.get(pivot_row, c)));
-                    a.set(row, c, val);
-                }
-            }
-
-            pivot_row += 1;
-        }
-
-        // Extract the pivot columns from the ORIGINAL matrix
-        let mut basis = Vec::new();
-        for &col_idx in &pivot_cols {
-            let mut col_vec = Vec::with_capacity(self.rows);
-            for row in 0..self.rows {
-                col_vec.push(self.get(row, col_idx));
-            }
-            basis.push(col_vec);
-        }
-
-        basis
+        // The pivot columns of the ORIGINAL matrix form a basis
+        let (_, pivot_cols) = self.echelon_big();
+        pivot_cols.iter().map(|&c| (0..self.rows).map(|r| self.get(r, c)).collect()).collect()
     }
 }
 
+/// `v` as Q, clamping numerator and denominator to i64 like the saturating Q helpers.
+fn saturate(v: &BigRat) -> Q {
+    v.to_q().unwrap_or_else(|| {
+        let clamp =
+            |n: &BigInt| n.to_i64().unwrap_or(if n.is_negative() { i64::MIN } else { i64::MAX });
+        Q(clamp(v.numer()), clamp(v.denom()))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use arith::add_q;
 
     #[test]
     fn det_2x2() {
@@ -710,6 +646,21 @@ mod tests {
         assert_eq!(m.det_bareiss().unwrap(), Q(0, 1));
     }
 
+    #[test]
+    fn overflow_is_reported() {
+        let big = i64::MAX / 2;
+        // det = big^2 - 1 does not fit in i64
+        let m = MatrixQ::from_i64(2, 2, &[big, 1, 1, big]);
+        assert_eq!(m.det_bareiss(), Err("numeric overflow"));
+        assert_eq!(m.solve_bareiss(&[Q::one(), Q::one()]), Err("numeric overflow"));
+        assert_eq!(m.mul(&m), Err("numeric overflow"));
+        let expected = &(&BigInt::from(big) * &BigInt::from(big)) - &BigInt::one();
+        assert_eq!(m.det_big().unwrap(), BigRat::from(expected));
+        // Large intermediates that cancel still give the exact answer
+        let n = MatrixQ::from_i64(2, 2, &[big, big, 1, 2]);
+        assert_eq!(n.det_bareiss(), Ok(Q(big, 1)));
+    }
+
     #[test]
     fn solve_2x2_unique() {
         // [ [1,2], [3,4] ] x = [5,11] -> x = [1,2]
@@ -761,6 +712,63 @@ mod tests {
         assert_eq!(m.det_bareiss().unwrap(), Q(1, 1));
     }
 
+    #[test]
+    fn det_big_matches_small_and_rationals() {
+        let m = MatrixQ::new(
+            3,
+            3,
+            vec![Q(1, 2), Q(2, 1), Q(0, 1), Q(0, 1), Q(1, 1), Q(3, 1), Q(4, 1), Q(0, 1), Q(1, 3)],
+        );
+        assert_eq!(m.det_big().unwrap().to_q(), Some(m.det_bareiss().unwrap()));
+        // Zero in the leading pivot forces a row swap
+        let p = MatrixQ::from_i64(2, 2, &[0, 1, 1, 0]);
+        assert_eq!(p.det_big().unwrap().to_q(), Some(Q(-1, 1)));
+        let singular = MatrixQ::from_i64(2, 2, &[1, 2, 2, 4]);
+        assert!(singular.det_big().unwrap().is_zero());
+        assert!(MatrixQ::from_i64(1, 2, &[1, 2]).det_big().is_err());
+    }
+
+    #[test]
+    fn det_big_beyond_i64() {
+        // diag(10^10, 10^10) has determinant 10^20 > i64::MAX
+        let e = 10_000_000_000;
+        let m = MatrixQ::from_i64(2, 2, &[e, 0, 0, e]);
+        assert_eq!(m.det_big().unwrap().to_string(), "100000000000000000000");
+    }
+
+    #[test]
+    fn elimination_beyond_i64() {
+        // Eliminating with pivot e needs (e + 1)^2 / e, past i64 for e = 4·10^9;
+        // the determinant is -1, so the matrix has full rank
+        let e = 4_000_000_000;
+        let m = MatrixQ::from_i64(2, 2, &[e, e + 1, e + 1, e + 2]);
+        assert_eq!(m.rank(), 2);
+        assert!(m.nullspace().is_empty());
+        assert_eq!(m.columnspace().len(), 2);
+        // [e, e + 1] has kernel spanned by (-(e + 1)/e, 1)
+        let row = MatrixQ::from_i64(1, 2, &[e, e + 1]);
+        assert_eq!(row.nullspace(), vec![vec![Q::new(-(e + 1), e), Q::one()]]);
+    }
+
+    #[test]
+    fn scalar_mul_overflow_is_error() {
+        let m = MatrixQ::from_i64(1, 1, &[i64::MAX]);
+        assert_eq!(m.checked_scalar_mul(Q(2, 1)), Err(ArithError::Overflow));
+        assert_eq!(m.scalar_mul(Q(2, 1)), MatrixQ::from_i64(1, 1, &[i64::MAX]));
+    }
+
+    #[test]
+    fn nullspace_overflow_is_error() {
+        // [a, 1, 0; 0, a - 1, 1] has kernel (1/(a(a - 1)), -1/(a - 1), 1),
+        // whose first denominator is past i64
+        let a = i64::MAX;
+        let m = MatrixQ::from_i64(2, 3, &[a, 1, 0, 0, a - 1, 1]);
+        assert_eq!(m.checked_nullspace(), Err(ArithError::Overflow));
+        let null = m.nullspace();
+        assert_eq!(null.len(), 1);
+        assert_eq!(null[0][1..], [Q::new(-1, a - 1), Q::one()]);
+    }
+
     #[test]
     fn solve_zero_size() {
         let m = MatrixQ::new(0, 0, vec![]);
@@ -9,7 +9,7 @@ pub mod registry;
 pub mod rewrite;
 pub mod scheduler;
 
-use expr_core::{ExprId, Op, Payload, Store};
+use expr_core::{ExprId, Store};
 
 /// Substitute all occurrences of symbol `sym` with `with_expr` inside `id`.
 /// Does not recurse into `with_expr` (it is inserted as-is).
@@ -20,81 +20,40 @@ pub fn subst_symbol(store: &mut Store, id: ExprId, sym: &str, with_expr: ExprId)
         return cached;
     }
 
-    // Compute the substitution
-    let result = subst_symbol_impl(store, id, sym, with_expr);
+    // Compute the substitution (binder-aware and capture-avoiding; see `Store::subs`)
+    let result = store.subs(id, sym, with_expr);
 
     // Cache the result before returning
     store.cache_subst(id, sym.to_string(), with_expr, result);
     result
 }
 
-/// Internal substitution implementation (without memoization)
-fn subst_symbol_impl(store: &mut Store, id: ExprId, sym: &str, with_expr: ExprId) -> ExprId {
-    match store.get(id).op {
-        Op::Integer | Op::Rational => id,
-        Op::Symbol => match &store.get(id).payload {
-            Payload::Sym(s) if s == sym => with_expr,
-            _ => id,
-        },
-        Op::Add => {
-            let children = store.get(id).children.clone();
-            let mapped = children
-                .into_iter()
-                .map(|c| subst_symbol(store, c, sym, with_expr))
-                .collect::<Vec<_>>();
-            store.add(mapped)
-        }
-        Op::Mul => {
-            let children = store.get(id).children.clone();
-            let mapped = children
-                .into_iter()
-                .map(|c| subst_symbol(store, c, sym, with_expr))
-                .collect::<Vec<_>>();
-            store.mul(mapped)
-        }
-        Op::Pow => {
-            let (b_id, e_id) = {
-                let n = store.get(id);
-                (n.children[0], n.children[1])
-            };
-            let b = subst_symbol(store, b_id, sym, with_expr);
-            let e = subst_symbol(store, e_id, sym, with_expr);
-            store.pow(b, e)
-        }
-        Op::Function => {
-            let name = match &store.get(id).payload {
-                Payload::Func(s) => s.clone(),
-                _ => "<f>".into(),
-            };
-            let children = store.get(id).children.clone();
-            let mapped = children
-                .into_iter()
-                .map(|c| subst_symbol(store, c, sym, with_expr))
-                .collect::<Vec<_>>();
-            store.func(name, mapped)
-        }
-        Op::Piecewise => {
-            let children = store.get(id).children.clone();
-            let mapped = children
-                .into_iter()
-                .map(|c| subst_symbol(store, c, sym, with_expr))
-                .collect::<Vec<_>>();
-            // Rebuild as pairs
-            let mut pairs = Vec::new();
-            for chunk in mapped.chunks(2) {
-                if chunk.len() == 2 {
-                    pairs.push((chunk[0], chunk[1]));
-                }
-            }
-            store.piecewise(pairs)
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn subst_respects_bound_variables() {
+        let mut st = Store::new();
+        let k = st.sym("k");
+        let n = st.sym("n");
+        let one = st.int(1);
+        let ten = st.int(10);
+        let s = st.sum(k, "k", one, n);
+        // The summation index is bound: only the limits change
+        assert_eq!(subst_symbol(&mut st, s, "k", ten), s);
+        assert_eq!(subst_symbol(&mut st, s, "n", ten), st.sum(k, "k", one, ten));
+        // A derivative's variable can be renamed, or evaluated through Subs
+        let x = st.sym("x");
+        let t = st.sym("t");
+        let fx = st.func("f", vec![x]);
+        let d = st.derivative(fx, "x", 1);
+        let ft = st.func("f", vec![t]);
+        assert_eq!(subst_symbol(&mut st, d, "x", t), st.derivative(ft, "t", 1));
+        let at_one = subst_symbol(&mut st, d, "x", one);
+        assert_eq!(at_one, st.func("Subs", vec![d, x, one]));
+    }
+
     #[test]
     fn subst_in_pow_and_add() {
         let mut st = Store::new();
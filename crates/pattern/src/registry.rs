@@ -5,7 +5,6 @@
 
 use crate::ac::{match_expr, Bindings, Pat};
 use expr_core::{ExprId, Store};
-use std::collections::HashSet;
 
 pub type GuardFn = fn(store: &Store, bindings: &Bindings) -> bool;
 pub type BuildFn = fn(store: &mut Store, bindings: &Bindings) -> ExprId;
@@ -46,18 +45,7 @@ pub fn apply_best_rule_by_node_count(
 }
 
 fn count_nodes(store: &Store, id: ExprId) -> usize {
-    let mut seen: HashSet<ExprId> = HashSet::new();
-    let mut stack = vec![id];
-    while let Some(nid) = stack.pop() {
-        if !seen.insert(nid) {
-            continue;
-        }
-        let node = store.get(nid);
-        for &c in &node.children {
-            stack.push(c);
-        }
-    }
-    seen.len()
+    store.preorder(id).count()
 }
 
 /// Try rules in order; return the first rewrite result if any matches at root.
@@ -4,7 +4,6 @@
 
 use crate::rewrite::rewrite_basic;
 use expr_core::{ExprId, Store};
-use std::collections::HashSet;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct RewriteStats {
@@ -59,18 +58,7 @@ pub fn rewrite_fixpoint(store: &mut Store, id: ExprId, max_steps: usize) -> (Exp
 }
 
 fn count_nodes(store: &Store, id: ExprId) -> usize {
-    let mut seen: HashSet<ExprId> = HashSet::new();
-    let mut stack = vec![id];
-    while let Some(nid) = stack.pop() {
-        if !seen.insert(nid) {
-            continue;
-        }
-        let node = store.get(nid);
-        for &c in &node.children {
-            stack.push(c);
-        }
-    }
-    seen.len()
+    store.preorder(id).count()
 }
 
 #[cfg(test)]
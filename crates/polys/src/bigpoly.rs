@@ -0,0 +1,233 @@
+//! Univariate dense polynomials over arbitrary-precision rationals.
+//! Companion to `UniPoly` for computations whose coefficients outgrow i64,
+//! e.g. high-degree expansions like (1 + 3x)^40.
+
+use crate::UniPoly;
+use arith::{BigInt, BigRat, Q};
+use expr_core::{ExprId, Op, Payload, Store};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigUniPoly {
+    pub var: String,
+    // coeffs[k] is coefficient of x^k; no trailing zeros
+    pub coeffs: Vec<BigRat>,
+}
+
+impl BigUniPoly {
+    pub fn new<S: Into<String>>(var: S, mut coeffs: Vec<BigRat>) -> Self {
+        while coeffs.last().is_some_and(|c| c.is_zero()) {
+            coeffs.pop();
+        }
+        Self { var: var.into(), coeffs }
+    }
+    pub fn zero<S: Into<String>>(var: S) -> Self {
+        Self { var: var.into(), coeffs: vec![] }
+    }
+    pub fn constant<S: Into<String>>(var: S, c: BigRat) -> Self {
+        Self::new(var, vec![c])
+    }
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+    pub fn degree(&self) -> Option<usize> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(self.coeffs.len() - 1)
+        }
+    }
+
+    pub fn add(&self, rhs: &Self) -> Self {
+        assert_eq!(self.var, rhs.var);
+        let n = self.coeffs.len().max(rhs.coeffs.len());
+        let zero = BigRat::zero();
+        let coeffs = (0..n)
+            .map(|i| self.coeffs.get(i).unwrap_or(&zero) + rhs.coeffs.get(i).unwrap_or(&zero))
+            .collect();
+        Self::new(self.var.clone(), coeffs)
+    }
+    pub fn sub(&self, rhs: &Self) -> Self {
+        assert_eq!(self.var, rhs.var);
+        let n = self.coeffs.len().max(rhs.coeffs.len());
+        let zero = BigRat::zero();
+        let coeffs = (0..n)
+            .map(|i| self.coeffs.get(i).unwrap_or(&zero) - rhs.coeffs.get(i).unwrap_or(&zero))
+            .collect();
+        Self::new(self.var.clone(), coeffs)
+    }
+    pub fn mul(&self, rhs: &Self) -> Self {
+        assert_eq!(self.var, rhs.var);
+        if self.is_zero() || rhs.is_zero() {
+            return Self::zero(&self.var);
+        }
+        let mut coeffs = vec![BigRat::zero(); self.coeffs.len() + rhs.coeffs.len() - 1];
+        for (i, a) in self.coeffs.iter().enumerate() {
+            if a.is_zero() {
+                continue;
+            }
+            for (j, b) in rhs.coeffs.iter().enumerate() {
+                coeffs[i + j] = &coeffs[i + j] + &(a * b);
+            }
+        }
+        Self::new(self.var.clone(), coeffs)
+    }
+    pub fn pow(&self, mut e: u32) -> Self {
+        let mut base = self.clone();
+        let mut acc = Self::constant(&self.var, BigRat::one());
+        while e > 0 {
+            if e & 1 == 1 {
+                acc = acc.mul(&base);
+            }
+            e >>= 1;
+            if e > 0 {
+                base = base.mul(&base);
+            }
+        }
+        acc
+    }
+    pub fn deriv(&self) -> Self {
+        let coeffs = self
+            .coeffs
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(k, c)| c * &BigRat::from(k as i64))
+            .collect();
+        Self::new(self.var.clone(), coeffs)
+    }
+    pub fn eval(&self, x: &BigRat) -> BigRat {
+        // Horner's method
+        let mut acc = BigRat::zero();
+        for c in self.coeffs.iter().rev() {
+            acc = &(&acc * x) + c;
+        }
+        acc
+    }
+
+    /// Convert to the i64 `UniPoly` if every coefficient fits.
+    pub fn to_unipoly(&self) -> Option<UniPoly> {
+        let coeffs = self.coeffs.iter().map(|c| c.to_q()).collect::<Option<Vec<Q>>>()?;
+        Some(UniPoly::new(self.var.clone(), coeffs))
+    }
+}
+
+impl From<&UniPoly> for BigUniPoly {
+    fn from(p: &UniPoly) -> Self {
+        Self::new(p.var.clone(), p.coeffs.iter().map(|&q| BigRat::from(q)).collect())
+    }
+}
+
+/// Convert a polynomial expression in `var` into a `BigUniPoly`.
+/// Accepts sums, products and non-negative integer powers of polynomial
+/// subexpressions, so products of sums are expanded exactly.
+pub fn expr_to_bigunipoly(store: &Store, id: ExprId, var: &str) -> Option<BigUniPoly> {
+    let n = store.get(id);
+    match (&n.op, &n.payload) {
+        (Op::Integer, _) | (Op::Rational, _) => {
+            Some(BigUniPoly::constant(var, store.as_bigrat(id)?))
+        }
+        (Op::Symbol, Payload::Sym(s)) if s == var => {
+            Some(BigUniPoly::new(var, vec![BigRat::zero(), BigRat::one()]))
+        }
+        (Op::Add, _) => {
+            let mut acc = BigUniPoly::zero(var);
+            for &c in &n.children {
+                acc = acc.add(&expr_to_bigunipoly(store, c, var)?);
+            }
+            Some(acc)
+        }
+        (Op::Mul, _) => {
+            let mut acc = BigUniPoly::constant(var, BigRat::one());
+            for &c in &n.children {
+                acc = acc.mul(&expr_to_bigunipoly(store, c, var)?);
+            }
+            Some(acc)
+        }
+        (Op::Pow, _) => {
+            let k = match &store.get(n.children[1]).payload {
+                Payload::Int(k) if *k >= 0 => u32::try_from(*k).ok()?,
+                _ => return None,
+            };
+            Some(expr_to_bigunipoly(store, n.children[0], var)?.pow(k))
+        }
+        _ => None,
+    }
+}
+
+/// Build an expression from a `BigUniPoly`; coefficients are emitted with
+/// `Store::bigrat`, so small ones stay on the i64 fast path.
+pub fn bigunipoly_to_expr(store: &mut Store, p: &BigUniPoly) -> ExprId {
+    if p.is_zero() {
+        return store.int(0);
+    }
+    let x = store.sym(&p.var);
+    let mut terms: Vec<ExprId> = Vec::new();
+    for (k, c) in p.coeffs.iter().enumerate() {
+        if c.is_zero() {
+            continue;
+        }
+        let coeff = store.bigrat(c.clone());
+        let term = if k == 0 {
+            coeff
+        } else {
+            let kint = store.int(k as i64);
+            let pow = store.pow(x, kint);
+            store.mul(vec![coeff, pow])
+        };
+        terms.push(term);
+    }
+    store.add(terms)
+}
+
+/// Binomial coefficient C(n, k) as an exact big integer.
+pub fn binomial_big(n: u64, k: u64) -> BigInt {
+    if k > n {
+        return BigInt::zero();
+    }
+    let k = k.min(n - k);
+    let mut acc = BigInt::one();
+    for i in 0..k {
+        acc = &acc * &BigInt::from((n - i) as i64);
+        acc = acc.div_rem(&BigInt::from((i + 1) as i64)).0;
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_degree_40_exactly() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let three = st.int(3);
+        let three_x = st.mul(vec![three, x]);
+        let one = st.int(1);
+        let base = st.add(vec![one, three_x]);
+        let forty = st.int(40);
+        let expr = st.pow(base, forty);
+        let p = expr_to_bigunipoly(&st, expr, "x").unwrap();
+        assert_eq!(p.degree(), Some(40));
+        // Coefficient of x^20 is C(40,20) * 3^20, far beyond i64
+        let expected = &binomial_big(40, 20) * &BigInt::from(3i64).pow(20);
+        assert_eq!(p.coeffs[20], BigRat::from(expected));
+        assert!(p.to_unipoly().is_none());
+        // p(-1/3) = 0
+        let root = BigRat::from(Q(-1, 3));
+        assert!(p.eval(&root).is_zero());
+    }
+
+    #[test]
+    fn roundtrip_through_expr_and_unipoly() {
+        let mut st = Store::new();
+        let small = UniPoly::new("x", vec![Q(1, 2), Q(0, 1), Q(-3, 1)]);
+        let big = BigUniPoly::from(&small);
+        assert_eq!(big.to_unipoly(), Some(small));
+        let cube = big.pow(3);
+        let e = bigunipoly_to_expr(&mut st, &cube);
+        let back = expr_to_bigunipoly(&st, e, "x").unwrap();
+        assert_eq!(back, cube);
+        assert_eq!(cube.deriv().degree(), Some(5));
+    }
+}
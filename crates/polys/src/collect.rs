@@ -0,0 +1,394 @@
+//! Coefficients of expressions with respect to chosen variables.
+//!
+//! An expression is read as a (Laurent) polynomial in the given symbols whose
+//! coefficients are arbitrary expressions free of them. Sums, products and
+//! integer powers that involve the variables are multiplied out; coefficients
+//! are combined but not expanded (`(a + b)^2`, not `a^2 + 2ab + b^2`).
+//! Subexpressions that depend on a variable in another way (`sin(x)`,
+//! `x^(1/2)`, `(x + 1)^-1`) make `as_poly_in` fail; `coeff` and `collect` keep
+//! them as part of the coefficient, so `x sin(x)` has coefficient `sin(x)` at
+//! `x^1`. So do powers of sums too large to multiply out (more than
+//! `MAX_POWER_TERMS` terms).
+
+use arith::BigRat;
+use expr_core::{ExprId, Op, Payload, Store};
+use std::collections::{BTreeMap, HashSet};
+
+/// Exponent vector (one entry per variable) -> coefficient.
+type Terms = BTreeMap<Vec<i64>, ExprId>;
+
+/// Most terms a power of a sum is multiplied out to; larger powers are kept
+/// whole, like `sin(x)`.
+pub(crate) const MAX_POWER_TERMS: usize = 10_000;
+
+fn is_zero(store: &Store, id: ExprId) -> bool {
+    matches!(store.get(id).payload, Payload::Int(0))
+}
+
+fn add_terms(store: &mut Store, mut a: Terms, b: Terms) -> Terms {
+    for (k, c) in b {
+        let sum = match a.get(&k) {
+            Some(&old) => sum(store, old, c),
+            None => c,
+        };
+        if is_zero(store, sum) {
+            a.remove(&k);
+        } else {
+            a.insert(k, sum);
+        }
+    }
+    a
+}
+
+fn mul_terms(store: &mut Store, a: &Terms, b: &Terms) -> Terms {
+    let mut out = Terms::new();
+    for (ka, &ca) in a {
+        for (kb, &cb) in b {
+            let k: Vec<i64> = ka.iter().zip(kb).map(|(x, y)| x + y).collect();
+            let c = product(store, ca, cb);
+            out = add_terms(store, out, Terms::from([(k, c)]));
+        }
+    }
+    out
+}
+
+/// `a + b` with like terms merged (`a + a = 2a`), which `Store::add` keeps
+/// apart.
+fn sum(store: &mut Store, a: ExprId, b: ExprId) -> ExprId {
+    let mut terms: Vec<(ExprId, BigRat)> = Vec::new();
+    for t in [a, b] {
+        let ts = match store.get(t).op {
+            Op::Add => store.get(t).children.clone(),
+            _ => vec![t],
+        };
+        for t in ts {
+            let (rest, k) = split_number(store, t);
+            match terms.iter_mut().find(|(r, _)| *r == rest) {
+                Some((_, acc)) => *acc = &*acc + &k,
+                None => terms.push((rest, k)),
+            }
+        }
+    }
+    let terms: Vec<ExprId> = terms
+        .into_iter()
+        .filter(|(_, k)| !k.is_zero())
+        .map(|(rest, k)| {
+            let k = store.bigrat(k);
+            store.mul(vec![k, rest])
+        })
+        .collect();
+    store.add(terms)
+}
+
+/// `t = k * rest` with `k` the numeric factor of a product.
+fn split_number(store: &mut Store, t: ExprId) -> (ExprId, BigRat) {
+    if let Some(k) = store.as_bigrat(t) {
+        return (store.int(1), k);
+    }
+    if store.get(t).op != Op::Mul {
+        return (t, BigRat::one());
+    }
+    let children = store.get(t).children.clone();
+    let mut k = BigRat::one();
+    let mut rest = Vec::with_capacity(children.len());
+    for c in children {
+        match store.as_bigrat(c) {
+            Some(v) => k = &k * &v,
+            None => rest.push(c),
+        }
+    }
+    (store.mul(rest), k)
+}
+
+/// `a * b` with powers of a common base merged (`a * a = a^2`), which
+/// `Store::mul` keeps apart.
+fn product(store: &mut Store, a: ExprId, b: ExprId) -> ExprId {
+    let mut numbers = Vec::new();
+    let mut powers: Vec<(ExprId, BigRat)> = Vec::new();
+    for f in [a, b] {
+        let fs = match store.get(f).op {
+            Op::Mul => store.get(f).children.clone(),
+            _ => vec![f],
+        };
+        for f in fs {
+            if matches!(store.get(f).op, Op::Integer | Op::Rational) {
+                numbers.push(f);
+                continue;
+            }
+            let (base, k) = rational_power(store, f).unwrap_or((f, BigRat::one()));
+            match powers.iter_mut().find(|(b, _)| *b == base) {
+                Some((_, acc)) => *acc = &*acc + &k,
+                None => powers.push((base, k)),
+            }
+        }
+    }
+    for (base, k) in powers {
+        if !k.is_zero() {
+            let e = store.bigrat(k);
+            numbers.push(store.pow(base, e));
+        }
+    }
+    store.mul(numbers)
+}
+
+/// `(base, k)` for `id = base^k` with a rational exponent.
+fn rational_power(store: &Store, id: ExprId) -> Option<(ExprId, BigRat)> {
+    let n = store.get(id);
+    if n.op != Op::Pow {
+        return None;
+    }
+    Some((n.children[0], store.as_bigrat(n.children[1])?))
+}
+
+/// `b^e` by repeated squaring, or None if the result could have more than
+/// `MAX_POWER_TERMS` terms.
+fn pow_terms(store: &mut Store, b: &Terms, e: i64, arity: usize) -> Option<Terms> {
+    let mut bound: usize = 1;
+    for v in 0..arity {
+        let lo = b.keys().map(|k| k[v]).min().unwrap_or(0);
+        let hi = b.keys().map(|k| k[v]).max().unwrap_or(0);
+        let span = hi.checked_sub(lo)?.checked_mul(e)?.checked_add(1)?;
+        bound = bound.checked_mul(usize::try_from(span).ok()?)?;
+        if bound > MAX_POWER_TERMS {
+            return None;
+        }
+    }
+    let one = store.int(1);
+    let mut acc = Terms::from([(vec![0; arity], one)]);
+    let (mut base, mut e) = (b.clone(), e);
+    while e > 0 {
+        if e & 1 == 1 {
+            acc = mul_terms(store, &acc, &base);
+        }
+        e >>= 1;
+        if e > 0 {
+            base = mul_terms(store, &base, &base);
+        }
+    }
+    Some(acc)
+}
+
+/// Exponent vector of `sym^e` for a symbol among `vars`.
+fn var_power(store: &Store, sym: ExprId, vars: &[&str], e: i64) -> Vec<i64> {
+    let mut k = vec![0; vars.len()];
+    if let Payload::Sym(s) = &store.get(sym).payload {
+        k[vars.iter().position(|v| v == s).expect("free variable")] = e;
+    }
+    k
+}
+
+/// The terms of `id` in `vars`; with `strict`, None if some part depends on
+/// the variables other than polynomially (negative powers included).
+fn read_terms(store: &mut Store, id: ExprId, vars: &[&str], strict: bool) -> Option<Terms> {
+    let mut dependent = HashSet::new();
+    for v in vars {
+        dependent.extend(store.nodes_with_free_symbol(id, v));
+    }
+    terms_in(store, id, vars, &dependent, strict)
+}
+
+/// `read_terms` below `id`; `dependent` holds the nodes that mention a
+/// variable.
+fn terms_in(
+    store: &mut Store,
+    id: ExprId,
+    vars: &[&str],
+    dependent: &HashSet<ExprId>,
+    strict: bool,
+) -> Option<Terms> {
+    let constant = |id| Terms::from([(vec![0; vars.len()], id)]);
+    if is_zero(store, id) {
+        return Some(Terms::new());
+    }
+    if !dependent.contains(&id) {
+        return Some(constant(id));
+    }
+    let node = store.get(id);
+    let (op, children) = (node.op.clone(), node.children.clone());
+    match op {
+        Op::Symbol => {
+            let one = store.int(1);
+            return Some(Terms::from([(var_power(store, id, vars, 1), one)]));
+        }
+        Op::Add => {
+            let mut acc = Terms::new();
+            for c in children {
+                let t = terms_in(store, c, vars, dependent, strict)?;
+                acc = add_terms(store, acc, t);
+            }
+            return Some(acc);
+        }
+        Op::Mul => {
+            let one = store.int(1);
+            let mut acc = constant(one);
+            for c in children {
+                let t = terms_in(store, c, vars, dependent, strict)?;
+                acc = mul_terms(store, &acc, &t);
+            }
+            return Some(acc);
+        }
+        Op::Pow => {
+            if let Payload::Int(e) = store.get(children[1]).payload {
+                let base = children[0];
+                if store.get(base).op == Op::Symbol {
+                    if e >= 0 || !strict {
+                        let one = store.int(1);
+                        return Some(Terms::from([(var_power(store, base, vars, e), one)]));
+                    }
+                } else if e >= 0 {
+                    let b = terms_in(store, base, vars, dependent, strict)?;
+                    if let Some(p) = pow_terms(store, &b, e, vars.len()) {
+                        return Some(p);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    (!strict).then(|| constant(id))
+}
+
+/// The coefficient of `var^n` in `id` (`n` may be negative). Terms are read
+/// after multiplying out sums and powers involving `var`.
+pub fn coeff(store: &mut Store, id: ExprId, var: &str, n: i64) -> ExprId {
+    let terms = read_terms(store, id, &[var], false).expect("non-strict reading");
+    match terms.get(&vec![n]) {
+        Some(&c) => c,
+        None => store.int(0),
+    }
+}
+
+/// `id` as a sum of powers of `vars` with collected coefficients, nested in
+/// the order given: `collect(a x^2 y + b x^2 + x y + c y, [x, y])` is
+/// `x^2 (a y + b) + x y + c y`.
+pub fn collect(store: &mut Store, id: ExprId, vars: &[&str]) -> ExprId {
+    let terms = read_terms(store, id, vars, false).expect("non-strict reading");
+    nest(store, terms.into_iter().collect(), vars, 0)
+}
+
+fn nest(
+    store: &mut Store,
+    entries: Vec<(Vec<i64>, ExprId)>,
+    vars: &[&str],
+    depth: usize,
+) -> ExprId {
+    if depth == vars.len() {
+        return store.add(entries.into_iter().map(|(_, c)| c));
+    }
+    let mut groups: BTreeMap<i64, Vec<(Vec<i64>, ExprId)>> = BTreeMap::new();
+    for (k, c) in entries {
+        groups.entry(k[depth]).or_default().push((k, c));
+    }
+    let mut terms = Vec::with_capacity(groups.len());
+    for (k, group) in groups {
+        let inner = nest(store, group, vars, depth + 1);
+        let v = store.sym(vars[depth]);
+        let e = store.int(k);
+        let p = store.pow(v, e);
+        terms.push(store.mul(vec![p, inner]));
+    }
+    store.add(terms)
+}
+
+/// The polynomial in `vars` that `id` is: exponent vectors (in the order of
+/// `vars`) to nonzero coefficients free of `vars`. None if `id` depends on a
+/// variable other than through non-negative integer powers.
+pub fn as_poly_in(
+    store: &mut Store,
+    id: ExprId,
+    vars: &[&str],
+) -> Option<BTreeMap<Vec<u32>, ExprId>> {
+    let terms = read_terms(store, id, vars, true)?;
+    terms
+        .into_iter()
+        .map(|(k, c)| {
+            Some((k.into_iter().map(|e| u32::try_from(e).ok()).collect::<Option<_>>()?, c))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coefficients_and_nested_collection() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let a = st.sym("a");
+        let b = st.sym("b");
+        let one = st.int(1);
+        let two = st.int(2);
+        let half = st.rat(1, 2);
+        // (a x + 1)^2 + b x / 2 = a^2 x^2 + (2a + b/2) x + 1
+        let ax = st.mul(vec![a, x]);
+        let s = st.add(vec![ax, one]);
+        let sq = st.pow(s, two);
+        let bx = st.mul(vec![half, b, x]);
+        let e = st.add(vec![sq, bx]);
+        let a2 = st.pow(a, two);
+        assert_eq!(coeff(&mut st, e, "x", 2), a2);
+        let two_a = st.mul(vec![two, a]);
+        let hb = st.mul(vec![half, b]);
+        let c1 = st.add(vec![two_a, hb]);
+        assert_eq!(coeff(&mut st, e, "x", 1), c1);
+        assert_eq!(coeff(&mut st, e, "x", 0), one);
+        assert_eq!(coeff(&mut st, e, "x", 3), st.int(0));
+        // Other dependence on x stays in the coefficient; negative powers count
+        let sx = st.func("sin", vec![x]);
+        let m_one = st.int(-1);
+        let ix = st.pow(x, m_one);
+        let xs = st.mul(vec![x, sx]);
+        let e2 = st.add(vec![xs, ix, x]);
+        let c = coeff(&mut st, e2, "x", 1);
+        assert_eq!(c, st.add(vec![sx, one]));
+        assert_eq!(coeff(&mut st, e2, "x", -1), one);
+        assert_eq!(as_poly_in(&mut st, e2, &["x"]), None);
+        // a x^2 y + b x^2 + x y + a y = x^2 (a y + b) + x y + a y
+        let x2 = st.pow(x, two);
+        let t1 = st.mul(vec![a, x2, y]);
+        let t2 = st.mul(vec![b, x2]);
+        let t3 = st.mul(vec![x, y]);
+        let t4 = st.mul(vec![a, y]);
+        let e3 = st.add(vec![t1, t2, t3, t4]);
+        let r = collect(&mut st, e3, &["x", "y"]);
+        let ay = st.mul(vec![a, y]);
+        let inner = st.add(vec![ay, b]);
+        let outer = st.mul(vec![x2, inner]);
+        assert_eq!(r, st.add(vec![outer, t3, ay]));
+        let p = as_poly_in(&mut st, e3, &["x", "y"]).unwrap();
+        assert_eq!(p.len(), 4);
+        assert_eq!(p[&vec![2, 1]], a);
+        assert_eq!(p[&vec![0, 1]], a);
+    }
+
+    #[test]
+    fn large_powers_and_coefficients() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let one = st.int(1);
+        let s = st.add(vec![x, one]);
+        // C(70, 35) does not fit in an i64
+        let seventy = st.int(70);
+        let p = st.pow(s, seventy);
+        let c = coeff(&mut st, p, "x", 35);
+        let expected: arith::BigInt = "112186277816662845432".parse().unwrap();
+        assert_eq!(c, st.bigint(expected));
+        // Too many terms to multiply out: kept whole in the constant term
+        let big = st.int(1_000_000);
+        let p = st.pow(s, big);
+        assert_eq!(coeff(&mut st, p, "x", 1), st.int(0));
+        assert_eq!(coeff(&mut st, p, "x", 0), p);
+        assert_eq!(as_poly_in(&mut st, p, &["x"]), None);
+        // Merged coefficients are exact past i64::MAX
+        let m = st.int(i64::MAX);
+        let mx = st.mul(vec![m, x]);
+        let a = st.sym("a");
+        let ax = st.mul(vec![a, x]);
+        let e = st.add(vec![mx, ax, mx]);
+        let two_m = st.bigint(arith::BigInt::from(2 * i128::from(i64::MAX)));
+        let c = coeff(&mut st, e, "x", 1);
+        assert_eq!(c, sum(&mut st, two_m, a));
+    }
+}
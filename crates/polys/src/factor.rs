@@ -0,0 +1,781 @@
+//! Factorization of multivariate polynomials over Q.
+//!
+//! `BigMultiPoly::factor` pulls out the rational content and the monomial
+//! factor, then maps the rest to one variable by the Kronecker substitution
+//! `x_i -> t^(w_i)`, which is injective on polynomials of bounded degree. The
+//! image is split square-free (Yun) and factored over Z (Zassenhaus:
+//! Cantor–Zassenhaus modulo a small prime, Hensel lifting past the Mignotte
+//! bound, recombination of modular factors). Products of subsets of its
+//! factors are mapped back and trial-divided, smallest subsets first, so every
+//! factor found is irreducible. Univariate work is done with `BigInt`
+//! coefficients; multivariate PRS GCDs, whose coefficients grow quickly, are
+//! avoided.
+//!
+//! The image has degree up to the product of `deg + 1` over the variables,
+//! so the work is capped: images of degree above `MAX_IMAGE_DEGREE` or more
+//! than `MAX_SUBSETS` recombination candidates make `factor` give up (None)
+//! rather than return a factorization it has not proved complete.
+
+use crate::{BigMultiPoly, Monomial};
+use arith::{BigInt, BigRat};
+use std::collections::BTreeMap;
+
+/// Largest Kronecker image degree factored.
+const MAX_IMAGE_DEGREE: usize = 128;
+/// Subsets tried by one recombination.
+const MAX_SUBSETS: usize = 1024;
+
+impl BigMultiPoly {
+    /// Irreducible factorization over Q: `self = c * prod(f^k)` with each `f`
+    /// normalized (coprime integer coefficients, positive leading coefficient)
+    /// and the factors in a canonical order. The zero polynomial gives `(0, [])`.
+    /// None if the work limits (see the module docs) are reached first.
+    pub fn factor(&self) -> Option<(BigRat, Vec<(Self, usize)>)> {
+        if self.is_zero() {
+            return Some((BigRat::zero(), Vec::new()));
+        }
+        let c = self.content();
+        let mut f = self.scale(c.recip().expect("nonzero content"));
+        let mut out = Vec::new();
+        for v in f.vars() {
+            let e = f.terms.keys().map(|m| m.exponent(&v)).min().unwrap_or(0);
+            if e > 0 {
+                f.terms = std::mem::take(&mut f.terms)
+                    .into_iter()
+                    .map(|(mut m, c)| {
+                        let k = m.exponent(&v) - e;
+                        if k == 0 {
+                            m.0.remove(&v);
+                        } else {
+                            m.0.insert(v.clone(), k);
+                        }
+                        (m, c)
+                    })
+                    .collect();
+                out.push((BigMultiPoly::var(v), e));
+            }
+        }
+        if !f.is_constant() {
+            for h in irreducible_factors(&f)? {
+                match out.iter_mut().find(|(p, _)| *p == h) {
+                    Some((_, k)) => *k += 1,
+                    None => out.push((h, 1)),
+                }
+            }
+        }
+        out.sort_by_cached_key(|(p, _)| {
+            let terms: Vec<(Monomial, BigRat)> =
+                p.terms.iter().map(|(m, q)| (m.clone(), q.clone())).collect();
+            (p.total_degree(), terms)
+        });
+        Some((c, out))
+    }
+}
+
+/// Irreducible factors, with repetition, of a primitive `f` that has no
+/// monomial factor; None past the work limits.
+fn irreducible_factors(f: &BigMultiPoly) -> Option<Vec<BigMultiPoly>> {
+    if f.total_degree() == 1 {
+        return Some(vec![f.clone()]);
+    }
+    let kron = Kronecker::new(f)?;
+    // The image need not be square-free: y - x^2 becomes t^3 - t^2
+    let mut parts = Vec::new();
+    for (s, i) in square_free(&kron.apply(f)) {
+        for h in factor_over_z(&s)? {
+            parts.extend(std::iter::repeat_n(h, i));
+        }
+    }
+    let mut rest = f.clone();
+    let mut out = Vec::new();
+    let complete = recombine(&mut parts, |sel| {
+        let prod = sel.iter().fold(vec![BigInt::one()], |acc, h| zi_mul(&acc, h));
+        // Images of factors have degree below the bound
+        if prod.len() > kron.bound {
+            return false;
+        }
+        let h = kron.invert(&prod);
+        match rest.div_exact(&h).filter(|_| !h.is_constant()) {
+            Some(q) => {
+                out.push(h.normalize());
+                rest = q;
+                true
+            }
+            None => false,
+        }
+    });
+    if !complete {
+        return None;
+    }
+    if !rest.is_constant() {
+        out.push(rest.normalize());
+    }
+    Some(out)
+}
+
+/// Split factors off as products of subsets of `parts`, smallest subsets
+/// first. `accept` returns whether the product of a subset was a factor of
+/// what remains (and took it); its parts are then dropped. Stops once the
+/// remainder cannot have a proper factor, returning true, or after
+/// `MAX_SUBSETS` subsets, returning false. Equal parts must be adjacent; a
+/// subset is tried once however many ways it can be picked.
+fn recombine<T: PartialEq>(parts: &mut Vec<T>, mut accept: impl FnMut(&[&T]) -> bool) -> bool {
+    let mut tried = 0;
+    let mut s = 1;
+    'size: while 2 * s <= parts.len() {
+        let n = parts.len();
+        let mut idx: Vec<usize> = (0..s).collect();
+        loop {
+            // Of a run of equal parts, only the leading ones are picked
+            let repeat = (0..s).any(|k| {
+                let i = idx[k];
+                i > 0 && parts[i] == parts[i - 1] && (k == 0 || idx[k - 1] != i - 1)
+            });
+            if !repeat {
+                tried += 1;
+                if tried > MAX_SUBSETS {
+                    return false;
+                }
+                let sel: Vec<&T> = idx.iter().map(|&i| &parts[i]).collect();
+                if accept(&sel) {
+                    for &i in idx.iter().rev() {
+                        parts.remove(i);
+                    }
+                    continue 'size;
+                }
+            }
+            // Next s-subset in lexicographic order
+            let Some(j) = (0..s).rev().find(|&j| idx[j] < n - s + j) else {
+                break;
+            };
+            idx[j] += 1;
+            for l in j + 1..s {
+                idx[l] = idx[l - 1] + 1;
+            }
+        }
+        s += 1;
+    }
+    true
+}
+
+/// `x_i -> t^(w_i)` with `w_1 = 1` and `w_(i+1) = w_i * (deg_(x_i) f + 1)`.
+struct Kronecker {
+    /// Variable, weight and radix `deg + 1`.
+    vars: Vec<(String, usize, usize)>,
+    /// Product of the radices: images have degree below this.
+    bound: usize,
+}
+
+impl Kronecker {
+    /// None if images could have degree above `MAX_IMAGE_DEGREE`.
+    fn new(f: &BigMultiPoly) -> Option<Self> {
+        let mut w: usize = 1;
+        let mut vars = Vec::new();
+        for v in f.vars() {
+            let r = f.degree_in(&v) + 1;
+            vars.push((v, w, r));
+            w = w.checked_mul(r).filter(|&b| b <= MAX_IMAGE_DEGREE + 1)?;
+        }
+        Some(Kronecker { vars, bound: w })
+    }
+
+    /// The image of a polynomial with integer coefficients.
+    fn apply(&self, p: &BigMultiPoly) -> Zi {
+        let mut g = vec![BigInt::zero(); self.bound];
+        for (m, c) in &p.terms {
+            let k: usize = self.vars.iter().map(|(v, w, _)| m.exponent(v) * w).sum();
+            g[k] = c.numer().clone();
+        }
+        zi_trim(g)
+    }
+
+    /// The preimage of a dense polynomial in `t` of degree below `bound`.
+    fn invert(&self, g: &Zi) -> BigMultiPoly {
+        let mut p = BigMultiPoly::zero();
+        for (k, c) in g.iter().enumerate().filter(|(_, c)| !c.is_zero()) {
+            let mut m = BTreeMap::new();
+            for (v, w, r) in &self.vars {
+                let e = (k / w) % r;
+                if e > 0 {
+                    m.insert(v.clone(), e);
+                }
+            }
+            p.terms.insert(Monomial(m), BigRat::from_integer(c.clone()));
+        }
+        p
+    }
+}
+
+// ---------- Univariate factorization over Z ----------
+
+/// Dense integer polynomial, low degree first, no trailing zeros.
+type Zi = Vec<BigInt>;
+
+/// Dense polynomial modulo a prime, low degree first, no trailing zeros.
+type Zp = Vec<u64>;
+
+/// Yun's algorithm: `(s_i, i)` with `g = +-prod(s_i^i)`, the `s_i` primitive,
+/// square-free, pairwise coprime and of positive degree.
+fn square_free(g: &Zi) -> Vec<(Zi, usize)> {
+    let g = primitive_part(g);
+    let dg = zi_deriv(&g);
+    let a = zi_gcd(&g, &dg);
+    let mut b = zi_div_exact(&g, &a).expect("gcd divides");
+    let mut d = zi_sub(&zi_div_exact(&dg, &a).expect("gcd divides"), &zi_deriv(&b));
+    let mut out = Vec::new();
+    let mut i = 1;
+    while b.len() > 1 {
+        let a = zi_gcd(&b, &d);
+        b = zi_div_exact(&b, &a).expect("gcd divides");
+        d = zi_sub(&zi_div_exact(&d, &a).expect("gcd divides"), &zi_deriv(&b));
+        if a.len() > 1 {
+            out.push((a, i));
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Irreducible factors over Z of a primitive square-free `g` of positive
+/// degree, each primitive; None if recombination hits `MAX_SUBSETS`.
+fn factor_over_z(g: &Zi) -> Option<Vec<Zi>> {
+    if g.len() <= 2 {
+        return Some(vec![g.clone()]);
+    }
+    let Some((p, modular)) = modular_factors(g) else {
+        return Some(vec![g.clone()]);
+    };
+    if modular.len() == 1 {
+        return Some(vec![g.clone()]);
+    }
+    // Coefficients of lc(g) h / lc(h) for a factor h are below |lc| 2^n |g|_1
+    let lc = g.last().expect("nonzero").abs();
+    let norm = g.iter().fold(BigInt::zero(), |acc, c| &acc + &c.abs());
+    let bound = &(&lc * &norm).shl(g.len() as u64) * &BigInt::from(2i64);
+    let pb = BigInt::from(p as i64);
+    let (mut k, mut m) = (1, pb.clone());
+    while m <= bound {
+        k += 1;
+        m = &m * &pb;
+    }
+    let mut lifted = hensel_lift(g, &modular, p, k);
+    let mut rest = g.clone();
+    let mut out = Vec::new();
+    let complete = recombine(&mut lifted, |sel| {
+        let lc = rest.last().expect("nonzero").clone();
+        let prod = sel.iter().fold(vec![lc], |acc, h| zi_rem(&zi_mul(&acc, h), &m));
+        let cand = primitive_part(&symmetric(&prod, &m));
+        // Cheap necessary condition before the trial division
+        if !rest[0].is_zero() && (cand[0].is_zero() || !rest[0].div_rem(&cand[0]).1.is_zero()) {
+            return false;
+        }
+        match zi_div_exact(&rest, &cand) {
+            Some(q) => {
+                out.push(cand);
+                rest = q;
+                true
+            }
+            None => false,
+        }
+    });
+    if !complete {
+        return None;
+    }
+    if rest.len() > 1 {
+        out.push(primitive_part(&rest));
+    }
+    Some(out)
+}
+
+/// A prime not dividing lc(g) with `g mod p` square-free, and the monic
+/// irreducible factors of `g mod p`. Of the first few such primes, the one
+/// giving the fewest factors.
+fn modular_factors(g: &Zi) -> Option<(u64, Vec<Zp>)> {
+    let mut best: Option<(u64, Vec<Zp>)> = None;
+    let mut tried = 0;
+    for p in (3..10_000).step_by(2).filter(|&p| is_odd_prime(p)) {
+        let gp = zi_to_zp(g, p);
+        if gp.len() != g.len() || zp_gcd(&gp, &zp_deriv(&gp, p), p).len() > 1 {
+            continue;
+        }
+        let fs = factor_mod_p(&gp, p);
+        if best.as_ref().is_none_or(|(_, b)| fs.len() < b.len()) {
+            best = Some((p, fs));
+        }
+        tried += 1;
+        if tried == 3 || best.as_ref().is_some_and(|(_, b)| b.len() == 1) {
+            break;
+        }
+    }
+    best
+}
+
+fn is_odd_prime(n: u64) -> bool {
+    (3..).step_by(2).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d))
+}
+
+/// Monic irreducible factors of a square-free `f` modulo an odd prime `p`:
+/// distinct-degree, then equal-degree factorization.
+fn factor_mod_p(f: &Zp, p: u64) -> Vec<Zp> {
+    let mut f = zp_monic(f, p);
+    let x: Zp = vec![0, 1];
+    let mut h = zp_div_rem(&x, &f, p).1;
+    let mut out = Vec::new();
+    let mut rng = 0x9e37_79b9_7f4a_7c15u64;
+    let mut d = 1;
+    while 2 * d < f.len() {
+        // h = x^(p^d) mod f; gcd(h - x, f) is the product of the degree-d factors
+        h = zp_pow_mod(&h, p, &f, p);
+        let g = zp_gcd(&zp_sub(&h, &x, p), &f, p);
+        if g.len() > 1 {
+            f = zp_div_rem(&f, &g, p).0;
+            h = zp_div_rem(&h, &f, p).1;
+            equal_degree(&g, d, p, &mut rng, &mut out);
+        }
+        d += 1;
+    }
+    if f.len() > 1 {
+        out.push(f);
+    }
+    out
+}
+
+/// Cantor–Zassenhaus: split a monic `f` whose irreducible factors all have
+/// degree `d`.
+fn equal_degree(f: &Zp, d: usize, p: u64, rng: &mut u64, out: &mut Vec<Zp>) {
+    if f.len() == d + 1 {
+        out.push(f.clone());
+        return;
+    }
+    loop {
+        let a: Zp = zp_trim(
+            (0..f.len() - 1)
+                .map(|_| {
+                    *rng ^= *rng << 13;
+                    *rng ^= *rng >> 7;
+                    *rng ^= *rng << 17;
+                    *rng % p
+                })
+                .collect(),
+        );
+        // a^((p^d - 1) / 2) = (a^(1 + p + ... + p^(d-1)))^((p - 1) / 2)
+        let (mut t, mut acc) = (a.clone(), a);
+        for _ in 1..d {
+            t = zp_pow_mod(&t, p, f, p);
+            acc = zp_div_rem(&zp_mul(&acc, &t, p), f, p).1;
+        }
+        let b = zp_pow_mod(&acc, (p - 1) / 2, f, p);
+        let g = zp_gcd(&zp_sub(&b, &[1], p), f, p);
+        if g.len() > 1 && g.len() < f.len() {
+            equal_degree(&g, d, p, rng, out);
+            equal_degree(&zp_div_rem(f, &g, p).0, d, p, rng, out);
+            return;
+        }
+    }
+}
+
+/// Lift `g = lc(g) * prod(factors) (mod p)` to monic factors modulo `p^k`.
+fn hensel_lift(g: &Zi, factors: &[Zp], p: u64, k: u32) -> Vec<Zi> {
+    let m = BigInt::from(p as i64).pow(k);
+    let mut f = zi_rem(g, &m);
+    let mut out = Vec::new();
+    for (i, a) in factors.iter().enumerate() {
+        if i + 1 == factors.len() {
+            let inv = inv_mod(f.last().expect("nonzero"), &m);
+            out.push(zi_rem(&zi_scale(&f, &inv), &m));
+            break;
+        }
+        let lc = vec![mod_p(g.last().expect("nonzero"), p)];
+        let b = factors[i + 1..].iter().fold(lc, |acc, h| zp_mul(&acc, h, p));
+        let (a, b) = lift_pair(&f, a, &b, p, k);
+        out.push(a);
+        f = b;
+    }
+    out
+}
+
+/// Linear Hensel lifting of `f = a0 b0 (mod p)`, `a0` monic and coprime to
+/// `b0`, to `f = a b (mod p^k)` with `a` monic.
+fn lift_pair(f: &Zi, a0: &Zp, b0: &Zp, p: u64, k: u32) -> (Zi, Zi) {
+    let (s, t) = zp_ext_gcd(a0, b0, p);
+    let (mut a, mut b) = (zp_to_zi(a0), zp_to_zi(b0));
+    let pb = BigInt::from(p as i64);
+    let mut m = pb.clone();
+    for _ in 1..k {
+        // f - a b = m e; find da, db with a0 db + b0 da = e (mod p)
+        let diff = zi_sub(f, &zi_mul(&a, &b));
+        let e: Zp = zp_trim(diff.iter().map(|c| mod_p(&c.div_rem(&m).0, p)).collect());
+        let (q, da) = zp_div_rem(&zp_mul(&t, &e, p), a0, p);
+        let db = zp_add(&zp_mul(&s, &e, p), &zp_mul(&q, b0, p), p);
+        a = zi_add(&a, &zi_scale(&zp_to_zi(&da), &m));
+        b = zi_add(&b, &zi_scale(&zp_to_zi(&db), &m));
+        m = &m * &pb;
+    }
+    (a, b)
+}
+
+fn zi_trim(mut a: Zi) -> Zi {
+    while a.last().is_some_and(BigInt::is_zero) {
+        a.pop();
+    }
+    a
+}
+
+fn zi_add(a: &Zi, b: &Zi) -> Zi {
+    let zero = BigInt::zero();
+    let n = a.len().max(b.len());
+    zi_trim((0..n).map(|i| a.get(i).unwrap_or(&zero) + b.get(i).unwrap_or(&zero)).collect())
+}
+
+fn zi_sub(a: &Zi, b: &Zi) -> Zi {
+    let zero = BigInt::zero();
+    let n = a.len().max(b.len());
+    zi_trim((0..n).map(|i| a.get(i).unwrap_or(&zero) - b.get(i).unwrap_or(&zero)).collect())
+}
+
+fn zi_mul(a: &Zi, b: &Zi) -> Zi {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![BigInt::zero(); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            out[i + j] = &out[i + j] + &(x * y);
+        }
+    }
+    zi_trim(out)
+}
+
+fn zi_scale(a: &Zi, c: &BigInt) -> Zi {
+    zi_trim(a.iter().map(|x| x * c).collect())
+}
+
+/// Coefficients reduced into `[0, m)`.
+fn zi_rem(a: &Zi, m: &BigInt) -> Zi {
+    zi_trim(a.iter().map(|c| rem_euclid(c, m)).collect())
+}
+
+/// Coefficients reduced into `(-m/2, m/2]`.
+fn symmetric(a: &Zi, m: &BigInt) -> Zi {
+    let half = m.shr(1);
+    zi_trim(
+        a.iter()
+            .map(|c| {
+                let r = rem_euclid(c, m);
+                if r > half {
+                    &r - m
+                } else {
+                    r
+                }
+            })
+            .collect(),
+    )
+}
+
+fn primitive_part(a: &Zi) -> Zi {
+    let g = a.iter().fold(BigInt::zero(), |acc, c| acc.gcd(c));
+    let g = if a.last().is_some_and(BigInt::is_negative) { -g } else { g };
+    a.iter().map(|c| c.div_rem(&g).0).collect()
+}
+
+fn zi_deriv(a: &Zi) -> Zi {
+    zi_trim(a.iter().enumerate().skip(1).map(|(i, c)| c * &BigInt::from(i as i64)).collect())
+}
+
+/// Primitive GCD with a positive leading coefficient, by the primitive
+/// pseudo-remainder sequence; `a` must be nonzero.
+fn zi_gcd(a: &Zi, b: &Zi) -> Zi {
+    let (mut f, mut g) = (primitive_part(a), b.clone());
+    if !g.is_empty() {
+        g = primitive_part(&g);
+    }
+    while !g.is_empty() {
+        let r = zi_prem(&f, &g);
+        f = g;
+        g = if r.is_empty() { r } else { primitive_part(&r) };
+    }
+    f
+}
+
+/// `lc(g)^(deg f - deg g + 1) f mod g` for a nonzero `g`.
+fn zi_prem(f: &Zi, g: &Zi) -> Zi {
+    let lc = g.last().expect("nonzero divisor");
+    let mut r = f.clone();
+    while r.len() >= g.len() {
+        let t = r.last().expect("nonempty").clone();
+        let shift = r.len() - g.len();
+        for c in r.iter_mut() {
+            *c = &*c * lc;
+        }
+        for (i, gi) in g.iter().enumerate() {
+            r[shift + i] = &r[shift + i] - &(&t * gi);
+        }
+        r = zi_trim(r);
+    }
+    r
+}
+
+/// `a / b` over Z if `b` divides `a` exactly.
+fn zi_div_exact(a: &Zi, b: &Zi) -> Option<Zi> {
+    if a.is_empty() {
+        return Some(Vec::new());
+    }
+    if a.len() < b.len() {
+        return None;
+    }
+    let lb = b.last()?;
+    let mut r = a.clone();
+    let mut q = vec![BigInt::zero(); a.len() - b.len() + 1];
+    for k in (0..q.len()).rev() {
+        let (c, rem) = r[k + b.len() - 1].div_rem(lb);
+        if !rem.is_zero() {
+            return None;
+        }
+        for (i, bi) in b.iter().enumerate() {
+            r[k + i] = &r[k + i] - &(&c * bi);
+        }
+        q[k] = c;
+    }
+    r.iter().all(BigInt::is_zero).then_some(q)
+}
+
+fn rem_euclid(a: &BigInt, m: &BigInt) -> BigInt {
+    let r = a.div_rem(m).1;
+    if r.is_negative() {
+        &r + m
+    } else {
+        r
+    }
+}
+
+/// `a^-1 mod m` for `a` coprime to `m`.
+fn inv_mod(a: &BigInt, m: &BigInt) -> BigInt {
+    let (mut r0, mut r1) = (m.clone(), rem_euclid(a, m));
+    let (mut s0, mut s1) = (BigInt::zero(), BigInt::one());
+    while !r1.is_zero() {
+        let (q, r) = r0.div_rem(&r1);
+        let s = &s0 - &(&q * &s1);
+        (r0, r1) = (r1, r);
+        (s0, s1) = (s1, s);
+    }
+    rem_euclid(&s0, m)
+}
+
+fn mod_p(a: &BigInt, p: u64) -> u64 {
+    let r = rem_euclid(a, &BigInt::from(p as i64));
+    r.to_i64().expect("below p") as u64
+}
+
+fn zi_to_zp(a: &Zi, p: u64) -> Zp {
+    zp_trim(a.iter().map(|c| mod_p(c, p)).collect())
+}
+
+fn zp_to_zi(a: &Zp) -> Zi {
+    a.iter().map(|&c| BigInt::from(c as i64)).collect()
+}
+
+fn zp_trim(mut a: Zp) -> Zp {
+    while a.last() == Some(&0) {
+        a.pop();
+    }
+    a
+}
+
+fn zp_add(a: &[u64], b: &[u64], p: u64) -> Zp {
+    let n = a.len().max(b.len());
+    let at = |v: &[u64], i: usize| v.get(i).copied().unwrap_or(0);
+    zp_trim((0..n).map(|i| (at(a, i) + at(b, i)) % p).collect())
+}
+
+fn zp_sub(a: &[u64], b: &[u64], p: u64) -> Zp {
+    let n = a.len().max(b.len());
+    let at = |v: &[u64], i: usize| v.get(i).copied().unwrap_or(0);
+    zp_trim((0..n).map(|i| (at(a, i) + p - at(b, i)) % p).collect())
+}
+
+fn zp_mul(a: &[u64], b: &[u64], p: u64) -> Zp {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![0; a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            out[i + j] = (out[i + j] + x * y) % p;
+        }
+    }
+    zp_trim(out)
+}
+
+fn zp_deriv(a: &[u64], p: u64) -> Zp {
+    zp_trim(a.iter().enumerate().skip(1).map(|(i, &c)| (i as u64 % p) * c % p).collect())
+}
+
+fn pow_mod(mut b: u64, mut e: u64, p: u64) -> u64 {
+    let mut r = 1;
+    b %= p;
+    while e > 0 {
+        if e & 1 == 1 {
+            r = r * b % p;
+        }
+        b = b * b % p;
+        e >>= 1;
+    }
+    r
+}
+
+fn zp_monic(a: &[u64], p: u64) -> Zp {
+    let inv = pow_mod(*a.last().expect("nonzero"), p - 2, p);
+    a.iter().map(|&c| c * inv % p).collect()
+}
+
+/// Division with remainder by a nonzero `b`.
+fn zp_div_rem(a: &[u64], b: &[u64], p: u64) -> (Zp, Zp) {
+    let inv = pow_mod(*b.last().expect("nonzero divisor"), p - 2, p);
+    let mut r = a.to_vec();
+    let mut q = vec![0; a.len().saturating_sub(b.len()) + 1];
+    while r.len() >= b.len() {
+        let shift = r.len() - b.len();
+        let c = r.last().expect("nonempty") * inv % p;
+        for (i, &bi) in b.iter().enumerate() {
+            r[shift + i] = (r[shift + i] + p - c * bi % p) % p;
+        }
+        q[shift] = c;
+        r = zp_trim(r);
+    }
+    (zp_trim(q), r)
+}
+
+/// Monic GCD (empty for gcd(0, 0)).
+fn zp_gcd(a: &[u64], b: &[u64], p: u64) -> Zp {
+    let (mut a, mut b) = (a.to_vec(), b.to_vec());
+    while !b.is_empty() {
+        let r = zp_div_rem(&a, &b, p).1;
+        a = b;
+        b = r;
+    }
+    if a.is_empty() {
+        a
+    } else {
+        zp_monic(&a, p)
+    }
+}
+
+/// `(s, t)` with `s a + t b = 1` for coprime `a`, `b`.
+fn zp_ext_gcd(a: &[u64], b: &[u64], p: u64) -> (Zp, Zp) {
+    let (mut r0, mut r1) = (a.to_vec(), b.to_vec());
+    let (mut s0, mut s1): (Zp, Zp) = (vec![1], Vec::new());
+    let (mut t0, mut t1): (Zp, Zp) = (Vec::new(), vec![1]);
+    while !r1.is_empty() {
+        let (q, r) = zp_div_rem(&r0, &r1, p);
+        let s = zp_sub(&s0, &zp_mul(&q, &s1, p), p);
+        let t = zp_sub(&t0, &zp_mul(&q, &t1, p), p);
+        (r0, r1) = (r1, r);
+        (s0, s1) = (s1, s);
+        (t0, t1) = (t1, t);
+    }
+    // r0 is a nonzero constant
+    let inv = vec![pow_mod(r0[0], p - 2, p)];
+    (zp_mul(&s0, &inv, p), zp_mul(&t0, &inv, p))
+}
+
+/// `b^e mod m`.
+fn zp_pow_mod(b: &[u64], mut e: u64, m: &[u64], p: u64) -> Zp {
+    let mut r: Zp = vec![1];
+    let mut b = zp_div_rem(b, m, p).1;
+    while e > 0 {
+        if e & 1 == 1 {
+            r = zp_div_rem(&zp_mul(&r, &b, p), m, p).1;
+        }
+        b = zp_div_rem(&zp_mul(&b, &b, p), m, p).1;
+        e >>= 1;
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int_poly(coeffs: &[i64]) -> Zi {
+        coeffs.iter().map(|&c| BigInt::from(c)).collect()
+    }
+
+    /// Sum of `c * x^i * y^j` over `(c, i, j)`.
+    fn xy(terms: &[(i64, usize, usize)]) -> BigMultiPoly {
+        let mut p = BigMultiPoly::zero();
+        for &(c, i, j) in terms {
+            let mut m = BTreeMap::new();
+            for (v, e) in [("x", i), ("y", j)] {
+                if e > 0 {
+                    m.insert(v.to_string(), e);
+                }
+            }
+            p = p.add(&BigMultiPoly { terms: [(Monomial(m), BigRat::from(c))].into() });
+        }
+        p
+    }
+
+    #[test]
+    fn multivariate_factorization() {
+        // x^4 - y^4 = (x - y)(x + y)(x^2 + y^2)
+        let (c, fs) = xy(&[(1, 4, 0), (-1, 0, 4)]).factor().unwrap();
+        assert_eq!(c, BigRat::one());
+        let expected = [
+            (xy(&[(1, 1, 0), (-1, 0, 1)]), 1),
+            (xy(&[(1, 1, 0), (1, 0, 1)]), 1),
+            (xy(&[(1, 2, 0), (1, 0, 2)]), 1),
+        ];
+        assert_eq!(fs.len(), 3);
+        assert!(expected.iter().all(|f| fs.contains(f)));
+        // -6 y^2 (x^2 + y^2 + 1)^2 (x^2 y^2 - 2) (x^3 - y^2): content, a factor
+        // free of x, a repeated factor and factors of degree >= 2 in each variable
+        let a = xy(&[(1, 2, 0), (1, 0, 2), (1, 0, 0)]);
+        let b = xy(&[(1, 2, 2), (-2, 0, 0)]);
+        let d = xy(&[(1, 3, 0), (-1, 0, 2)]);
+        let y = xy(&[(1, 0, 1)]);
+        let p = a.pow(2).mul(&b).mul(&d).mul(&y.pow(2)).scale(-6);
+        let (c, fs) = p.factor().unwrap();
+        assert_eq!(c, BigRat::from(-6));
+        assert_eq!(fs.len(), 4);
+        for f in [(y, 2), (a, 2), (b, 1), (d, 1)] {
+            assert!(fs.contains(&f), "missing {f:?}");
+        }
+        // The Kronecker image t^2 (t^3 - 1)(t^12 + 1) is not square-free
+        let f = xy(&[(1, 0, 1), (-1, 2, 0)]).mul(&xy(&[(1, 2, 2), (1, 0, 0)]));
+        assert_eq!(f.factor().unwrap().1.len(), 2);
+        // (x - 1)(x + 1)^2 (x^2 - y)^2: the image repeats t, t - 1 and t + 1
+        let f = xy(&[(1, 1, 0), (-1, 0, 0)])
+            .mul(&xy(&[(1, 1, 0), (1, 0, 0)]).pow(2))
+            .mul(&xy(&[(1, 2, 0), (-1, 0, 1)]).pow(2));
+        assert_eq!(f.factor().unwrap().1.len(), 3);
+        assert_eq!(BigMultiPoly::zero().factor(), Some((BigRat::zero(), Vec::new())));
+    }
+
+    #[test]
+    fn gives_up_past_the_work_limits() {
+        // (x^8 y^8 + 1)(x^8 - y^8 + 2): an image of degree 17^2 - 1 is not
+        // attempted, and no unsplit part is passed off as irreducible
+        let f = xy(&[(1, 8, 8), (1, 0, 0)]).mul(&xy(&[(1, 8, 0), (-1, 0, 8), (2, 0, 0)]));
+        assert_eq!(f.factor(), None);
+        assert!(Kronecker::new(&f).is_none());
+        // Within the limits: (x y + 1)(x^2 y^2 - x y + 1)(x^3 - y^3 + 2)
+        let f = xy(&[(1, 3, 3), (1, 0, 0)]).mul(&xy(&[(1, 3, 0), (-1, 0, 3), (2, 0, 0)]));
+        assert_eq!(f.factor().unwrap().1.len(), 3);
+    }
+
+    #[test]
+    fn univariate_factors_without_rational_roots() {
+        // x^4 + 4 = (x^2 - 2x + 2)(x^2 + 2x + 2)
+        let mut fs = factor_over_z(&int_poly(&[4, 0, 0, 0, 1])).unwrap();
+        fs.sort();
+        assert_eq!(fs, vec![int_poly(&[2, -2, 1]), int_poly(&[2, 2, 1])]);
+        // x^4 + 1 is irreducible over Q but splits modulo every prime
+        assert_eq!(factor_over_z(&int_poly(&[1, 0, 0, 0, 1])).unwrap().len(), 1);
+        // (2x + 3)(3x^2 - 5)(x^3 + x + 1)
+        let g =
+            zi_mul(&zi_mul(&int_poly(&[3, 2]), &int_poly(&[-5, 0, 3])), &int_poly(&[1, 1, 0, 1]));
+        let mut fs = factor_over_z(&g).unwrap();
+        fs.sort_by_key(Vec::len);
+        assert_eq!(fs, vec![int_poly(&[3, 2]), int_poly(&[-5, 0, 3]), int_poly(&[1, 1, 0, 1])]);
+    }
+}
@@ -2,16 +2,39 @@
 //! - Univariate dense polynomials over Q (i64 rationals)
 //! - Division with remainder, Euclidean GCD, square-free decomposition
 //! - Resultants and discriminants
-//! - Multivariate sparse polynomials over Q
+//! - Multivariate sparse polynomials over Q, with exact division and GCD (`BigMultiPoly`)
+//! - Irreducible factorization over Q of multivariate polynomials (`BigMultiPoly::factor`)
+//! - Rational functions of expressions: `together`, `cancel`, `apart`, `factor`
 //! - Conversions: Expr ⟷ Poly (for sums of monomials in single or multiple symbols)
-
-use arith::{add_q, div_q, mul_q, sub_q, Q};
+//! - `BigUniPoly`, `BigMultiPoly`: arbitrary-precision coefficients when i64 rationals overflow
+
+mod bigpoly;
+mod collect;
+mod factor;
+mod multi;
+mod ratfunc;
+
+pub use bigpoly::{bigunipoly_to_expr, binomial_big, expr_to_bigunipoly, BigUniPoly};
+pub use collect::{as_poly_in, coeff, collect};
+pub use multi::{
+    bigmultipoly_to_expr, expand_poly, expand_poly_with, expr_to_bigmultipoly, expr_to_multipoly,
+    multipoly_to_expr, BigMultiPoly,
+};
+pub use ratfunc::{apart, cancel, factor, together};
+
+use arith::{
+    add_q, checked_add_q, checked_div_q, checked_mul_q, checked_sub_q, div_q, gcd_i64, mul_q,
+    sub_q, ArithError, Q,
+};
 use expr_core::{ExprId, Op, Payload, Store};
 use matrix::MatrixQ;
 use std::collections::BTreeMap;
 
 // ---------- Univariate dense polynomial over Q ----------
 
+/// Dense univariate polynomial with `Q` coefficients. Like `Q` it is
+/// i64-only: operations saturate, `checked_*` methods report overflow, and
+/// `BigUniPoly` carries coefficients past i64.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct UniPoly {
     pub var: String,
@@ -58,6 +81,15 @@ impl UniPoly {
         Self::new(self.var.clone(), out)
     }
 
+    /// `deriv` that fails instead of overflowing.
+    pub fn checked_deriv(&self) -> Result<Self, ArithError> {
+        let mut out: Vec<Q> = Vec::with_capacity(self.coeffs.len().saturating_sub(1));
+        for (k, &c) in self.coeffs.iter().enumerate().skip(1) {
+            out.push(checked_mul_q(c, Q(k as i64, 1))?);
+        }
+        Ok(Self::new(self.var.clone(), out))
+    }
+
     pub fn eval_q(&self, x: Q) -> Q {
         // Horner's method
         let mut acc = Q::zero();
@@ -67,6 +99,15 @@ impl UniPoly {
         acc
     }
 
+    /// `eval_q` that fails instead of overflowing.
+    pub fn checked_eval_q(&self, x: Q) -> Result<Q, ArithError> {
+        let mut acc = Q::zero();
+        for &c in self.coeffs.iter().rev() {
+            acc = checked_add_q(checked_mul_q(acc, x)?, c)?;
+        }
+        Ok(acc)
+    }
+
     pub fn add(&self, rhs: &Self) -> Self {
         assert_eq!(self.var, rhs.var);
         let mut coeffs = Vec::with_capacity(self.coeffs.len().max(rhs.coeffs.len()));
@@ -77,6 +118,10 @@ impl UniPoly {
         }
         Self::new(self.var.clone(), coeffs)
     }
+    /// `add` that fails instead of overflowing.
+    pub fn checked_add(&self, rhs: &Self) -> Result<Self, ArithError> {
+        self.checked_zip(rhs, checked_add_q)
+    }
     pub fn sub(&self, rhs: &Self) -> Self {
         assert_eq!(self.var, rhs.var);
         let mut coeffs = Vec::with_capacity(self.coeffs.len().max(rhs.coeffs.len()));
@@ -87,6 +132,25 @@ impl UniPoly {
         }
         Self::new(self.var.clone(), coeffs)
     }
+    /// `sub` that fails instead of overflowing.
+    pub fn checked_sub(&self, rhs: &Self) -> Result<Self, ArithError> {
+        self.checked_zip(rhs, checked_sub_q)
+    }
+    /// Coefficient-wise `op`, padding the shorter operand with zeros.
+    fn checked_zip(
+        &self,
+        rhs: &Self,
+        op: fn(Q, Q) -> Result<Q, ArithError>,
+    ) -> Result<Self, ArithError> {
+        assert_eq!(self.var, rhs.var);
+        let mut coeffs = Vec::with_capacity(self.coeffs.len().max(rhs.coeffs.len()));
+        for i in 0..self.coeffs.len().max(rhs.coeffs.len()) {
+            let a = self.coeffs.get(i).copied().unwrap_or(Q::zero());
+            let b = rhs.coeffs.get(i).copied().unwrap_or(Q::zero());
+            coeffs.push(op(a, b)?);
+        }
+        Ok(Self::new(self.var.clone(), coeffs))
+    }
     pub fn mul(&self, rhs: &Self) -> Self {
         assert_eq!(self.var, rhs.var);
         if self.is_zero() || rhs.is_zero() {
@@ -106,6 +170,20 @@ impl UniPoly {
         }
         Self::new(self.var.clone(), coeffs)
     }
+    /// `mul` that fails instead of overflowing.
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self, ArithError> {
+        assert_eq!(self.var, rhs.var);
+        if self.is_zero() || rhs.is_zero() {
+            return Ok(Self::zero(&self.var));
+        }
+        let mut coeffs = vec![Q::zero(); self.coeffs.len() + rhs.coeffs.len() - 1];
+        for (i, &a) in self.coeffs.iter().enumerate() {
+            for (j, &b) in rhs.coeffs.iter().enumerate() {
+                coeffs[i + j] = checked_add_q(coeffs[i + j], checked_mul_q(a, b)?)?;
+            }
+        }
+        Ok(Self::new(self.var.clone(), coeffs))
+    }
     pub fn monic(&self) -> Self {
         if self.is_zero() {
             return self.clone();
@@ -115,8 +193,19 @@ impl UniPoly {
         let coeffs = self.coeffs.iter().map(|&c| mul_q(c, inv)).collect();
         Self::new(self.var.clone(), coeffs)
     }
+    /// `monic` that fails instead of overflowing.
+    pub fn checked_monic(&self) -> Result<Self, ArithError> {
+        if self.is_zero() {
+            return Ok(self.clone());
+        }
+        let inv = checked_div_q(Q::one(), self.leading_coeff())?;
+        let coeffs =
+            self.coeffs.iter().map(|&c| checked_mul_q(c, inv)).collect::<Result<_, _>>()?;
+        Ok(Self::new(self.var.clone(), coeffs))
+    }
 
     // Division with remainder: self = q*div + r, deg r < deg div
+    // Fails with "numeric overflow" if a coefficient outgrows i64.
     pub fn div_rem(&self, div: &Self) -> Result<(Self, Self), &'static str> {
         assert_eq!(self.var, div.var);
         if div.is_zero() {
@@ -134,18 +223,17 @@ impl UniPoly {
                 break;
             }
             let shift = rdeg - ddeg;
-            let coeff = div_q(r.leading_coeff(), dlc);
+            let coeff = checked_div_q(r.leading_coeff(), dlc)?;
             // q += coeff * x^shift
             if q.coeffs.len() <= shift {
                 q.coeffs.resize(shift + 1, Q::zero());
             }
-            q.coeffs[shift] = add_q(q.coeffs[shift], coeff);
+            q.coeffs[shift] = checked_add_q(q.coeffs[shift], coeff)?;
             // r -= (coeff * x^shift) * div
-            let mut to_sub = vec![Q::zero(); shift + div.coeffs.len()];
             for (i, &c) in div.coeffs.iter().enumerate() {
-                to_sub[shift + i] = mul_q(coeff, c);
+                r.coeffs[shift + i] = checked_sub_q(r.coeffs[shift + i], checked_mul_q(coeff, c)?)?;
             }
-            r = r.sub(&UniPoly::new(self.var.clone(), to_sub));
+            trim_trailing_zeros(&mut r.coeffs);
             if r.is_zero() {
                 break;
             }
@@ -153,15 +241,23 @@ impl UniPoly {
         Ok((q, r))
     }
 
-    pub fn gcd(mut a: Self, mut b: Self) -> Self {
+    /// Monic gcd. If a remainder outgrows i64 the result is 1, i.e. no common
+    /// factor is reported; `checked_gcd` surfaces the overflow instead.
+    pub fn gcd(a: Self, b: Self) -> Self {
+        let var = a.var.clone();
+        Self::checked_gcd(a, b).unwrap_or_else(|_| Self::new(var, vec![Q::one()]))
+    }
+
+    /// `gcd` that fails instead of overflowing.
+    pub fn checked_gcd(mut a: Self, mut b: Self) -> Result<Self, ArithError> {
         assert_eq!(a.var, b.var);
-        // Euclidean algorithm
+        // Euclidean algorithm; with a non-zero divisor only overflow can fail
         while !b.is_zero() {
-            let r = a.div_rem(&b).expect("non-zero divisor").1;
+            let r = a.div_rem(&b).map_err(|_| ArithError::Overflow)?.1;
             a = b;
             b = r;
         }
-        a.monic()
+        a.checked_monic()
     }
 
     /// Square-free decomposition using a simplified approach.
@@ -170,31 +266,41 @@ impl UniPoly {
     /// Returns a list with the square-free part.
     ///
     /// For a polynomial with repeated roots, extracts square-free factors.
+    ///
+    /// If a coefficient outgrows i64 the polynomial is returned whole (as its
+    /// monic form when that fits); `checked_square_free_decomposition` reports
+    /// the overflow instead.
     pub fn square_free_decomposition(&self) -> Vec<(Self, usize)> {
+        self.checked_square_free_decomposition()
+            .unwrap_or_else(|_| vec![(self.checked_monic().unwrap_or_else(|_| self.clone()), 1)])
+    }
+
+    /// `square_free_decomposition` that fails instead of overflowing.
+    pub fn checked_square_free_decomposition(&self) -> Result<Vec<(Self, usize)>, ArithError> {
         if self.is_zero() {
-            return vec![];
+            return Ok(vec![]);
         }
 
-        let p = self.monic();
-        let dp = p.deriv();
+        let p = self.checked_monic()?;
+        let dp = p.checked_deriv()?;
 
         if dp.is_zero() {
-            return vec![(p, 1)];
+            return Ok(vec![(p, 1)]);
         }
 
         // Compute gcd(p, p')
-        let g = Self::gcd(p.clone(), dp.clone());
+        let g = Self::checked_gcd(p.clone(), dp)?;
 
         // If gcd = 1, p is already square-free
         if g.degree() == Some(0) || g.is_zero() {
-            return vec![(p, 1)];
+            return Ok(vec![(p, 1)]);
         }
 
         // Simple approach: return square-free part
         // p / gcd(p, p') is square-free
-        let (square_free_part, _) = p.div_rem(&g).expect("gcd divides p");
+        let (square_free_part, _) = p.div_rem(&g).map_err(|_| ArithError::Overflow)?;
 
-        vec![(square_free_part.monic(), 1)]
+        Ok(vec![(square_free_part.checked_monic()?, 1)])
     }
 
     /// Compute the resultant of two polynomials using the Sylvester matrix determinant.
@@ -203,7 +309,7 @@ impl UniPoly {
     /// For polynomials f of degree n and g of degree m, constructs an (m+n) × (m+n)
     /// Sylvester matrix and returns its determinant.
     ///
-    /// Returns None if both polynomials are zero.
+    /// Returns None if both polynomials are zero or the determinant overflows i64.
     pub fn resultant(f: &Self, g: &Self) -> Option<Q> {
         assert_eq!(f.var, g.var, "polynomials must have the same variable");
 
@@ -231,7 +337,7 @@ impl UniPoly {
             let f0 = f.coeffs[0];
             let mut result = Q::one();
             for _ in 0..m {
-                result = mul_q(result, f0);
+                result = checked_mul_q(result, f0).ok()?;
             }
             return Some(result);
         }
@@ -240,7 +346,7 @@ impl UniPoly {
             let g0 = g.coeffs[0];
             let mut result = Q::one();
             for _ in 0..n {
-                result = mul_q(result, g0);
+                result = checked_mul_q(result, g0).ok()?;
             }
             return Some(result);
         }
@@ -274,7 +380,7 @@ impl UniPoly {
         }
 
         let sylvester = MatrixQ::new(size, size, entries);
-        Some(sylvester.det_bareiss().expect("square matrix"))
+        sylvester.det_bareiss().ok()
     }
 
     /// Compute the discriminant of a polynomial.
@@ -283,7 +389,7 @@ impl UniPoly {
     /// For a polynomial f of degree n with leading coefficient a_n:
     ///   disc(f) = (-1)^(n(n-1)/2) / a_n * resultant(f, f')
     ///
-    /// Returns None if the polynomial is zero or constant.
+    /// Returns None if the polynomial is zero or constant, or on overflow.
     pub fn discriminant(&self) -> Option<Q> {
         if self.is_zero() {
             return None;
@@ -294,7 +400,7 @@ impl UniPoly {
             return None; // Constant polynomial has no discriminant
         }
 
-        let fp = self.deriv();
+        let fp = self.checked_deriv().ok()?;
         let res = Self::resultant(self, &fp)?;
 
         let lc = self.leading_coeff();
@@ -306,20 +412,30 @@ impl UniPoly {
         let sign_power = (n * (n - 1)) / 2;
         let sign = if sign_power % 2 == 0 { Q::one() } else { Q(-1, 1) };
 
-        let disc = div_q(mul_q(sign, res), lc);
-        Some(disc)
+        checked_div_q(checked_mul_q(sign, res).ok()?, lc).ok()
     }
 
     /// Factor a polynomial over Q into irreducible factors.
     /// Returns a list of (factor, multiplicity) pairs.
     /// Uses rational root search and recursive factoring.
+    ///
+    /// If a coefficient outgrows i64 the polynomial is returned unfactored;
+    /// `checked_factor` reports the overflow instead.
     pub fn factor(&self) -> Vec<(Self, usize)> {
+        match self.checked_factor() {
+            Ok(factors) => factors,
+            Err(_) => vec![(self.clone(), 1)],
+        }
+    }
+
+    /// `factor` that fails instead of overflowing.
+    pub fn checked_factor(&self) -> Result<Vec<(Self, usize)>, ArithError> {
         if self.is_zero() {
-            return vec![];
+            return Ok(vec![]);
         }
 
         // Start with square-free decomposition
-        let square_free_factors = self.square_free_decomposition();
+        let square_free_factors = self.checked_square_free_decomposition()?;
         let mut result = Vec::new();
 
         for (sf_poly, multiplicity) in square_free_factors {
@@ -330,7 +446,7 @@ impl UniPoly {
             }
         }
 
-        result
+        Ok(result)
     }
 }
 
@@ -384,7 +500,7 @@ fn find_rational_root(p: &UniPoly) -> Option<Q> {
     }
 
     // Clear denominators to work with integer coefficients
-    let (int_coeffs, _lcm) = clear_denominators(p);
+    let (int_coeffs, _lcm) = clear_denominators(p)?;
 
     // Leading coefficient and constant term
     let lc = *int_coeffs.last()?;
@@ -406,7 +522,8 @@ fn find_rational_root(p: &UniPoly) -> Option<Q> {
         for &pn in &ct_divisors {
             for &sign in &[1i64, -1i64] {
                 let candidate = Q(sign * pn, q);
-                if p.eval_q(candidate).is_zero() {
+                // A candidate whose evaluation overflows is skipped
+                if p.checked_eval_q(candidate) == Ok(Q::zero()) {
                     return Some(candidate);
                 }
             }
@@ -417,19 +534,19 @@ fn find_rational_root(p: &UniPoly) -> Option<Q> {
 }
 
 /// Clear denominators from polynomial coefficients, returning integer coefficients and LCM.
-fn clear_denominators(p: &UniPoly) -> (Vec<i64>, i64) {
+/// Returns None if either overflows i64.
+fn clear_denominators(p: &UniPoly) -> Option<(Vec<i64>, i64)> {
     let mut lcm = 1i64;
     for &Q(_, d) in &p.coeffs {
-        let dd = d.abs().max(1);
-        lcm = lcm_i64(lcm.abs().max(1), dd);
+        lcm = lcm_i64(lcm, d.max(1))?;
     }
 
     let mut ints = Vec::with_capacity(p.coeffs.len());
     for &Q(n, d) in &p.coeffs {
-        ints.push(n * (lcm / d));
+        ints.push(n.checked_mul(lcm / d)?);
     }
 
-    (ints, lcm)
+    Some((ints, lcm))
 }
 
 /// Find all positive divisors of n (including 1 and n).
@@ -455,22 +572,9 @@ fn divisors(mut n: i64) -> Vec<i64> {
     ds
 }
 
-fn gcd_i64(mut a: i64, mut b: i64) -> i64 {
-    a = a.abs();
-    b = b.abs();
-    while b != 0 {
-        let t = a % b;
-        a = b;
-        b = t;
-    }
-    a
-}
-
-fn lcm_i64(a: i64, b: i64) -> i64 {
-    if a == 0 || b == 0 {
-        return 0;
-    }
-    (a.abs() / gcd_i64(a, b)) * b.abs()
+/// lcm of two positive integers (None on overflow).
+fn lcm_i64(a: i64, b: i64) -> Option<i64> {
+    (a / gcd_i64(a, b)).checked_mul(b)
 }
 
 fn trim_trailing_zeros(v: &mut Vec<Q>) {
@@ -529,7 +633,7 @@ pub fn expr_to_unipoly(store: &Store, id: ExprId, var: &str) -> Option<UniPoly>
                 let mut k: usize = 0;
                 for &f in &store.get(id).children {
                     if let Some(q) = as_rat(store, f) {
-                        coeff = mul_q(coeff, q);
+                        coeff = checked_mul_q(coeff, q).ok()?;
                         continue;
                     }
                     if as_symbol(store, f, var) {
@@ -570,7 +674,7 @@ pub fn expr_to_unipoly(store: &Store, id: ExprId, var: &str) -> Option<UniPoly>
             let mut acc = UniPoly::zero(var.to_string());
             for &t in &store.get(id).children {
                 let mono = expr_to_unipoly(store, t, var)?;
-                acc = acc.add(&mono);
+                acc = acc.checked_add(&mono).ok()?;
             }
             Some(acc)
         }
@@ -613,51 +717,12 @@ pub fn partial_fractions_simple(num: &UniPoly, den: &UniPoly) -> Option<(UniPoly
     let (q, r) = num.div_rem(den).ok()?;
 
     // Factor denominator into distinct rational linear factors using Rational Root Theorem.
-    fn lcm_i64(a: i64, b: i64) -> i64 {
-        if a == 0 || b == 0 {
-            return 0;
-        }
-        (a / gcd_i64(a.abs(), b.abs())) * b
-    }
-    fn clear_denominators(p: &UniPoly) -> (Vec<i64>, i64) {
-        let mut l = 1i64;
-        for &Q(_, d) in &p.coeffs {
-            let dd = d.abs().max(1);
-            l = if l == 0 { dd } else { lcm_i64(l, dd) };
-        }
-        let mut ints = Vec::with_capacity(p.coeffs.len());
-        for &Q(n, d) in &p.coeffs {
-            ints.push(n * (if d == 0 { 0 } else { l / d }));
-        }
-        (ints, l)
-    }
-    fn divisors(mut n: i64) -> Vec<i64> {
-        if n < 0 {
-            n = -n;
-        }
-        if n == 0 {
-            // convention: only 0; callers handle specially
-            return vec![0];
-        }
-        let mut ds = Vec::new();
-        let mut i = 1;
-        while (i as i128) * (i as i128) <= (n as i128) {
-            if n % i == 0 {
-                ds.push(i);
-                if i != n / i {
-                    ds.push(n / i);
-                }
-            }
-            i += 1;
-        }
-        ds
-    }
     fn deflate_by_root(p: &UniPoly, r: Q) -> Option<UniPoly> {
         let var = p.var.clone();
         let mut new_coeffs: Vec<Q> = Vec::with_capacity(p.coeffs.len().saturating_sub(1));
         let mut acc = Q::zero();
         for &c in p.coeffs.iter().rev() {
-            acc = add_q(mul_q(acc, r), c);
+            acc = checked_add_q(checked_mul_q(acc, r).ok()?, c).ok()?;
             new_coeffs.push(acc);
         }
         if !acc.is_zero() {
@@ -681,12 +746,12 @@ pub fn partial_fractions_simple(num: &UniPoly, den: &UniPoly) -> Option<(UniPoly
                 if a.is_zero() {
                     return None;
                 }
-                let root = div_q(Q(-b.0, b.1), a);
+                let root = checked_sub_q(Q::zero(), checked_div_q(b, a).ok()?).ok()?;
                 roots.push(root);
                 break;
             }
             Some(_) => {
-                let (ints, _) = clear_denominators(&den_work);
+                let (ints, _) = clear_denominators(&den_work)?;
                 let lc = *ints.last().unwrap_or(&0);
                 let ct = *ints.first().unwrap_or(&0);
                 let mut found = None;
@@ -696,7 +761,7 @@ pub fn partial_fractions_simple(num: &UniPoly, den: &UniPoly) -> Option<(UniPoly
                     }
                     for pn in divisors(ct).into_iter().flat_map(|pn| vec![pn, -pn]) {
                         let r = Q(pn, qd);
-                        if den_work.eval_q(r).is_zero() {
+                        if den_work.checked_eval_q(r).ok()?.is_zero() {
                             found = Some(r);
                             break 'outer;
                         }
@@ -710,9 +775,9 @@ pub fn partial_fractions_simple(num: &UniPoly, den: &UniPoly) -> Option<(UniPoly
     }
 
     // Ensure distinct (no repeated roots): derivative at each root must be non-zero.
-    let dprime = den.deriv();
+    let dprime = den.checked_deriv().ok()?;
     for &rv in &roots {
-        if dprime.eval_q(rv).is_zero() {
+        if dprime.checked_eval_q(rv).ok()?.is_zero() {
             return None;
         }
     }
@@ -720,12 +785,12 @@ pub fn partial_fractions_simple(num: &UniPoly, den: &UniPoly) -> Option<(UniPoly
     // Compute residues A_i = r(root_i) / den'(root_i)
     let mut terms: Vec<(Q, Q)> = Vec::with_capacity(roots.len());
     for &rv in &roots {
-        let numv = r.eval_q(rv);
-        let denv = dprime.eval_q(rv);
+        let numv = r.checked_eval_q(rv).ok()?;
+        let denv = dprime.checked_eval_q(rv).ok()?;
         if denv.is_zero() {
             return None;
         }
-        let a = div_q(numv, denv);
+        let a = checked_div_q(numv, denv).ok()?;
         terms.push((a, rv));
     }
 
@@ -1040,6 +1105,33 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn unipoly_overflow_is_reported() {
+        let big = i64::MAX / 2;
+        // x^2 / (3x + 1): quotient coefficients stay small
+        let p = UniPoly::new("x", vec![Q(0, 1), Q(0, 1), Q(1, 1)]);
+        let d = UniPoly::new("x", vec![Q(1, 1), Q(3, 1)]);
+        assert!(p.div_rem(&d).is_ok());
+        // x^3 / (big*x + big - 1) needs denominators around big^3
+        let p = UniPoly::new("x", vec![Q(0, 1), Q(0, 1), Q(0, 1), Q(1, 1)]);
+        let d = UniPoly::new("x", vec![Q(big - 1, 1), Q(big, 1)]);
+        assert_eq!(p.div_rem(&d), Err("numeric overflow"));
+        // Resultant of big*x + 1 and x - big overflows instead of wrapping
+        let f = UniPoly::new("x", vec![Q(1, 1), Q(big, 1)]);
+        let g = UniPoly::new("x", vec![Q(-big, 1), Q(1, 1)]);
+        assert_eq!(UniPoly::resultant(&f, &g), None);
+        // The checked siblings report what the infallible forms saturate
+        let top = UniPoly::new("x", vec![Q(i64::MAX, 1)]);
+        assert_eq!(top.checked_add(&top), Err(ArithError::Overflow));
+        assert_eq!(top.checked_mul(&top), Err(ArithError::Overflow));
+        assert_eq!(top.add(&top).coeffs, vec![Q(i64::MAX, 1)]);
+        // x^4 + (big - 1)/big x^3 has a gcd with its derivative past i64,
+        // so factor leaves it whole
+        let p = UniPoly::new("x", vec![Q(0, 1), Q(0, 1), Q(0, 1), Q(big - 1, 1), Q(big, 1)]);
+        assert_eq!(p.checked_factor(), Err(ArithError::Overflow));
+        assert_eq!(p.factor(), vec![(p.clone(), 1)]);
+    }
+
     #[test]
     fn expr_to_unipoly_rational_coeff() {
         let mut st = Store::new();
@@ -0,0 +1,632 @@
+//! Multivariate polynomial algorithms: exact division, GCD, and conversion
+//! between expressions and `BigMultiPoly`.
+//!
+//! A polynomial is viewed recursively as univariate in one variable with
+//! coefficients in the others. `div_exact` is long division on that view;
+//! `gcd` splits off the contents recursively and runs a primitive
+//! pseudo-remainder sequence on the primitive parts. Nonzero constants are
+//! units over Q, so GCDs are normalized: integer coefficients with content 1
+//! and a positive leading coefficient (see `BigMultiPoly::normalize`).
+
+use crate::collect::MAX_POWER_TERMS;
+use crate::{Monomial, MultiPoly};
+use arith::{BigInt, BigRat};
+use expr_core::{ExprId, Op, Payload, Store};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+impl Monomial {
+    /// Exponent of `var` (0 if absent).
+    pub fn exponent(&self, var: &str) -> usize {
+        self.0.get(var).copied().unwrap_or(0)
+    }
+
+    /// Variables with their exponents, in name order.
+    pub fn powers(&self) -> impl Iterator<Item = (&str, usize)> + '_ {
+        self.0.iter().map(|(v, &e)| (v.as_str(), e))
+    }
+
+    /// Lexicographic comparison, earlier variable names being more significant.
+    pub fn lex_cmp(&self, other: &Self) -> Ordering {
+        let vars: BTreeSet<&String> = self.0.keys().chain(other.0.keys()).collect();
+        vars.into_iter()
+            .map(|v| self.exponent(v).cmp(&other.exponent(v)))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    fn without(&self, var: &str) -> Self {
+        let mut m = self.0.clone();
+        m.remove(var);
+        Self(m)
+    }
+}
+
+/// Multivariate sparse polynomial over arbitrary-precision rationals.
+/// Companion to `MultiPoly` whose coefficients cannot overflow; division,
+/// GCD, factorization and expansion run on it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigMultiPoly {
+    /// Map from monomial to coefficient; zero coefficients are removed
+    pub terms: BTreeMap<Monomial, BigRat>,
+}
+
+impl BigMultiPoly {
+    pub fn zero() -> Self {
+        Self { terms: BTreeMap::new() }
+    }
+
+    pub fn constant(c: impl Into<BigRat>) -> Self {
+        let c = c.into();
+        if c.is_zero() {
+            return Self::zero();
+        }
+        Self { terms: [(Monomial::one(), c)].into() }
+    }
+
+    pub fn var<S: Into<String>>(name: S) -> Self {
+        Self { terms: [(Monomial::var(name), BigRat::one())].into() }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn total_degree(&self) -> usize {
+        self.terms.keys().map(|m| m.degree()).max().unwrap_or(0)
+    }
+
+    pub fn num_terms(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let mut result = self.terms.clone();
+        for (mon, coeff) in &other.terms {
+            add_term(&mut result, mon.clone(), coeff);
+        }
+        Self { terms: result }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        let mut result = self.terms.clone();
+        for (mon, coeff) in &other.terms {
+            add_term(&mut result, mon.clone(), &-coeff);
+        }
+        Self { terms: result }
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        let mut result = BTreeMap::new();
+        for (m1, c1) in &self.terms {
+            for (m2, c2) in &other.terms {
+                add_term(&mut result, m1.mul(m2), &(c1 * c2));
+            }
+        }
+        Self { terms: result }
+    }
+
+    /// The same polynomial over i64 rationals; None if a coefficient does not fit.
+    pub fn to_multipoly(&self) -> Option<MultiPoly> {
+        let terms = self.terms.iter().map(|(m, c)| Some((m.clone(), c.to_q()?)));
+        Some(MultiPoly { terms: terms.collect::<Option<_>>()? })
+    }
+}
+
+impl From<&MultiPoly> for BigMultiPoly {
+    fn from(p: &MultiPoly) -> Self {
+        Self { terms: p.terms.iter().map(|(m, &c)| (m.clone(), BigRat::from(c))).collect() }
+    }
+}
+
+/// Add `c * mon` to `terms`, dropping the entry if it cancels.
+fn add_term(terms: &mut BTreeMap<Monomial, BigRat>, mon: Monomial, c: &BigRat) {
+    let sum = match terms.get(&mon) {
+        Some(old) => old + c,
+        None => c.clone(),
+    };
+    if sum.is_zero() {
+        terms.remove(&mon);
+    } else {
+        terms.insert(mon, sum);
+    }
+}
+
+impl BigMultiPoly {
+    /// Variables that occur with a nonzero exponent.
+    pub fn vars(&self) -> BTreeSet<String> {
+        self.terms.keys().flat_map(|m| m.0.keys().cloned()).collect()
+    }
+
+    /// Highest exponent of `var`.
+    pub fn degree_in(&self, var: &str) -> usize {
+        self.terms.keys().map(|m| m.exponent(var)).max().unwrap_or(0)
+    }
+
+    /// The value if the polynomial is constant.
+    pub fn as_constant(&self) -> Option<BigRat> {
+        match self.terms.len() {
+            0 => Some(BigRat::zero()),
+            1 => self.terms.get(&Monomial::one()).cloned(),
+            _ => None,
+        }
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.as_constant().is_some()
+    }
+
+    /// Coefficients as a polynomial in `var`: entry k multiplies `var^k`.
+    /// Empty for the zero polynomial.
+    pub fn coeffs_in(&self, var: &str) -> Vec<BigMultiPoly> {
+        let mut out =
+            vec![BigMultiPoly::zero(); if self.is_zero() { 0 } else { self.degree_in(var) + 1 }];
+        for (m, c) in &self.terms {
+            out[m.exponent(var)].terms.insert(m.without(var), c.clone());
+        }
+        out
+    }
+
+    /// Inverse of `coeffs_in`: `sum_k coeffs[k] * var^k`.
+    pub fn from_coeffs_in(var: &str, coeffs: &[BigMultiPoly]) -> Self {
+        let mut p = Self::zero();
+        for (k, c) in coeffs.iter().enumerate() {
+            for (m, q) in &c.terms {
+                let mut m = m.clone();
+                if k > 0 {
+                    m.0.insert(var.to_string(), k);
+                }
+                p.terms.insert(m, q.clone());
+            }
+        }
+        p
+    }
+
+    /// Multiply every coefficient by `c`.
+    pub fn scale(&self, c: impl Into<BigRat>) -> Self {
+        let c = c.into();
+        if c.is_zero() {
+            return Self::zero();
+        }
+        Self { terms: self.terms.iter().map(|(m, q)| (m.clone(), q * &c)).collect() }
+    }
+
+    pub fn neg(&self) -> Self {
+        self.scale(-1)
+    }
+
+    /// `self^k` by repeated squaring. The result can have as many as
+    /// `C(n + k - 1, k)` terms for `n` terms here (see `power_fits`).
+    pub fn pow(&self, mut k: u32) -> Self {
+        let mut acc = Self::constant(1);
+        let mut base = self.clone();
+        while k > 0 {
+            if k & 1 == 1 {
+                acc = acc.mul(&base);
+            }
+            k >>= 1;
+            if k > 0 {
+                base = base.mul(&base);
+            }
+        }
+        acc
+    }
+
+    /// Coefficient of the greatest monomial in lexicographic order with
+    /// variables ranked by name (`x > y`); zero for 0.
+    pub fn leading_coeff(&self) -> BigRat {
+        self.terms.iter().max_by(|a, b| a.0.lex_cmp(b.0)).map_or(BigRat::zero(), |(_, c)| c.clone())
+    }
+
+    /// The rational `c` such that `self / c` has coprime integer coefficients
+    /// and a positive leading coefficient (1 for the zero polynomial).
+    pub fn content(&self) -> BigRat {
+        let (mut num, mut den) = (BigInt::zero(), BigInt::one());
+        for q in self.terms.values() {
+            num = num.gcd(q.numer());
+            den = &den * &q.denom().div_rem(&den.gcd(q.denom())).0;
+        }
+        if num.is_zero() {
+            return BigRat::one();
+        }
+        if self.leading_coeff().is_negative() {
+            num = -num;
+        }
+        BigRat::new(num, den)
+    }
+
+    /// `self / content()`: the canonical associate over Q.
+    pub fn normalize(&self) -> Self {
+        self.scale(self.content().recip().expect("nonzero content"))
+    }
+
+    /// `self / d` if `d` divides `self` exactly; None otherwise (or if `d = 0`).
+    pub fn div_exact(&self, d: &Self) -> Option<Self> {
+        if d.is_zero() {
+            return None;
+        }
+        if self.is_zero() {
+            return Some(Self::zero());
+        }
+        if let Some(c) = d.as_constant() {
+            return Some(self.scale(c.recip()?));
+        }
+        let x = d.vars().into_iter().next().expect("non-constant divisor");
+        let (mut r, b) = (self.coeffs_in(&x), d.coeffs_in(&x));
+        if r.len() < b.len() {
+            return None;
+        }
+        let lb = b.last().expect("nonzero divisor");
+        let mut q = vec![Self::zero(); r.len() - b.len() + 1];
+        for k in (0..q.len()).rev() {
+            // Coefficients are free of x, so this recursion has fewer variables
+            let c = r[k + b.len() - 1].div_exact(lb)?;
+            for (i, bi) in b.iter().enumerate() {
+                r[k + i] = r[k + i].sub(&c.mul(bi));
+            }
+            q[k] = c;
+        }
+        if r.iter().any(|c| !c.is_zero()) {
+            return None;
+        }
+        Some(Self::from_coeffs_in(&x, &q))
+    }
+
+    /// Greatest common divisor, normalized; `gcd(0, 0) = 0`.
+    pub fn gcd(a: &Self, b: &Self) -> Self {
+        if a.is_zero() {
+            return b.normalize();
+        }
+        if b.is_zero() {
+            return a.normalize();
+        }
+        let Some(x) = a.vars().into_iter().chain(b.vars()).min() else {
+            return Self::constant(1);
+        };
+        let (ca, cb) = (a.content_in(&x), b.content_in(&x));
+        let c = Self::gcd(&ca, &cb);
+        // Should a content not divide, keeping it only costs coefficient size:
+        // the final primitive part drops it again
+        let mut f = a.div_exact(&ca).unwrap_or_else(|| a.clone()).coeffs_in(&x);
+        let mut g = b.div_exact(&cb).unwrap_or_else(|| b.clone()).coeffs_in(&x);
+        if f.len() < g.len() {
+            std::mem::swap(&mut f, &mut g);
+        }
+        while !g.is_empty() {
+            let r = prem(&f, &g);
+            f = g;
+            g = primitive(r);
+        }
+        let h = primitive(f);
+        c.mul(&Self::from_coeffs_in(&x, &h)).normalize()
+    }
+
+    /// Least common multiple, normalized.
+    pub fn lcm(a: &Self, b: &Self) -> Self {
+        if a.is_zero() || b.is_zero() {
+            return Self::zero();
+        }
+        let g = Self::gcd(a, b);
+        match a.div_exact(&g) {
+            Some(q) => q.mul(b).normalize(),
+            // Still a common multiple
+            None => a.mul(b).normalize(),
+        }
+    }
+
+    /// GCD of the coefficients of `self` as a polynomial in `var`.
+    fn content_in(&self, var: &str) -> Self {
+        self.coeffs_in(var).iter().fold(Self::zero(), |acc, c| Self::gcd(&acc, c))
+    }
+}
+
+/// Pseudo-remainder of `f` by `g` (coefficient vectors, `g` nonzero):
+/// `lc(g)^(deg f - deg g + 1) f mod g`, computed without division.
+fn prem(f: &[BigMultiPoly], g: &[BigMultiPoly]) -> Vec<BigMultiPoly> {
+    let lc = g.last().expect("nonzero divisor");
+    let mut r = f.to_vec();
+    while r.len() >= g.len() {
+        let t = r.last().expect("nonempty").clone();
+        let shift = r.len() - g.len();
+        for c in r.iter_mut() {
+            *c = c.mul(lc);
+        }
+        for (i, gi) in g.iter().enumerate() {
+            r[shift + i] = r[shift + i].sub(&t.mul(gi));
+        }
+        while r.last().is_some_and(|c| c.is_zero()) {
+            r.pop();
+        }
+    }
+    r
+}
+
+/// Divide a coefficient vector by the GCD of its entries (left as is if that
+/// fails to divide).
+fn primitive(v: Vec<BigMultiPoly>) -> Vec<BigMultiPoly> {
+    let c = v.iter().fold(BigMultiPoly::zero(), |acc, x| BigMultiPoly::gcd(&acc, x));
+    if c.is_zero() {
+        return v;
+    }
+    v.iter().map(|x| x.div_exact(&c)).collect::<Option<_>>().unwrap_or(v)
+}
+
+/// Convert a polynomial expression in its symbols (sums and products of
+/// rationals and symbols raised to non-negative integer powers).
+pub fn expr_to_bigmultipoly(store: &Store, id: ExprId) -> Option<BigMultiPoly> {
+    let node = store.get(id);
+    if matches!(node.op, Op::Integer | Op::Rational) {
+        return store.as_bigrat(id).map(BigMultiPoly::constant);
+    }
+    match (&node.op, &node.payload) {
+        (Op::Symbol, Payload::Sym(s)) => Some(BigMultiPoly::var(s.as_str())),
+        (Op::Add, _) => node.children.iter().try_fold(BigMultiPoly::zero(), |acc, &c| {
+            Some(acc.add(&expr_to_bigmultipoly(store, c)?))
+        }),
+        (Op::Mul, _) => node.children.iter().try_fold(BigMultiPoly::constant(1), |acc, &c| {
+            Some(acc.mul(&expr_to_bigmultipoly(store, c)?))
+        }),
+        (Op::Pow, _) => {
+            let (base, k) = natural_power(store, id)?;
+            let b = expr_to_bigmultipoly(store, base)?;
+            power_fits(&b, k).then(|| b.pow(k))
+        }
+        _ => None,
+    }
+}
+
+/// `expr_to_bigmultipoly` over i64 rationals; None also if a coefficient
+/// does not fit.
+pub fn expr_to_multipoly(store: &Store, id: ExprId) -> Option<MultiPoly> {
+    expr_to_bigmultipoly(store, id)?.to_multipoly()
+}
+
+/// Expression for `p` with each variable a symbol of the same name.
+pub fn bigmultipoly_to_expr(store: &mut Store, p: &BigMultiPoly) -> ExprId {
+    bigmultipoly_to_expr_with(store, p, &BTreeMap::new())
+}
+
+/// `bigmultipoly_to_expr` for a polynomial over i64 rationals.
+pub fn multipoly_to_expr(store: &mut Store, p: &MultiPoly) -> ExprId {
+    bigmultipoly_to_expr(store, &p.into())
+}
+
+/// `bigmultipoly_to_expr`, reading variables listed in `gens` as those expressions.
+/// Within a term, powers of the same base with numeric exponents are combined
+/// (`x * x^-1 = 1`, `(x^(1/2))^2 = x`).
+pub(crate) fn bigmultipoly_to_expr_with(
+    store: &mut Store,
+    p: &BigMultiPoly,
+    gens: &BTreeMap<String, ExprId>,
+) -> ExprId {
+    let mut terms = Vec::with_capacity(p.terms.len());
+    for (m, c) in &p.terms {
+        let mut powers: Vec<(ExprId, BigRat)> = Vec::new();
+        for (v, e) in m.powers() {
+            let g = match gens.get(v) {
+                Some(&g) => g,
+                None => store.sym(v),
+            };
+            let e = BigRat::from(e as i64);
+            let (base, k) = match numeric_power(store, g) {
+                Some((b, q)) => (b, &q * &e),
+                None => (g, e),
+            };
+            match powers.iter_mut().find(|(b, _)| *b == base) {
+                Some((_, acc)) => *acc = &*acc + &k,
+                None => powers.push((base, k)),
+            }
+        }
+        let mut factors = vec![store.bigrat(c.clone())];
+        for (base, k) in powers {
+            if !k.is_zero() {
+                let k = store.bigrat(k);
+                factors.push(store.pow(base, k));
+            }
+        }
+        terms.push(store.mul(factors));
+    }
+    store.add(terms)
+}
+
+/// `(b, q)` for `b^q` with a rational exponent.
+pub(crate) fn numeric_power(store: &Store, id: ExprId) -> Option<(ExprId, BigRat)> {
+    let n = store.get(id);
+    if n.op != Op::Pow {
+        return None;
+    }
+    Some((n.children[0], store.as_bigrat(n.children[1])?))
+}
+
+/// Register `id` as a polynomial variable: symbols are named by themselves,
+/// other generators by their printed form.
+pub(crate) fn generator(
+    store: &Store,
+    id: ExprId,
+    gens: &mut BTreeMap<String, ExprId>,
+) -> BigMultiPoly {
+    let name = match (&store.get(id).op, &store.get(id).payload) {
+        (Op::Symbol, Payload::Sym(s)) => s.clone(),
+        _ => store.to_string(id),
+    };
+    gens.entry(name.clone()).or_insert(id);
+    BigMultiPoly::var(name)
+}
+
+/// Multiply out the sums, products and natural powers of sums in `id` with
+/// `BigMultiPoly` arithmetic: `(x + 1)^2 (x - y)` becomes
+/// `x^3 + 2x^2 + x - x^2 y - 2x y - y`. Other subexpressions (`sin(x)`,
+/// `x^(1/2)`, `(x + 1)^-1`) are kept as opaque factors, and so are powers
+/// too large to multiply out (see `power_fits`).
+pub fn expand_poly(store: &mut Store, id: ExprId) -> ExprId {
+    expand_poly_with(store, id, &mut |_, g| g)
+}
+
+/// `expand_poly` that first rewrites each opaque factor with `on_generator`
+/// (e.g. to expand inside function arguments). A rewrite into a sum or
+/// product is multiplied out as well.
+pub fn expand_poly_with(
+    store: &mut Store,
+    id: ExprId,
+    on_generator: &mut dyn FnMut(&mut Store, ExprId) -> ExprId,
+) -> ExprId {
+    let mut gens = BTreeMap::new();
+    let p = to_poly_with(store, id, &mut gens, on_generator);
+    bigmultipoly_to_expr_with(store, &p, &gens)
+}
+
+fn to_poly_with(
+    store: &mut Store,
+    id: ExprId,
+    gens: &mut BTreeMap<String, ExprId>,
+    on_generator: &mut dyn FnMut(&mut Store, ExprId) -> ExprId,
+) -> BigMultiPoly {
+    if let Some((base, k)) = natural_power(store, id) {
+        let b = to_poly_with(store, base, gens, on_generator);
+        if power_fits(&b, k) {
+            return b.pow(k);
+        }
+        // Too large to multiply out: kept whole, over the expanded base
+        let base = bigmultipoly_to_expr_with(store, &b, gens);
+        let k = store.int(i64::from(k));
+        let g = store.pow(base, k);
+        return generator(store, g, gens);
+    }
+    if let Some(c) = store.as_bigrat(id) {
+        return BigMultiPoly::constant(c);
+    }
+    let node = store.get(id);
+    let (op, children) = (node.op.clone(), node.children.clone());
+    match op {
+        Op::Add => children.iter().fold(BigMultiPoly::zero(), |acc, &c| {
+            acc.add(&to_poly_with(store, c, gens, on_generator))
+        }),
+        Op::Mul => children.iter().fold(BigMultiPoly::constant(1), |acc, &c| {
+            acc.mul(&to_poly_with(store, c, gens, on_generator))
+        }),
+        _ => {
+            let g = on_generator(store, id);
+            let polynomial =
+                matches!(store.get(g).op, Op::Add | Op::Mul) || natural_power(store, g).is_some();
+            if g != id && polynomial {
+                to_poly_with(store, g, gens, &mut |_, h| h)
+            } else {
+                generator(store, g, gens)
+            }
+        }
+    }
+}
+
+/// Largest exponent a power is multiplied out to.
+const MAX_EXPONENT: u32 = 10_000;
+
+/// Whether `p^k` is small enough to multiply out: `k <= MAX_EXPONENT` and at
+/// most `MAX_POWER_TERMS` terms, counting the `C(n + k - 1, k)` products of
+/// `k` of the `n` terms of `p` as distinct.
+pub(crate) fn power_fits(p: &BigMultiPoly, k: u32) -> bool {
+    if k > MAX_EXPONENT {
+        return false;
+    }
+    let n = p.num_terms();
+    if n <= 1 {
+        return true;
+    }
+    // C(n + k - 1, k) = C(hi + lo, lo) as the running product C(hi + i, i)
+    let (lo, hi) = ((k as usize).min(n - 1), (k as usize).max(n - 1));
+    let mut c: usize = 1;
+    for i in 1..=lo {
+        c = match c.checked_mul(hi + i) {
+            Some(v) => v / i,
+            None => return false,
+        };
+        if c > MAX_POWER_TERMS {
+            return false;
+        }
+    }
+    true
+}
+
+/// `(b, k)` for `b^k` with an exponent `k` in `0..=u32::MAX`.
+pub(crate) fn natural_power(store: &Store, id: ExprId) -> Option<(ExprId, u32)> {
+    let n = store.get(id);
+    if n.op != Op::Pow {
+        return None;
+    }
+    match store.get(n.children[1]).payload {
+        Payload::Int(k) => Some((n.children[0], u32::try_from(k).ok()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arith::Q;
+
+    fn poly(st: &mut Store, f: impl FnOnce(&mut Store) -> ExprId) -> BigMultiPoly {
+        let e = f(st);
+        expr_to_bigmultipoly(st, e).unwrap()
+    }
+
+    #[test]
+    fn multivariate_gcd_and_division() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let one = st.int(1);
+        let two = st.int(2);
+        let m_one = st.int(-1);
+        // (x + y)^2 (x - 1) and 2 (x + y)(x y + 1)
+        let a = poly(&mut st, |st| {
+            let s = st.add(vec![x, y]);
+            let s2 = st.pow(s, two);
+            let xm1 = st.add(vec![x, m_one]);
+            st.mul(vec![s2, xm1])
+        });
+        let b = poly(&mut st, |st| {
+            let s = st.add(vec![x, y]);
+            let xy = st.mul(vec![x, y]);
+            let xy1 = st.add(vec![xy, one]);
+            st.mul(vec![two, s, xy1])
+        });
+        let s = poly(&mut st, |st| st.add(vec![x, y]));
+        assert_eq!(BigMultiPoly::gcd(&a, &b), s);
+        assert_eq!(BigMultiPoly::gcd(&a.scale(Q(-3, 4)), &b), s);
+        assert_eq!(a.div_exact(&s).unwrap().mul(&s), a);
+        assert_eq!(b.div_exact(&a), None);
+        // Coprime inputs have gcd 1; lcm is the normalized product
+        let xm1 = poly(&mut st, |st| st.add(vec![x, m_one]));
+        let yp1 = poly(&mut st, |st| st.add(vec![y, one]));
+        assert_eq!(BigMultiPoly::gcd(&xm1, &yp1), BigMultiPoly::constant(Q::one()));
+        assert_eq!(BigMultiPoly::lcm(&xm1.scale(Q(2, 1)), &yp1), xm1.mul(&yp1));
+        // Round trip through expressions
+        let e = bigmultipoly_to_expr(&mut st, &a);
+        assert_eq!(expr_to_bigmultipoly(&st, e), Some(a));
+    }
+
+    #[test]
+    fn conversion_to_and_from_multipoly() {
+        let p = MultiPoly::var("x").mul(&MultiPoly::var("y")).add(&MultiPoly::constant(Q(3, 2)));
+        let big = BigMultiPoly::from(&p);
+        assert_eq!(big.to_multipoly(), Some(p));
+        // Coefficients scaled by 2^70 have no i64 form
+        assert_eq!(big.scale(BigRat::from(1i64 << 35).pow(2).unwrap()).to_multipoly(), None);
+    }
+
+    #[test]
+    fn expand_keeps_generators_opaque() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let half = st.rat(1, 2);
+        let two = st.int(2);
+        let sx = st.func("sin", vec![x]);
+        let rx = st.pow(x, half);
+        // (sin(x) + x^(1/2))^2 = sin(x)^2 + 2 x^(1/2) sin(x) + x
+        let s = st.add(vec![sx, rx]);
+        let e = st.pow(s, two);
+        let r = expand_poly(&mut st, e);
+        let s2 = st.pow(sx, two);
+        let mid = st.mul(vec![two, rx, sx]);
+        assert_eq!(r, st.add(vec![s2, mid, x]));
+    }
+}
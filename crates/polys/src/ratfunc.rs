@@ -0,0 +1,669 @@
+//! Rational-function normal forms of expressions: `together`, `cancel`,
+//! `apart`, `factor`.
+//!
+//! An expression is read as a quotient of multivariate polynomials. Its
+//! variables are the symbols and any non-polynomial subexpressions (`sin(x)`,
+//! `sqrt(y)`, ...), which are kept as opaque generators. Denominators are kept
+//! as products of pairwise coprime factors, so a sum is put over the least
+//! common multiple of its denominators rather than their product.
+
+use crate::multi::{bigmultipoly_to_expr_with, generator, power_fits};
+use crate::BigMultiPoly;
+use expr_core::{ExprId, Op, Payload, Store};
+use std::collections::BTreeMap;
+
+/// Generator name -> expression (see `multi::generator`).
+type Gens = BTreeMap<String, ExprId>;
+
+/// `num / prod(p^e)` with the `p` normalized, non-constant and pairwise coprime.
+#[derive(Clone, Debug)]
+struct Frac {
+    num: BigMultiPoly,
+    den: Vec<(BigMultiPoly, u32)>,
+}
+
+fn one() -> BigMultiPoly {
+    BigMultiPoly::constant(1)
+}
+
+fn product(factors: &[(BigMultiPoly, u32)]) -> BigMultiPoly {
+    factors.iter().fold(one(), |acc, (p, e)| acc.mul(&p.pow(*e)))
+}
+
+/// Refine factors into a pairwise coprime basis; each element keeps, per
+/// input slot, the total multiplicity it receives from that slot. None if a
+/// GCD fails to divide.
+fn coprime_basis<const N: usize>(
+    items: Vec<(BigMultiPoly, [u32; N])>,
+) -> Option<Vec<(BigMultiPoly, [u32; N])>> {
+    let mut work = items;
+    let mut out: Vec<(BigMultiPoly, [u32; N])> = Vec::new();
+    while let Some((p, e)) = work.pop() {
+        if p.is_constant() {
+            continue;
+        }
+        let shared = out.iter().position(|(q, _)| !BigMultiPoly::gcd(&p, q).is_constant());
+        match shared {
+            None => out.push((p.normalize(), e)),
+            Some(i) => {
+                let (q, f) = out.swap_remove(i);
+                let g = BigMultiPoly::gcd(&p, &q);
+                let sum = std::array::from_fn(|k| e[k] + f[k]);
+                work.push((p.div_exact(&g)?, e));
+                work.push((q.div_exact(&g)?, f));
+                work.push((g, sum));
+            }
+        }
+    }
+    Some(out)
+}
+
+impl Frac {
+    fn poly(num: BigMultiPoly) -> Self {
+        Frac { num, den: Vec::new() }
+    }
+
+    fn pairs(&self, other: &Self) -> Option<Vec<(BigMultiPoly, [u32; 2])>> {
+        let a = self.den.iter().map(|(p, e)| (p.clone(), [*e, 0]));
+        let b = other.den.iter().map(|(p, e)| (p.clone(), [0, *e]));
+        coprime_basis(a.chain(b).collect())
+    }
+
+    fn mul(&self, other: &Self) -> Option<Self> {
+        let den = self.pairs(other)?.into_iter().map(|(p, [a, b])| (p, a + b)).collect();
+        Some(Frac { num: self.num.mul(&other.num), den })
+    }
+
+    fn add(&self, other: &Self) -> Option<Self> {
+        let basis = self.pairs(other)?;
+        let (mut ca, mut cb) = (self.num.clone(), other.num.clone());
+        for (p, [a, b]) in &basis {
+            let l = (*a).max(*b);
+            ca = ca.mul(&p.pow(l - a));
+            cb = cb.mul(&p.pow(l - b));
+        }
+        let den = basis.into_iter().map(|(p, [a, b])| (p, a.max(b))).collect();
+        Some(Frac { num: ca.add(&cb), den })
+    }
+
+    /// `self^k`; None if that is too large to multiply out (see
+    /// `multi::power_fits`).
+    fn pow(&self, k: u32) -> Option<Self> {
+        if !power_fits(&self.num, k) {
+            return None;
+        }
+        let den = self.den.iter().map(|(p, e)| Some((p.clone(), e.checked_mul(k)?)));
+        Some(Frac { num: self.num.pow(k), den: den.collect::<Option<_>>()? })
+    }
+
+    /// `1 / self`; None if the numerator is zero.
+    fn inv(&self) -> Option<Self> {
+        if self.num.is_zero() {
+            return None;
+        }
+        let c = self.num.content().recip()?;
+        let p = self.num.scale(c.clone());
+        let den = if p.is_constant() { Vec::new() } else { vec![(p, 1)] };
+        Some(Frac { num: product(&self.den).scale(c), den })
+    }
+}
+
+/// Read `id` as a rational function; None on a literal division by zero (or
+/// a GCD that fails to divide).
+fn to_frac(store: &mut Store, id: ExprId, gens: &mut Gens) -> Option<Frac> {
+    let node = store.get(id);
+    let (op, payload, children) = (node.op.clone(), node.payload.clone(), node.children.clone());
+    if matches!(op, Op::Integer | Op::Rational) {
+        return store.as_bigrat(id).map(|c| Frac::poly(BigMultiPoly::constant(c)));
+    }
+    match (op, payload) {
+        (Op::Add, _) => children.iter().try_fold(Frac::poly(BigMultiPoly::zero()), |acc, &c| {
+            acc.add(&to_frac(store, c, gens)?)
+        }),
+        (Op::Mul, _) => children
+            .iter()
+            .try_fold(Frac::poly(one()), |acc, &c| acc.mul(&to_frac(store, c, gens)?)),
+        (Op::Pow, _) => {
+            let (base, exp) = (children[0], children[1]);
+            match (store.get(exp).op.clone(), store.get(exp).payload.clone()) {
+                (Op::Integer, Payload::Int(k)) => {
+                    let b = if k < 0 {
+                        inv_frac(store, base, gens)?
+                    } else {
+                        to_frac(store, base, gens)?
+                    };
+                    // A power too large to multiply out is kept whole
+                    match u32::try_from(k.unsigned_abs()).ok().and_then(|k| b.pow(k)) {
+                        Some(f) => Some(f),
+                        None => Some(Frac::poly(generator(store, id, gens))),
+                    }
+                }
+                // b^(-p/q) = 1 / b^(p/q) with b^(p/q) a generator
+                (Op::Rational, Payload::Rat(n, d)) if n < 0 => {
+                    let e = store.rat(-n, d);
+                    let g = store.pow(base, e);
+                    Frac::poly(generator(store, g, gens)).inv()
+                }
+                _ => Some(Frac::poly(generator(store, id, gens))),
+            }
+        }
+        _ => Some(Frac::poly(generator(store, id, gens))),
+    }
+}
+
+/// `1 / id`, inverting the factors of a product one by one so that the
+/// denominator keeps them apart.
+fn inv_frac(store: &mut Store, id: ExprId, gens: &mut Gens) -> Option<Frac> {
+    if store.get(id).op != Op::Mul {
+        return to_frac(store, id, gens)?.inv();
+    }
+    let children = store.get(id).children.clone();
+    children.iter().try_fold(Frac::poly(one()), |acc, &c| acc.mul(&inv_frac(store, c, gens)?))
+}
+
+/// `num / prod(p^e)` as an expression with the given factors.
+fn quotient_expr(
+    store: &mut Store,
+    num: &BigMultiPoly,
+    den: &[(BigMultiPoly, u32)],
+    gens: &Gens,
+) -> ExprId {
+    // A rational content stays a separate numeric factor: 1/2 (x - y), not x/2 - y/2
+    let c = num.content();
+    let mut factors =
+        vec![store.bigrat(c), bigmultipoly_to_expr_with(store, &num.normalize(), gens)];
+    for (p, e) in den {
+        let b = bigmultipoly_to_expr_with(store, p, gens);
+        let k = store.int(-i64::from(*e));
+        factors.push(store.pow(b, k));
+    }
+    store.mul(factors)
+}
+
+/// Combine a sum of fractions over its least common denominator:
+/// `1/x + 1/(x + 1)` becomes `(2x + 1) / (x (x + 1))`. The numerator is
+/// expanded, the denominator kept factored; common factors are not cancelled
+/// (see `cancel`).
+pub fn together(store: &mut Store, id: ExprId) -> ExprId {
+    let mut gens = Gens::new();
+    match to_frac(store, id, &mut gens) {
+        Some(f) => quotient_expr(store, &f.num, &f.den, &gens),
+        None => id,
+    }
+}
+
+/// Numerator and denominator with their GCD removed, both expanded; the
+/// denominator has coprime integer coefficients and a positive leading
+/// coefficient: `(x^2 - 1) / (2x + 2)` becomes `(1/2) (x - 1)`.
+pub fn cancel(store: &mut Store, id: ExprId) -> ExprId {
+    let mut gens = Gens::new();
+    let Some(f) = to_frac(store, id, &mut gens) else {
+        return id;
+    };
+    let den = product(&f.den);
+    let g = BigMultiPoly::gcd(&f.num, &den);
+    let (Some(n), Some(d)) = (f.num.div_exact(&g), den.div_exact(&g)) else {
+        return id;
+    };
+    let c = d.content().recip().expect("nonzero content");
+    let (n, d) = (n.scale(c.clone()), d.scale(c));
+    let den = if d.is_constant() { Vec::new() } else { vec![(d, 1)] };
+    quotient_expr(store, &n, &den, &gens)
+}
+
+/// Irreducible factorization over Q of numerator and denominator, with
+/// common factors cancelled: `(x^2 y - y) / (2x^2 + 2x)` becomes
+/// `(1/2) x^-1 y (x - 1)`. Factors are normalized (coprime integer
+/// coefficients, positive leading coefficient) and the sign and rational
+/// content form a single numeric factor. Polynomials too large to factor
+/// (see `BigMultiPoly::factor`) leave `id` unchanged.
+pub fn factor(store: &mut Store, id: ExprId) -> ExprId {
+    let mut gens = Gens::new();
+    let Some(f) = to_frac(store, id, &mut gens) else {
+        return id;
+    };
+    let Some((mut c, num)) = f.num.factor() else {
+        return id;
+    };
+    let mut powers: Vec<(BigMultiPoly, i64)> =
+        num.into_iter().map(|(p, k)| (p, k as i64)).collect();
+    for (p, e) in &f.den {
+        let Some((cp, fs)) = p.factor() else {
+            return id;
+        };
+        for _ in 0..*e {
+            c = c.checked_div(&cp).expect("nonzero content");
+        }
+        for (q, k) in fs {
+            let k = -((k as i64) * i64::from(*e));
+            match powers.iter_mut().find(|(r, _)| *r == q) {
+                Some((_, acc)) => *acc += k,
+                None => powers.push((q, k)),
+            }
+        }
+    }
+    let mut factors = vec![store.bigrat(c)];
+    for (p, k) in powers.into_iter().filter(|(_, k)| *k != 0) {
+        let b = bigmultipoly_to_expr_with(store, &p, &gens);
+        let k = store.int(k);
+        factors.push(store.pow(b, k));
+    }
+    store.mul(factors)
+}
+
+/// An element `num / den` of Q(params), reduced with `den` normalized.
+#[derive(Clone, Debug)]
+struct Coef {
+    num: BigMultiPoly,
+    den: BigMultiPoly,
+}
+
+impl Coef {
+    fn new(num: BigMultiPoly, den: BigMultiPoly) -> Self {
+        let g = BigMultiPoly::gcd(&num, &den);
+        // Unreduced is still the same element
+        let (n, d) = match (num.div_exact(&g), den.div_exact(&g)) {
+            (Some(n), Some(d)) => (n, d),
+            _ => (num, den),
+        };
+        let c = d.content().recip().expect("nonzero content");
+        Coef { num: n.scale(c.clone()), den: d.scale(c) }
+    }
+
+    fn poly(num: BigMultiPoly) -> Self {
+        Coef { num, den: one() }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.num.is_zero()
+    }
+
+    fn add(&self, o: &Self) -> Self {
+        Coef::new(self.num.mul(&o.den).add(&o.num.mul(&self.den)), self.den.mul(&o.den))
+    }
+
+    fn sub(&self, o: &Self) -> Self {
+        self.add(&Coef { num: o.num.neg(), den: o.den.clone() })
+    }
+
+    fn mul(&self, o: &Self) -> Self {
+        Coef::new(self.num.mul(&o.num), self.den.mul(&o.den))
+    }
+
+    fn div(&self, o: &Self) -> Self {
+        Coef::new(self.num.mul(&o.den), self.den.mul(&o.num))
+    }
+}
+
+/// Polynomial in the apart variable over Q(params); entry k multiplies var^k,
+/// no trailing zeros.
+type KPoly = Vec<Coef>;
+
+fn kpoly(p: &BigMultiPoly, var: &str) -> KPoly {
+    p.coeffs_in(var).into_iter().map(Coef::poly).collect()
+}
+
+fn trim(mut a: KPoly) -> KPoly {
+    while a.last().is_some_and(Coef::is_zero) {
+        a.pop();
+    }
+    a
+}
+
+fn kp_sub(a: &[Coef], b: &[Coef]) -> KPoly {
+    let zero = Coef::poly(BigMultiPoly::zero());
+    let n = a.len().max(b.len());
+    trim((0..n).map(|i| a.get(i).unwrap_or(&zero).sub(b.get(i).unwrap_or(&zero))).collect())
+}
+
+fn kp_mul(a: &[Coef], b: &[Coef]) -> KPoly {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![Coef::poly(BigMultiPoly::zero()); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            out[i + j] = out[i + j].add(&x.mul(y));
+        }
+    }
+    trim(out)
+}
+
+/// Division with remainder by a nonzero `b`.
+fn kp_div_rem(a: &[Coef], b: &[Coef]) -> (KPoly, KPoly) {
+    let lb = b.last().expect("nonzero divisor");
+    let mut r = a.to_vec();
+    let mut q = vec![Coef::poly(BigMultiPoly::zero()); a.len().saturating_sub(b.len()) + 1];
+    while r.len() >= b.len() {
+        let shift = r.len() - b.len();
+        let c = r.last().expect("nonempty").div(lb);
+        for (i, bi) in b.iter().enumerate() {
+            r[shift + i] = r[shift + i].sub(&c.mul(bi));
+        }
+        q[shift] = c;
+        r.pop();
+        r = trim(r);
+    }
+    (trim(q), r)
+}
+
+/// `s` with `s a = 1 (mod m)`, for `a` coprime to `m`.
+fn kp_inverse_mod(a: &[Coef], m: &[Coef]) -> KPoly {
+    let (mut r0, mut r1) = (m.to_vec(), kp_div_rem(a, m).1);
+    let (mut s0, mut s1): (KPoly, KPoly) = (Vec::new(), vec![Coef::poly(one())]);
+    while !r1.is_empty() {
+        let (q, r) = kp_div_rem(&r0, &r1);
+        let s = kp_sub(&s0, &kp_mul(&q, &s1));
+        (r0, r1) = (r1, r);
+        (s0, s1) = (s1, s);
+    }
+    // r0 is a nonzero constant since gcd(a, m) = 1
+    let inv = Coef::poly(one()).div(&r0[0]);
+    let s: KPoly = s0.iter().map(|c| c.mul(&inv)).collect();
+    kp_div_rem(&s, m).1
+}
+
+/// `(c / d) * p^-k` where `c` has coefficients over Q(params) and `d` is
+/// constant in the variable; None if the common denominator fails to divide.
+fn term_expr(
+    store: &mut Store,
+    c: &[Coef],
+    var: &str,
+    d: &BigMultiPoly,
+    p: Option<(&BigMultiPoly, u32)>,
+    gens: &Gens,
+) -> Option<ExprId> {
+    // Over a common denominator of the coefficients
+    let l = c.iter().fold(one(), |acc, x| BigMultiPoly::lcm(&acc, &x.den));
+    let scaled: Vec<BigMultiPoly> =
+        c.iter().map(|x| Some(x.num.mul(&l.div_exact(&x.den)?))).collect::<Option<_>>()?;
+    let num = BigMultiPoly::from_coeffs_in(var, &scaled);
+    let mut den = Vec::new();
+    let l = l.mul(d);
+    let (cl, l) = (l.content(), l.normalize());
+    if !l.is_constant() {
+        den.push((l, 1));
+    }
+    if let Some((p, k)) = p {
+        den.push((p.clone(), k));
+    }
+    Some(quotient_expr(store, &num.scale(cl.recip().expect("nonzero content")), &den, gens))
+}
+
+/// Partial fraction decomposition in `var`: a polynomial part plus terms
+/// `c / p^k` with `deg c < deg p` for each denominator factor `p`. Other
+/// symbols (and generators such as `sin(y)`) are treated as parameters.
+///
+/// Denominator factors are split into irreducible factors over Q; one that is
+/// not linear in `var` keeps a single term per power (`1/(x^2 + 1)`). A
+/// denominator too large to factor, or an inexact division along the way,
+/// leaves `id` unchanged.
+pub fn apart(store: &mut Store, id: ExprId, var: &str) -> ExprId {
+    let mut gens = Gens::new();
+    let Some(f) = to_frac(store, id, &mut gens) else {
+        return id;
+    };
+    if !matches!(gens.get(var), Some(&g) if store.get(g).op == Op::Symbol) {
+        return id;
+    }
+    // Factors free of var are part of the coefficient field
+    let mut params = one();
+    let mut split = Vec::new();
+    for (p, e) in &f.den {
+        let Some((c, fs)) = p.factor() else {
+            return id;
+        };
+        params = params.mul(&BigMultiPoly::constant(c).pow(*e));
+        for (q, k) in fs {
+            let k = k as u32 * e;
+            if q.degree_in(var) == 0 {
+                params = params.mul(&q.pow(k));
+            } else {
+                split.push((q, [k]));
+            }
+        }
+    }
+    let Some(basis) = coprime_basis(split) else {
+        return id;
+    };
+    let factors: Vec<(BigMultiPoly, u32)> = basis.into_iter().map(|(p, [e])| (p, e)).collect();
+
+    let num = kpoly(&f.num, var);
+    let powers: Vec<KPoly> = factors.iter().map(|(p, e)| kpoly(&p.pow(*e), var)).collect();
+    let den = powers.iter().fold(vec![Coef::poly(one())], |acc, d| kp_mul(&acc, d));
+    let (q, r) = kp_div_rem(&num, &den);
+
+    let Some(head) = term_expr(store, &q, var, &params, None, &gens) else {
+        return id;
+    };
+    let mut terms = vec![head];
+    for ((p, e), di) in factors.iter().zip(&powers) {
+        let (cofactor, _) = kp_div_rem(&den, di);
+        let s = kp_inverse_mod(&cofactor, di);
+        // r / den = sum_i a_i / d_i, then a_i = sum_j c_j p^j
+        let mut a = kp_div_rem(&kp_mul(&r, &s), di).1;
+        let pk = kpoly(p, var);
+        for j in 0..*e {
+            let (next, c) = kp_div_rem(&a, &pk);
+            if !c.is_empty() {
+                let Some(t) = term_expr(store, &c, var, &params, Some((p, e - j)), &gens) else {
+                    return id;
+                };
+                terms.push(t);
+            }
+            a = next;
+        }
+    }
+    store.add(terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recip(st: &mut Store, e: ExprId) -> ExprId {
+        let m_one = st.int(-1);
+        st.pow(e, m_one)
+    }
+
+    #[test]
+    fn together_uses_least_common_denominator() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let one = st.int(1);
+        let m_one = st.int(-1);
+        let two = st.int(2);
+        // 1/(x - 1) + 1/(x^2 - 1) = (x + 2) / ((x - 1)(x + 1))
+        let xm1 = st.add(vec![x, m_one]);
+        let x2 = st.pow(x, two);
+        let x2m1 = st.add(vec![x2, m_one]);
+        let (a, b) = (recip(&mut st, xm1), recip(&mut st, x2m1));
+        let s = st.add(vec![a, b]);
+        let t = together(&mut st, s);
+        let xp1 = st.add(vec![x, one]);
+        let xp2 = st.add(vec![x, two]);
+        let (ia, ib) = (recip(&mut st, xm1), recip(&mut st, xp1));
+        assert_eq!(t, st.mul(vec![xp2, ia, ib]));
+        // Non-polynomial parts are generators: x + 1/sin(y) = (x sin(y) + 1) / sin(y)
+        let sy = st.func("sin", vec![y]);
+        let isy = recip(&mut st, sy);
+        let e = st.add(vec![x, isy]);
+        let t = together(&mut st, e);
+        assert_eq!(st.to_string(t), "sin(y)^-1 * (1 + sin(y) * x)");
+        // 1/0 is left alone
+        let zero = st.int(0);
+        let z = recip(&mut st, zero);
+        assert_eq!(together(&mut st, z), z);
+    }
+
+    #[test]
+    fn cancel_removes_multivariate_gcd() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let two = st.int(2);
+        let m_one = st.int(-1);
+        // (x^2 - y^2) / (2x^2 + 4xy + 2y^2) = (x - y) / (2x + 2y)
+        let x2 = st.pow(x, two);
+        let y2 = st.pow(y, two);
+        let my2 = st.mul(vec![m_one, y2]);
+        let num = st.add(vec![x2, my2]);
+        let four = st.int(4);
+        let t1 = st.mul(vec![two, x2]);
+        let t2 = st.mul(vec![four, x, y]);
+        let t3 = st.mul(vec![two, y2]);
+        let den = st.add(vec![t1, t2, t3]);
+        let iden = recip(&mut st, den);
+        let e = st.mul(vec![num, iden]);
+        let c = cancel(&mut st, e);
+        assert_eq!(st.to_string(c), "1/2 * (x + -1 * y) * (x + y)^-1");
+        // Polynomial results have no denominator
+        let xy = st.add(vec![x, y]);
+        let ixy = recip(&mut st, xy);
+        let e = st.mul(vec![num, ixy]);
+        let c = cancel(&mut st, e);
+        assert_eq!(st.to_string(c), "x + -1 * y");
+    }
+
+    #[test]
+    fn apart_splits_over_denominator_factors() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let a = st.sym("a");
+        let one = st.int(1);
+        let m_one = st.int(-1);
+        let two = st.int(2);
+        // 1/(x^2 - 1): the expanded denominator is split at its rational roots
+        let x2 = st.pow(x, two);
+        let x2m1 = st.add(vec![x2, m_one]);
+        let e = recip(&mut st, x2m1);
+        let r = apart(&mut st, e, "x");
+        assert_eq!(st.to_string(r), "-1/2 * (1 + x)^-1 + 1/2 * (-1 + x)^-1");
+        // Parameters: 1/((x - a)(x + a)) = 1/(2a (x - a)) - 1/(2a (x + a))
+        let ma = st.mul(vec![m_one, a]);
+        let xma = st.add(vec![x, ma]);
+        let xpa = st.add(vec![x, a]);
+        let d = st.mul(vec![xma, xpa]);
+        let e = recip(&mut st, d);
+        let r = apart(&mut st, e, "x");
+        assert_eq!(st.to_string(r), "(a + -1 * x)^-1 * -1/2 * a^-1 + -1/2 * a^-1 * (x + a)^-1");
+        // Repeated and irreducible factors: (x^3 + y) / (x^2 (x^2 + 1))
+        let three = st.int(3);
+        let x3 = st.pow(x, three);
+        let n = st.add(vec![x3, y]);
+        let x2p1 = st.add(vec![x2, one]);
+        let d = st.mul(vec![x2, x2p1]);
+        let id = recip(&mut st, d);
+        let e = st.mul(vec![n, id]);
+        let r = apart(&mut st, e, "x");
+        assert_eq!(st.to_string(r), "x^-2 * y + (1 + x^2)^-1 * (x + -1 * y)");
+        assert_eq!(cancel(&mut st, r), cancel(&mut st, e));
+        // Polynomial part and a double root: (x^3 + 1) / (x^2 - 2x + 1)
+        let n = st.add(vec![x3, one]);
+        let m_two = st.int(-2);
+        let m2x = st.mul(vec![m_two, x]);
+        let d = st.add(vec![x2, m2x, one]);
+        let id = recip(&mut st, d);
+        let e = st.mul(vec![n, id]);
+        let r = apart(&mut st, e, "x");
+        assert_eq!(st.to_string(r), "2 + 3 * (-1 + x)^-1 + (-1 + x)^-2 * 2 + x");
+        assert_eq!(cancel(&mut st, r), cancel(&mut st, e));
+        // Expressions not involving the variable are returned as they are
+        assert_eq!(apart(&mut st, e, "z"), e);
+        // x^4 + 4 has no rational roots but splits into two quadratics
+        let four = st.int(4);
+        let x4 = st.pow(x, four);
+        let d = st.add(vec![x4, four]);
+        let e = recip(&mut st, d);
+        let r = apart(&mut st, e, "x");
+        assert_eq!(st.get(r).op, Op::Add);
+        assert_eq!(cancel(&mut st, r), cancel(&mut st, e));
+    }
+
+    #[test]
+    fn factor_numerator_and_denominator() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let one = st.int(1);
+        let two = st.int(2);
+        let m_one = st.int(-1);
+        // (x^2 y - y) / (2x^2 + 2x) = (1/2) y (x - 1) / x
+        let x2 = st.pow(x, two);
+        let x2y = st.mul(vec![x2, y]);
+        let my = st.mul(vec![m_one, y]);
+        let num = st.add(vec![x2y, my]);
+        let t1 = st.mul(vec![two, x2]);
+        let t2 = st.mul(vec![two, x]);
+        let den = st.add(vec![t1, t2]);
+        let iden = recip(&mut st, den);
+        let e = st.mul(vec![num, iden]);
+        let f = factor(&mut st, e);
+        let half = st.rat(1, 2);
+        let xm1 = st.add(vec![x, m_one]);
+        let ix = recip(&mut st, x);
+        assert_eq!(f, st.mul(vec![half, y, xm1, ix]));
+        // Generators are factored over too: sin(y)^2 - 1
+        let s = st.func("sin", vec![y]);
+        let s2 = st.pow(s, two);
+        let e = st.add(vec![s2, m_one]);
+        let f = factor(&mut st, e);
+        let sm1 = st.add(vec![s, m_one]);
+        let sp1 = st.add(vec![s, one]);
+        assert_eq!(f, st.mul(vec![sm1, sp1]));
+    }
+
+    #[test]
+    fn cancel_with_coefficients_past_i64() {
+        // (x + 3)^45 (x + y) / ((x + 3)^45 (x - y)) = (x + y) / (x - y)
+        let mut st = Store::new();
+        let (x, y) = (st.sym("x"), st.sym("y"));
+        let three = st.int(3);
+        let n = st.int(45);
+        let m_one = st.int(-1);
+        let s = st.add(vec![x, three]);
+        let s45 = st.pow(s, n);
+        let xpy = st.add(vec![x, y]);
+        let my = st.mul(vec![m_one, y]);
+        let xmy = st.add(vec![x, my]);
+        let top = st.mul(vec![s45, xpy]);
+        let bottom = st.mul(vec![s45, xmy]);
+        let (top, bottom) = (crate::expand_poly(&mut st, top), crate::expand_poly(&mut st, bottom));
+        let ib = recip(&mut st, bottom);
+        let q = st.mul(vec![top, ib]);
+        let r = cancel(&mut st, q);
+        let id = recip(&mut st, xmy);
+        assert_eq!(r, st.mul(vec![xpy, id]));
+        // Big numbers are coefficients, not generators: 2^70 x / 2^71 = x / 2
+        let big = st.bigint(arith::BigInt::from(2i64).pow(70));
+        let bigger = st.bigint(arith::BigInt::from(2i64).pow(71));
+        let ib = recip(&mut st, bigger);
+        let q = st.mul(vec![big, x, ib]);
+        let half = st.rat(1, 2);
+        assert_eq!(cancel(&mut st, q), st.mul(vec![half, x]));
+        let e = crate::expr_to_bigmultipoly(&st, big).unwrap();
+        assert_eq!(e.as_constant(), st.as_bigrat(big));
+    }
+
+    #[test]
+    fn factor_leaves_large_inputs_alone() {
+        // (x + y + z + w + 1)^4 (x y - z w + 3) has a Kronecker image of degree
+        // 6^4 - 1, past the work limits: returned unchanged, promptly
+        let mut st = Store::new();
+        let (x, y, z, w) = (st.sym("x"), st.sym("y"), st.sym("z"), st.sym("w"));
+        let one = st.int(1);
+        let three = st.int(3);
+        let four = st.int(4);
+        let m_one = st.int(-1);
+        let s = st.add(vec![x, y, z, w, one]);
+        let s4 = st.pow(s, four);
+        let xy = st.mul(vec![x, y]);
+        let mzw = st.mul(vec![m_one, z, w]);
+        let q = st.add(vec![xy, mzw, three]);
+        let p = st.mul(vec![s4, q]);
+        let e = crate::expand_poly(&mut st, p);
+        assert_eq!(factor(&mut st, e), e);
+    }
+}
@@ -2,7 +2,7 @@
 
 use arith::{add_q, mul_q, Q};
 use expr_core::Store;
-use polys::{expr_to_unipoly, unipoly_to_expr, UniPoly};
+use polys::{expr_to_unipoly, unipoly_to_expr, BigMultiPoly, UniPoly};
 use proptest::prelude::*;
 
 fn small_q() -> impl Strategy<Value = Q> {
@@ -19,6 +19,16 @@ fn nonzero_poly() -> impl Strategy<Value = UniPoly> {
     small_poly().prop_filter("non-zero polynomial", |p| !p.is_zero() && p.degree().is_some())
 }
 
+fn small_bivariate() -> impl Strategy<Value = BigMultiPoly> {
+    // c x^i y^j with i, j <= 2
+    prop::collection::vec((-3i64..=3, 0u32..=2, 0u32..=2), 1..=4).prop_map(|terms| {
+        let (x, y) = (BigMultiPoly::var("x"), BigMultiPoly::var("y"));
+        terms.into_iter().fold(BigMultiPoly::zero(), |acc, (c, i, j)| {
+            acc.add(&x.pow(i).mul(&y.pow(j)).scale(Q::new(c, 1)))
+        })
+    })
+}
+
 proptest! {
     #[test]
     fn prop_add_eval_consistency(p in small_poly(), q in small_poly(), x in small_q()) {
@@ -84,4 +94,15 @@ proptest! {
         // gcd should be constant (degree 0) after reduction
         prop_assert!(matches!(g2.degree(), Some(0)) || g2.is_zero());
     }
+
+    #[test]
+    fn prop_factor_reconstructs(a in small_bivariate(), b in small_bivariate(), c in small_bivariate()) {
+        let p = a.mul(&b).mul(&b).mul(&c);
+        let (k, factors) = p.factor().expect("within the work limits");
+        let back = factors
+            .iter()
+            .fold(BigMultiPoly::constant(k), |acc, (f, e)| acc.mul(&f.pow(*e as u32)));
+        prop_assert_eq!(back, p);
+        prop_assert!(factors.iter().all(|(f, _)| !f.is_constant()));
+    }
 }
@@ -0,0 +1,367 @@
+//! Complex parts: `re`, `im`, `conjugate`, `abs` and `arg`.
+//!
+//! Symbols are complex unless the assumptions make them real (`Prop::Real`,
+//! or a sign). `as_real_imag` splits an expression into real and imaginary
+//! parts through sums, products, integer powers, `exp`, `ln`, the circular
+//! and hyperbolic functions and `conjugate`; parts it cannot split stay as
+//! `re(u)` and `im(u)`. `as_polar` gives a modulus and an argument.
+//!
+//! The simplifier evaluates calls of the five functions with the same rules:
+//! `re(x + I y) = x` for real `x` and `y`, `conjugate(exp(I t)) = exp(-I t)`,
+//! `abs(x) = x` for nonnegative `x`, `abs(3 + 4 I) = 5`, `arg(-2) = pi`.
+
+use crate::relational::{is_real, sign_set, NEG, POS, ZERO};
+use crate::{simplify_rec, simplify_with};
+use assumptions::{Context, Facts};
+use expr_core::{Constant, ExprId, Op, Payload, Store};
+
+/// Largest integer power multiplied out by `as_real_imag`.
+const MAX_POWER: i64 = 16;
+
+/// The real and imaginary parts of `id`, simplified.
+pub fn as_real_imag(store: &mut Store, id: ExprId, ctx: &Context) -> (ExprId, ExprId) {
+    let (re, im) = rect(store, id, ctx);
+    (simplify_with(store, re, ctx), simplify_with(store, im, ctx))
+}
+
+/// A modulus `r >= 0` and an argument `theta` with `id = r exp(I theta)`,
+/// simplified. The argument of a product is the sum of the arguments of its
+/// factors, so it need not lie in (-pi, pi].
+pub fn as_polar(store: &mut Store, id: ExprId, ctx: &Context) -> (ExprId, ExprId) {
+    let (r, theta) = polar(store, id, ctx);
+    (simplify_with(store, r, ctx), simplify_with(store, theta, ctx))
+}
+
+fn polar(store: &mut Store, id: ExprId, ctx: &dyn Facts) -> (ExprId, ExprId) {
+    let n = store.get(id);
+    match (&n.op, &n.payload) {
+        (Op::Mul, _) => {
+            let factors = n.children.clone();
+            let (rs, thetas): (Vec<ExprId>, Vec<ExprId>) =
+                factors.into_iter().map(|f| polar(store, f, ctx)).unzip();
+            (store.mul(rs), store.add(thetas))
+        }
+        (Op::Pow, _) => {
+            let (base, exp) = (n.children[0], n.children[1]);
+            if store.get(exp).op != Op::Integer {
+                return opaque_polar(store, id);
+            }
+            let (r, theta) = polar(store, base, ctx);
+            (store.pow(r, exp), store.mul(vec![exp, theta]))
+        }
+        (Op::Function, Payload::Func(f)) if f == "exp" && n.children.len() == 1 => {
+            let (a, b) = rect(store, n.children[0], ctx);
+            (store.func("exp", vec![a]), b)
+        }
+        _ => opaque_polar(store, id),
+    }
+}
+
+fn opaque_polar(store: &mut Store, id: ExprId) -> (ExprId, ExprId) {
+    (store.func("abs", vec![id]), store.func("arg", vec![id]))
+}
+
+/// Unsimplified real and imaginary parts.
+fn rect(store: &mut Store, id: ExprId, ctx: &dyn Facts) -> (ExprId, ExprId) {
+    if is_real(store, id, ctx) {
+        return (id, store.int(0));
+    }
+    let n = store.get(id);
+    let (op, payload, children) = (n.op.clone(), n.payload.clone(), n.children.clone());
+    match (op, payload) {
+        (Op::Constant, Payload::Const(Constant::I)) => (store.int(0), store.int(1)),
+        (Op::Add, _) => {
+            let (res, ims): (Vec<ExprId>, Vec<ExprId>) =
+                children.into_iter().map(|c| rect(store, c, ctx)).unzip();
+            (store.add(res), store.add(ims))
+        }
+        (Op::Mul, _) => {
+            // Real factors scale both parts; the others multiply as complex numbers
+            let (real, other): (Vec<ExprId>, Vec<ExprId>) =
+                children.into_iter().partition(|&c| is_real(store, c, ctx));
+            let one = store.int(1);
+            let zero = store.int(0);
+            let (a, b) = other.into_iter().fold((one, zero), |acc, c| {
+                let z = rect(store, c, ctx);
+                mul_parts(store, acc, z)
+            });
+            let r = store.mul(real);
+            (store.mul(vec![r, a]), store.mul(vec![r, b]))
+        }
+        (Op::Pow, _) => match store.get(children[1]).payload {
+            Payload::Int(k) if k != 0 && k.abs() <= MAX_POWER => {
+                let z = rect(store, children[0], ctx);
+                let z = if k < 0 { inv_parts(store, z) } else { z };
+                let one = store.int(1);
+                let zero = store.int(0);
+                (0..k.abs()).fold((one, zero), |acc, _| mul_parts(store, acc, z))
+            }
+            _ => opaque_rect(store, id),
+        },
+        (Op::Symbol, _) => opaque_rect(store, id),
+        (Op::Function, Payload::Func(f)) if children.len() == 1 => {
+            let w = children[0];
+            match f.as_str() {
+                "exp" => {
+                    let (a, b) = rect(store, w, ctx);
+                    let ea = store.func("exp", vec![a]);
+                    let (cb, sb) = (store.func("cos", vec![b]), store.func("sin", vec![b]));
+                    (store.mul(vec![ea, cb]), store.mul(vec![ea, sb]))
+                }
+                "sin" | "cos" | "sinh" | "cosh" => circular(store, &f, w, ctx),
+                "ln" | "log" => {
+                    let r = store.func("abs", vec![w]);
+                    (store.func("ln", vec![r]), store.func("arg", vec![w]))
+                }
+                "conjugate" => {
+                    let (a, b) = rect(store, w, ctx);
+                    let m_one = store.int(-1);
+                    (a, store.mul(vec![m_one, b]))
+                }
+                _ => opaque_rect(store, id),
+            }
+        }
+        _ => opaque_rect(store, id),
+    }
+}
+
+fn opaque_rect(store: &mut Store, id: ExprId) -> (ExprId, ExprId) {
+    (store.func("re", vec![id]), store.func("im", vec![id]))
+}
+
+/// (a + I b)(c + I d) = (ac - bd) + I (ad + bc)
+fn mul_parts(
+    store: &mut Store,
+    (a, b): (ExprId, ExprId),
+    (c, d): (ExprId, ExprId),
+) -> (ExprId, ExprId) {
+    let m_one = store.int(-1);
+    let ac = store.mul(vec![a, c]);
+    let bd = store.mul(vec![m_one, b, d]);
+    let ad = store.mul(vec![a, d]);
+    let bc = store.mul(vec![b, c]);
+    (store.add(vec![ac, bd]), store.add(vec![ad, bc]))
+}
+
+/// 1/(a + I b) = (a - I b)/(a^2 + b^2)
+fn inv_parts(store: &mut Store, (a, b): (ExprId, ExprId)) -> (ExprId, ExprId) {
+    let m_one = store.int(-1);
+    let norm = sq_norm(store, a, b);
+    let inv = recip(store, norm);
+    (store.mul(vec![a, inv]), store.mul(vec![m_one, b, inv]))
+}
+
+/// a^2 + b^2, written as products so that numbers fold (`Store::pow` keeps
+/// `3^2`).
+fn sq_norm(store: &mut Store, a: ExprId, b: ExprId) -> ExprId {
+    let a2 = store.mul(vec![a, a]);
+    let b2 = store.mul(vec![b, b]);
+    store.add(vec![a2, b2])
+}
+
+/// 1/x, as a rational for numeric `x`.
+fn recip(store: &mut Store, x: ExprId) -> ExprId {
+    match store.get(x).payload {
+        Payload::Int(n) if n != 0 => store.rat(1, n),
+        Payload::Rat(n, d) => store.rat(d, n),
+        _ => {
+            let m_one = store.int(-1);
+            store.pow(x, m_one)
+        }
+    }
+}
+
+/// sin, cos, sinh and cosh of `a + I b`.
+fn circular(store: &mut Store, f: &str, w: ExprId, ctx: &dyn Facts) -> (ExprId, ExprId) {
+    let (a, b) = rect(store, w, ctx);
+    let mut call = |g: &str, x: ExprId| store.func(g, vec![x]);
+    let (sa, ca, sha, cha) = (call("sin", a), call("cos", a), call("sinh", a), call("cosh", a));
+    let (sb, cb, shb, chb) = (call("sin", b), call("cos", b), call("sinh", b), call("cosh", b));
+    let m_one = store.int(-1);
+    match f {
+        // sin(a + I b) = sin a cosh b + I cos a sinh b
+        "sin" => (store.mul(vec![sa, chb]), store.mul(vec![ca, shb])),
+        // cos(a + I b) = cos a cosh b - I sin a sinh b
+        "cos" => (store.mul(vec![ca, chb]), store.mul(vec![m_one, sa, shb])),
+        // sinh(a + I b) = sinh a cos b + I cosh a sin b
+        "sinh" => (store.mul(vec![sha, cb]), store.mul(vec![cha, sb])),
+        // cosh(a + I b) = cosh a cos b + I sinh a sin b
+        _ => (store.mul(vec![cha, cb]), store.mul(vec![sha, sb])),
+    }
+}
+
+/// Whether `id` contains a `re` or `im` call.
+fn has_part_call(store: &Store, id: ExprId) -> bool {
+    store
+        .preorder(id)
+        .any(|n| matches!(&store.get(n).payload, Payload::Func(f) if f == "re" || f == "im"))
+}
+
+/// Real and imaginary parts of `z` if both come out free of `re`/`im` calls.
+fn known_parts(store: &mut Store, z: ExprId, ctx: &dyn Facts) -> Option<(ExprId, ExprId)> {
+    let (a, b) = rect(store, z, ctx);
+    if has_part_call(store, a) || has_part_call(store, b) {
+        return None;
+    }
+    Some((simplify_rec(store, a, ctx), simplify_rec(store, b, ctx)))
+}
+
+/// Value of `name(arg)` for the five complex-part functions, if a rule applies.
+pub(crate) fn complex_function(
+    store: &mut Store,
+    name: &str,
+    z: ExprId,
+    ctx: &dyn Facts,
+) -> Option<ExprId> {
+    let out = match name {
+        "re" | "im" => {
+            let (re, im) = rect(store, z, ctx);
+            let part = if name == "re" { re } else { im };
+            // An opaque part is the call itself; simplifying it would recurse
+            if part == store.func(name, vec![z]) {
+                return None;
+            }
+            simplify_rec(store, part, ctx)
+        }
+        "conjugate" => conjugate(store, z, ctx),
+        "abs" => abs(store, z, ctx)?,
+        "arg" => arg(store, z, ctx)?,
+        _ => return None,
+    };
+    (out != store.func(name, vec![z])).then_some(out)
+}
+
+/// Structural conjugate: reals are fixed, I becomes -I, and conjugation
+/// passes through sums, products, integer powers and the functions with
+/// real Taylor coefficients.
+fn conjugate(store: &mut Store, z: ExprId, ctx: &dyn Facts) -> ExprId {
+    if is_real(store, z, ctx) {
+        return z;
+    }
+    let n = store.get(z);
+    let (op, payload, children) = (n.op.clone(), n.payload.clone(), n.children.clone());
+    match (op, payload) {
+        (Op::Constant, Payload::Const(Constant::I)) => {
+            let m_one = store.int(-1);
+            store.mul(vec![m_one, z])
+        }
+        (Op::Add, _) => {
+            let terms: Vec<ExprId> =
+                children.into_iter().map(|c| conjugate(store, c, ctx)).collect();
+            store.add(terms)
+        }
+        (Op::Mul, _) => {
+            let factors: Vec<ExprId> =
+                children.into_iter().map(|c| conjugate(store, c, ctx)).collect();
+            store.mul(factors)
+        }
+        (Op::Pow, _) if store.get(children[1]).op == Op::Integer => {
+            let b = conjugate(store, children[0], ctx);
+            store.pow(b, children[1])
+        }
+        (Op::Function, Payload::Func(f)) if children.len() == 1 => match f.as_str() {
+            "conjugate" => children[0],
+            "exp" | "sin" | "cos" | "tan" | "sinh" | "cosh" | "tanh" => {
+                let w = conjugate(store, children[0], ctx);
+                store.func(f, vec![w])
+            }
+            _ => store.func("conjugate", vec![z]),
+        },
+        _ => store.func("conjugate", vec![z]),
+    }
+}
+
+fn abs(store: &mut Store, z: ExprId, ctx: &dyn Facts) -> Option<ExprId> {
+    let sign = sign_set(store, z, ctx);
+    if sign & NEG == 0 {
+        return Some(z);
+    }
+    if sign & POS == 0 {
+        let m_one = store.int(-1);
+        return Some(store.mul(vec![m_one, z]));
+    }
+    let n = store.get(z);
+    let (op, payload, children) = (n.op.clone(), n.payload.clone(), n.children.clone());
+    match (op, payload) {
+        (Op::Mul, _) => {
+            let factors: Vec<ExprId> =
+                children.into_iter().map(|c| store.func("abs", vec![c])).collect();
+            let p = store.mul(factors);
+            Some(simplify_rec(store, p, ctx))
+        }
+        (Op::Pow, _) => {
+            let (base, exp) = (children[0], children[1]);
+            if let Payload::Int(k) = store.get(exp).payload {
+                // |b^k| = b^k for real b and even k
+                if k % 2 == 0 && is_real(store, base, ctx) {
+                    return Some(z);
+                }
+                let b = store.func("abs", vec![base]);
+                let b = simplify_rec(store, b, ctx);
+                return Some(store.pow(b, exp));
+            }
+            None
+        }
+        (Op::Function, Payload::Func(f)) if children.len() == 1 => match f.as_str() {
+            "abs" | "conjugate" => Some(store.func("abs", vec![children[0]])),
+            "exp" => {
+                let (a, _) = rect(store, children[0], ctx);
+                let a = simplify_rec(store, a, ctx);
+                Some(store.func("exp", vec![a]))
+            }
+            _ => None,
+        },
+        _ => {
+            // |a + I b| = sqrt(a^2 + b^2) once both parts are known; a real
+            // value of unknown sign keeps its abs
+            let (a, b) = known_parts(store, z, ctx)?;
+            if store.get(b).payload == Payload::Int(0) {
+                return None;
+            }
+            let s = sq_norm(store, a, b);
+            let s = simplify_rec(store, s, ctx);
+            let half = store.rat(1, 2);
+            Some(store.pow(s, half))
+        }
+    }
+}
+
+fn arg(store: &mut Store, z: ExprId, ctx: &dyn Facts) -> Option<ExprId> {
+    let pi = store.constant(Constant::Pi);
+    match sign_set(store, z, ctx) {
+        POS => return Some(store.int(0)),
+        NEG => return Some(pi),
+        // arg(0) is undefined
+        ZERO => return None,
+        _ => {}
+    }
+    // arg(c w) = arg(w) for positive c
+    if store.get(z).op == Op::Mul {
+        let factors = store.get(z).children.clone();
+        let (pos, rest): (Vec<ExprId>, Vec<ExprId>) =
+            factors.into_iter().partition(|&f| sign_set(store, f, ctx) == POS);
+        if !pos.is_empty() {
+            let w = store.mul(rest);
+            let a = store.func("arg", vec![w]);
+            return Some(simplify_rec(store, a, ctx));
+        }
+    }
+    let (a, b) = known_parts(store, z, ctx)?;
+    let (sa, sb) = (sign_set(store, a, ctx), sign_set(store, b, ctx));
+    let half = store.rat(1, 2);
+    let m_half = store.rat(-1, 2);
+    Some(match (sa, sb) {
+        (ZERO, ZERO) => return None,
+        // On the imaginary axis
+        (ZERO, POS) => store.mul(vec![half, pi]),
+        (ZERO, NEG) => store.mul(vec![m_half, pi]),
+        // Right half-plane: atan(b/a)
+        (POS, _) => {
+            let inv = recip(store, a);
+            let q = store.mul(vec![b, inv]);
+            let q = simplify_rec(store, q, ctx);
+            store.func("atan", vec![q])
+        }
+        _ => store.func("atan2", vec![b, a]),
+    })
+}
@@ -0,0 +1,263 @@
+//! Configurable expansion.
+//!
+//! `expand` rewrites bottom-up. Each flag in `ExpandOptions` enables one family
+//! of rules:
+//! - `multinomial`: products and natural powers of sums are multiplied out
+//!   through sparse `BigMultiPoly` arithmetic (`polys::expand_poly`)
+//! - `trig`: sin/cos/tan of sums and integer multiples
+//! - `log`: ln(a b) = ln a + ln b, ln(a^n) = n ln a, only where the assumptions
+//!   make the factors positive (see `log_simplify`)
+//! - `power_base`: (a b)^c = a^c b^c for integer c, or for the positive factors
+//! - `power_exp`: a^(b + c) = a^b a^c
+
+use crate::log_simplify::simplify_logarithms;
+use assumptions::{Context, Prop, Truth};
+use expr_core::{ExprId, Op, Payload, Store};
+use std::collections::HashMap;
+
+/// Which expansions `expand` performs. The default enables everything except
+/// `trig`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExpandOptions {
+    pub multinomial: bool,
+    pub trig: bool,
+    pub log: bool,
+    pub power_base: bool,
+    pub power_exp: bool,
+}
+
+impl Default for ExpandOptions {
+    fn default() -> Self {
+        Self { multinomial: true, trig: false, log: true, power_base: true, power_exp: true }
+    }
+}
+
+impl ExpandOptions {
+    /// All flags off; enable the wanted ones with struct update syntax.
+    pub fn none() -> Self {
+        Self { multinomial: false, trig: false, log: false, power_base: false, power_exp: false }
+    }
+}
+
+/// Expand with a default assumptions context (so `log` only fires on
+/// positive numeric factors).
+pub fn expand(store: &mut Store, id: ExprId, opts: &ExpandOptions) -> ExprId {
+    expand_with(store, id, opts, &Context::default())
+}
+
+/// Expand with an explicit assumptions context.
+pub fn expand_with(store: &mut Store, id: ExprId, opts: &ExpandOptions, ctx: &Context) -> ExprId {
+    let mut memo = HashMap::new();
+    expand_rec(store, id, opts, ctx, &mut memo)
+}
+
+fn expand_rec(
+    store: &mut Store,
+    id: ExprId,
+    opts: &ExpandOptions,
+    ctx: &Context,
+    memo: &mut HashMap<ExprId, ExprId>,
+) -> ExprId {
+    if let Some(&r) = memo.get(&id) {
+        return r;
+    }
+    let node = store.get(id);
+    if node.children.is_empty() {
+        return id;
+    }
+    let (op, payload, old) = (node.op.clone(), node.payload.clone(), node.children.clone());
+    let children: Vec<ExprId> =
+        old.iter().map(|&c| expand_rec(store, c, opts, ctx, memo)).collect();
+    let rebuilt = if children == old { id } else { store.build(op, payload, children) };
+    let out = match rewrite(store, rebuilt, opts, ctx) {
+        // A rule introduced new structure: expand that too
+        Some(r) => expand_rec(store, r, opts, ctx, memo),
+        None if opts.multinomial && is_polynomial(store, rebuilt) => {
+            polys::expand_poly(store, rebuilt)
+        }
+        None => rebuilt,
+    };
+    memo.insert(id, out);
+    out
+}
+
+/// A sum, product or natural power of a sum: what `multinomial` multiplies
+/// out (sums so that like terms from different products are collected).
+fn is_polynomial(store: &Store, id: ExprId) -> bool {
+    let n = store.get(id);
+    match n.op {
+        Op::Add | Op::Mul => true,
+        Op::Pow => {
+            store.get(n.children[0]).op == Op::Add
+                && matches!(store.get(n.children[1]).payload, Payload::Int(k) if k >= 2)
+        }
+        _ => false,
+    }
+}
+
+/// One rule application at the root of `id`, if any applies.
+fn rewrite(store: &mut Store, id: ExprId, opts: &ExpandOptions, ctx: &Context) -> Option<ExprId> {
+    let node = store.get(id);
+    match (&node.op, &node.payload) {
+        (Op::Pow, _) => {
+            let (base, exp) = (node.children[0], node.children[1]);
+            if opts.power_exp && store.get(exp).op == Op::Add {
+                let terms = store.get(exp).children.clone();
+                let factors: Vec<ExprId> = terms.into_iter().map(|t| store.pow(base, t)).collect();
+                return Some(store.mul(factors));
+            }
+            if opts.power_base && store.get(base).op == Op::Mul {
+                return split_power_base(store, base, exp, ctx);
+            }
+            None
+        }
+        (Op::Function, Payload::Func(name)) if node.children.len() == 1 => {
+            let (name, arg) = (name.clone(), node.children[0]);
+            match name.as_str() {
+                "sin" | "cos" | "tan" if opts.trig => expand_trig(store, &name, arg),
+                "ln" | "log" if opts.log => {
+                    let r = simplify_logarithms(store, id, ctx);
+                    (r != id).then_some(r)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// (a b)^c = a^c b^c: all factors for integer c, otherwise the positive ones.
+fn split_power_base(store: &mut Store, base: ExprId, exp: ExprId, ctx: &Context) -> Option<ExprId> {
+    let factors = store.get(base).children.clone();
+    if store.get(exp).op == Op::Integer {
+        let powers: Vec<ExprId> = factors.into_iter().map(|f| store.pow(f, exp)).collect();
+        return Some(store.mul(powers));
+    }
+    let (positive, rest): (Vec<ExprId>, Vec<ExprId>) =
+        factors.into_iter().partition(|&f| is_positive(store, f, ctx));
+    if positive.is_empty() {
+        return None;
+    }
+    let mut powers: Vec<ExprId> = positive.into_iter().map(|f| store.pow(f, exp)).collect();
+    if !rest.is_empty() {
+        let r = store.mul(rest);
+        powers.push(store.pow(r, exp));
+    }
+    Some(store.mul(powers))
+}
+
+fn is_positive(store: &Store, id: ExprId, ctx: &Context) -> bool {
+    match (&store.get(id).op, &store.get(id).payload) {
+        (Op::Integer, Payload::Int(n)) => *n > 0,
+        (Op::Rational, Payload::Rat(n, _)) => *n > 0,
+        (Op::Symbol, Payload::Sym(s)) => matches!(ctx.has(s, Prop::Positive), Truth::True),
+        _ => false,
+    }
+}
+
+/// sin/cos/tan of `a + b` or of `n u` for an integer `n` (|n| >= 2, or n < 0).
+fn expand_trig(store: &mut Store, name: &str, arg: ExprId) -> Option<ExprId> {
+    let (a, b) = match split_arg(store, arg)? {
+        Split::Sum(a, b) => (a, b),
+        Split::Negated(u) => {
+            let f = store.func(name, vec![u]);
+            if name == "cos" {
+                return Some(f);
+            }
+            let m_one = store.int(-1);
+            return Some(store.mul(vec![m_one, f]));
+        }
+    };
+    let m_one = store.int(-1);
+    Some(match name {
+        "sin" => {
+            let (sa, cb, ca, sb) = (sin(store, a), cos(store, b), cos(store, a), sin(store, b));
+            let t1 = store.mul(vec![sa, cb]);
+            let t2 = store.mul(vec![ca, sb]);
+            store.add(vec![t1, t2])
+        }
+        "cos" => {
+            let (ca, cb, sa, sb) = (cos(store, a), cos(store, b), sin(store, a), sin(store, b));
+            let t1 = store.mul(vec![ca, cb]);
+            let t2 = store.mul(vec![m_one, sa, sb]);
+            store.add(vec![t1, t2])
+        }
+        _ => {
+            let ta = store.func("tan", vec![a]);
+            let tb = store.func("tan", vec![b]);
+            let num = store.add(vec![ta, tb]);
+            let one = store.int(1);
+            let prod = store.mul(vec![m_one, ta, tb]);
+            let den = store.add(vec![one, prod]);
+            let inv = store.pow(den, m_one);
+            store.mul(vec![num, inv])
+        }
+    })
+}
+
+fn sin(store: &mut Store, x: ExprId) -> ExprId {
+    store.func("sin", vec![x])
+}
+
+fn cos(store: &mut Store, x: ExprId) -> ExprId {
+    store.func("cos", vec![x])
+}
+
+enum Split {
+    /// The argument is `a + b`.
+    Sum(ExprId, ExprId),
+    /// The argument is `-u`.
+    Negated(ExprId),
+}
+
+fn split_arg(store: &mut Store, arg: ExprId) -> Option<Split> {
+    let node = store.get(arg);
+    match node.op {
+        Op::Add => {
+            let children = node.children.clone();
+            let rest = store.add(children[1..].to_vec());
+            Some(Split::Sum(children[0], rest))
+        }
+        Op::Mul => {
+            let children = node.children.clone();
+            let Payload::Int(n) = store.get(children[0]).payload else {
+                return None;
+            };
+            let u = store.mul(children[1..].to_vec());
+            if n < 0 {
+                let m = store.int(n.checked_neg()?);
+                return Some(Split::Negated(store.mul(vec![m, u])));
+            }
+            if n < 2 {
+                return None;
+            }
+            // n u = (n - 1) u + u, built without re-collecting the terms
+            let k = store.int(n - 1);
+            Some(Split::Sum(store.mul(vec![k, u]), u))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trig_multiple_angles() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let three = st.int(3);
+        let tx = st.mul(vec![three, x]);
+        let s3 = st.func("sin", vec![tx]);
+        let opts = ExpandOptions { trig: true, ..ExpandOptions::default() };
+        let r = expand(&mut st, s3, &opts);
+        // sin(3x) = 3 sin(x) cos(x)^2 - sin(x)^3
+        assert_eq!(st.to_string(r), "-1 * sin(x)^3 + sin(x) * cos(x)^2 * 3");
+        // sin(-x) = -sin(x), cos(-x) = cos(x)
+        let m_one = st.int(-1);
+        let mx = st.mul(vec![m_one, x]);
+        let c = st.func("cos", vec![mx]);
+        assert_eq!(expand(&mut st, c, &opts), st.func("cos", vec![x]));
+    }
+}
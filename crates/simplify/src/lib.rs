@@ -5,18 +5,47 @@
 //!   - Trigonometric identities (sum-to-product, product-to-sum, half-angle)
 //!   - Radical simplification (denesting, rationalization, perfect powers)
 //!   - Logarithm expansion/contraction with branch-cut awareness
-
+//!   - Relations and connectives decided under assumptions (`x > 0` for positive x)
+//!   - Elementary functions at constants (sin(pi) = 0, ln(E) = 1, exp(-oo) = 0)
+//!   - Float literals are left as-is; `nsimplify` converts them to exact rationals
+//!   - `expand` with independent multinomial/trig/log/power-base/power-exp flags
+//!   - `simplify_traced` records each rewrite step (rule, subexpressions, assumptions)
+//!   - `trigsimp`: Fu-style search over trigonometric rewrite rules for the cheapest form
+//!   - `simplify_with_options`: preferred-form measure, step/time budget, enabled passes
+//!   - re/im/conjugate/abs/arg under assumptions; `as_real_imag` and `as_polar`
+//!   - `rewrite` into a target family (exp, trig, sin/cos, log, Gamma, factorial)
+
+mod complex;
+mod expand;
 mod log_simplify;
+mod nsimplify;
+mod options;
 mod radical_simplify;
+mod relational;
+mod rewrite;
+mod special_values;
+mod trace;
 mod trig_identities;
+mod trigsimp;
 
+pub use complex::{as_polar, as_real_imag};
+pub use expand::{expand, expand_with, ExpandOptions};
 pub use log_simplify::{contract_logarithms, simplify_logarithms};
+pub use nsimplify::{float_to_simplest_rat, nsimplify};
+pub use options::{Measure, Pass, SimplifyOptions};
 pub use radical_simplify::simplify_radicals;
+pub use rewrite::{rewrite, RewriteTarget};
+pub use trace::{Step, Trace};
 pub use trig_identities::simplify_trig;
+pub use trigsimp::trigsimp;
 
-use arith::{rat_add, rat_mul};
-use assumptions::{Context, Prop, Truth};
+use assumptions::{Context, Facts, Prop, Recorder, Truth};
 use expr_core::{ExprId, Op, Payload, Store};
+use log_simplify::{contract_logarithms_within, simplify_logarithms_within};
+use options::Budget;
+use radical_simplify::simplify_radicals_within;
+use trig_identities::simplify_trig_within;
+use trigsimp::trigsimp_within;
 
 /// Simplify with a default assumptions context.
 /// Results are memoized in the store to avoid redundant computation.
@@ -35,18 +64,134 @@ pub fn simplify_with(store: &mut Store, id: ExprId, ctx: &Context) -> ExprId {
         if let Some(cached) = store.get_simplify_cached(id) {
             return cached;
         }
-        let result = simplify_full(store, id, ctx);
+        let result = simplify_full(store, id, ctx, &SimplifyOptions::default(), None);
         store.cache_simplify(id, result);
         result
     } else {
-        simplify_full(store, id, ctx)
+        simplify_full(store, id, ctx, &SimplifyOptions::default(), None)
+    }
+}
+
+/// How the pipeline simplifies trigonometric subexpressions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TrigStrategy {
+    /// The fixed identities of `simplify_trig`.
+    #[default]
+    Identities,
+    /// `trigsimp`: search over Fu's rules for the cheapest form. Slower, but
+    /// finds rewrites that need several steps (sin^4 - cos^4 = -cos(2x)).
+    Fu,
+}
+
+/// `simplify_with` using the given trigonometric strategy. Only the default
+/// strategy shares the memo cache.
+pub fn simplify_with_strategy(
+    store: &mut Store,
+    id: ExprId,
+    ctx: &Context,
+    strategy: TrigStrategy,
+) -> ExprId {
+    match strategy {
+        TrigStrategy::Identities => simplify_with(store, id, ctx),
+        _ => {
+            let opts = SimplifyOptions { strategy, ..SimplifyOptions::default() };
+            simplify_full(store, id, ctx, &opts, None)
+        }
+    }
+}
+
+/// Simplify under `opts`: only the enabled passes run, no rewrite may
+/// increase `opts.measure`, and rewriting stops once `max_steps` rewrites
+/// were taken or `time_limit` has passed (never cached).
+pub fn simplify_with_options(
+    store: &mut Store,
+    id: ExprId,
+    ctx: &Context,
+    opts: &SimplifyOptions,
+) -> ExprId {
+    simplify_full(store, id, ctx, opts, None)
+}
+
+/// `simplify_with` that also returns the rewrite steps taken (never cached).
+pub fn simplify_traced(store: &mut Store, id: ExprId, ctx: &Context) -> (ExprId, Trace) {
+    let mut trace = Trace::default();
+    let result = simplify_full(store, id, ctx, &SimplifyOptions::default(), Some(&mut trace));
+    (result, trace)
+}
+
+/// The state shared by the passes of one simplification.
+struct Pipeline<'a, 't> {
+    ctx: &'a Context,
+    opts: &'a SimplifyOptions,
+    budget: Budget<'a>,
+    trace: Option<&'t mut Trace>,
+}
+
+impl Pipeline<'_, '_> {
+    /// Run one pass on the whole expression, recording it in the trace if it
+    /// changed. Disabled passes and passes after the budget ran out leave it
+    /// unchanged.
+    fn run(
+        &mut self,
+        store: &mut Store,
+        current: ExprId,
+        (kind, rule): (Pass, &'static str),
+        pass: impl FnOnce(&mut Store, ExprId, &dyn Facts, &Budget) -> ExprId,
+    ) -> ExprId {
+        if !self.opts.enabled(kind) || self.budget.exhausted() {
+            return current;
+        }
+        let Some(trace) = self.trace.as_deref_mut() else {
+            return pass(store, current, self.ctx, &self.budget);
+        };
+        let recorder = Recorder::new(self.ctx);
+        let next = pass(store, current, &recorder, &self.budget);
+        let facts = recorder.into_facts();
+        if next != current {
+            trace.record(store, rule, current, next, facts);
+        }
+        next
+    }
+}
+
+/// Basic simplification is canonicalization; the budget judges it as a whole.
+/// Under a time limit the top-level children are simplified one at a time and
+/// the pass gives up, leaving `id` unchanged, once the deadline passes between
+/// two of them; a single child is never interrupted.
+fn basic_pass(store: &mut Store, id: ExprId, ctx: &dyn Facts, budget: &Budget) -> ExprId {
+    let children = store.get(id).children.clone();
+    if !budget.timed() || children.is_empty() {
+        let r = simplify_rec(store, id, ctx);
+        return budget.choose(store, id, r);
+    }
+    let mut simplified = Vec::with_capacity(children.len());
+    for c in children {
+        if budget.exhausted() {
+            return id;
+        }
+        simplified.push(simplify_rec(store, c, ctx));
+    }
+    if budget.exhausted() {
+        return id;
     }
+    let (op, payload) = (store.get(id).op.clone(), store.get(id).payload.clone());
+    let rebuilt = store.build(op, payload, simplified);
+    let r = simplify_rec(store, rebuilt, ctx);
+    budget.choose(store, id, r)
 }
 
 /// Full simplification pipeline: basic + advanced passes
-fn simplify_full(store: &mut Store, id: ExprId, ctx: &Context) -> ExprId {
+fn simplify_full(
+    store: &mut Store,
+    id: ExprId,
+    ctx: &Context,
+    opts: &SimplifyOptions,
+    trace: Option<&mut Trace>,
+) -> ExprId {
+    let mut p = Pipeline { ctx, opts, budget: Budget::new(opts), trace };
+
     // Phase 1: Basic simplification (canonical forms, like-term collection)
-    let after_basic = simplify_rec(store, id, ctx);
+    let after_basic = p.run(store, id, (Pass::Basic, "basic"), basic_pass);
 
     // Phase 2: Advanced passes (iteratively until fixpoint or max iterations)
     let mut current = after_basic;
@@ -57,15 +202,28 @@ fn simplify_full(store: &mut Store, id: ExprId, ctx: &Context) -> ExprId {
 
         // Apply advanced simplifiers in sequence
         // First try calculus-specific simplifiers (includes Pythagorean identity)
-        current = apply_calculus_simplify(store, current);
-        current = simplify_trig(store, current);
-        current = simplify_radicals(store, current);
-        current = simplify_logarithms(store, current, ctx);
+        current = p.run(store, current, (Pass::Pythagorean, "pythagorean"), |st, e, _, b| {
+            apply_calculus_simplify(st, e, b)
+        });
+        current = match opts.strategy {
+            TrigStrategy::Identities => {
+                p.run(store, current, (Pass::Trig, "trig"), |st, e, _, b| {
+                    simplify_trig_within(st, e, b)
+                })
+            }
+            TrigStrategy::Fu => p.run(store, current, (Pass::Trig, "trigsimp"), trigsimp_within),
+        };
+        current = p.run(store, current, (Pass::Radicals, "radicals"), |st, e, _, b| {
+            simplify_radicals_within(st, e, b)
+        });
+        current =
+            p.run(store, current, (Pass::ExpandLog, "expand_log"), simplify_logarithms_within);
         // After expansion opportunities, try contracting log sums back where beneficial
-        current = contract_logarithms(store, current, ctx);
+        current =
+            p.run(store, current, (Pass::ContractLog, "contract_log"), contract_logarithms_within);
 
         // Recursively simplify to catch nested patterns
-        current = simplify_rec(store, current, ctx);
+        current = p.run(store, current, (Pass::Basic, "basic"), basic_pass);
 
         // Check for fixpoint
         if current == before {
@@ -78,13 +236,18 @@ fn simplify_full(store: &mut Store, id: ExprId, ctx: &Context) -> ExprId {
 
 /// Apply calculus-specific simplification (Pythagorean, double-angle, hyperbolic)
 /// This recursively traverses the expression tree
-fn apply_calculus_simplify(store: &mut Store, expr: ExprId) -> ExprId {
+fn apply_calculus_simplify(store: &mut Store, expr: ExprId, budget: &Budget) -> ExprId {
+    if budget.exhausted() {
+        return expr;
+    }
     // First recurse into children
     let expr_after_children = match store.get(expr).op {
         Op::Add | Op::Mul => {
             let children = store.get(expr).children.clone();
-            let simplified_children: Vec<ExprId> =
-                children.iter().map(|&c| apply_calculus_simplify(store, c)).collect::<Vec<_>>();
+            let simplified_children: Vec<ExprId> = children
+                .iter()
+                .map(|&c| apply_calculus_simplify(store, c, budget))
+                .collect::<Vec<_>>();
 
             // Early exit if children unchanged
             if simplified_children.iter().zip(children.iter()).all(|(a, b)| a == b) {
@@ -99,8 +262,8 @@ fn apply_calculus_simplify(store: &mut Store, expr: ExprId) -> ExprId {
         }
         Op::Pow => {
             let children = store.get(expr).children.clone();
-            let base = apply_calculus_simplify(store, children[0]);
-            let exp = apply_calculus_simplify(store, children[1]);
+            let base = apply_calculus_simplify(store, children[0], budget);
+            let exp = apply_calculus_simplify(store, children[1], budget);
 
             // Early exit if unchanged
             if base == children[0] && exp == children[1] {
@@ -115,8 +278,10 @@ fn apply_calculus_simplify(store: &mut Store, expr: ExprId) -> ExprId {
                 _ => return expr,
             };
             let children = store.get(expr).children.clone();
-            let simplified_children: Vec<ExprId> =
-                children.iter().map(|&c| apply_calculus_simplify(store, c)).collect::<Vec<_>>();
+            let simplified_children: Vec<ExprId> = children
+                .iter()
+                .map(|&c| apply_calculus_simplify(store, c, budget))
+                .collect::<Vec<_>>();
 
             // Early exit if children unchanged
             if simplified_children.iter().zip(children.iter()).all(|(a, b)| a == b) {
@@ -129,7 +294,8 @@ fn apply_calculus_simplify(store: &mut Store, expr: ExprId) -> ExprId {
     };
 
     // Then apply Pythagorean identity at this level
-    apply_pythagorean_identity(store, expr_after_children)
+    let rewritten = apply_pythagorean_identity(store, expr_after_children);
+    budget.choose(store, expr_after_children, rewritten)
 }
 
 /// Inline Pythagorean identity: sin²(x) + cos²(x) → 1
@@ -218,10 +384,20 @@ fn is_cos_squared(store: &Store, expr: ExprId) -> bool {
         && matches!(&store.get(base).payload, Payload::Func(name) if name == "cos")
 }
 
-fn simplify_rec(store: &mut Store, id: ExprId, _ctx: &Context) -> ExprId {
+fn simplify_rec(store: &mut Store, id: ExprId, _ctx: &dyn Facts) -> ExprId {
     match store.get(id).op {
         Op::Add => simplify_add(store, id, _ctx),
         Op::Mul => simplify_mul(store, id, _ctx),
+        Op::Eq | Op::Lt | Op::Le | Op::And | Op::Or | Op::Not => {
+            relational::simplify_logic(store, id, _ctx)
+        }
+        // Held nodes stay held (see calculus::doit) and lambdas stay lambdas; only
+        // their parts are simplified
+        Op::Derivative | Op::Integral | Op::Sum | Op::Product | Op::Lambda => {
+            let (op, children) = (store.get(id).op.clone(), store.get(id).children.clone());
+            let mapped = children.into_iter().map(|c| simplify_rec(store, c, _ctx)).collect();
+            store.build(op, Payload::None, mapped)
+        }
         Op::Pow => {
             let (b_id, e_id) = {
                 let n = store.get(id);
@@ -264,6 +440,14 @@ fn simplify_rec(store: &mut Store, id: ExprId, _ctx: &Context) -> ExprId {
             };
             let args =
                 child_ids.into_iter().map(|c| simplify_rec(store, c, _ctx)).collect::<Vec<_>>();
+            if args.len() == 1 {
+                if let Some(v) = special_values::special_value(store, &name, args[0]) {
+                    return v;
+                }
+                if let Some(v) = complex::complex_function(store, &name, args[0], _ctx) {
+                    return v;
+                }
+            }
             // Specific rewrite: ln(exp(u)) -> u
             if name == "ln" && args.len() == 1 {
                 let a = args[0];
@@ -401,7 +585,10 @@ fn simplify_rec(store: &mut Store, id: ExprId, _ctx: &Context) -> ExprId {
 }
 
 /// Check if a condition expression evaluates to true
-fn is_true_condition(store: &Store, cond: ExprId, _ctx: &Context) -> bool {
+fn is_true_condition(store: &Store, cond: ExprId, _ctx: &dyn Facts) -> bool {
+    if store.as_bool(cond) == Some(true) {
+        return true;
+    }
     // Check for explicit True function
     if let (Op::Function, Payload::Func(name)) = (&store.get(cond).op, &store.get(cond).payload) {
         if name == "True" {
@@ -417,6 +604,9 @@ fn is_true_condition(store: &Store, cond: ExprId, _ctx: &Context) -> bool {
 
 /// Check if a condition expression evaluates to false
 fn is_false_condition(store: &Store, cond: ExprId) -> bool {
+    if store.as_bool(cond) == Some(false) {
+        return true;
+    }
     // Check for explicit False function
     if let (Op::Function, Payload::Func(name)) = (&store.get(cond).op, &store.get(cond).payload) {
         if name == "False" {
@@ -430,28 +620,28 @@ fn is_false_condition(store: &Store, cond: ExprId) -> bool {
     false
 }
 
-fn is_positive_symbol(ctx: &Context, store: &Store, id: ExprId) -> bool {
+fn is_positive_symbol(ctx: &dyn Facts, store: &Store, id: ExprId) -> bool {
     if let (Op::Symbol, Payload::Sym(s)) = (&store.get(id).op, &store.get(id).payload) {
         return matches!(ctx.has(s, Prop::Positive), Truth::True);
     }
     false
 }
 
-fn is_nonnegative_symbol(ctx: &Context, store: &Store, id: ExprId) -> bool {
+fn is_nonnegative_symbol(ctx: &dyn Facts, store: &Store, id: ExprId) -> bool {
     if let (Op::Symbol, Payload::Sym(s)) = (&store.get(id).op, &store.get(id).payload) {
         return matches!(ctx.has(s, Prop::Nonnegative), Truth::True);
     }
     false
 }
 
-fn is_real_symbol(ctx: &Context, store: &Store, id: ExprId) -> bool {
+fn is_real_symbol(ctx: &dyn Facts, store: &Store, id: ExprId) -> bool {
     if let (Op::Symbol, Payload::Sym(s)) = (&store.get(id).op, &store.get(id).payload) {
         return matches!(ctx.has(s, Prop::Real), Truth::True);
     }
     false
 }
 
-fn simplify_add(store: &mut Store, id: ExprId, ctx: &Context) -> ExprId {
+fn simplify_add(store: &mut Store, id: ExprId, ctx: &dyn Facts) -> ExprId {
     // First simplify children
     let child_ids = {
         let n = store.get(id);
@@ -461,27 +651,31 @@ fn simplify_add(store: &mut Store, id: ExprId, ctx: &Context) -> ExprId {
     for c in child_ids {
         terms.push(simplify_rec(store, c, ctx));
     }
-    // Split each term into (coeff, base), then collect coefficients per base
+    // Split each term into (coeff, base), then collect coefficients per base.
+    // Coefficients are numeric nodes summed by `Store::add`, so they widen to
+    // BigInt/BigRat instead of overflowing and stay inexact once a float joins.
     use std::collections::HashMap;
-    let mut map: HashMap<ExprId, (i64, i64)> = HashMap::new(); // base -> rational coeff (num, den)
+    let mut map: HashMap<ExprId, ExprId> = HashMap::new(); // base -> numeric coeff
     for t in terms {
         let (coeff, base) = split_coeff(store, t);
-        let entry = map.entry(base).or_insert((0, 1));
-        *entry = rat_add(*entry, coeff);
+        let sum = match map.get(&base) {
+            Some(&acc) => store.add(vec![acc, coeff]),
+            None => coeff,
+        };
+        map.insert(base, sum);
     }
 
     // Rebuild sum; numeric-only terms are under base==1
     let mut new_terms: Vec<ExprId> = Vec::new();
-    for (base, (n, d)) in map {
-        if n == 0 {
+    for (base, coeff) in map {
+        if is_zero(store, coeff) {
             continue;
         }
         let term = if is_one(store, base) {
-            store.rat(n, d)
-        } else if n == 1 && d == 1 {
+            coeff
+        } else if is_one(store, coeff) {
             base
         } else {
-            let coeff = store.rat(n, d);
             store.mul(vec![coeff, base])
         };
         new_terms.push(term);
@@ -492,7 +686,7 @@ fn simplify_add(store: &mut Store, id: ExprId, ctx: &Context) -> ExprId {
     store.add(new_terms)
 }
 
-fn simplify_mul(store: &mut Store, id: ExprId, ctx: &Context) -> ExprId {
+fn simplify_mul(store: &mut Store, id: ExprId, ctx: &dyn Facts) -> ExprId {
     let child_ids = {
         let n = store.get(id);
         n.children.clone()
@@ -553,33 +747,21 @@ fn simplify_mul(store: &mut Store, id: ExprId, ctx: &Context) -> ExprId {
     store.mul(rebuilt)
 }
 
-/// Split term into (coeff rational, base expr) where term == coeff * base
-fn split_coeff(store: &mut Store, id: ExprId) -> ((i64, i64), ExprId) {
-    match (&store.get(id).op, &store.get(id).payload) {
-        (Op::Integer, Payload::Int(k)) => (((*k), 1), store.int(1)),
-        (Op::Rational, Payload::Rat(n, d)) => (((*n), (*d)), store.int(1)),
-        (Op::Mul, _) => {
-            let mut coeff = (1i64, 1i64);
-            let mut rest: Vec<ExprId> = Vec::new();
-            let child_ids = {
-                let n = store.get(id);
-                n.children.clone()
-            };
-            for f in child_ids {
-                match (&store.get(f).op, &store.get(f).payload) {
-                    (Op::Integer, Payload::Int(k)) => {
-                        coeff = rat_mul(coeff, (*k, 1));
-                    }
-                    (Op::Rational, Payload::Rat(n, d)) => {
-                        coeff = rat_mul(coeff, (*n, *d));
-                    }
-                    _ => rest.push(f),
-                }
-            }
+/// Split term into (numeric coeff, base expr) where term == coeff * base.
+/// Any numeric payload (Int, Rat, BigInt, BigRat, Float) counts as a coefficient.
+fn split_coeff(store: &mut Store, id: ExprId) -> (ExprId, ExprId) {
+    match store.get(id).op {
+        Op::Integer | Op::Rational | Op::Float => (id, store.int(1)),
+        Op::Mul => {
+            let child_ids = store.get(id).children.clone();
+            let (nums, rest): (Vec<ExprId>, Vec<ExprId>) = child_ids
+                .into_iter()
+                .partition(|&f| matches!(store.get(f).op, Op::Integer | Op::Rational | Op::Float));
+            let coeff = store.mul(nums);
             let base = if rest.is_empty() { store.int(1) } else { store.mul(rest) };
             (coeff, base)
         }
-        _ => ((1, 1), id),
+        _ => (store.int(1), id),
     }
 }
 
@@ -587,9 +769,38 @@ fn is_one(store: &Store, id: ExprId) -> bool {
     matches!((&store.get(id).op, &store.get(id).payload), (Op::Integer, Payload::Int(1)))
 }
 
+fn is_zero(store: &Store, id: ExprId) -> bool {
+    matches!((&store.get(id).op, &store.get(id).payload), (Op::Integer, Payload::Int(0)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn basic_pass_stops_between_children_at_the_deadline() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let xx = st.add(vec![x, x]);
+        let yy = st.add(vec![y, y]);
+        let sx = st.func("sin", vec![xx]);
+        let cy = st.func("cos", vec![yy]);
+        let e = st.add(vec![sx, cy]);
+        let ctx = Context::default();
+        // An expired deadline is seen before the first child: nothing is built
+        let expired = SimplifyOptions { time_limit: Some(Duration::ZERO), ..Default::default() };
+        let nodes = st.node_count();
+        assert_eq!(basic_pass(&mut st, e, &ctx, &Budget::new(&expired)), e);
+        assert_eq!(st.node_count(), nodes);
+        // A deadline that does not pass gives the untimed result
+        let ample =
+            SimplifyOptions { time_limit: Some(Duration::from_secs(60)), ..Default::default() };
+        let timed = basic_pass(&mut st, e, &ctx, &Budget::new(&ample));
+        assert_eq!(timed, simplify_rec(&mut st, e, &ctx));
+        assert_ne!(timed, e);
+    }
 
     #[test]
     fn idempotent_and_collect_like_terms() {
@@ -615,6 +826,30 @@ mod tests {
         assert_eq!(s1, expected);
     }
 
+    #[test]
+    fn collect_like_terms_beyond_i64() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let max = st.int(i64::MAX);
+        let mx = st.mul(vec![max, x]);
+        let expr = st.add(vec![mx, mx]);
+        let s = simplify(&mut st, expr);
+        // 2 * (2^63 - 1) widens to a BigInt coefficient instead of saturating
+        let coeff = st.bigint("18446744073709551614".parse().unwrap());
+        let expected = st.mul(vec![coeff, x]);
+        assert_eq!(s, expected);
+
+        // Float and BigInt coefficients are collected like any other
+        let y = st.sym("y");
+        let h = st.float(0.5);
+        let hy = st.mul(vec![h, y]);
+        let sy = st.add(vec![hy, y, s]);
+        let s2 = simplify(&mut st, sy);
+        let c = st.float(1.5);
+        let cy = st.mul(vec![c, y]);
+        assert_eq!(s2, st.add(vec![cy, expected]));
+    }
+
     #[test]
     fn combine_powers_simple() {
         let mut st = Store::new();
@@ -918,9 +1153,10 @@ mod tests {
         ctx.assume("x", Prop::Negative);
         let s = super::simplify_with(&mut st, sqrt_x2, &ctx);
 
-        // Negative implies Real, so should get |x|
-        let abs_x = st.func("abs", vec![x]);
-        assert_eq!(s, abs_x);
+        // Negative implies Real, so |x| = -x
+        let m_one = st.int(-1);
+        let neg_x = st.mul(vec![m_one, x]);
+        assert_eq!(s, neg_x);
     }
 
     #[test]
@@ -1152,4 +1388,30 @@ mod tests {
         // Empty piecewise becomes Undefined
         assert!(matches!(st.get(s).op, Op::Function));
     }
+
+    #[test]
+    fn floats_are_kept_inexact() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let h = st.float(0.5);
+        let hx = st.mul(vec![h, x]);
+        let sum = st.add(vec![hx, hx]);
+        let out = simplify(&mut st, sum);
+        // Like terms still combine, but the coefficient stays a float
+        let one = st.float(1.0);
+        assert_eq!(out, st.mul(vec![one, x]));
+        assert_eq!(nsimplify(&mut st, out), x);
+    }
+
+    #[test]
+    fn held_nodes_simplify_inside() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let sum = st.add(vec![x, x]);
+        let d = st.derivative(sum, "x", 1);
+        let two = st.int(2);
+        let two_x = st.mul(vec![two, x]);
+        // The body is simplified but the derivative is not taken
+        assert_eq!(simplify(&mut st, d), st.derivative(two_x, "x", 1));
+    }
 }
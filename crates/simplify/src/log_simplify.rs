@@ -7,7 +7,8 @@
 //! - exp(log(x)) → x (with domain restrictions)
 //! - Multi-valued function handling
 
-use assumptions::{Context, Prop, Truth};
+use crate::options::Budget;
+use assumptions::{Context, Facts, Prop, Truth};
 use expr_core::{ExprId, Op, Payload, Store};
 
 /// Apply advanced logarithm and exponential simplification rules
@@ -17,14 +18,25 @@ use expr_core::{ExprId, Op, Payload, Store};
 /// - log(x^n) → n*log(x) (when x > 0, n real)
 /// - log(x/y) → log(x) - log(y) (when x, y > 0)
 pub fn simplify_logarithms(store: &mut Store, expr: ExprId, ctx: &Context) -> ExprId {
-    match &store.get(expr).op {
+    simplify_logarithms_within(store, expr, ctx, &Budget::unlimited())
+}
+
+/// `simplify_logarithms` taking the rewrite only as `budget` allows.
+pub(crate) fn simplify_logarithms_within(
+    store: &mut Store,
+    expr: ExprId,
+    ctx: &dyn Facts,
+    budget: &Budget,
+) -> ExprId {
+    let rewritten = match &store.get(expr).op {
         Op::Function => simplify_log_function(store, expr, ctx),
         _ => expr,
-    }
+    };
+    budget.choose(store, expr, rewritten)
 }
 
 /// Simplify logarithm function calls
-fn simplify_log_function(store: &mut Store, expr: ExprId, ctx: &Context) -> ExprId {
+fn simplify_log_function(store: &mut Store, expr: ExprId, ctx: &dyn Facts) -> ExprId {
     let fname = match &store.get(expr).payload {
         Payload::Func(s) => s.clone(),
         _ => return expr,
@@ -53,7 +65,7 @@ fn simplify_log_function(store: &mut Store, expr: ExprId, ctx: &Context) -> Expr
 fn try_expand_log_product(
     store: &mut Store,
     product: ExprId,
-    ctx: &Context,
+    ctx: &dyn Facts,
     log_name: &str,
 ) -> ExprId {
     let factors = store.get(product).children.clone();
@@ -102,7 +114,12 @@ fn try_expand_log_product(
 }
 
 /// Expand log(x^n) → n*log(x) when x is positive
-fn try_expand_log_power(store: &mut Store, power: ExprId, ctx: &Context, log_name: &str) -> ExprId {
+fn try_expand_log_power(
+    store: &mut Store,
+    power: ExprId,
+    ctx: &dyn Facts,
+    log_name: &str,
+) -> ExprId {
     let pow_children = store.get(power).children.clone();
     if pow_children.len() != 2 {
         return store.func(log_name, vec![power]);
@@ -129,7 +146,7 @@ fn try_expand_log_power(store: &mut Store, power: ExprId, ctx: &Context, log_nam
 }
 
 /// Check if an expression is guaranteed to be positive
-fn is_positive_expr(store: &Store, expr: ExprId, ctx: &Context) -> bool {
+fn is_positive_expr(store: &Store, expr: ExprId, ctx: &dyn Facts) -> bool {
     // Check assumptions
     if let (Op::Symbol, Payload::Sym(s)) = (&store.get(expr).op, &store.get(expr).payload) {
         if matches!(ctx.has(s, Prop::Positive), Truth::True) {
@@ -137,10 +154,15 @@ fn is_positive_expr(store: &Store, expr: ExprId, ctx: &Context) -> bool {
         }
     }
 
-    // Check for positive constants
+    // Check for positive constants and real powers of positive bases
     match (&store.get(expr).op, &store.get(expr).payload) {
         (Op::Integer, Payload::Int(n)) if *n > 0 => true,
         (Op::Rational, Payload::Rat(n, d)) if *n > 0 && *d > 0 => true,
+        (Op::Pow, _) => {
+            let (base, exp) = (store.get(expr).children[0], store.get(expr).children[1]);
+            matches!(store.get(exp).op, Op::Integer | Op::Rational)
+                && is_positive_expr(store, base, ctx)
+        }
         _ => false,
     }
 }
@@ -165,7 +187,22 @@ fn extract_negative_power(store: &Store, expr: ExprId) -> Option<ExprId> {
 }
 
 /// Contract log expressions: log(x) + log(y) → log(x*y) when beneficial
-pub fn contract_logarithms(store: &mut Store, expr: ExprId, _ctx: &Context) -> ExprId {
+pub fn contract_logarithms(store: &mut Store, expr: ExprId, ctx: &Context) -> ExprId {
+    contract_logarithms_within(store, expr, ctx, &Budget::unlimited())
+}
+
+/// `contract_logarithms` taking the rewrite only as `budget` allows.
+pub(crate) fn contract_logarithms_within(
+    store: &mut Store,
+    expr: ExprId,
+    _ctx: &dyn Facts,
+    budget: &Budget,
+) -> ExprId {
+    let rewritten = contract_log_sum(store, expr);
+    budget.choose(store, expr, rewritten)
+}
+
+fn contract_log_sum(store: &mut Store, expr: ExprId) -> ExprId {
     if store.get(expr).op != Op::Add {
         return expr;
     }
@@ -0,0 +1,138 @@
+//! Float → exact rational conversion (`nsimplify`).
+//!
+//! Each float literal is replaced by the simplest rational (smallest
+//! denominator, then numerator) that rounds back to the same float at its
+//! precision, so 0.1 becomes 1/10 and 0.333...3 (53 bits) becomes 1/3.
+//! Every other node is rebuilt through the canonical constructors, so
+//! relations, binders and calls are converted too.
+
+use arith::{BigInt, BigRat, Float};
+use expr_core::{ExprId, Payload, Store};
+
+/// Replace every float literal in `id` with its simplest exact rational.
+pub fn nsimplify(store: &mut Store, id: ExprId) -> ExprId {
+    store.map_bottom_up(id, |st, n| match &st.get(n).payload {
+        Payload::Float(f) => {
+            let r = float_to_simplest_rat(f);
+            st.bigrat(r)
+        }
+        _ => n,
+    })
+}
+
+/// Simplest rational within half an ulp of `f` that rounds back to `f`.
+/// Falls back to the exact binary value when an interval endpoint is a tie
+/// that rounds away.
+pub fn float_to_simplest_rat(f: &Float) -> BigRat {
+    if f.is_zero() {
+        return BigRat::zero();
+    }
+    let exact = f.to_bigrat();
+    let v = exact.abs();
+    let (mant, exp) = f.parts();
+    // Half ulp at this precision: 2^(exp + bits(mant) - prec - 1)
+    let e = exp + mant.bits() as i64 - f.prec() as i64 - 1;
+    let half_ulp = pow2(e);
+    let lo = &v - &half_ulp;
+    let hi = &v + &half_ulp;
+    let r = simplest_between(&lo, &hi);
+    let r = if f.is_negative() { -&r } else { r };
+    if Float::from_bigrat(&r, f.prec()) == *f {
+        r
+    } else {
+        exact
+    }
+}
+
+fn pow2(e: i64) -> BigRat {
+    let p = BigInt::one().shl(e.unsigned_abs());
+    if e >= 0 {
+        BigRat::from_integer(p)
+    } else {
+        BigRat::new(BigInt::one(), p)
+    }
+}
+
+/// Simplest rational in the closed interval [lo, hi], 0 < lo <= hi
+/// (continued-fraction descent of the Stern–Brocot tree).
+fn simplest_between(lo: &BigRat, hi: &BigRat) -> BigRat {
+    let a = lo.numer().div_rem(lo.denom()).0;
+    let a_rat = BigRat::from_integer(a.clone());
+    if a_rat == *lo {
+        return a_rat;
+    }
+    let next = BigRat::from_integer(&a + &BigInt::one());
+    if next <= *hi {
+        return next;
+    }
+    // lo and hi share the integer part a: recurse on reciprocals of the fractional parts
+    // (fractional parts are nonzero here, so the reciprocals exist)
+    let inner = simplest_between(
+        &(hi - &a_rat).recip().expect("nonzero"),
+        &(lo - &a_rat).recip().expect("nonzero"),
+    );
+    &a_rat + &inner.recip().expect("nonzero")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovers_short_rationals() {
+        let mut st = Store::new();
+        for (x, n, d) in
+            [(0.1, 1, 10), (0.5, 1, 2), (1.0 / 3.0, 1, 3), (-2.75, -11, 4), (3.0, 3, 1)]
+        {
+            let f = st.float(x);
+            let r = nsimplify(&mut st, f);
+            assert_eq!(r, st.rat(n, d), "{x}");
+        }
+        let z = st.float(0.0);
+        assert_eq!(nsimplify(&mut st, z), st.int(0));
+    }
+
+    #[test]
+    fn precision_bounds_the_denominator() {
+        // 22/7 at 8 bits lands on a coarse float whose simplest preimage is still 22/7,
+        // while at 53 bits pi's float is not a short fraction
+        let r = BigRat::new(BigInt::from(22i64), BigInt::from(7i64));
+        let coarse = Float::from_bigrat(&r, 8);
+        assert_eq!(float_to_simplest_rat(&coarse), r);
+        let pi = Float::from_f64(std::f64::consts::PI).unwrap();
+        let back = float_to_simplest_rat(&pi);
+        assert_eq!(Float::from_bigrat(&back, 53), pi);
+        assert!(back.denom().bits() > 20);
+    }
+
+    #[test]
+    fn rebuilds_inside_expressions() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let c = st.float(0.25);
+        let two = st.int(2);
+        let xp = st.pow(x, two);
+        let term = st.mul(vec![c, xp]);
+        let h = st.float(1.5);
+        let sinx = st.func("sin", vec![x]);
+        let arg = st.mul(vec![h, sinx]);
+        let e = st.add(vec![term, arg]);
+        let out = nsimplify(&mut st, e);
+        let quarter = st.rat(1, 4);
+        let t2 = st.mul(vec![quarter, xp]);
+        let three_halves = st.rat(3, 2);
+        let a2 = st.mul(vec![three_halves, sinx]);
+        let expected = st.add(vec![t2, a2]);
+        assert_eq!(out, expected);
+        // Expressions without floats are returned unchanged
+        assert_eq!(nsimplify(&mut st, expected), expected);
+        // Relations are rebuilt too: Eq(0.5 x, 0) -> Eq(x/2, 0)
+        let h = st.float(0.5);
+        let hx = st.mul(vec![h, x]);
+        let zero = st.int(0);
+        let eq = st.eq(hx, zero);
+        let half = st.rat(1, 2);
+        let qx = st.mul(vec![half, x]);
+        assert_eq!(nsimplify(&mut st, eq), st.eq(qx, zero));
+    }
+}
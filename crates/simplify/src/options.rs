@@ -0,0 +1,153 @@
+//! Options for the simplification pipeline: which passes run, which form is
+//! preferred, and how much work may be done.
+//!
+//! Every pass takes a rewrite only while the budget lasts and, when a measure
+//! is given, only if the measure does not grow. The trig, radical, log and
+//! Pythagorean passes decide this per rewritten subexpression; `basic` per
+//! whole pass, checking the time limit between top-level children. `trigsimp`
+//! minimizes the measure (then Fu's trig count).
+
+use crate::TrigStrategy;
+use expr_core::{ExprId, Store};
+use std::cell::Cell;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A cost for an expression; smaller is preferred. `Store::count_ops` is a
+/// ready-made one.
+pub type Measure = Arc<dyn Fn(&Store, ExprId) -> usize + Send + Sync>;
+
+/// One pass of the pipeline, named as in `Trace` steps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Pass {
+    /// Canonical forms and like-term collection ("basic").
+    Basic,
+    /// sin^2 + cos^2 = 1 ("pythagorean").
+    Pythagorean,
+    /// `simplify_trig` or `trigsimp`, per the strategy ("trig" / "trigsimp").
+    Trig,
+    /// `simplify_radicals` ("radicals").
+    Radicals,
+    /// `simplify_logarithms` ("expand_log").
+    ExpandLog,
+    /// `contract_logarithms` ("contract_log").
+    ContractLog,
+}
+
+impl Pass {
+    pub const ALL: [Pass; 6] = [
+        Pass::Basic,
+        Pass::Pythagorean,
+        Pass::Trig,
+        Pass::Radicals,
+        Pass::ExpandLog,
+        Pass::ContractLog,
+    ];
+}
+
+/// Configuration for `simplify_with_options`. The default runs every pass
+/// with no measure and no budget, as `simplify_with` does.
+#[derive(Clone)]
+pub struct SimplifyOptions {
+    pub strategy: TrigStrategy,
+    /// Rewrites that make this larger are refused.
+    pub measure: Option<Measure>,
+    /// Maximum number of rewrites taken over all passes.
+    pub max_steps: Option<usize>,
+    /// Wall-clock limit; passes stop rewriting once it has passed.
+    pub time_limit: Option<Duration>,
+    /// The passes that run, in pipeline order regardless of the order here.
+    pub passes: Vec<Pass>,
+}
+
+impl Default for SimplifyOptions {
+    fn default() -> Self {
+        Self {
+            strategy: TrigStrategy::default(),
+            measure: None,
+            max_steps: None,
+            time_limit: None,
+            passes: Pass::ALL.to_vec(),
+        }
+    }
+}
+
+impl fmt::Debug for SimplifyOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimplifyOptions")
+            .field("strategy", &self.strategy)
+            .field("measure", &self.measure.as_ref().map(|_| "<fn>"))
+            .field("max_steps", &self.max_steps)
+            .field("time_limit", &self.time_limit)
+            .field("passes", &self.passes)
+            .finish()
+    }
+}
+
+impl SimplifyOptions {
+    pub(crate) fn enabled(&self, pass: Pass) -> bool {
+        self.passes.contains(&pass)
+    }
+}
+
+/// The remaining budget of one simplification, shared by its passes.
+pub(crate) struct Budget<'a> {
+    measure: Option<&'a Measure>,
+    steps: Cell<usize>,
+    max_steps: Option<usize>,
+    deadline: Option<Instant>,
+}
+
+impl<'a> Budget<'a> {
+    pub(crate) fn new(opts: &'a SimplifyOptions) -> Self {
+        Budget {
+            measure: opts.measure.as_ref(),
+            steps: Cell::new(0),
+            max_steps: opts.max_steps,
+            deadline: opts.time_limit.map(|t| Instant::now() + t),
+        }
+    }
+
+    pub(crate) fn unlimited() -> Budget<'static> {
+        Budget { measure: None, steps: Cell::new(0), max_steps: None, deadline: None }
+    }
+
+    /// Whether a time limit applies, i.e. `exhausted` can change without a
+    /// step being taken.
+    pub(crate) fn timed(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    pub(crate) fn exhausted(&self) -> bool {
+        self.max_steps.is_some_and(|m| self.steps.get() >= m)
+            || self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// The user measure of `id`, 0 without one.
+    pub(crate) fn measure(&self, store: &Store, id: ExprId) -> usize {
+        self.measure.map_or(0, |m| m(store, id))
+    }
+
+    /// `new` in place of `old` if another step is allowed and the measure
+    /// does not grow (counting the step); `old` otherwise.
+    pub(crate) fn choose(&self, store: &Store, old: ExprId, new: ExprId) -> ExprId {
+        if new == old || self.exhausted() {
+            return old;
+        }
+        if self.measure.is_some() && self.measure(store, new) > self.measure(store, old) {
+            return old;
+        }
+        self.steps.set(self.steps.get() + 1);
+        new
+    }
+
+    /// Count one step of a search; false once the budget is spent.
+    pub(crate) fn tick(&self) -> bool {
+        if self.exhausted() {
+            return false;
+        }
+        self.steps.set(self.steps.get() + 1);
+        true
+    }
+}
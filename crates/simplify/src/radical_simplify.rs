@@ -6,6 +6,7 @@
 //! - Perfect power detection and extraction
 //! - Combined radical simplification
 
+use crate::options::Budget;
 use expr_core::{ExprId, Op, Payload, Store};
 
 /// Apply radical simplification rules to an expression
@@ -16,12 +17,22 @@ use expr_core::{ExprId, Op, Payload, Store};
 /// - Rationalization: 1/√x → √x/x
 /// - Combined radicals: √2 + √2 → 2√2
 pub fn simplify_radicals(store: &mut Store, expr: ExprId) -> ExprId {
+    simplify_radicals_within(store, expr, &Budget::unlimited())
+}
+
+/// `simplify_radicals` taking each rewrite only as `budget` allows.
+pub(crate) fn simplify_radicals_within(store: &mut Store, expr: ExprId, budget: &Budget) -> ExprId {
+    if budget.exhausted() {
+        return expr;
+    }
     // First recurse into children
     let expr_after_children = match store.get(expr).op {
         Op::Add | Op::Mul => {
             let children = store.get(expr).children.clone();
-            let simplified_children: Vec<ExprId> =
-                children.iter().map(|&c| simplify_radicals(store, c)).collect::<Vec<_>>();
+            let simplified_children: Vec<ExprId> = children
+                .iter()
+                .map(|&c| simplify_radicals_within(store, c, budget))
+                .collect::<Vec<_>>();
 
             // Early exit if children unchanged
             if simplified_children.iter().zip(children.iter()).all(|(a, b)| a == b) {
@@ -36,8 +47,8 @@ pub fn simplify_radicals(store: &mut Store, expr: ExprId) -> ExprId {
         }
         Op::Pow => {
             let children = store.get(expr).children.clone();
-            let base = simplify_radicals(store, children[0]);
-            let exp = simplify_radicals(store, children[1]);
+            let base = simplify_radicals_within(store, children[0], budget);
+            let exp = simplify_radicals_within(store, children[1], budget);
 
             // Early exit if unchanged
             if base == children[0] && exp == children[1] {
@@ -52,8 +63,10 @@ pub fn simplify_radicals(store: &mut Store, expr: ExprId) -> ExprId {
                 _ => return expr,
             };
             let children = store.get(expr).children.clone();
-            let simplified_children: Vec<ExprId> =
-                children.iter().map(|&c| simplify_radicals(store, c)).collect::<Vec<_>>();
+            let simplified_children: Vec<ExprId> = children
+                .iter()
+                .map(|&c| simplify_radicals_within(store, c, budget))
+                .collect::<Vec<_>>();
 
             // Early exit if children unchanged
             if simplified_children.iter().zip(children.iter()).all(|(a, b)| a == b) {
@@ -66,14 +79,15 @@ pub fn simplify_radicals(store: &mut Store, expr: ExprId) -> ExprId {
     };
 
     // Then apply radical simplification at this level
-    match &store.get(expr_after_children).op {
+    let rewritten = match &store.get(expr_after_children).op {
         Op::Pow => try_simplify_radical_power(store, expr_after_children),
         Op::Mul => {
             try_rationalize_denominator(store, expr_after_children).unwrap_or(expr_after_children)
         }
         Op::Add => try_combine_like_radicals(store, expr_after_children),
         _ => expr_after_children,
-    }
+    };
+    budget.choose(store, expr_after_children, rewritten)
 }
 
 /// Simplify radical powers (square roots, cube roots, etc.)
@@ -0,0 +1,294 @@
+//! Relational and boolean simplification under assumptions.
+//!
+//! Relations are decided from the sign of the difference of their sides,
+//! inferred bottom-up from numeric values and symbol assumptions
+//! (`x > 0` with `x` positive is `True`; `x^2 < 0` for real `x` is `False`).
+//! Undecided relations keep their (simplified) sides.
+
+use crate::simplify_rec;
+use assumptions::{Facts, Prop, Truth};
+use expr_core::{Constant, ExprId, Op, Payload, Store};
+
+// Possible signs of a real quantity, as a bit set
+pub(crate) const NEG: u8 = 1;
+pub(crate) const ZERO: u8 = 2;
+pub(crate) const POS: u8 = 4;
+pub(crate) const ANY: u8 = NEG | ZERO | POS;
+
+pub(crate) fn simplify_logic(store: &mut Store, id: ExprId, ctx: &dyn Facts) -> ExprId {
+    let op = store.get(id).op.clone();
+    let children: Vec<ExprId> =
+        store.get(id).children.clone().into_iter().map(|c| simplify_rec(store, c, ctx)).collect();
+    match op {
+        Op::Eq | Op::Lt | Op::Le => {
+            let (a, b) = (children[0], children[1]);
+            match decide_relation(store, &op, a, b, ctx) {
+                Some(t) => store.boolean(t),
+                None => store.build(op, Payload::None, children),
+            }
+        }
+        Op::And | Op::Or => {
+            // p & ~p is False, p | ~p is True
+            let complementary = children.iter().any(|&c| {
+                let neg = store.not(c);
+                children.contains(&neg)
+            });
+            if complementary {
+                return store.boolean(op == Op::Or);
+            }
+            store.build(op, Payload::None, children)
+        }
+        _ => store.build(op, Payload::None, children),
+    }
+}
+
+/// Truth value of `a op b` if the assumptions determine it.
+fn decide_relation(
+    store: &mut Store,
+    op: &Op,
+    a: ExprId,
+    b: ExprId,
+    ctx: &dyn Facts,
+) -> Option<bool> {
+    // d = b - a, so a < b iff d > 0
+    let m1 = store.int(-1);
+    let neg_a = store.mul(vec![m1, a]);
+    let d = store.add(vec![b, neg_a]);
+    let d = simplify_rec(store, d, ctx);
+    if let (Op::Integer, Payload::Int(0)) = (&store.get(d).op, &store.get(d).payload) {
+        return Some(!matches!(op, Op::Lt));
+    }
+    let s = sign_set(store, d, ctx);
+    match op {
+        Op::Eq if s & ZERO == 0 => Some(false),
+        Op::Lt if s == POS => Some(true),
+        Op::Lt if s & POS == 0 => Some(false),
+        Op::Le if s & NEG == 0 => Some(true),
+        Op::Le if s == NEG => Some(false),
+        _ => None,
+    }
+}
+
+/// Over-approximation of the signs `id` can take; `ANY` when unknown or
+/// possibly non-real (so callers never conclude anything from it).
+pub(crate) fn sign_set(store: &Store, id: ExprId, ctx: &dyn Facts) -> u8 {
+    if let Some(v) = store.as_bigrat(id).or_else(|| store.as_float(id).map(|f| f.to_bigrat())) {
+        return if v.is_zero() {
+            ZERO
+        } else if v.is_negative() {
+            NEG
+        } else {
+            POS
+        };
+    }
+    let n = store.get(id);
+    match (&n.op, &n.payload) {
+        (Op::Constant, Payload::Const(c)) => match c {
+            Constant::Pi | Constant::E | Constant::Infinity => POS,
+            Constant::NegativeInfinity => NEG,
+            _ => ANY,
+        },
+        (Op::Symbol, Payload::Sym(s)) => {
+            let has = |p| matches!(ctx.has(s, p), Truth::True);
+            if has(Prop::Positive) {
+                POS
+            } else if has(Prop::Negative) {
+                NEG
+            } else if has(Prop::Nonnegative) {
+                ZERO | POS
+            } else if has(Prop::Real) && has(Prop::Nonzero) {
+                NEG | POS
+            } else {
+                ANY
+            }
+        }
+        (Op::Add, _) => {
+            let sets: Vec<u8> = n.children.iter().map(|&c| sign_set(store, c, ctx)).collect();
+            let any_pos = sets.iter().any(|s| s & POS != 0);
+            let any_neg = sets.iter().any(|s| s & NEG != 0);
+            let all_zero = sets.iter().all(|s| s & ZERO != 0);
+            let mut out = 0;
+            if any_pos {
+                out |= POS;
+            }
+            if any_neg {
+                out |= NEG;
+            }
+            if all_zero || (any_pos && any_neg) {
+                out |= ZERO;
+            }
+            out
+        }
+        (Op::Mul, _) => {
+            n.children.iter().fold(POS, |acc, &c| mul_signs(acc, sign_set(store, c, ctx)))
+        }
+        (Op::Pow, _) => {
+            let base = sign_set(store, n.children[0], ctx);
+            match &store.get(n.children[1]).payload {
+                // A possibly non-real base says nothing about the power
+                Payload::Int(_) if base == ANY && !is_real(store, n.children[0], ctx) => ANY,
+                Payload::Int(k) => {
+                    // Negative exponents exclude a zero base (undefined there)
+                    let base = if *k < 0 { base & !ZERO } else { base };
+                    if k % 2 == 0 {
+                        let mut out = 0;
+                        if base & (NEG | POS) != 0 {
+                            out |= POS;
+                        }
+                        if base & ZERO != 0 {
+                            out |= ZERO;
+                        }
+                        out
+                    } else {
+                        base
+                    }
+                }
+                // A positive base to a real power stays positive; 2^I does not
+                _ if base == POS && is_real(store, n.children[1], ctx) => POS,
+                _ => ANY,
+            }
+        }
+        (Op::Function, Payload::Func(name)) if n.children.len() == 1 => {
+            let arg = n.children[0];
+            match name.as_str() {
+                "exp" if is_real(store, arg, ctx) => POS,
+                "abs" => {
+                    if sign_set(store, arg, ctx) & ZERO == 0 {
+                        POS
+                    } else {
+                        ZERO | POS
+                    }
+                }
+                _ => ANY,
+            }
+        }
+        _ => ANY,
+    }
+}
+
+fn mul_signs(a: u8, b: u8) -> u8 {
+    let mut out = 0;
+    for (x, sx) in [(NEG, -1), (ZERO, 0), (POS, 1)] {
+        for (y, sy) in [(NEG, -1), (ZERO, 0), (POS, 1)] {
+            if a & x != 0 && b & y != 0 {
+                out |= match sx * sy {
+                    -1 => NEG,
+                    0 => ZERO,
+                    _ => POS,
+                };
+            }
+        }
+    }
+    out
+}
+
+/// Known to be real: numbers, real symbols, sums/products/integer powers of
+/// reals, real powers of positive bases, re/im/abs/arg, and the elementary
+/// functions that map reals to reals at real arguments.
+pub(crate) fn is_real(store: &Store, id: ExprId, ctx: &dyn Facts) -> bool {
+    let n = store.get(id);
+    match (&n.op, &n.payload) {
+        (Op::Integer | Op::Rational | Op::Float, _) => true,
+        (Op::Constant, Payload::Const(c)) => matches!(c, Constant::Pi | Constant::E),
+        (Op::Symbol, Payload::Sym(s)) => matches!(ctx.has(s, Prop::Real), Truth::True),
+        (Op::Add | Op::Mul, _) => n.children.iter().all(|&c| is_real(store, c, ctx)),
+        (Op::Pow, _) => {
+            let (base, exp) = (n.children[0], n.children[1]);
+            (matches!(store.get(exp).payload, Payload::Int(_)) && is_real(store, base, ctx))
+                || (sign_set(store, base, ctx) == POS && is_real(store, exp, ctx))
+        }
+        (Op::Function, Payload::Func(name)) if n.children.len() == 1 => match name.as_str() {
+            "re" | "im" | "abs" | "arg" => true,
+            "sin" | "cos" | "tan" | "exp" | "sinh" | "cosh" | "tanh" | "atan" | "arctan" => {
+                is_real(store, n.children[0], ctx)
+            }
+            _ => sign_set(store, id, ctx) != ANY,
+        },
+        _ => sign_set(store, id, ctx) != ANY,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simplify_with;
+    use assumptions::Context;
+
+    #[test]
+    fn decides_relations_from_assumptions() {
+        let mut st = Store::new();
+        let mut ctx = Context::new();
+        ctx.assume("x", Prop::Positive);
+        ctx.assume("y", Prop::Real);
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let zero = st.int(0);
+        let one = st.int(1);
+        let tru = st.boolean(true);
+        let fls = st.boolean(false);
+
+        let c = st.gt(x, zero);
+        assert_eq!(simplify_with(&mut st, c, &ctx), tru);
+        // x + 1 > 1 for positive x
+        let xp1 = st.add(vec![x, one]);
+        let c = st.lt(one, xp1);
+        assert_eq!(simplify_with(&mut st, c, &ctx), tru);
+        // y^2 >= 0 for real y, y^2 < 0 never
+        let two = st.int(2);
+        let y2 = st.pow(y, two);
+        let c = st.ge(y2, zero);
+        assert_eq!(simplify_with(&mut st, c, &ctx), tru);
+        let c = st.lt(y2, zero);
+        assert_eq!(simplify_with(&mut st, c, &ctx), fls);
+        // Eq(x, 0) is False for positive x
+        let c = st.eq(x, zero);
+        assert_eq!(simplify_with(&mut st, c, &ctx), fls);
+        // pi and E are positive: pi * x > 0
+        let pi = st.constant(Constant::Pi);
+        let pix = st.mul(vec![pi, x]);
+        let c = st.lt(zero, pix);
+        assert_eq!(simplify_with(&mut st, c, &ctx), tru);
+        // Unknown sign stays symbolic
+        let c = st.lt(y, zero);
+        assert_eq!(simplify_with(&mut st, c, &ctx), c);
+        // Without assumptions, y^2 might be negative (complex y)
+        let c = st.lt(y2, zero);
+        assert_eq!(simplify_with(&mut st, c, &Context::new()), c);
+        // ... and so might y^-2
+        let m_two = st.int(-2);
+        let ym2 = st.pow(y, m_two);
+        let c = st.lt(ym2, zero);
+        assert_eq!(simplify_with(&mut st, c, &Context::new()), c);
+        // 2^y > 0 for real y, but 2^I is not real
+        let y_pow = st.pow(two, y);
+        let c = st.gt(y_pow, zero);
+        assert_eq!(simplify_with(&mut st, c, &ctx), tru);
+        let i = st.constant(Constant::I);
+        let two_i = st.pow(two, i);
+        let c = st.gt(two_i, zero);
+        assert_eq!(simplify_with(&mut st, c, &ctx), c);
+    }
+
+    #[test]
+    fn connectives_and_piecewise_conditions() {
+        let mut st = Store::new();
+        let mut ctx = Context::new();
+        ctx.assume("x", Prop::Negative);
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let zero = st.int(0);
+        let x_neg = st.lt(x, zero);
+        let y_neg = st.lt(y, zero);
+        let both = st.and(vec![x_neg, y_neg]);
+        assert_eq!(simplify_with(&mut st, both, &ctx), y_neg);
+        let not_y = st.not(y_neg);
+        let taut = st.or(vec![y_neg, not_y]);
+        assert_eq!(simplify_with(&mut st, taut, &ctx), st.boolean(true));
+        // Piecewise picks the branch whose condition the assumptions decide
+        let one = st.int(1);
+        let two = st.int(2);
+        let x_pos = st.gt(x, zero);
+        let pw = st.piecewise(vec![(x_pos, one), (x_neg, two)]);
+        assert_eq!(simplify_with(&mut st, pw, &ctx), two);
+    }
+}
@@ -0,0 +1,358 @@
+//! Rewriting between function families.
+//!
+//! `rewrite` replaces, bottom-up, every function the target has a rule for and
+//! leaves everything else alone. Nothing is simplified afterwards, so the
+//! result is in the requested form even where another form would be shorter:
+//! - `Exp`: circular and hyperbolic functions through `exp` (Euler)
+//! - `Trig`: `exp(a + I t)` as `exp(a) (cos t + I sin t)`
+//! - `SinCos`: tan, cot, sec and csc (and their hyperbolic analogues) as
+//!   quotients of sin and cos (sinh and cosh)
+//! - `Log`: inverse circular and hyperbolic functions through `ln` and square
+//!   roots
+//! - `Gamma`, `Factorial`: factorial, binomial and Gamma in terms of one
+//!   another (`binomial` is only ever a source)
+
+use expr_core::{Constant, ExprId, Op, Payload, Store};
+use std::collections::HashMap;
+
+/// The form `rewrite` produces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RewriteTarget {
+    /// sin, cos, tan, cot, sec, csc and sinh, ..., csch via `exp`.
+    Exp,
+    /// `exp` of an imaginary argument via cos and sin.
+    Trig,
+    /// tan = sin/cos and the other reciprocal/quotient functions.
+    SinCos,
+    /// asin, acos, atan, asinh, acosh, atanh via `ln`.
+    Log,
+    /// factorial(n) = Gamma(n + 1), binomial via Gamma.
+    Gamma,
+    /// Gamma(z) = factorial(z - 1), binomial via factorials.
+    Factorial,
+}
+
+/// `id` with every function the target covers rewritten into that form.
+pub fn rewrite(store: &mut Store, id: ExprId, target: RewriteTarget) -> ExprId {
+    let mut memo = HashMap::new();
+    rewrite_rec(store, id, target, &mut memo)
+}
+
+fn rewrite_rec(
+    store: &mut Store,
+    id: ExprId,
+    target: RewriteTarget,
+    memo: &mut HashMap<ExprId, ExprId>,
+) -> ExprId {
+    if let Some(&r) = memo.get(&id) {
+        return r;
+    }
+    let node = store.get(id);
+    if node.children.is_empty() {
+        return id;
+    }
+    let (op, payload, old) = (node.op.clone(), node.payload.clone(), node.children.clone());
+    let children: Vec<ExprId> = old.iter().map(|&c| rewrite_rec(store, c, target, memo)).collect();
+    let out = match (&op, &payload) {
+        (Op::Function, Payload::Func(f)) => apply(store, f, &children, target),
+        _ => None,
+    };
+    let out =
+        out.unwrap_or_else(
+            || {
+                if children == old {
+                    id
+                } else {
+                    store.build(op, payload, children)
+                }
+            },
+        );
+    memo.insert(id, out);
+    out
+}
+
+fn apply(store: &mut Store, f: &str, args: &[ExprId], target: RewriteTarget) -> Option<ExprId> {
+    match (target, args) {
+        (RewriteTarget::Exp, &[x]) => to_exp(store, f, x),
+        (RewriteTarget::Trig, &[w]) if f == "exp" => exp_to_trig(store, w),
+        (RewriteTarget::SinCos, &[x]) => to_sin_cos(store, f, x),
+        (RewriteTarget::Log, &[x]) => to_log(store, f, x),
+        (RewriteTarget::Gamma, _) => to_gamma(store, f, args),
+        (RewriteTarget::Factorial, _) => to_factorial(store, f, args),
+        _ => None,
+    }
+}
+
+/// -x, with the factors of a product passed through so that its number folds
+/// (`Store::mul` flattens nested products without folding them).
+fn neg(store: &mut Store, x: ExprId) -> ExprId {
+    let mut factors = match store.get(x).op {
+        Op::Mul => store.get(x).children.clone(),
+        _ => vec![x],
+    };
+    factors.push(store.int(-1));
+    store.mul(factors)
+}
+
+/// z + k, with the terms of a sum passed through so that its number folds.
+fn shift(store: &mut Store, z: ExprId, k: i64) -> ExprId {
+    let mut terms = match store.get(z).op {
+        Op::Add => store.get(z).children.clone(),
+        _ => vec![z],
+    };
+    terms.push(store.int(k));
+    store.add(terms)
+}
+
+/// A term with a negative numeric factor.
+fn is_negative(store: &Store, t: ExprId) -> bool {
+    let factors = match store.get(t).op {
+        Op::Mul => store.get(t).children.clone(),
+        _ => vec![t],
+    };
+    factors.iter().any(|&f| match store.get(f).payload {
+        Payload::Int(n) | Payload::Rat(n, _) => n < 0,
+        _ => false,
+    })
+}
+
+fn sub(store: &mut Store, a: ExprId, b: ExprId) -> ExprId {
+    let nb = neg(store, b);
+    store.add(vec![a, nb])
+}
+
+fn inv(store: &mut Store, x: ExprId) -> ExprId {
+    let m_one = store.int(-1);
+    store.pow(x, m_one)
+}
+
+fn sqrt(store: &mut Store, x: ExprId) -> ExprId {
+    let half = store.rat(1, 2);
+    store.pow(x, half)
+}
+
+fn exp(store: &mut Store, x: ExprId) -> ExprId {
+    store.func("exp", vec![x])
+}
+
+/// Euler's formulas. With `p = exp(I x)`, `q = exp(-I x)`:
+/// sin = (p - q)/(2 I), cos = (p + q)/2; the hyperbolic functions use
+/// `p = exp(x)` and `q = exp(-x)` instead.
+fn to_exp(store: &mut Store, f: &str, x: ExprId) -> Option<ExprId> {
+    let i = store.constant(Constant::I);
+    let circular = matches!(f, "sin" | "cos" | "tan" | "cot" | "sec" | "csc");
+    let hyperbolic = matches!(f, "sinh" | "cosh" | "tanh" | "coth" | "sech" | "csch");
+    if !circular && !hyperbolic {
+        return None;
+    }
+    let t = if circular { store.mul(vec![i, x]) } else { x };
+    let nt = neg(store, t);
+    let (p, q) = (exp(store, t), exp(store, nt));
+    let diff = sub(store, p, q);
+    let sum = store.add(vec![p, q]);
+    // sin = diff/(2 I), sinh = diff/2: `k` and `j` carry the circular sign and I
+    let (k, j) = if circular { (-1, vec![i]) } else { (1, vec![]) };
+    let scaled = |store: &mut Store, c: ExprId, rest: Vec<ExprId>| {
+        let factors: Vec<ExprId> = [c].into_iter().chain(j.iter().copied()).chain(rest).collect();
+        store.mul(factors)
+    };
+    let out = match f {
+        "sin" | "sinh" => {
+            let c = store.rat(k, 2);
+            scaled(store, c, vec![diff])
+        }
+        "cos" | "cosh" => {
+            let half = store.rat(1, 2);
+            store.mul(vec![half, sum])
+        }
+        "tan" | "tanh" => {
+            let c = store.int(k);
+            let s = inv(store, sum);
+            scaled(store, c, vec![diff, s])
+        }
+        "cot" | "coth" => {
+            let c = store.int(1);
+            let d = inv(store, diff);
+            scaled(store, c, vec![sum, d])
+        }
+        "sec" | "sech" => {
+            let two = store.int(2);
+            let s = inv(store, sum);
+            store.mul(vec![two, s])
+        }
+        _ => {
+            let c = store.int(2);
+            let d = inv(store, diff);
+            scaled(store, c, vec![d])
+        }
+    };
+    Some(out)
+}
+
+/// exp(a + I t) = exp(a) (cos t + I sin t) when some term of the argument has
+/// a factor I.
+fn exp_to_trig(store: &mut Store, w: ExprId) -> Option<ExprId> {
+    let i = store.constant(Constant::I);
+    let terms = match store.get(w).op {
+        Op::Add => store.get(w).children.clone(),
+        _ => vec![w],
+    };
+    let (mut imag, mut real) = (Vec::new(), Vec::new());
+    for t in terms {
+        match without_i(store, t, i) {
+            Some(c) => imag.push(c),
+            None => real.push(t),
+        }
+    }
+    if imag.is_empty() {
+        return None;
+    }
+    // exp(-I t) = cos t - I sin t
+    let (t, sign) = match imag.as_slice() {
+        &[t] if is_negative(store, t) => (neg(store, t), -1),
+        _ => (store.add(imag), 1),
+    };
+    let (c, s) = (store.func("cos", vec![t]), store.func("sin", vec![t]));
+    let sign = store.int(sign);
+    let is = store.mul(vec![sign, i, s]);
+    let euler = store.add(vec![c, is]);
+    if real.is_empty() {
+        return Some(euler);
+    }
+    let a = store.add(real);
+    let ea = exp(store, a);
+    Some(store.mul(vec![ea, euler]))
+}
+
+/// `t / I` if `t` is `I` or a product with a factor `I`.
+fn without_i(store: &mut Store, t: ExprId, i: ExprId) -> Option<ExprId> {
+    if t == i {
+        return Some(store.int(1));
+    }
+    if store.get(t).op != Op::Mul {
+        return None;
+    }
+    let mut factors = store.get(t).children.clone();
+    let pos = factors.iter().position(|&c| c == i)?;
+    factors.remove(pos);
+    Some(store.mul(factors))
+}
+
+fn to_sin_cos(store: &mut Store, f: &str, x: ExprId) -> Option<ExprId> {
+    let (s, c) = match f {
+        "tan" | "cot" | "sec" | "csc" => ("sin", "cos"),
+        "tanh" | "coth" | "sech" | "csch" => ("sinh", "cosh"),
+        _ => return None,
+    };
+    let sx = store.func(s, vec![x]);
+    let cx = store.func(c, vec![x]);
+    let out = match f {
+        "tan" | "tanh" => {
+            let ic = inv(store, cx);
+            store.mul(vec![sx, ic])
+        }
+        "cot" | "coth" => {
+            let is = inv(store, sx);
+            store.mul(vec![cx, is])
+        }
+        "sec" | "sech" => inv(store, cx),
+        _ => inv(store, sx),
+    };
+    Some(out)
+}
+
+/// Principal-branch logarithmic forms of the inverse functions.
+fn to_log(store: &mut Store, f: &str, x: ExprId) -> Option<ExprId> {
+    let i = store.constant(Constant::I);
+    let one = store.int(1);
+    let two = store.int(2);
+    let x2 = store.pow(x, two);
+    let out = match f {
+        "asin" | "arcsin" | "acos" | "arccos" => {
+            // asin x = -I ln(I x + sqrt(1 - x^2)), acos x = -I ln(x + I sqrt(1 - x^2))
+            let d = sub(store, one, x2);
+            let r = sqrt(store, d);
+            let (a, b) = if matches!(f, "asin" | "arcsin") {
+                (store.mul(vec![i, x]), r)
+            } else {
+                (x, store.mul(vec![i, r]))
+            };
+            let s = store.add(vec![a, b]);
+            let l = store.func("ln", vec![s]);
+            let m_one = store.int(-1);
+            store.mul(vec![m_one, i, l])
+        }
+        "atan" | "arctan" => {
+            // atan x = I/2 (ln(1 - I x) - ln(1 + I x))
+            let ix = store.mul(vec![i, x]);
+            let a = sub(store, one, ix);
+            let b = store.add(vec![one, ix]);
+            let (la, lb) = (store.func("ln", vec![a]), store.func("ln", vec![b]));
+            let d = sub(store, la, lb);
+            let half = store.rat(1, 2);
+            store.mul(vec![half, i, d])
+        }
+        "asinh" | "arcsinh" => {
+            // asinh x = ln(x + sqrt(x^2 + 1))
+            let s = store.add(vec![x2, one]);
+            let r = sqrt(store, s);
+            let a = store.add(vec![x, r]);
+            store.func("ln", vec![a])
+        }
+        "acosh" | "arccosh" => {
+            // acosh x = ln(x + sqrt(x - 1) sqrt(x + 1)), valid off the real line too
+            let (m, p) = (shift(store, x, -1), shift(store, x, 1));
+            let (rm, rp) = (sqrt(store, m), sqrt(store, p));
+            let r = store.mul(vec![rm, rp]);
+            let a = store.add(vec![x, r]);
+            store.func("ln", vec![a])
+        }
+        "atanh" | "arctanh" => {
+            // atanh x = (ln(1 + x) - ln(1 - x))/2
+            let p = shift(store, x, 1);
+            let m = sub(store, one, x);
+            let (lp, lm) = (store.func("ln", vec![p]), store.func("ln", vec![m]));
+            let d = sub(store, lp, lm);
+            let half = store.rat(1, 2);
+            store.mul(vec![half, d])
+        }
+        _ => return None,
+    };
+    Some(out)
+}
+
+fn to_gamma(store: &mut Store, f: &str, args: &[ExprId]) -> Option<ExprId> {
+    let gamma_succ = |store: &mut Store, n: ExprId| {
+        let s = shift(store, n, 1);
+        store.func("Gamma", vec![s])
+    };
+    match (f, args) {
+        ("factorial", &[n]) => Some(gamma_succ(store, n)),
+        ("binomial", &[n, k]) => {
+            // binomial(n, k) = Gamma(n + 1) / (Gamma(k + 1) Gamma(n - k + 1))
+            let nk = sub(store, n, k);
+            let (a, b, c) = (gamma_succ(store, n), gamma_succ(store, k), gamma_succ(store, nk));
+            let (ib, ic) = (inv(store, b), inv(store, c));
+            Some(store.mul(vec![a, ib, ic]))
+        }
+        _ => None,
+    }
+}
+
+fn to_factorial(store: &mut Store, f: &str, args: &[ExprId]) -> Option<ExprId> {
+    let fact = |store: &mut Store, n: ExprId| store.func("factorial", vec![n]);
+    match (f, args) {
+        ("Gamma", &[z]) => {
+            let z1 = shift(store, z, -1);
+            Some(fact(store, z1))
+        }
+        ("binomial", &[n, k]) => {
+            // binomial(n, k) = n! / (k! (n - k)!)
+            let nk = sub(store, n, k);
+            let (a, b, c) = (fact(store, n), fact(store, k), fact(store, nk));
+            let (ib, ic) = (inv(store, b), inv(store, c));
+            Some(store.mul(vec![a, ib, ic]))
+        }
+        _ => None,
+    }
+}
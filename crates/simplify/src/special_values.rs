@@ -0,0 +1,144 @@
+//! Known values of elementary functions at constants
+//!
+//! - sin/cos/tan at integer and half-integer multiples of pi (zero included)
+//! - sinh/tanh(0) = 0, cosh(0) = 1
+//! - ln(E) = 1, ln(E^q) = q for rational q, ln(1) = 0, ln(oo) = oo
+//! - exp(0) = 1, exp(1) = E, exp(oo) = oo, exp(-oo) = 0
+//! - atan(±oo) = ±pi/2
+
+use expr_core::{Constant, ExprId, Op, Payload, Store};
+
+/// Value of `name(arg)` if it is a known constant, else None.
+pub(crate) fn special_value(store: &mut Store, name: &str, arg: ExprId) -> Option<ExprId> {
+    let c = store.as_constant(arg);
+    match name {
+        "sin" | "cos" | "tan" => trig_at_pi_multiple(store, name, arg),
+        "sinh" | "tanh" | "cosh" if store.get(arg).payload == Payload::Int(0) => {
+            Some(store.int(i64::from(name == "cosh")))
+        }
+        "ln" | "log" => match c {
+            Some(Constant::E) => Some(store.int(1)),
+            Some(Constant::Infinity) => Some(arg),
+            _ if store.get(arg).payload == Payload::Int(1) => Some(store.int(0)),
+            _ => {
+                let n = store.get(arg);
+                if n.op == Op::Pow && store.as_constant(n.children[0]) == Some(Constant::E) {
+                    let e = n.children[1];
+                    if matches!(store.get(e).op, Op::Integer | Op::Rational) {
+                        return Some(e);
+                    }
+                }
+                None
+            }
+        },
+        "exp" => match c {
+            Some(Constant::Infinity) => Some(arg),
+            Some(Constant::NegativeInfinity) => Some(store.int(0)),
+            _ if store.get(arg).payload == Payload::Int(1) => Some(store.constant(Constant::E)),
+            _ if store.get(arg).payload == Payload::Int(0) => Some(store.int(1)),
+            _ => None,
+        },
+        "atan" | "arctan" => {
+            let half = match c {
+                Some(Constant::Infinity) => store.rat(1, 2),
+                Some(Constant::NegativeInfinity) => store.rat(-1, 2),
+                _ => return None,
+            };
+            let pi = store.constant(Constant::Pi);
+            Some(store.mul(vec![half, pi]))
+        }
+        _ => None,
+    }
+}
+
+/// Rational `q` with `id == q * pi`.
+fn pi_coefficient(store: &Store, id: ExprId) -> Option<(i64, i64)> {
+    if store.as_constant(id) == Some(Constant::Pi) {
+        return Some((1, 1));
+    }
+    if store.get(id).payload == Payload::Int(0) {
+        return Some((0, 1));
+    }
+    let n = store.get(id);
+    if n.op != Op::Mul || n.children.len() != 2 {
+        return None;
+    }
+    let (a, b) = (n.children[0], n.children[1]);
+    let (coeff, other) = if store.as_constant(a) == Some(Constant::Pi) { (b, a) } else { (a, b) };
+    if store.as_constant(other) != Some(Constant::Pi) {
+        return None;
+    }
+    match store.get(coeff).payload {
+        Payload::Int(k) => Some((k, 1)),
+        Payload::Rat(p, q) => Some((p, q)),
+        _ => None,
+    }
+}
+
+fn trig_at_pi_multiple(store: &mut Store, name: &str, arg: ExprId) -> Option<ExprId> {
+    let (p, q) = pi_coefficient(store, arg)?;
+    // Only multiples of pi/2 have values in this table
+    let half_turns = match q {
+        // Reduced first so that doubling cannot overflow
+        1 => 2 * p.rem_euclid(2),
+        2 => p,
+        _ => return None,
+    };
+    // sin/cos at k * pi/2 cycle with period 4
+    let k = half_turns.rem_euclid(4);
+    let (sin, cos) = [(0, 1), (1, 0), (0, -1), (-1, 0)][k as usize];
+    let v = match name {
+        "sin" => sin,
+        "cos" => cos,
+        _ if cos == 0 => return Some(store.constant(Constant::ComplexInfinity)),
+        _ => 0,
+    };
+    Some(store.int(v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simplify;
+
+    #[test]
+    fn trig_and_log_at_constants() {
+        let mut st = Store::new();
+        let pi = st.constant(Constant::Pi);
+        let e = st.constant(Constant::E);
+        let s = st.func("sin", vec![pi]);
+        assert_eq!(simplify(&mut st, s), st.int(0));
+        let three_halves = st.rat(3, 2);
+        let a = st.mul(vec![three_halves, pi]);
+        let c = st.func("cos", vec![a]);
+        assert_eq!(simplify(&mut st, c), st.int(0));
+        let s = st.func("sin", vec![a]);
+        assert_eq!(simplify(&mut st, s), st.int(-1));
+        let l = st.func("ln", vec![e]);
+        assert_eq!(simplify(&mut st, l), st.int(1));
+        let three = st.int(3);
+        let e3 = st.pow(e, three);
+        let l = st.func("ln", vec![e3]);
+        assert_eq!(simplify(&mut st, l), three);
+        let one = st.int(1);
+        let ex = st.func("exp", vec![one]);
+        assert_eq!(simplify(&mut st, ex), e);
+        let oo = st.constant(Constant::Infinity);
+        let at = st.func("atan", vec![oo]);
+        let half = st.rat(1, 2);
+        let half_pi = st.mul(vec![half, pi]);
+        assert_eq!(simplify(&mut st, at), half_pi);
+        // Not a multiple of pi/2: left alone
+        let third = st.rat(1, 3);
+        let a = st.mul(vec![third, pi]);
+        let s = st.func("sin", vec![a]);
+        assert_eq!(simplify(&mut st, s), s);
+        // Huge multiples reduce without overflow
+        let big = st.int(i64::MAX);
+        let a = st.mul(vec![big, pi]);
+        let s = st.func("sin", vec![a]);
+        assert_eq!(simplify(&mut st, s), st.int(0));
+        let c = st.func("cos", vec![a]);
+        assert_eq!(simplify(&mut st, c), st.int(-1));
+    }
+}
@@ -0,0 +1,171 @@
+//! Opt-in tracing of the simplification pipeline.
+//!
+//! `simplify_traced` runs the same passes as `simplify_with` and records every
+//! pass application that changed the expression as one or more `Step`s. A step
+//! holds the smallest subexpression that changed (descending while only one
+//! child differs, or each differing argument of a non-commutative node), the
+//! pass name, and the assumptions the pass relied on (`Context` queries that
+//! answered True while it ran, logged by an `assumptions::Recorder`).
+
+use assumptions::Prop;
+use expr_core::{ExprId, Op, Store};
+
+/// One rewrite: `before` became `after` under `rule`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub rule: &'static str,
+    pub before: ExprId,
+    pub after: ExprId,
+    pub assumptions: Vec<(String, Prop)>,
+}
+
+/// The rewrite steps of one simplification, in order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub steps: Vec<Step>,
+}
+
+impl Trace {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Record a pass that rewrote the whole expression `before` into `after`.
+    pub(crate) fn record(
+        &mut self,
+        store: &Store,
+        rule: &'static str,
+        before: ExprId,
+        after: ExprId,
+        assumptions: Vec<(String, Prop)>,
+    ) {
+        let mut diffs = Vec::new();
+        localize(store, before, after, &mut diffs);
+        for (b, a) in diffs {
+            self.steps.push(Step { rule, before: b, after: a, assumptions: assumptions.clone() });
+        }
+    }
+
+    /// One line per step: `rule: before -> after [assuming x > 0]`.
+    pub fn to_text(&self, store: &Store) -> String {
+        let mut out = String::new();
+        for (i, s) in self.steps.iter().enumerate() {
+            out.push_str(&format!(
+                "{}. {}: {} -> {}",
+                i + 1,
+                s.rule,
+                store.to_string(s.before),
+                store.to_string(s.after)
+            ));
+            if !s.assumptions.is_empty() {
+                let facts: Vec<String> =
+                    s.assumptions.iter().map(|(v, p)| prop_text(v, *p)).collect();
+                out.push_str(&format!(" [assuming {}]", facts.join(", ")));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// An `aligned` block with one row per step and the rule (and assumptions)
+    /// as a text annotation; `latex` renders the expressions (e.g.
+    /// `|e| io::to_latex(&store, e)`).
+    pub fn to_latex(&self, latex: impl Fn(ExprId) -> String) -> String {
+        let rows: Vec<String> = self
+            .steps
+            .iter()
+            .map(|s| {
+                let mut note = format!("\\text{{{}}}", s.rule.replace('_', "\\_"));
+                if !s.assumptions.is_empty() {
+                    let facts: Vec<String> =
+                        s.assumptions.iter().map(|(v, p)| prop_latex(v, *p)).collect();
+                    note.push_str(&format!(",\\ \\text{{assuming }} {}", facts.join(",\\ ")));
+                }
+                format!("{} &\\to {} && {}", latex(s.before), latex(s.after), note)
+            })
+            .collect();
+        format!("\\begin{{aligned}}\n{}\n\\end{{aligned}}", rows.join(" \\\\\n"))
+    }
+}
+
+fn prop_text(v: &str, p: Prop) -> String {
+    match p {
+        Prop::Real => format!("{v} real"),
+        Prop::Positive => format!("{v} > 0"),
+        Prop::Negative => format!("{v} < 0"),
+        Prop::Integer => format!("{v} integer"),
+        Prop::Nonzero => format!("{v} != 0"),
+        Prop::Nonnegative => format!("{v} >= 0"),
+    }
+}
+
+fn prop_latex(v: &str, p: Prop) -> String {
+    match p {
+        Prop::Real => format!("{v} \\in \\mathbb{{R}}"),
+        Prop::Positive => format!("{v} > 0"),
+        Prop::Negative => format!("{v} < 0"),
+        Prop::Integer => format!("{v} \\in \\mathbb{{Z}}"),
+        Prop::Nonzero => format!("{v} \\neq 0"),
+        Prop::Nonnegative => format!("{v} \\geq 0"),
+    }
+}
+
+/// Pairs of smallest differing subexpressions of `a` and `b`. Children of
+/// `Add`/`Mul` are matched as sets since canonical ordering may move them.
+fn localize(store: &Store, a: ExprId, b: ExprId, out: &mut Vec<(ExprId, ExprId)>) {
+    if a == b {
+        return;
+    }
+    let (na, nb) = (store.get(a), store.get(b));
+    if na.op != nb.op || na.payload != nb.payload || na.children.len() != nb.children.len() {
+        out.push((a, b));
+        return;
+    }
+    if matches!(na.op, Op::Add | Op::Mul) {
+        let removed: Vec<ExprId> =
+            na.children.iter().copied().filter(|c| !nb.children.contains(c)).collect();
+        let added: Vec<ExprId> =
+            nb.children.iter().copied().filter(|c| !na.children.contains(c)).collect();
+        match (removed.as_slice(), added.as_slice()) {
+            ([r], [d]) => localize(store, *r, *d, out),
+            _ => out.push((a, b)),
+        }
+        return;
+    }
+    for (&ca, &cb) in na.children.iter().zip(&nb.children) {
+        localize(store, ca, cb, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn localizes_to_changed_subexpressions() {
+        let mut st = Store::new();
+        let x = st.sym("x");
+        let y = st.sym("y");
+        let two = st.int(2);
+        let three = st.int(3);
+        // f(x, y) -> f(2x, y): the change is the first argument
+        let f1 = st.func("f", vec![x, y]);
+        let x2 = st.mul(vec![two, x]);
+        let f2 = st.func("f", vec![x2, y]);
+        let mut out = Vec::new();
+        localize(&st, f1, f2, &mut out);
+        assert_eq!(out, vec![(x, x2)]);
+        // y + f(x, y) -> y + f(2x, y): descends through the sum
+        let s1 = st.add(vec![y, f1]);
+        let s2 = st.add(vec![y, f2]);
+        out.clear();
+        localize(&st, s1, s2, &mut out);
+        assert_eq!(out, vec![(x, x2)]);
+        // Two terms merge: the sum itself is the step
+        let s3 = st.add(vec![x, y, three]);
+        let s4 = st.add(vec![x2, two]);
+        out.clear();
+        localize(&st, s3, s4, &mut out);
+        assert_eq!(out, vec![(s3, s4)]);
+    }
+}
@@ -9,6 +9,7 @@
 //! These are production-quality implementations that extend the basic
 //! trigonometric identities in calculus/symbolic_simplify.rs
 
+use crate::options::Budget;
 use expr_core::{ExprId, Op, Payload, Store};
 
 /// Apply advanced trigonometric simplification rules to an expression
@@ -21,12 +22,20 @@ use expr_core::{ExprId, Op, Payload, Store};
 ///
 /// Returns the simplified expression, or the original if no rules apply.
 pub fn simplify_trig(store: &mut Store, expr: ExprId) -> ExprId {
+    simplify_trig_within(store, expr, &Budget::unlimited())
+}
+
+/// `simplify_trig` taking each rewrite only as `budget` allows.
+pub(crate) fn simplify_trig_within(store: &mut Store, expr: ExprId, budget: &Budget) -> ExprId {
+    if budget.exhausted() {
+        return expr;
+    }
     // First recurse into children
     let expr_after_children = match store.get(expr).op {
         Op::Add | Op::Mul => {
             let children = store.get(expr).children.clone();
             let simplified_children: Vec<ExprId> =
-                children.iter().map(|&c| simplify_trig(store, c)).collect();
+                children.iter().map(|&c| simplify_trig_within(store, c, budget)).collect();
 
             // Early exit if children unchanged
             if simplified_children.iter().zip(children.iter()).all(|(a, b)| a == b) {
@@ -44,8 +53,8 @@ pub fn simplify_trig(store: &mut Store, expr: ExprId) -> ExprId {
             if children.len() != 2 {
                 return expr;
             }
-            let base = simplify_trig(store, children[0]);
-            let exp = simplify_trig(store, children[1]);
+            let base = simplify_trig_within(store, children[0], budget);
+            let exp = simplify_trig_within(store, children[1], budget);
 
             // Early exit if unchanged
             if base == children[0] && exp == children[1] {
@@ -61,7 +70,7 @@ pub fn simplify_trig(store: &mut Store, expr: ExprId) -> ExprId {
             };
             let children = store.get(expr).children.clone();
             let simplified_children: Vec<ExprId> =
-                children.iter().map(|&c| simplify_trig(store, c)).collect();
+                children.iter().map(|&c| simplify_trig_within(store, c, budget)).collect();
 
             // Early exit if children unchanged
             if simplified_children.iter().zip(children.iter()).all(|(a, b)| a == b) {
@@ -74,7 +83,7 @@ pub fn simplify_trig(store: &mut Store, expr: ExprId) -> ExprId {
     };
 
     // Then apply trig identities at this level
-    match &store.get(expr_after_children).op {
+    let rewritten = match &store.get(expr_after_children).op {
         Op::Add => try_sum_to_product(store, expr_after_children),
         Op::Mul => {
             // Only apply double-angle simplification (sin(2x) ← 2*sin(x)*cos(x))
@@ -87,7 +96,8 @@ pub fn simplify_trig(store: &mut Store, expr: ExprId) -> ExprId {
         }
         Op::Pow => try_half_angle_expansion(store, expr_after_children),
         _ => expr_after_children,
-    }
+    };
+    budget.choose(store, expr_after_children, rewritten)
 }
 
 /// Detects 2*sin(x)*cos(x) in a Mul and rewrites to sin(2x), preserving other factors
@@ -397,7 +407,7 @@ fn try_product_to_sum(store: &mut Store, expr: ExprId) -> ExprId {
 /// - sin(A) - sin(B) → 2 cos((A+B)/2) sin((A-B)/2)
 /// - cos(A) + cos(B) → 2 cos((A+B)/2) cos((A-B)/2)
 /// - cos(A) - cos(B) → -2 sin((A+B)/2) sin((A-B)/2)
-fn try_sum_to_product(store: &mut Store, expr: ExprId) -> ExprId {
+pub(crate) fn try_sum_to_product(store: &mut Store, expr: ExprId) -> ExprId {
     let children = store.get(expr).children.clone();
 
     if children.len() < 2 {
@@ -0,0 +1,571 @@
+//! Fu-style trigonometric simplification.
+//!
+//! `trigsimp` searches over sequences of the transformation rules of Fu, Zhong
+//! and Zeng ("Automated and readable simplification of trigonometric
+//! expressions", 2006) and returns the cheapest form it meets. Each rule is
+//! applied to every subexpression at once; the result is then normalized
+//! (multiplied out, like terms collected, `sin(-u) = -sin(u)`). A beam of the
+//! cheapest candidates is extended level by level, so a rule that makes the
+//! expression larger (sin^2 = 1 - cos^2) can still lead somewhere smaller.
+//!
+//! Cost is Fu's measure: the number of trigonometric function occurrences,
+//! then `Store::count_ops`. Under `SimplifyOptions` with a measure, that
+//! measure ranks first.
+//!
+//! Rules (Fu's names):
+//! - TR1: sec(u) = 1/cos(u), csc(u) = 1/sin(u)
+//! - TR2: tan(u) = sin(u)/cos(u), cot(u) = cos(u)/sin(u)
+//! - TR2i: sin(u)^k cos(u)^-k = tan(u)^k
+//! - TR5: sin(u)^2k = (1 - cos(u)^2)^k
+//! - TR6: cos(u)^2k = (1 - sin(u)^2)^k
+//! - TR7: power reduction, cos(u)^2 = (1 + cos(2u))/2, sin(u)^2 = (1 - cos(2u))/2
+//! - TR8: product to sum, sin(a) cos(b) = (sin(a + b) + sin(a - b))/2, ...
+//! - TR9: sum to product, sin(a) + sin(b) = 2 sin((a + b)/2) cos((a - b)/2), ...
+//! - TR10/TR11: angle sums and multiples expanded, sin(a + b) = sin(a) cos(b) + ...
+//! - TR10i: angle sums contracted, sin(a) cos(b) + cos(a) sin(b) = sin(a + b), ...
+//! - TR0: factorization of the rational function in the trigonometric terms
+//!   (only with few terms of low degree)
+
+use crate::expand::{expand, ExpandOptions};
+use crate::options::Budget;
+use crate::trig_identities::try_sum_to_product;
+use crate::{simplify_rec, split_coeff};
+use assumptions::{Context, Facts};
+use expr_core::{ExprId, Op, Payload, Store};
+use std::collections::{HashMap, HashSet};
+
+/// Levels of rule applications explored.
+const MAX_DEPTH: usize = 5;
+/// Candidates kept per level.
+const BEAM_WIDTH: usize = 8;
+/// TR0 is skipped above these: multivariate factoring is slow with many
+/// trigonometric terms (the squared sums of a three-link arm have six).
+const TR0_MAX_GENERATORS: usize = 4;
+const TR0_MAX_DEGREE: u64 = 8;
+
+type Rule = fn(&mut Store, ExprId) -> ExprId;
+
+const RULES: [Rule; 11] = [tr1, tr2, tr2i, tr5, tr6, tr7, tr8, tr9, tr10, tr10i, tr0];
+
+/// The cheapest form of `id` found by searching over Fu's rules; `id` itself
+/// if nothing cheaper turns up.
+///
+/// `sin(x)^4 - cos(x)^4` becomes `-cos(2x)` (TR5, then TR7) and
+/// `2 sin(x) cos(y) - sin(x + y)` becomes `sin(x - y)` (TR8).
+pub fn trigsimp(store: &mut Store, id: ExprId) -> ExprId {
+    trigsimp_within(store, id, &Context::default(), &Budget::unlimited())
+}
+
+/// `trigsimp` under the assumptions of `ctx`, ranking candidates by the
+/// budget's measure first and stopping when the budget is spent (each rule
+/// application is one step).
+pub(crate) fn trigsimp_within(
+    store: &mut Store,
+    id: ExprId,
+    ctx: &dyn Facts,
+    budget: &Budget,
+) -> ExprId {
+    if !has_trig(store, id) {
+        return id;
+    }
+    let key = |store: &Store, e: ExprId| (budget.measure(store, e), measure(store, e));
+    let start = normalize(store, id, ctx);
+    let (k_id, k_start) = (key(store, id), key(store, start));
+    let mut best = if k_start < k_id { (k_start, start) } else { (k_id, id) };
+    let mut seen = HashSet::from([id, start]);
+    let mut frontier = vec![start];
+    'search: for _ in 0..MAX_DEPTH {
+        let mut next = Vec::new();
+        for &e in &frontier {
+            for rule in RULES {
+                if !budget.tick() {
+                    break 'search;
+                }
+                let r = rule(store, e);
+                let r = normalize(store, r, ctx);
+                if seen.insert(r) {
+                    let k = key(store, r);
+                    if k < best.0 {
+                        best = (k, r);
+                    }
+                    next.push((k, r));
+                }
+            }
+        }
+        next.sort_by_key(|&(k, _)| k);
+        next.truncate(BEAM_WIDTH);
+        frontier = next.into_iter().map(|(_, e)| e).collect();
+        if frontier.is_empty() {
+            break;
+        }
+    }
+    best.1
+}
+
+/// (trigonometric function occurrences, operations), counted with
+/// multiplicity.
+fn measure(store: &Store, id: ExprId) -> (usize, usize) {
+    let mut trig: HashMap<ExprId, usize> = HashMap::new();
+    for n in store.postorder(id) {
+        let node = store.get(n);
+        let own = usize::from(matches!(&node.payload, Payload::Func(f) if is_trig_name(f)));
+        let t = node.children.iter().fold(own, |acc, c| acc.saturating_add(trig[c]));
+        trig.insert(n, t);
+    }
+    (trig[&id], store.count_ops(id))
+}
+
+fn is_trig_name(name: &str) -> bool {
+    matches!(name, "sin" | "cos" | "tan" | "cot" | "sec" | "csc")
+}
+
+fn has_trig(store: &Store, id: ExprId) -> bool {
+    store.preorder(id).any(|n| matches!(&store.get(n).payload, Payload::Func(f) if is_trig_name(f)))
+}
+
+/// Multiply out, collect like terms and canonicalize trigonometric arguments.
+fn normalize(store: &mut Store, id: ExprId, ctx: &dyn Facts) -> ExprId {
+    let opts = ExpandOptions { multinomial: true, ..ExpandOptions::none() };
+    let e = expand(store, id, &opts);
+    let e = bottom_up(store, e, &mut |st, n| tr3(st, n));
+    simplify_rec(store, e, ctx)
+}
+
+/// Rebuild `id` bottom-up, rewriting each node with `f` where it applies.
+fn bottom_up(
+    store: &mut Store,
+    id: ExprId,
+    f: &mut dyn FnMut(&mut Store, ExprId) -> Option<ExprId>,
+) -> ExprId {
+    store.map_bottom_up(id, |st, n| f(st, n).unwrap_or(n))
+}
+
+/// `name(arg)` for a one-argument function node.
+fn as_func(store: &Store, id: ExprId) -> Option<(&str, ExprId)> {
+    let n = store.get(id);
+    match (&n.op, &n.payload) {
+        (Op::Function, Payload::Func(f)) if n.children.len() == 1 => Some((f, n.children[0])),
+        _ => None,
+    }
+}
+
+/// `(name, arg, k)` for `name(arg)^k` with `name` sin or cos and integer `k`.
+fn trig_power(store: &Store, id: ExprId) -> Option<(&'static str, ExprId, i64)> {
+    let (base, k) = match store.get(id).op {
+        Op::Pow => {
+            let n = store.get(id);
+            let Payload::Int(k) = store.get(n.children[1]).payload else {
+                return None;
+            };
+            (n.children[0], k)
+        }
+        _ => (id, 1),
+    };
+    match as_func(store, base)? {
+        ("sin", u) => Some(("sin", u, k)),
+        ("cos", u) => Some(("cos", u, k)),
+        _ => None,
+    }
+}
+
+fn inv(store: &mut Store, id: ExprId) -> ExprId {
+    let m_one = store.int(-1);
+    store.pow(id, m_one)
+}
+
+fn neg(store: &mut Store, id: ExprId) -> ExprId {
+    let m_one = store.int(-1);
+    store.mul(vec![m_one, id])
+}
+
+/// `a + b` or `a - b`.
+fn combine(store: &mut Store, a: ExprId, b: ExprId, minus: bool) -> ExprId {
+    let b = if minus { neg(store, b) } else { b };
+    store.add(vec![a, b])
+}
+
+/// TR3: trigonometric functions at zero and of negated arguments.
+fn tr3(store: &mut Store, id: ExprId) -> Option<ExprId> {
+    let (name, u) = as_func(store, id)?;
+    let name = name.to_string();
+    if !matches!(name.as_str(), "sin" | "cos" | "tan") {
+        return None;
+    }
+    if store.get(u).payload == Payload::Int(0) {
+        return Some(store.int(i64::from(name == "cos")));
+    }
+    let negated = negate_if_negative(store, u)?;
+    let f = store.func(name.as_str(), vec![negated]);
+    Some(if name == "cos" { f } else { neg(store, f) })
+}
+
+/// `-u` if `u` reads as negative: a product with a negative coefficient, or a
+/// sum with more negative terms than positive ones (ties broken by the
+/// printed form, so exactly one of `u` and `-u` counts).
+fn negate_if_negative(store: &mut Store, u: ExprId) -> Option<ExprId> {
+    let is_neg = |store: &mut Store, t: ExprId| {
+        let c = split_coeff(store, t).0;
+        store.as_bigrat(c).is_some_and(|r| r.is_negative())
+            || store.as_float(c).is_some_and(|f| f.is_negative())
+    };
+    match store.get(u).op {
+        Op::Mul => is_neg(store, u).then(|| neg(store, u)),
+        Op::Add => {
+            let terms = store.get(u).children.clone();
+            let negative = terms.iter().filter(|&&t| is_neg(store, t)).count();
+            let positive = terms.len() - negative;
+            let negated_terms: Vec<ExprId> = terms.iter().map(|&t| neg(store, t)).collect();
+            let negated = store.add(negated_terms);
+            let flip = negative > positive
+                || (negative == positive && store.to_string(negated) < store.to_string(u));
+            flip.then_some(negated)
+        }
+        _ => None,
+    }
+}
+
+/// TR1: sec and csc as reciprocals.
+fn tr1(store: &mut Store, id: ExprId) -> ExprId {
+    bottom_up(store, id, &mut |st, n| {
+        let (name, u) = as_func(st, n)?;
+        let f = match name {
+            "sec" => "cos",
+            "csc" => "sin",
+            _ => return None,
+        };
+        let g = st.func(f, vec![u]);
+        Some(inv(st, g))
+    })
+}
+
+/// TR2: tan and cot as quotients of sin and cos.
+fn tr2(store: &mut Store, id: ExprId) -> ExprId {
+    bottom_up(store, id, &mut |st, n| {
+        let (name, u) = as_func(st, n)?;
+        let (num, den) = match name {
+            "tan" => ("sin", "cos"),
+            "cot" => ("cos", "sin"),
+            _ => return None,
+        };
+        let num = st.func(num, vec![u]);
+        let den = st.func(den, vec![u]);
+        let den = inv(st, den);
+        Some(st.mul(vec![num, den]))
+    })
+}
+
+/// TR2i: sin(u)^k cos(u)^-k in a product becomes tan(u)^k.
+fn tr2i(store: &mut Store, id: ExprId) -> ExprId {
+    bottom_up(store, id, &mut |st, n| {
+        if st.get(n).op != Op::Mul {
+            return None;
+        }
+        let mut factors = st.get(n).children.clone();
+        let mut changed = false;
+        let mut i = 0;
+        while i < factors.len() {
+            if let Some(("sin", u, k)) = trig_power(st, factors[i]) {
+                let partner =
+                    factors.iter().position(|&f| trig_power(st, f) == Some(("cos", u, -k)));
+                if let Some(j) = partner {
+                    let t = st.func("tan", vec![u]);
+                    let k = st.int(k);
+                    factors[i] = st.pow(t, k);
+                    factors.remove(j);
+                    changed = true;
+                }
+            }
+            i += 1;
+        }
+        changed.then(|| st.mul(factors))
+    })
+}
+
+/// `sin(u)^2k` (`from` = "sin") rewritten through `sin^2 = 1 - cos^2`.
+fn pythagorean(store: &mut Store, id: ExprId, from: &str, to: &str) -> ExprId {
+    bottom_up(store, id, &mut |st, n| {
+        let (name, u, k) = trig_power(st, n)?;
+        if name != from || k < 2 || k % 2 != 0 {
+            return None;
+        }
+        let g = st.func(to, vec![u]);
+        let two = st.int(2);
+        let g2 = st.pow(g, two);
+        let one = st.int(1);
+        let m_g2 = neg(st, g2);
+        let s = st.add(vec![one, m_g2]);
+        let half_k = st.int(k / 2);
+        Some(st.pow(s, half_k))
+    })
+}
+
+/// TR5: even powers of sin through cos.
+fn tr5(store: &mut Store, id: ExprId) -> ExprId {
+    pythagorean(store, id, "sin", "cos")
+}
+
+/// TR6: even powers of cos through sin.
+fn tr6(store: &mut Store, id: ExprId) -> ExprId {
+    pythagorean(store, id, "cos", "sin")
+}
+
+/// TR7: even powers of sin and cos through cos of the double angle.
+fn tr7(store: &mut Store, id: ExprId) -> ExprId {
+    bottom_up(store, id, &mut |st, n| {
+        let (name, u, k) = trig_power(st, n)?;
+        if k < 2 || k % 2 != 0 {
+            return None;
+        }
+        let two = st.int(2);
+        let two_u = st.mul(vec![two, u]);
+        let c = st.func("cos", vec![two_u]);
+        let one = st.int(1);
+        let s = combine(st, one, c, name == "sin");
+        let half = st.rat(1, 2);
+        let h = st.mul(vec![half, s]);
+        let half_k = st.int(k / 2);
+        Some(st.pow(h, half_k))
+    })
+}
+
+/// TR8: products of sin and cos become sums, until no product of two is left.
+fn tr8(store: &mut Store, id: ExprId) -> ExprId {
+    bottom_up(store, id, &mut |st, n| (st.get(n).op == Op::Mul).then(|| product_to_sum(st, n)))
+}
+
+fn product_to_sum(store: &mut Store, id: ExprId) -> ExprId {
+    match store.get(id).op {
+        Op::Add => {
+            let terms = store.get(id).children.clone();
+            let terms: Vec<ExprId> = terms.into_iter().map(|t| product_to_sum(store, t)).collect();
+            return store.add(terms);
+        }
+        Op::Mul => {}
+        _ => return id,
+    }
+    let mut factors = store.get(id).children.clone();
+    // Two units sin(a), cos(b) taken from the factors (possibly the same power)
+    let mut units = Vec::new();
+    for factor in factors.iter_mut() {
+        while units.len() < 2 {
+            match trig_power(store, *factor) {
+                Some((name, u, k)) if k >= 1 => {
+                    units.push((name, u));
+                    let base = store.func(name, vec![u]);
+                    let rest = store.int(k - 1);
+                    *factor = store.pow(base, rest);
+                }
+                _ => break,
+            }
+        }
+    }
+    let [(f, a), (g, b)] = units[..] else {
+        return id;
+    };
+    // Order the pair as (sin, cos), (cos, cos) or (sin, sin)
+    let ((f, a), (g, b)) =
+        if f == "cos" && g == "sin" { ((g, b), (f, a)) } else { ((f, a), (g, b)) };
+    let sum = store.add(vec![a, b]);
+    let m_b = neg(store, b);
+    let diff = store.add(vec![a, m_b]);
+    let pair = match (f, g) {
+        ("sin", "cos") => {
+            let s1 = store.func("sin", vec![sum]);
+            let s2 = store.func("sin", vec![diff]);
+            store.add(vec![s1, s2])
+        }
+        ("cos", "cos") => {
+            let c1 = store.func("cos", vec![diff]);
+            let c2 = store.func("cos", vec![sum]);
+            store.add(vec![c1, c2])
+        }
+        _ => {
+            let c1 = store.func("cos", vec![diff]);
+            let c2 = store.func("cos", vec![sum]);
+            combine(store, c1, c2, true)
+        }
+    };
+    let half = store.rat(1, 2);
+    factors.push(half);
+    factors.push(pair);
+    let product = store.mul(factors);
+    let expanded = polys::expand_poly(store, product);
+    product_to_sum(store, expanded)
+}
+
+/// TR9: sums of two sines or two cosines become products.
+fn tr9(store: &mut Store, id: ExprId) -> ExprId {
+    bottom_up(store, id, &mut |st, n| {
+        if st.get(n).op != Op::Add {
+            return None;
+        }
+        let r = try_sum_to_product(st, n);
+        (r != n).then_some(r)
+    })
+}
+
+/// TR10 and TR11: sin, cos and tan of sums and integer multiples expanded.
+fn tr10(store: &mut Store, id: ExprId) -> ExprId {
+    let opts = ExpandOptions { trig: true, ..ExpandOptions::none() };
+    expand(store, id, &opts)
+}
+
+/// TR10i: pairs of terms `k sin(a) cos(b) ± k cos(a) sin(b)` and
+/// `k cos(a) cos(b) ∓ k sin(a) sin(b)` with the same remaining factors
+/// contracted to `k sin(a ± b)` and `k cos(a ± b)`.
+fn tr10i(store: &mut Store, id: ExprId) -> ExprId {
+    bottom_up(store, id, &mut |st, n| {
+        if st.get(n).op != Op::Add {
+            return None;
+        }
+        let mut terms = st.get(n).children.clone();
+        let mut changed = false;
+        let mut i = 0;
+        'outer: while i < terms.len() {
+            for j in i + 1..terms.len() {
+                if let Some(r) = contract_pair(st, terms[i], terms[j]) {
+                    terms[i] = r;
+                    terms.remove(j);
+                    changed = true;
+                    continue 'outer;
+                }
+            }
+            i += 1;
+        }
+        changed.then(|| st.add(terms))
+    })
+}
+
+/// A term split as `coeff * f(a) g(b) * rest` with `f`, `g` sin or cos.
+struct TrigPair {
+    coeff: ExprId,
+    units: [(&'static str, ExprId); 2],
+    rest: ExprId,
+}
+
+impl TrigPair {
+    /// The two function names, sorted.
+    fn kinds(&self) -> [&'static str; 2] {
+        let mut k = [self.units[0].0, self.units[1].0];
+        k.sort_unstable();
+        k
+    }
+
+    /// The argument of the (first) unit named `name`.
+    fn arg(&self, name: &str) -> ExprId {
+        self.units.iter().find(|u| u.0 == name).expect("unit present").1
+    }
+}
+
+fn trig_pair(store: &mut Store, t: ExprId) -> Option<TrigPair> {
+    let (coeff, base) = split_coeff(store, t);
+    let factors = match store.get(base).op {
+        Op::Mul => store.get(base).children.clone(),
+        _ => vec![base],
+    };
+    let mut units = Vec::new();
+    let mut rest = Vec::new();
+    for f in factors {
+        match trig_power(store, f) {
+            Some((name, u, k)) if (1..=2).contains(&k) => {
+                units.extend(std::iter::repeat_n((name, u), k as usize))
+            }
+            _ => rest.push(f),
+        }
+    }
+    let units: [(&'static str, ExprId); 2] = units.try_into().ok()?;
+    let rest = store.mul(rest);
+    Some(TrigPair { coeff, units, rest })
+}
+
+fn contract_pair(store: &mut Store, s: ExprId, t: ExprId) -> Option<ExprId> {
+    let p = trig_pair(store, s)?;
+    let q = trig_pair(store, t)?;
+    if p.rest != q.rest {
+        return None;
+    }
+    let same = p.coeff == q.coeff;
+    if !same && p.coeff != neg(store, q.coeff) {
+        return None;
+    }
+    let combined = match (p.kinds(), q.kinds()) {
+        // k sin(a) cos(b) ± k cos(a) sin(b) = k sin(a ± b)
+        (["cos", "sin"], ["cos", "sin"]) => {
+            let (a, b) = (p.arg("sin"), p.arg("cos"));
+            if q.arg("cos") != a || q.arg("sin") != b || a == b {
+                return None;
+            }
+            let u = combine(store, a, b, !same);
+            store.func("sin", vec![u])
+        }
+        // k cos(a) cos(b) ∓ k sin(a) sin(b) = k cos(a ± b)
+        (["cos", "cos"], ["sin", "sin"]) | (["sin", "sin"], ["cos", "cos"]) => {
+            let (cs, ss) =
+                if p.units[0].0 == "cos" { (&p.units, &q.units) } else { (&q.units, &p.units) };
+            let (a, b) = (cs[0].1, cs[1].1);
+            let matches = (ss[0].1 == a && ss[1].1 == b) || (ss[0].1 == b && ss[1].1 == a);
+            if !matches {
+                return None;
+            }
+            let u = combine(store, a, b, same);
+            // The sign is that of the cos cos term
+            let k = if cs == &p.units { p.coeff } else { q.coeff };
+            let c = store.func("cos", vec![u]);
+            return Some(store.mul(vec![k, c, p.rest]));
+        }
+        _ => return None,
+    };
+    Some(store.mul(vec![p.coeff, combined, p.rest]))
+}
+
+/// TR0: factor as a rational function of the trigonometric terms, when it
+/// has few of them and a low degree.
+fn tr0(store: &mut Store, id: ExprId) -> ExprId {
+    let (generators, degree) = generators_and_degree(store, id);
+    if generators > TR0_MAX_GENERATORS || degree > TR0_MAX_DEGREE {
+        return id;
+    }
+    polys::factor(store, id)
+}
+
+/// The number of generators of `id` read as a rational function (the
+/// symbols, calls and non-integer powers outside any call) and its degree in
+/// them (negative powers counted by magnitude).
+fn generators_and_degree(store: &Store, id: ExprId) -> (usize, u64) {
+    let mut generators = HashSet::new();
+    let mut degree: HashMap<ExprId, u64> = HashMap::new();
+    for n in store.postorder(id) {
+        let node = store.get(n);
+        let d = match node.op {
+            Op::Integer | Op::Rational | Op::Float | Op::Constant => 0,
+            Op::Add => node.children.iter().map(|c| degree[c]).max().unwrap_or(0),
+            Op::Mul => node.children.iter().fold(0, |acc: u64, c| acc.saturating_add(degree[c])),
+            Op::Pow => match store.get(node.children[1]).payload {
+                Payload::Int(k) => degree[&node.children[0]].saturating_mul(k.unsigned_abs()),
+                _ => 1,
+            },
+            _ => 1,
+        };
+        degree.insert(n, d);
+    }
+    // Generators are the degree-1 leaves of the polynomial structure
+    let mut stack = vec![id];
+    let mut seen = HashSet::new();
+    while let Some(n) = stack.pop() {
+        if !seen.insert(n) {
+            continue;
+        }
+        let node = store.get(n);
+        match node.op {
+            Op::Integer | Op::Rational | Op::Float | Op::Constant => {}
+            Op::Add | Op::Mul => stack.extend(&node.children),
+            Op::Pow if matches!(store.get(node.children[1]).payload, Payload::Int(_)) => {
+                stack.push(node.children[0])
+            }
+            _ => {
+                generators.insert(n);
+            }
+        }
+    }
+    (generators.len(), degree[&id])
+}
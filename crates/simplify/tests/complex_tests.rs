@@ -0,0 +1,140 @@
+//! Complex parts: re, im, conjugate, abs, arg, as_real_imag and as_polar
+
+use assumptions::{Context, Prop};
+use expr_core::{Constant, ExprId, Store};
+use simplify::{as_polar, as_real_imag, simplify, simplify_with};
+
+fn real_ctx() -> Context {
+    let mut ctx = Context::new();
+    ctx.assume("x", Prop::Real);
+    ctx.assume("y", Prop::Real);
+    ctx.assume("p", Prop::Positive);
+    ctx
+}
+
+fn call(st: &mut Store, name: &str, arg: ExprId, ctx: &Context) -> ExprId {
+    let f = st.func(name, vec![arg]);
+    simplify_with(st, f, ctx)
+}
+
+#[test]
+fn parts_of_rectangular_form() {
+    let mut st = Store::new();
+    let ctx = real_ctx();
+    let x = st.sym("x");
+    let y = st.sym("y");
+    let i = st.constant(Constant::I);
+    let iy = st.mul(vec![i, y]);
+    let w = st.add(vec![x, iy]);
+    assert_eq!(call(&mut st, "re", w, &ctx), x);
+    assert_eq!(call(&mut st, "im", w, &ctx), y);
+    let m_one = st.int(-1);
+    let miy = st.mul(vec![m_one, i, y]);
+    let conj = st.add(vec![x, miy]);
+    assert_eq!(call(&mut st, "conjugate", w, &ctx), conj);
+    // Without assumptions the parts stay unevaluated
+    let z = st.sym("z");
+    let re_z = st.func("re", vec![z]);
+    assert_eq!(call(&mut st, "re", z, &ctx), re_z);
+    let zi = st.add(vec![z, i]);
+    let one = st.int(1);
+    let im_z = st.func("im", vec![z]);
+    assert_eq!(call(&mut st, "im", zi, &ctx), st.add(vec![im_z, one]));
+}
+
+#[test]
+fn abs_and_arg() {
+    let mut st = Store::new();
+    let ctx = real_ctx();
+    let x = st.sym("x");
+    let y = st.sym("y");
+    let z = st.sym("z");
+    let p = st.sym("p");
+    let i = st.constant(Constant::I);
+    // |3 + 4 I| = 5, arg(3 + 4 I) = atan(4/3)
+    let three = st.int(3);
+    let four = st.int(4);
+    let fi = st.mul(vec![four, i]);
+    let c = st.add(vec![three, fi]);
+    assert_eq!(call(&mut st, "abs", c, &ctx), st.int(5));
+    let q = st.rat(4, 3);
+    assert_eq!(call(&mut st, "arg", c, &ctx), st.func("atan", vec![q]));
+    // |p| = p, |-2 z| = 2 |z|, |exp(I y)| = 1
+    assert_eq!(call(&mut st, "abs", p, &ctx), p);
+    let m_two = st.int(-2);
+    let mz = st.mul(vec![m_two, z]);
+    let two = st.int(2);
+    let abs_z = st.func("abs", vec![z]);
+    assert_eq!(call(&mut st, "abs", mz, &ctx), st.mul(vec![two, abs_z]));
+    let iy = st.mul(vec![i, y]);
+    let e = st.func("exp", vec![iy]);
+    assert_eq!(call(&mut st, "abs", e, &ctx), st.int(1));
+    // arg(-2) = pi, arg(I p) = pi/2, arg(x + I y) = atan2(y, x)
+    let pi = st.constant(Constant::Pi);
+    assert_eq!(call(&mut st, "arg", m_two, &ctx), pi);
+    let ip = st.mul(vec![i, p]);
+    let half = st.rat(1, 2);
+    assert_eq!(call(&mut st, "arg", ip, &ctx), st.mul(vec![half, pi]));
+    let w = st.add(vec![x, iy]);
+    assert_eq!(call(&mut st, "arg", w, &ctx), st.func("atan2", vec![y, x]));
+    // arg(0) is undefined and stays unevaluated
+    let zero = st.int(0);
+    assert_eq!(call(&mut st, "arg", zero, &ctx), st.func("arg", vec![zero]));
+    // A real symbol of unknown sign keeps its absolute value
+    let abs_x = st.func("abs", vec![x]);
+    assert_eq!(simplify(&mut st, abs_x), abs_x);
+}
+
+#[test]
+fn real_imag_decomposition() {
+    let mut st = Store::new();
+    let ctx = real_ctx();
+    let x = st.sym("x");
+    let y = st.sym("y");
+    let i = st.constant(Constant::I);
+    let iy = st.mul(vec![i, y]);
+    let w = st.add(vec![x, iy]);
+    // (x + I y)^2 = (x^2 - y^2) + I 2 x y
+    let two = st.int(2);
+    let w2 = st.pow(w, two);
+    let (a, b) = as_real_imag(&mut st, w2, &ctx);
+    let x2 = st.pow(x, two);
+    let y2 = st.pow(y, two);
+    let m_one = st.int(-1);
+    let my2 = st.mul(vec![y2, m_one]);
+    assert_eq!(a, st.add(vec![x2, my2]));
+    assert_eq!(b, st.mul(vec![two, x, y]));
+    // exp(x + I y) = exp(x) cos(y) + I exp(x) sin(y)
+    let ew = st.func("exp", vec![w]);
+    let (a, b) = as_real_imag(&mut st, ew, &ctx);
+    let ex = st.func("exp", vec![x]);
+    let (cy, sy) = (st.func("cos", vec![y]), st.func("sin", vec![y]));
+    assert_eq!(a, st.mul(vec![ex, cy]));
+    assert_eq!(b, st.mul(vec![ex, sy]));
+    // sin(x + I y) = sin(x) cosh(y) + I cos(x) sinh(y)
+    let sw = st.func("sin", vec![w]);
+    let (a, b) = as_real_imag(&mut st, sw, &ctx);
+    let (sx, cx) = (st.func("sin", vec![x]), st.func("cos", vec![x]));
+    let (shy, chy) = (st.func("sinh", vec![y]), st.func("cosh", vec![y]));
+    assert_eq!(a, st.mul(vec![sx, chy]));
+    assert_eq!(b, st.mul(vec![cx, shy]));
+    // Unknown symbols keep re and im
+    let z = st.sym("z");
+    let (a, b) = as_real_imag(&mut st, z, &ctx);
+    assert_eq!(a, st.func("re", vec![z]));
+    assert_eq!(b, st.func("im", vec![z]));
+}
+
+#[test]
+fn polar_form() {
+    let mut st = Store::new();
+    let ctx = real_ctx();
+    let y = st.sym("y");
+    let i = st.constant(Constant::I);
+    // 3 exp(I y) has modulus 3 and argument y
+    let three = st.int(3);
+    let iy = st.mul(vec![i, y]);
+    let e = st.func("exp", vec![iy]);
+    let z = st.mul(vec![three, e]);
+    assert_eq!(as_polar(&mut st, z, &ctx), (three, y));
+}
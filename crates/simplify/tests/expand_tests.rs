@@ -0,0 +1,182 @@
+//! Integration tests for configurable expansion
+
+use assumptions::{Context, Prop};
+use expr_core::{Op, Store};
+use simplify::{expand, expand_with, ExpandOptions};
+
+#[test]
+fn test_multinomial_expansion() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let y = st.sym("y");
+    let two = st.int(2);
+    let m_one = st.int(-1);
+    // (x + y)^2 (x - y) = x^3 + x^2 y - x y^2 - y^3
+    let s = st.add(vec![x, y]);
+    let s2 = st.pow(s, two);
+    let my = st.mul(vec![m_one, y]);
+    let d = st.add(vec![x, my]);
+    let e = st.mul(vec![s2, d]);
+    let r = expand(&mut st, e, &ExpandOptions::default());
+    let three = st.int(3);
+    let (x2, x3, y2, y3) = (st.pow(x, two), st.pow(x, three), st.pow(y, two), st.pow(y, three));
+    let terms = vec![x3, st.mul(vec![x2, y]), st.mul(vec![m_one, x, y2]), st.mul(vec![m_one, y3])];
+    assert_eq!(r, st.add(terms));
+    // Expansion inside function arguments; x * x^-1 cancels
+    let f = st.func("f", vec![e]);
+    let rf = expand(&mut st, f, &ExpandOptions::default());
+    assert_eq!(rf, st.func("f", vec![r]));
+    let ix = st.pow(x, m_one);
+    let q = st.mul(vec![ix, s]);
+    let r = expand(&mut st, q, &ExpandOptions::default());
+    assert_eq!(st.to_string(r), "1 + x^-1 * y");
+    // With the flag off products of sums stay
+    let off = ExpandOptions { multinomial: false, ..ExpandOptions::default() };
+    assert_eq!(expand(&mut st, e, &off), e);
+}
+
+#[test]
+fn test_large_expansion_term_count() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let y = st.sym("y");
+    let z = st.sym("z");
+    let one = st.int(1);
+    let n = st.int(20);
+    // (x + y + z + 1)^20 has C(23, 3) = 1771 terms
+    let s = st.add(vec![x, y, z, one]);
+    let p = st.pow(s, n);
+    let r = expand(&mut st, p, &ExpandOptions::default());
+    assert_eq!(st.get(r).op, Op::Add);
+    assert_eq!(st.get(r).children.len(), 1771);
+}
+
+#[test]
+fn test_expansion_coefficients_past_i64() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let one = st.int(1);
+    let seventy = st.int(70);
+    // C(70, 35) = 112186277816662845432 does not fit in i64
+    let s = st.add(vec![x, one]);
+    let p = st.pow(s, seventy);
+    let r = expand(&mut st, p, &ExpandOptions::default());
+    assert_eq!(st.get(r).children.len(), 71);
+    let c = st.bigint("112186277816662845432".parse().unwrap());
+    let thirty_five = st.int(35);
+    let x35 = st.pow(x, thirty_five);
+    let term = st.mul(vec![c, x35]);
+    assert!(st.get(r).children.contains(&term));
+}
+
+#[test]
+fn test_huge_powers_stay_whole() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let one = st.int(1);
+    let two = st.int(2);
+    // (x^2000000000 + 1)^2 multiplies out without expanding x^2000000000
+    let big = st.int(2_000_000_000);
+    let xb = st.pow(x, big);
+    let s = st.add(vec![xb, one]);
+    let p = st.pow(s, two);
+    let r = expand(&mut st, p, &ExpandOptions::default());
+    let bigger = st.int(4_000_000_000);
+    let x2b = st.pow(x, bigger);
+    let mid = st.mul(vec![two, xb]);
+    assert_eq!(r, st.add(vec![x2b, mid, one]));
+    // (x + 1)^100000 would have too many terms
+    let n = st.int(100_000);
+    let s = st.add(vec![x, one]);
+    let p = st.pow(s, n);
+    assert_eq!(expand(&mut st, p, &ExpandOptions::default()), p);
+}
+
+#[test]
+fn test_trig_expansion_is_opt_in() {
+    let mut st = Store::new();
+    let a = st.sym("a");
+    let b = st.sym("b");
+    let ab = st.add(vec![a, b]);
+    let s = st.func("sin", vec![ab]);
+    assert_eq!(expand(&mut st, s, &ExpandOptions::default()), s);
+    let trig = ExpandOptions { trig: true, ..ExpandOptions::none() };
+    let r = expand(&mut st, s, &trig);
+    let (sa, sb) = (st.func("sin", vec![a]), st.func("sin", vec![b]));
+    let (ca, cb) = (st.func("cos", vec![a]), st.func("cos", vec![b]));
+    let (t1, t2) = (st.mul(vec![sa, cb]), st.mul(vec![ca, sb]));
+    assert_eq!(r, st.add(vec![t1, t2]));
+    let c = st.func("cos", vec![ab]);
+    let r = expand(&mut st, c, &trig);
+    let m_one = st.int(-1);
+    let (t1, t2) = (st.mul(vec![ca, cb]), st.mul(vec![m_one, sa, sb]));
+    assert_eq!(r, st.add(vec![t1, t2]));
+}
+
+#[test]
+fn test_trig_expansion_of_min_coefficient() {
+    // -i64::MIN has no i64 value: sin(i64::MIN a) stays as it is
+    let mut st = Store::new();
+    let a = st.sym("a");
+    let min = st.int(i64::MIN);
+    let arg = st.mul(vec![min, a]);
+    let s = st.func("sin", vec![arg]);
+    let trig = ExpandOptions { trig: true, ..ExpandOptions::none() };
+    assert_eq!(expand(&mut st, s, &trig), s);
+}
+
+#[test]
+fn test_log_expansion_needs_assumptions() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let y = st.sym("y");
+    let three = st.int(3);
+    let y3 = st.pow(y, three);
+    let xy3 = st.mul(vec![x, y3]);
+    let l = st.func("ln", vec![xy3]);
+    let opts = ExpandOptions::default();
+    assert_eq!(expand(&mut st, l, &opts), l);
+    let mut ctx = Context::new();
+    ctx.assume("x", Prop::Positive);
+    ctx.assume("y", Prop::Positive);
+    // ln(x y^3) = ln(x) + 3 ln(y)
+    let r = expand_with(&mut st, l, &opts, &ctx);
+    let lx = st.func("ln", vec![x]);
+    let ly = st.func("ln", vec![y]);
+    let t = st.mul(vec![three, ly]);
+    assert_eq!(r, st.add(vec![lx, t]));
+}
+
+#[test]
+fn test_power_base_and_exp() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let y = st.sym("y");
+    let n = st.sym("n");
+    let half = st.rat(1, 2);
+    let xy = st.mul(vec![x, y]);
+    let root = st.pow(xy, half);
+    let base = ExpandOptions { power_base: true, ..ExpandOptions::none() };
+    // sqrt(x y) only splits for positive factors
+    assert_eq!(expand(&mut st, root, &base), root);
+    let mut ctx = Context::new();
+    ctx.assume("x", Prop::Positive);
+    let r = expand_with(&mut st, root, &base, &ctx);
+    let sx = st.pow(x, half);
+    let sy = st.pow(y, half);
+    assert_eq!(r, st.mul(vec![sx, sy]));
+    // Integer exponents always split
+    let m_two = st.int(-2);
+    let inv = st.pow(xy, m_two);
+    let r = expand(&mut st, inv, &base);
+    let (ix, iy) = (st.pow(x, m_two), st.pow(y, m_two));
+    assert_eq!(r, st.mul(vec![ix, iy]));
+    // x^(n + 1) = x^n x
+    let one = st.int(1);
+    let np1 = st.add(vec![n, one]);
+    let p = st.pow(x, np1);
+    let exp = ExpandOptions { power_exp: true, ..ExpandOptions::none() };
+    let r = expand(&mut st, p, &exp);
+    let xn = st.pow(x, n);
+    assert_eq!(r, st.mul(vec![xn, x]));
+}
@@ -0,0 +1,109 @@
+//! `simplify_with_options`: enabled passes, measure and budget
+
+use assumptions::Context;
+use expr_core::Store;
+use simplify::{simplify, simplify_with_options, Pass, SimplifyOptions, TrigStrategy};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn measure_refuses_growing_rewrites() {
+    // The trig pass turns sin(x/2)^2 into (1 - cos(x))/2, which has more ops
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let half = st.rat(1, 2);
+    let hx = st.mul(vec![half, x]);
+    let s = st.func("sin", vec![hx]);
+    let two = st.int(2);
+    let s2 = st.pow(s, two);
+    let ctx = Context::default();
+    assert_ne!(simplify(&mut st, s2), s2);
+    let opts = SimplifyOptions {
+        measure: Some(Arc::new(|st: &Store, id| st.count_ops(id))),
+        ..Default::default()
+    };
+    assert_eq!(simplify_with_options(&mut st, s2, &ctx, &opts), s2);
+    // Rewrites that shrink the expression are still taken
+    let sx = st.func("sin", vec![x]);
+    let cx = st.func("cos", vec![x]);
+    let e = st.mul(vec![two, sx, cx]);
+    let r = simplify_with_options(&mut st, e, &ctx, &opts);
+    let two_x = st.mul(vec![two, x]);
+    assert_eq!(r, st.func("sin", vec![two_x]));
+}
+
+#[test]
+fn enabled_passes() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let two = st.int(2);
+    let sx = st.func("sin", vec![x]);
+    let cx = st.func("cos", vec![x]);
+    let e = st.mul(vec![two, sx, cx]);
+    let ctx = Context::default();
+    let no_trig = SimplifyOptions {
+        passes: Pass::ALL.into_iter().filter(|&p| p != Pass::Trig).collect(),
+        ..Default::default()
+    };
+    assert_eq!(simplify_with_options(&mut st, e, &ctx, &no_trig), e);
+    // Without the basic pass, x + x is not collected
+    let xx = st.add(vec![x, x]);
+    let only_trig = SimplifyOptions { passes: vec![Pass::Trig], ..Default::default() };
+    assert_eq!(simplify_with_options(&mut st, xx, &ctx, &only_trig), xx);
+    assert_eq!(
+        simplify_with_options(&mut st, xx, &ctx, &SimplifyOptions::default()),
+        st.mul(vec![two, x])
+    );
+}
+
+#[test]
+fn step_and_time_budget() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let two = st.int(2);
+    let xx = st.add(vec![x, x]);
+    let sx = st.func("sin", vec![xx]);
+    let cx = st.func("cos", vec![xx]);
+    let e = st.mul(vec![two, sx, cx]);
+    let ctx = Context::default();
+    let none = SimplifyOptions { max_steps: Some(0), ..Default::default() };
+    assert_eq!(simplify_with_options(&mut st, e, &ctx, &none), e);
+    let expired = SimplifyOptions { time_limit: Some(Duration::ZERO), ..Default::default() };
+    assert_eq!(simplify_with_options(&mut st, e, &ctx, &expired), e);
+    // One step: the basic pass collects x + x, the trig pass is not reached
+    let one = SimplifyOptions { max_steps: Some(1), ..Default::default() };
+    let r = simplify_with_options(&mut st, e, &ctx, &one);
+    let two_x = st.mul(vec![two, x]);
+    let s2 = st.func("sin", vec![two_x]);
+    let c2 = st.func("cos", vec![two_x]);
+    assert_eq!(r, st.mul(vec![two, s2, c2]));
+    // Unlimited: 2 sin(2x) cos(2x) = sin(4x)
+    let r = simplify_with_options(&mut st, e, &ctx, &SimplifyOptions::default());
+    let four = st.int(4);
+    let four_x = st.mul(vec![four, x]);
+    assert_eq!(r, st.func("sin", vec![four_x]));
+}
+
+#[test]
+fn fu_search_ranks_by_measure() {
+    // 1 - 2 sin(x)^2 = cos(2x) under the Fu strategy, also with a measure and
+    // a step budget large enough for the search
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let two = st.int(2);
+    let m_two = st.int(-2);
+    let one = st.int(1);
+    let s = st.func("sin", vec![x]);
+    let s2 = st.pow(s, two);
+    let t = st.mul(vec![m_two, s2]);
+    let e = st.add(vec![one, t]);
+    let opts = SimplifyOptions {
+        strategy: TrigStrategy::Fu,
+        measure: Some(Arc::new(|st: &Store, id| st.count_ops(id))),
+        max_steps: Some(500),
+        ..Default::default()
+    };
+    let r = simplify_with_options(&mut st, e, &Context::default(), &opts);
+    let two_x = st.mul(vec![two, x]);
+    assert_eq!(r, st.func("cos", vec![two_x]));
+}
@@ -0,0 +1,167 @@
+//! `rewrite` between function families
+
+use expr_core::{Constant, Store};
+use simplify::{rewrite, simplify, RewriteTarget};
+
+#[test]
+fn trig_and_hyperbolic_to_exp() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let i = st.constant(Constant::I);
+    let m_one = st.int(-1);
+    // sin x = -I/2 (exp(I x) - exp(-I x))
+    let sin = st.func("sin", vec![x]);
+    let r = rewrite(&mut st, sin, RewriteTarget::Exp);
+    let ix = st.mul(vec![i, x]);
+    let mix = st.mul(vec![m_one, i, x]);
+    let (p, q) = (st.func("exp", vec![ix]), st.func("exp", vec![mix]));
+    let mq = st.mul(vec![m_one, q]);
+    let diff = st.add(vec![p, mq]);
+    let m_half = st.rat(-1, 2);
+    assert_eq!(r, st.mul(vec![m_half, i, diff]));
+    // tanh x = (exp(x) - exp(-x)) / (exp(x) + exp(-x))
+    let tanh = st.func("tanh", vec![x]);
+    let r = rewrite(&mut st, tanh, RewriteTarget::Exp);
+    let mx = st.mul(vec![m_one, x]);
+    let (p, q) = (st.func("exp", vec![x]), st.func("exp", vec![mx]));
+    let mq = st.mul(vec![m_one, q]);
+    let num = st.add(vec![p, mq]);
+    let den = st.add(vec![p, q]);
+    let inv = st.pow(den, m_one);
+    assert_eq!(r, st.mul(vec![num, inv]));
+    // Other functions and arguments are left alone
+    let ln = st.func("ln", vec![x]);
+    assert_eq!(rewrite(&mut st, ln, RewriteTarget::Exp), ln);
+}
+
+#[test]
+fn exp_to_trig_and_back() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let y = st.sym("y");
+    let i = st.constant(Constant::I);
+    // exp(x + I y) = exp(x) (cos y + I sin y)
+    let iy = st.mul(vec![i, y]);
+    let w = st.add(vec![x, iy]);
+    let e = st.func("exp", vec![w]);
+    let r = rewrite(&mut st, e, RewriteTarget::Trig);
+    let (c, s) = (st.func("cos", vec![y]), st.func("sin", vec![y]));
+    let is = st.mul(vec![i, s]);
+    let euler = st.add(vec![c, is]);
+    let ex = st.func("exp", vec![x]);
+    assert_eq!(r, st.mul(vec![ex, euler]));
+    // exp(-2 I y) = cos(2 y) - I sin(2 y)
+    let m_two = st.int(-2);
+    let t = st.mul(vec![m_two, i, y]);
+    let e = st.func("exp", vec![t]);
+    let r = rewrite(&mut st, e, RewriteTarget::Trig);
+    let two = st.int(2);
+    let y2 = st.mul(vec![two, y]);
+    let (c, s) = (st.func("cos", vec![y2]), st.func("sin", vec![y2]));
+    let m_one = st.int(-1);
+    let mis = st.mul(vec![m_one, i, s]);
+    assert_eq!(r, st.add(vec![c, mis]));
+    // Real exponentials stay
+    assert_eq!(rewrite(&mut st, ex, RewriteTarget::Trig), ex);
+    // cos -> exp -> trig simplifies back to cos
+    let cos = st.func("cos", vec![x]);
+    let r = rewrite(&mut st, cos, RewriteTarget::Exp);
+    let r = rewrite(&mut st, r, RewriteTarget::Trig);
+    assert_eq!(simplify(&mut st, r), cos);
+}
+
+#[test]
+fn quotients_to_sin_cos() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let m_one = st.int(-1);
+    // tan(x) + sec(x) = sin(x)/cos(x) + 1/cos(x)
+    let tan = st.func("tan", vec![x]);
+    let sec = st.func("sec", vec![x]);
+    let e = st.add(vec![tan, sec]);
+    let r = rewrite(&mut st, e, RewriteTarget::SinCos);
+    let (s, c) = (st.func("sin", vec![x]), st.func("cos", vec![x]));
+    let ic = st.pow(c, m_one);
+    let q = st.mul(vec![s, ic]);
+    assert_eq!(r, st.add(vec![q, ic]));
+    // Nested calls are rewritten inside out: coth(tan x)
+    let coth = st.func("coth", vec![tan]);
+    let r = rewrite(&mut st, coth, RewriteTarget::SinCos);
+    let (sh, ch) = (st.func("sinh", vec![q]), st.func("cosh", vec![q]));
+    let ish = st.pow(sh, m_one);
+    assert_eq!(r, st.mul(vec![ch, ish]));
+}
+
+#[test]
+fn inverse_functions_to_log() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let one = st.int(1);
+    let two = st.int(2);
+    let m_one = st.int(-1);
+    let half = st.rat(1, 2);
+    // asinh x = ln(x + sqrt(x^2 + 1))
+    let asinh = st.func("asinh", vec![x]);
+    let r = rewrite(&mut st, asinh, RewriteTarget::Log);
+    let x2 = st.pow(x, two);
+    let s = st.add(vec![x2, one]);
+    let root = st.pow(s, half);
+    let a = st.add(vec![x, root]);
+    assert_eq!(r, st.func("ln", vec![a]));
+    // atanh x = (ln(1 + x) - ln(1 - x))/2
+    let atanh = st.func("atanh", vec![x]);
+    let r = rewrite(&mut st, atanh, RewriteTarget::Log);
+    let mx = st.mul(vec![m_one, x]);
+    let p = st.add(vec![one, x]);
+    let m = st.add(vec![one, mx]);
+    let (lp, lm) = (st.func("ln", vec![p]), st.func("ln", vec![m]));
+    let mlm = st.mul(vec![m_one, lm]);
+    let d = st.add(vec![lp, mlm]);
+    assert_eq!(r, st.mul(vec![half, d]));
+    // asin x = -I ln(I x + sqrt(1 - x^2))
+    let asin = st.func("asin", vec![x]);
+    let r = rewrite(&mut st, asin, RewriteTarget::Log);
+    let i = st.constant(Constant::I);
+    let mx2 = st.mul(vec![m_one, x2]);
+    let d = st.add(vec![one, mx2]);
+    let root = st.pow(d, half);
+    let ix = st.mul(vec![i, x]);
+    let a = st.add(vec![ix, root]);
+    let l = st.func("ln", vec![a]);
+    assert_eq!(r, st.mul(vec![m_one, i, l]));
+}
+
+#[test]
+fn gamma_factorial_binomial() {
+    let mut st = Store::new();
+    let n = st.sym("n");
+    let k = st.sym("k");
+    let one = st.int(1);
+    let m_one = st.int(-1);
+    // factorial(n) = Gamma(n + 1), Gamma(5) = factorial(4)
+    let fact = st.func("factorial", vec![n]);
+    let n1 = st.add(vec![n, one]);
+    let g = st.func("Gamma", vec![n1]);
+    assert_eq!(rewrite(&mut st, fact, RewriteTarget::Gamma), g);
+    assert_eq!(rewrite(&mut st, g, RewriteTarget::Factorial), fact);
+    let five = st.int(5);
+    let g5 = st.func("Gamma", vec![five]);
+    let four = st.int(4);
+    let f4 = st.func("factorial", vec![four]);
+    assert_eq!(rewrite(&mut st, g5, RewriteTarget::Factorial), f4);
+    // binomial(n, k) = n! / (k! (n - k)!)
+    let b = st.func("binomial", vec![n, k]);
+    let r = rewrite(&mut st, b, RewriteTarget::Factorial);
+    let mk = st.mul(vec![m_one, k]);
+    let nk = st.add(vec![n, mk]);
+    let (fk, fnk) = (st.func("factorial", vec![k]), st.func("factorial", vec![nk]));
+    let (ifk, ifnk) = (st.pow(fk, m_one), st.pow(fnk, m_one));
+    assert_eq!(r, st.mul(vec![fact, ifk, ifnk]));
+    // and via Gamma: Gamma(n + 1) / (Gamma(k + 1) Gamma(n - k + 1))
+    let r = rewrite(&mut st, b, RewriteTarget::Gamma);
+    let k1 = st.add(vec![k, one]);
+    let nk1 = st.add(vec![nk, one]);
+    let (gk, gnk) = (st.func("Gamma", vec![k1]), st.func("Gamma", vec![nk1]));
+    let (igk, ignk) = (st.pow(gk, m_one), st.pow(gnk, m_one));
+    assert_eq!(r, st.mul(vec![g, igk, ignk]));
+}
@@ -0,0 +1,53 @@
+//! Integration tests for simplification traces
+
+use assumptions::{Context, Prop};
+use expr_core::Store;
+use simplify::{simplify_traced, simplify_with};
+
+#[test]
+fn test_trace_records_steps_and_assumptions() {
+    let mut st = Store::new();
+    let mut ctx = Context::new();
+    ctx.assume("x", Prop::Positive);
+    ctx.assume("y", Prop::Positive);
+    let x = st.sym("x");
+    let y = st.sym("y");
+    let xy = st.mul(vec![x, y]);
+    let ln_xy = st.func("ln", vec![xy]);
+    let ln_x = st.func("ln", vec![x]);
+    let m_one = st.int(-1);
+    let neg_ln_x = st.mul(vec![m_one, ln_x]);
+    // ln(x y) - ln(x) = ln(y) for x, y > 0
+    let e = st.add(vec![ln_xy, neg_ln_x]);
+
+    let (result, trace) = simplify_traced(&mut st, e, &ctx);
+    assert_eq!(result, simplify_with(&mut st, e, &ctx));
+    assert_eq!(result, st.func("ln", vec![y]));
+
+    // The basic pass expands ln(x y) using x, y > 0, then logs are contracted
+    let first = &trace.steps[0];
+    assert_eq!((first.rule, first.before), ("basic", e));
+    assert!(first.assumptions.contains(&("x".to_string(), Prop::Positive)));
+    assert!(trace.steps.iter().any(|s| s.rule == "contract_log" && s.assumptions.is_empty()));
+    // The final cancellation is localized to the argument of ln
+    let last = trace.steps.last().unwrap();
+    assert_eq!(last.after, y);
+
+    let text = trace.to_text(&st);
+    assert!(text.starts_with("1. basic: ln(x * y) + -1 * ln(x) -> "), "{text}");
+    assert!(text.contains("[assuming x > 0, y > 0]"), "{text}");
+    let latex = trace.to_latex(|e| io::to_latex(&st, e));
+    assert!(latex.starts_with("\\begin{aligned}"));
+    assert!(latex.contains("\\text{contract\\_log}"), "{latex}");
+}
+
+#[test]
+fn test_trace_of_simplified_input_is_empty() {
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let sx = st.func("sin", vec![x]);
+    let (result, trace) = simplify_traced(&mut st, sx, &Context::new());
+    assert_eq!(result, sx);
+    assert!(trace.is_empty());
+    assert_eq!(trace.to_text(&st), "");
+}
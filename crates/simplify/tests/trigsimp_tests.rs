@@ -0,0 +1,148 @@
+//! Fu-style trigonometric simplification (`trigsimp` and the `Fu` strategy)
+
+use assumptions::Context;
+use expr_core::Store;
+use simplify::{simplify_with_strategy, trigsimp, TrigStrategy};
+
+#[test]
+fn fourth_powers_reduce_to_double_angle() {
+    // sin(x)^4 - cos(x)^4 = -cos(2x)
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let four = st.int(4);
+    let s = st.func("sin", vec![x]);
+    let c = st.func("cos", vec![x]);
+    let s4 = st.pow(s, four);
+    let c4 = st.pow(c, four);
+    let m_one = st.int(-1);
+    let neg_c4 = st.mul(vec![m_one, c4]);
+    let e = st.add(vec![s4, neg_c4]);
+    let r = trigsimp(&mut st, e);
+    assert_eq!(st.to_string(r), "-1 * cos(2 * x)");
+}
+
+#[test]
+fn products_become_sums() {
+    // 2 sin(x) cos(y) - sin(x + y) = sin(x - y)
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let y = st.sym("y");
+    let two = st.int(2);
+    let m_one = st.int(-1);
+    let sx = st.func("sin", vec![x]);
+    let cy = st.func("cos", vec![y]);
+    let prod = st.mul(vec![two, sx, cy]);
+    let xy = st.add(vec![x, y]);
+    let sxy = st.func("sin", vec![xy]);
+    let neg = st.mul(vec![m_one, sxy]);
+    let e = st.add(vec![prod, neg]);
+    let r = trigsimp(&mut st, e);
+    let my = st.mul(vec![m_one, y]);
+    let x_my = st.add(vec![x, my]);
+    assert_eq!(r, st.func("sin", vec![x_my]));
+}
+
+#[test]
+fn angle_sums_contract() {
+    // cos(a) cos(b) - sin(a) sin(b) = cos(a + b), as in planar kinematics
+    let mut st = Store::new();
+    let a = st.sym("a");
+    let b = st.sym("b");
+    let m_one = st.int(-1);
+    let ca = st.func("cos", vec![a]);
+    let cb = st.func("cos", vec![b]);
+    let sa = st.func("sin", vec![a]);
+    let sb = st.func("sin", vec![b]);
+    let cc = st.mul(vec![ca, cb]);
+    let ss = st.mul(vec![m_one, sa, sb]);
+    let e = st.add(vec![cc, ss]);
+    let r = trigsimp(&mut st, e);
+    let ab = st.add(vec![a, b]);
+    assert_eq!(r, st.func("cos", vec![ab]));
+    // tan(x) cos(x) = sin(x)
+    let x = st.sym("x");
+    let t = st.func("tan", vec![x]);
+    let c = st.func("cos", vec![x]);
+    let e = st.mul(vec![t, c]);
+    assert_eq!(trigsimp(&mut st, e), st.func("sin", vec![x]));
+}
+
+#[test]
+fn fu_strategy_in_pipeline() {
+    // 1 - 2 sin(x)^2 is cos(2x) only with the Fu strategy
+    let mut st = Store::new();
+    let x = st.sym("x");
+    let two = st.int(2);
+    let m_two = st.int(-2);
+    let one = st.int(1);
+    let s = st.func("sin", vec![x]);
+    let s2 = st.pow(s, two);
+    let t = st.mul(vec![m_two, s2]);
+    let e = st.add(vec![one, t]);
+    let ctx = Context::default();
+    let r = simplify_with_strategy(&mut st, e, &ctx, TrigStrategy::Identities);
+    assert_eq!(r, simplify::simplify(&mut st, e));
+    let r = simplify_with_strategy(&mut st, e, &ctx, TrigStrategy::Fu);
+    let two_x = st.mul(vec![two, x]);
+    assert_eq!(r, st.func("cos", vec![two_x]));
+    // Expressions without trig functions are unaffected
+    let p = st.add(vec![x, one]);
+    assert_eq!(trigsimp(&mut st, p), p);
+}
+
+#[test]
+fn kinematics_norm() {
+    // (cos a cos b - sin a sin b)^2 + (sin a cos b + cos a sin b)^2 = 1
+    let mut st = Store::new();
+    let a = st.sym("a");
+    let b = st.sym("b");
+    let m_one = st.int(-1);
+    let two = st.int(2);
+    let ca = st.func("cos", vec![a]);
+    let cb = st.func("cos", vec![b]);
+    let sa = st.func("sin", vec![a]);
+    let sb = st.func("sin", vec![b]);
+    let cc = st.mul(vec![ca, cb]);
+    let ss = st.mul(vec![m_one, sa, sb]);
+    let px = st.add(vec![cc, ss]);
+    let sc = st.mul(vec![sa, cb]);
+    let cs = st.mul(vec![ca, sb]);
+    let py = st.add(vec![sc, cs]);
+    let px2 = st.pow(px, two);
+    let py2 = st.pow(py, two);
+    let e = st.add(vec![px2, py2]);
+    assert_eq!(trigsimp(&mut st, e), st.int(1));
+}
+
+#[test]
+fn three_link_arm_norm() {
+    // x = cos a + cos(a + b) + cos(a + b + c), y likewise with sin:
+    // x^2 + y^2 = 3 + 2 cos b + 2 cos c + 2 cos(b + c), without factoring
+    // the six trigonometric terms (TR0) on the way
+    let mut st = Store::new();
+    let a = st.sym("a");
+    let b = st.sym("b");
+    let c = st.sym("c");
+    let two = st.int(2);
+    let three = st.int(3);
+    let ab = st.add(vec![a, b]);
+    let abc = st.add(vec![a, b, c]);
+    let cs: Vec<_> = [a, ab, abc].into_iter().map(|u| st.func("cos", vec![u])).collect();
+    let ss: Vec<_> = [a, ab, abc].into_iter().map(|u| st.func("sin", vec![u])).collect();
+    let x = st.add(cs);
+    let y = st.add(ss);
+    let x2 = st.pow(x, two);
+    let y2 = st.pow(y, two);
+    let e = st.add(vec![x2, y2]);
+    let bc = st.add(vec![b, c]);
+    let terms: Vec<_> = [b, c, bc]
+        .into_iter()
+        .map(|u| {
+            let f = st.func("cos", vec![u]);
+            st.mul(vec![two, f])
+        })
+        .chain([three])
+        .collect();
+    let expected = st.add(terms);
+    assert_eq!(trigsimp(&mut st, e), expected);
+}
@@ -8,7 +8,7 @@
 
 pub mod transcendental;
 
-use arith::{add_q, div_q, mul_q, sub_q, Q};
+use arith::{checked_add_q, checked_div_q, checked_mul_q, checked_sub_q, Q};
 use expr_core::{ExprId, Op, Payload, Store};
 use polys::{expr_to_unipoly, UniPoly};
 
@@ -23,7 +23,7 @@ pub fn solve_univariate(store: &mut Store, expr: ExprId, var: &str) -> Option<Ve
     }
 
     // Use factorization to find all roots
-    let factors = p0.factor();
+    let factors = p0.checked_factor().ok()?;
     let mut roots: Vec<ExprId> = Vec::new();
 
     // Helper: convert Q to Expr
@@ -47,7 +47,7 @@ pub fn solve_univariate(store: &mut Store, expr: ExprId, var: &str) -> Option<Ve
                 let a0 = factor.coeffs.first().copied().unwrap_or(Q::zero());
                 let a1 = factor.coeffs.get(1).copied().unwrap_or(Q::zero());
                 if !a1.is_zero() {
-                    let root = div_q(Q(-a0.0, a0.1), a1);
+                    let root = checked_div_q(checked_mul_q(Q(-1, 1), a0).ok()?, a1).ok()?;
                     // Add root with its multiplicity
                     for _ in 0..multiplicity {
                         roots.push(q_to_expr(store, root));
@@ -110,29 +110,42 @@ fn solve_quartic(store: &mut Store, p: &UniPoly) -> Option<Vec<ExprId>> {
     }
 
     // Normalize: divide by a4 to get monic polynomial x^4 + bx^3 + cx^2 + dx + e
-    let b = div_q(a3, a4);
-    let c = div_q(a2, a4);
-    let d = div_q(a1, a4);
-    let e = div_q(a0, a4);
+    let b = checked_div_q(a3, a4).ok()?;
+    let c = checked_div_q(a2, a4).ok()?;
+    let d = checked_div_q(a1, a4).ok()?;
+    let e = checked_div_q(a0, a4).ok()?;
 
     // Convert to depressed quartic y^4 + py^2 + qy + r = 0
     // using substitution x = y - b/4
-    let b2 = mul_q(b, b);
-    let b3 = mul_q(b2, b);
-    let b4 = mul_q(b3, b);
-
-    let p_dep = sub_q(c, mul_q(Q(3, 8), b2));
-    let q_dep = add_q(sub_q(mul_q(Q(1, 8), mul_q(b3, b)), mul_q(Q(1, 2), mul_q(b, c))), d);
-    let r_dep = add_q(
-        add_q(mul_q(Q(-3, 256), b4), mul_q(Q(1, 16), mul_q(b2, c))),
-        add_q(mul_q(Q(-1, 4), mul_q(b, d)), e),
-    );
+    let b2 = checked_mul_q(b, b).ok()?;
+    let b3 = checked_mul_q(b2, b).ok()?;
+    let b4 = checked_mul_q(b3, b).ok()?;
+
+    let p_dep = checked_sub_q(c, checked_mul_q(Q(3, 8), b2).ok()?).ok()?;
+    let q_dep = checked_add_q(
+        checked_sub_q(
+            checked_mul_q(Q(1, 8), checked_mul_q(b3, b).ok()?).ok()?,
+            checked_mul_q(Q(1, 2), checked_mul_q(b, c).ok()?).ok()?,
+        )
+        .ok()?,
+        d,
+    )
+    .ok()?;
+    let r_dep = checked_add_q(
+        checked_add_q(
+            checked_mul_q(Q(-3, 256), b4).ok()?,
+            checked_mul_q(Q(1, 16), checked_mul_q(b2, c).ok()?).ok()?,
+        )
+        .ok()?,
+        checked_add_q(checked_mul_q(Q(-1, 4), checked_mul_q(b, d).ok()?).ok()?, e).ok()?,
+    )
+    .ok()?;
 
     // Build resolvent cubic: z^3 + 2p*z^2 + (p^2 - 4r)*z - q^2 = 0
-    let p2 = mul_q(p_dep, p_dep);
-    let two_p = mul_q(Q(2, 1), p_dep);
-    let p2_minus_4r = sub_q(p2, mul_q(Q(4, 1), r_dep));
-    let neg_q2 = mul_q(Q(-1, 1), mul_q(q_dep, q_dep));
+    let p2 = checked_mul_q(p_dep, p_dep).ok()?;
+    let two_p = checked_mul_q(Q(2, 1), p_dep).ok()?;
+    let p2_minus_4r = checked_sub_q(p2, checked_mul_q(Q(4, 1), r_dep).ok()?).ok()?;
+    let neg_q2 = checked_mul_q(Q(-1, 1), checked_mul_q(q_dep, q_dep).ok()?).ok()?;
 
     // Build resolvent cubic polynomial
     let resolvent =
@@ -162,7 +175,7 @@ fn solve_quartic(store: &mut Store, p: &UniPoly) -> Option<Vec<ExprId>> {
     // x = y - b/4, so we need to convert the y roots back to x roots
 
     // Simplified: return one symbolic root
-    let b_over_4 = q_to_expr(store, div_q(b, Q(4, 1)));
+    let b_over_4 = q_to_expr(store, checked_div_q(b, Q(4, 1)).ok()?);
     let neg_one = store.int(-1);
     let neg_b_over_4 = store.mul(vec![neg_one, b_over_4]);
 
@@ -197,38 +210,46 @@ fn solve_cubic(store: &mut Store, p: &UniPoly) -> Option<Vec<ExprId>> {
     }
 
     // Normalize: divide by a3 to get monic polynomial x^3 + bx^2 + cx + d
-    let b = div_q(a2, a3);
-    let c = div_q(a1, a3);
-    let d = div_q(a0, a3);
+    let b = checked_div_q(a2, a3).ok()?;
+    let c = checked_div_q(a1, a3).ok()?;
+    let d = checked_div_q(a0, a3).ok()?;
 
     // Convert to depressed cubic t^3 + pt + q = 0
     // using substitution x = t - b/3
     // p = c - b^2/3
     // q = 2b^3/27 - bc/3 + d
 
-    let b2 = mul_q(b, b);
-    let b3 = mul_q(b2, b);
+    let b2 = checked_mul_q(b, b).ok()?;
+    let b3 = checked_mul_q(b2, b).ok()?;
 
-    let p = sub_q(c, div_q(b2, Q(3, 1)));
-    let q = add_q(sub_q(div_q(mul_q(Q(2, 1), b3), Q(27, 1)), div_q(mul_q(b, c), Q(3, 1))), d);
+    let p = checked_sub_q(c, checked_div_q(b2, Q(3, 1)).ok()?).ok()?;
+    let q = checked_add_q(
+        checked_sub_q(
+            checked_div_q(checked_mul_q(Q(2, 1), b3).ok()?, Q(27, 1)).ok()?,
+            checked_div_q(checked_mul_q(b, c).ok()?, Q(3, 1)).ok()?,
+        )
+        .ok()?,
+        d,
+    )
+    .ok()?;
 
     // For simplicity, we'll construct one real root using the formula
     // t = cbrt(-q/2 + sqrt(q^2/4 + p^3/27)) + cbrt(-q/2 - sqrt(q^2/4 + p^3/27))
 
     // Calculate the expression under the square root: q^2/4 + p^3/27
-    let p2 = mul_q(p, p);
-    let p3 = mul_q(p2, p);
-    let q2 = mul_q(q, q);
-    let q2_over_4 = div_q(q2, Q(4, 1));
-    let p3_over_27 = div_q(p3, Q(27, 1));
-    let sqrt_arg = add_q(q2_over_4, p3_over_27);
+    let p2 = checked_mul_q(p, p).ok()?;
+    let p3 = checked_mul_q(p2, p).ok()?;
+    let q2 = checked_mul_q(q, q).ok()?;
+    let q2_over_4 = checked_div_q(q2, Q(4, 1)).ok()?;
+    let p3_over_27 = checked_div_q(p3, Q(27, 1)).ok()?;
+    let sqrt_arg = checked_add_q(q2_over_4, p3_over_27).ok()?;
 
     // Build symbolic expressions
     let sqrt_arg_expr = q_to_expr(store, sqrt_arg);
     let half = store.rat(1, 2);
     let sqrt_expr = store.pow(sqrt_arg_expr, half);
 
-    let neg_q_over_2 = q_to_expr(store, div_q(Q(-q.0, q.1), Q(2, 1)));
+    let neg_q_over_2 = q_to_expr(store, checked_div_q(q, Q(-2, 1)).ok()?);
 
     // u = cbrt(-q/2 + sqrt(...))
     let u_arg = store.add(vec![neg_q_over_2, sqrt_expr]);
@@ -246,7 +267,7 @@ fn solve_cubic(store: &mut Store, p: &UniPoly) -> Option<Vec<ExprId>> {
     let t = store.add(vec![u, v]);
 
     // Convert back: x = t - b/3
-    let b_over_3 = q_to_expr(store, div_q(b, Q(3, 1)));
+    let b_over_3 = q_to_expr(store, checked_div_q(b, Q(3, 1)).ok()?);
     let neg_one2 = store.int(-1);
     let neg_b_over_3 = store.mul(vec![neg_one2, b_over_3]);
     let x1 = store.add(vec![t, neg_b_over_3]);
@@ -276,25 +297,20 @@ fn solve_quadratic(store: &mut Store, p: &UniPoly) -> Option<Vec<ExprId>> {
     }
 
     // Discriminant D = b^2 - 4ac
-    let a1sq = mul_q(a1, a1);
-    let four_a2a0 = mul_q(Q(4, 1), mul_q(a2, a0));
-    let d = sub_q(a1sq, four_a2a0);
-    let minus_b = Q(-a1.0, a1.1);
-    let two_a = mul_q(Q(2, 1), a2);
+    let a1sq = checked_mul_q(a1, a1).ok()?;
+    let four_a2a0 = checked_mul_q(Q(4, 1), checked_mul_q(a2, a0).ok()?).ok()?;
+    let d = checked_sub_q(a1sq, four_a2a0).ok()?;
+    let minus_b = checked_mul_q(Q(-1, 1), a1).ok()?;
+    let two_a = checked_mul_q(Q(2, 1), a2).ok()?;
 
     // Check if discriminant is a perfect square
     fn is_square_i64(n: i64) -> Option<i64> {
         if n < 0 {
             return None;
         }
-        let mut i = 0i64;
-        while i * i <= n {
-            if i * i == n {
-                return Some(i);
-            }
-            i += 1;
-        }
-        None
+        // The f64 estimate is within one of the integer square root
+        let r = (n as f64).sqrt() as i64;
+        (r.saturating_sub(1)..=r.saturating_add(1)).find(|&i| i.checked_mul(i) == Some(n))
     }
 
     let sqrt_rational = (|| -> Option<Q> {
@@ -307,8 +323,8 @@ fn solve_quadratic(store: &mut Store, p: &UniPoly) -> Option<Vec<ExprId>> {
 
     if let Some(sq) = sqrt_rational {
         // Rational roots
-        let r1 = div_q(add_q(minus_b, sq), two_a);
-        let r2 = div_q(sub_q(minus_b, sq), two_a);
+        let r1 = checked_div_q(checked_add_q(minus_b, sq).ok()?, two_a).ok()?;
+        let r2 = checked_div_q(checked_sub_q(minus_b, sq).ok()?, two_a).ok()?;
         return Some(vec![q_to_expr(store, r1), q_to_expr(store, r2)]);
     }
 
@@ -320,7 +336,7 @@ fn solve_quadratic(store: &mut Store, p: &UniPoly) -> Option<Vec<ExprId>> {
         store.pow(d_expr, half)
     };
     let inv_two_a = {
-        let inv = div_q(Q(1, 1), two_a);
+        let inv = checked_div_q(Q(1, 1), two_a).ok()?;
         q_to_expr(store, inv)
     };
     let plus = {
@@ -481,43 +497,18 @@ fn solve_linear_for_var(
     rhs: ExprId,
     var: &str,
 ) -> Option<Vec<ExprId>> {
-    // Case 1: lhs is just var
-    if let (Op::Symbol, Payload::Sym(s)) = (&st.get(lhs).op, &st.get(lhs).payload) {
-        if s == var {
-            return Some(vec![rhs]);
-        }
+    let poly = polys::as_poly_in(st, lhs, &[var])?;
+    if poly.len() != 1 {
+        return None;
     }
-
-    // Case 2: lhs is b*var
-    if st.get(lhs).op == Op::Mul {
-        let children = &st.get(lhs).children.clone();
-        let mut var_found = false;
-        let mut coeff_parts = Vec::new();
-
-        for &child in children {
-            if let (Op::Symbol, Payload::Sym(s)) = (&st.get(child).op, &st.get(child).payload) {
-                if s == var {
-                    if var_found {
-                        return None; // var appears twice
-                    }
-                    var_found = true;
-                    continue;
-                }
-            }
-            coeff_parts.push(child);
-        }
-
-        if var_found {
-            let coeff = if coeff_parts.is_empty() { st.int(1) } else { st.mul(coeff_parts) };
-            // x = rhs / coeff
-            let minus_one = st.int(-1);
-            let inv_coeff = st.pow(coeff, minus_one);
-            let solution = st.mul(vec![rhs, inv_coeff]);
-            return Some(vec![solution]);
-        }
+    let &coeff = poly.get(&vec![1])?;
+    if coeff == st.int(1) {
+        return Some(vec![rhs]);
     }
-
-    None
+    // x = rhs / coeff
+    let minus_one = st.int(-1);
+    let inv_coeff = st.pow(coeff, minus_one);
+    Some(vec![st.mul(vec![rhs, inv_coeff])])
 }
 
 #[cfg(test)]
@@ -77,7 +77,7 @@ pub fn gosper_sum(store: &mut Store, term: ExprId, var: &str) -> Option<ExprId>
     // Handle simple cases that we can solve directly
 
     // Case 1: Constant term → n * constant
-    if !contains_var(store, term, var) {
+    if !store.has_free_symbol(term, var) {
         return Some(term); // Anti-difference is just the constant
     }
 
@@ -105,7 +105,7 @@ pub fn gosper_sum(store: &mut Store, term: ExprId, var: &str) -> Option<ExprId>
 
             // Check if exponent is the variable
             if let (Op::Symbol, Payload::Sym(s)) = (&store.get(exp).op, &store.get(exp).payload) {
-                if s == var && !contains_var(store, base, var) {
+                if s == var && !store.has_free_symbol(base, var) {
                     // ∑r^k has anti-difference r^k * r/(r-1)
                     let one = store.int(1);
                     let neg_one = store.int(-1);
@@ -319,21 +319,6 @@ pub fn sum(
 
 // ----------------- Helpers -----------------
 
-fn contains_var(store: &Store, id: ExprId, var: &str) -> bool {
-    match (&store.get(id).op, &store.get(id).payload) {
-        (Op::Symbol, Payload::Sym(s)) => s == var,
-        (Op::Add, _) | (Op::Mul, _) => {
-            store.get(id).children.iter().any(|&c| contains_var(store, c, var))
-        }
-        (Op::Pow, _) => {
-            let n = store.get(id);
-            contains_var(store, n.children[0], var) || contains_var(store, n.children[1], var)
-        }
-        (Op::Function, _) => store.get(id).children.iter().any(|&c| contains_var(store, c, var)),
-        _ => false,
-    }
-}
-
 fn is_one(store: &Store, id: ExprId) -> bool {
     matches!((&store.get(id).op, &store.get(id).payload), (Op::Integer, Payload::Int(1)))
         || matches!((&store.get(id).op, &store.get(id).payload), (Op::Rational, Payload::Rat(1, 1)))
@@ -370,54 +355,15 @@ fn extract_power(store: &Store, term: ExprId, var: &str) -> Option<usize> {
 }
 
 fn extract_arithmetic(store: &mut Store, term: ExprId, var: &str) -> Option<(ExprId, ExprId)> {
-    // Recognize a + d*k with a independent of var and linear term exactly d*k
-    if store.get(term).op != Op::Add {
+    // Recognize a + d*k with a and d independent of var
+    let poly = polys::as_poly_in(store, term, &[var])?;
+    if poly.keys().any(|k| k[0] > 1) {
         return None;
     }
-    let mut a_terms: Vec<ExprId> = Vec::new();
-    let mut linear_term: Option<ExprId> = None;
-    for &c in &store.get(term).children {
-        if contains_var(store, c, var) {
-            // Expect exactly one linear term in k
-            if linear_term.is_some() {
-                return None;
-            }
-            linear_term = Some(c);
-        } else {
-            a_terms.push(c);
-        }
-    }
-    let a = if a_terms.is_empty() { store.int(0) } else { store.add(a_terms) };
-    let lt = linear_term?;
-    // Linear term should be either k or d*k
-    let d = match store.get(lt).op {
-        Op::Symbol => match &store.get(lt).payload {
-            Payload::Sym(s) if s == var => store.int(1),
-            _ => return None,
-        },
-        Op::Mul => {
-            // find factor k and multiply the rest as d
-            let mut rest: Vec<ExprId> = Vec::new();
-            let mut saw_k = false;
-            for &f in &store.get(lt).children {
-                if !saw_k
-                    && matches!((&store.get(f).op, &store.get(f).payload), (Op::Symbol, Payload::Sym(ref s)) if s == var)
-                {
-                    saw_k = true;
-                } else {
-                    rest.push(f);
-                }
-            }
-            if !saw_k {
-                return None;
-            }
-            if rest.is_empty() {
-                store.int(1)
-            } else {
-                store.mul(rest)
-            }
-        }
-        _ => return None,
+    let d = *poly.get(&vec![1])?;
+    let a = match poly.get(&vec![0]) {
+        Some(&a) => a,
+        None => store.int(0),
     };
     Some((a, d))
 }
@@ -434,7 +380,7 @@ fn extract_geometric(store: &mut Store, term: ExprId, var: &str) -> Option<(Expr
                     let base = n.children[0];
                     let exp = n.children[1];
                     if matches!((&store.get(exp).op, &store.get(exp).payload), (Op::Symbol, Payload::Sym(ref s)) if s == var)
-                        && !contains_var(store, base, var)
+                        && !store.has_free_symbol(base, var)
                     {
                         if r_opt.is_some() {
                             return None;
@@ -444,7 +390,7 @@ fn extract_geometric(store: &mut Store, term: ExprId, var: &str) -> Option<(Expr
                     }
                 }
                 // otherwise part of coefficient; must not contain var
-                if contains_var(store, f, var) {
+                if store.has_free_symbol(f, var) {
                     return None;
                 }
                 coeffs.push(f);
@@ -458,7 +404,7 @@ fn extract_geometric(store: &mut Store, term: ExprId, var: &str) -> Option<(Expr
             let base = n.children[0];
             let exp = n.children[1];
             if matches!((&store.get(exp).op, &store.get(exp).payload), (Op::Symbol, Payload::Sym(ref s)) if s == var)
-                && !contains_var(store, base, var)
+                && !store.has_free_symbol(base, var)
             {
                 Some((store.int(1), base))
             } else {
@@ -66,23 +66,17 @@ where
     // Handle common patterns
     match (spec_a.as_str(), spec_b.as_str(), output.as_str()) {
         // Matrix multiplication: "ij,jk->ik"
-        ("ij", "jk", "ik") => {
-            if a.rank() == 2 && b.rank() == 2 {
-                return Ok(a.matmul(b));
-            }
+        ("ij", "jk", "ik") if a.rank() == 2 && b.rank() == 2 => {
+            return Ok(a.matmul(b));
         }
         // Dot product: "i,i->"
-        ("i", "i", "") => {
-            if a.rank() == 1 && b.rank() == 1 {
-                let result = a.dot(b);
-                return Ok(Tensor::from_vec(vec![1], vec![result]));
-            }
+        ("i", "i", "") if a.rank() == 1 && b.rank() == 1 => {
+            let result = a.dot(b);
+            return Ok(Tensor::from_vec(vec![1], vec![result]));
         }
         // Outer product: "i,j->ij"
-        ("i", "j", "ij") => {
-            if a.rank() == 1 && b.rank() == 1 {
-                return Ok(a.outer(b));
-            }
+        ("i", "j", "ij") if a.rank() == 1 && b.rank() == 1 => {
+            return Ok(a.outer(b));
         }
         // Element-wise multiplication: "ij,ij->ij"
         _ if spec_a == spec_b && spec_a == &output => {
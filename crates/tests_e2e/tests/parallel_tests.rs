@@ -0,0 +1,72 @@
+//! Parallel simplify/diff/evalf over a frozen store snapshot.
+//! Each thread works in its own overlay; results are merged back and must
+//! match a purely sequential run node-for-node.
+
+use calculus::diff;
+use evalf::{eval, EvalContext};
+use expr_core::{ExprId, Store};
+use simplify::simplify;
+use std::thread;
+
+/// (x + k)^3 * sin(k*x) for k = 1..=n
+fn build_inputs(st: &mut Store, n: i64) -> Vec<ExprId> {
+    let x = st.sym("x");
+    (1..=n)
+        .map(|k| {
+            let kk = st.int(k);
+            let sum = st.add(vec![x, kk]);
+            let three = st.int(3);
+            let cube = st.pow(sum, three);
+            let kx = st.mul(vec![kk, x]);
+            let s = st.func("sin", vec![kx]);
+            st.mul(vec![cube, s])
+        })
+        .collect()
+}
+
+#[test]
+fn parallel_diff_simplify_matches_sequential() {
+    let mut st = Store::new();
+    let inputs = build_inputs(&mut st, 8);
+    let snap = st.freeze();
+
+    let results: Vec<(Store, ExprId, f64)> = thread::scope(|s| {
+        let handles: Vec<_> = inputs
+            .iter()
+            .map(|&e| {
+                let snap = snap.clone();
+                s.spawn(move || {
+                    let mut local = snap.overlay();
+                    let d = diff(&mut local, e, "x");
+                    let d = simplify(&mut local, d);
+                    let mut ctx = EvalContext::new();
+                    ctx.bind("x", 0.5);
+                    let v = eval(&local, d, &ctx).unwrap();
+                    (local, d, v)
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let merged: Vec<ExprId> =
+        results.iter().map(|(local, d, _)| st.merge(local, &[*d])[0]).collect();
+
+    // Sequential reference in an independent store
+    let mut seq = Store::new();
+    let seq_inputs = build_inputs(&mut seq, 8);
+    let mut ctx = EvalContext::new();
+    ctx.bind("x", 0.5);
+    for (i, &e) in seq_inputs.iter().enumerate() {
+        let d = diff(&mut seq, e, "x");
+        let d = simplify(&mut seq, d);
+        assert_eq!(st.to_string(merged[i]), seq.to_string(d));
+        assert_eq!(st.get(merged[i]).digest, seq.get(d).digest);
+        assert_eq!(results[i].2, eval(&seq, d, &ctx).unwrap());
+    }
+
+    // Merged results are canonical: recomputing in the main store finds them
+    let again = diff(&mut st, inputs[0], "x");
+    let again = simplify(&mut st, again);
+    assert_eq!(again, merged[0]);
+}
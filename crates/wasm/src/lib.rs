@@ -5,12 +5,11 @@
 
 #![deny(warnings)]
 
-use std::collections::HashSet;
 use wasm_bindgen::prelude::*;
 
 use calculus::{diff, integrate};
 use evalf::{eval, EvalContext};
-use expr_core::{ExprId, Op, Payload, Store};
+use expr_core::{ExprId, Store};
 use io::{to_latex, to_sexpr};
 use pattern::subst_symbol;
 use simplify::simplify;
@@ -25,17 +24,7 @@ const MAX_NODES: usize = 512;
 
 /// Count the number of unique nodes reachable from the given expression id.
 fn expr_size_of(store: &Store, root: ExprId) -> usize {
-    let mut visited: HashSet<ExprId> = HashSet::new();
-    let mut stack: Vec<ExprId> = vec![root];
-    while let Some(id) = stack.pop() {
-        if visited.insert(id) {
-            let node = store.get(id);
-            for &child in &node.children {
-                stack.push(child);
-            }
-        }
-    }
-    visited.len()
+    store.preorder(root).count()
 }
 
 /// A symbolic expression for WebAssembly
@@ -77,8 +66,8 @@ impl Expr {
     /// Add two expressions
     pub fn add(&self, other: &Expr) -> Result<Expr, JsValue> {
         let mut store = Store::new();
-        let id1 = Self::rebuild_expr(&self.store, self.id, &mut store);
-        let id2 = Self::rebuild_expr(&other.store, other.id, &mut store);
+        let id1 = store.import(&self.store, self.id);
+        let id2 = store.import(&other.store, other.id);
         let id = store.add(vec![id1, id2]);
         Self::check_size(&store, id)?;
         Ok(Expr { store, id })
@@ -87,8 +76,8 @@ impl Expr {
     /// Subtract two expressions
     pub fn sub(&self, other: &Expr) -> Result<Expr, JsValue> {
         let mut store = Store::new();
-        let id1 = Self::rebuild_expr(&self.store, self.id, &mut store);
-        let id2 = Self::rebuild_expr(&other.store, other.id, &mut store);
+        let id1 = store.import(&self.store, self.id);
+        let id2 = store.import(&other.store, other.id);
         let neg_one = store.int(-1);
         let neg_id2 = store.mul(vec![neg_one, id2]);
         let id = store.add(vec![id1, neg_id2]);
@@ -99,8 +88,8 @@ impl Expr {
     /// Multiply two expressions
     pub fn mul(&self, other: &Expr) -> Result<Expr, JsValue> {
         let mut store = Store::new();
-        let id1 = Self::rebuild_expr(&self.store, self.id, &mut store);
-        let id2 = Self::rebuild_expr(&other.store, other.id, &mut store);
+        let id1 = store.import(&self.store, self.id);
+        let id2 = store.import(&other.store, other.id);
         let id = store.mul(vec![id1, id2]);
         Self::check_size(&store, id)?;
         Ok(Expr { store, id })
@@ -109,8 +98,8 @@ impl Expr {
     /// Divide two expressions
     pub fn div(&self, other: &Expr) -> Result<Expr, JsValue> {
         let mut store = Store::new();
-        let id1 = Self::rebuild_expr(&self.store, self.id, &mut store);
-        let id2 = Self::rebuild_expr(&other.store, other.id, &mut store);
+        let id1 = store.import(&self.store, self.id);
+        let id2 = store.import(&other.store, other.id);
         let neg_one = store.int(-1);
         let inv_id2 = store.pow(id2, neg_one);
         let id = store.mul(vec![id1, inv_id2]);
@@ -121,8 +110,8 @@ impl Expr {
     /// Raise expression to a power
     pub fn pow(&self, other: &Expr) -> Result<Expr, JsValue> {
         let mut store = Store::new();
-        let id1 = Self::rebuild_expr(&self.store, self.id, &mut store);
-        let id2 = Self::rebuild_expr(&other.store, other.id, &mut store);
+        let id1 = store.import(&self.store, self.id);
+        let id2 = store.import(&other.store, other.id);
         let id = store.pow(id1, id2);
         Self::check_size(&store, id)?;
         Ok(Expr { store, id })
@@ -131,7 +120,7 @@ impl Expr {
     /// Negate expression
     pub fn neg(&self) -> Result<Expr, JsValue> {
         let mut store = Store::new();
-        let id1 = Self::rebuild_expr(&self.store, self.id, &mut store);
+        let id1 = store.import(&self.store, self.id);
         let neg_one = store.int(-1);
         let id = store.mul(vec![neg_one, id1]);
         Self::check_size(&store, id)?;
@@ -141,7 +130,7 @@ impl Expr {
     /// Simplify the expression
     pub fn simplify(&self) -> Result<Expr, JsValue> {
         let mut store = Store::new();
-        let id = Self::rebuild_expr(&self.store, self.id, &mut store);
+        let id = store.import(&self.store, self.id);
         let simplified = simplify(&mut store, id);
         Self::check_size(&store, simplified)?;
         Ok(Expr { store, id: simplified })
@@ -150,7 +139,7 @@ impl Expr {
     /// Differentiate with respect to a variable
     pub fn diff(&self, var: &str) -> Result<Expr, JsValue> {
         let mut store = Store::new();
-        let id = Self::rebuild_expr(&self.store, self.id, &mut store);
+        let id = store.import(&self.store, self.id);
         let deriv = diff(&mut store, id, var);
         let simplified = simplify(&mut store, deriv);
         Self::check_size(&store, simplified)?;
@@ -160,7 +149,7 @@ impl Expr {
     /// Integrate with respect to a variable
     pub fn integrate(&self, var: &str) -> Result<Expr, JsValue> {
         let mut store = Store::new();
-        let id = Self::rebuild_expr(&self.store, self.id, &mut store);
+        let id = store.import(&self.store, self.id);
         match integrate(&mut store, id, var) {
             Some(integral) => {
                 let simplified = simplify(&mut store, integral);
@@ -174,8 +163,8 @@ impl Expr {
     /// Substitute a symbol with another expression
     pub fn subs(&self, var: &str, val: &Expr) -> Result<Expr, JsValue> {
         let mut store = Store::new();
-        let id = Self::rebuild_expr(&self.store, self.id, &mut store);
-        let val_id = Self::rebuild_expr(&val.store, val.id, &mut store);
+        let id = store.import(&self.store, self.id);
+        let val_id = store.import(&val.store, val.id);
         let subst = subst_symbol(&mut store, id, var, val_id);
         let simplified = simplify(&mut store, subst);
         Self::check_size(&store, simplified)?;
@@ -185,7 +174,7 @@ impl Expr {
     /// Solve equation for a variable (returns JSON array of solutions)
     pub fn solve(&self, var: &str) -> Result<JsValue, JsValue> {
         let mut store = Store::new();
-        let id = Self::rebuild_expr(&self.store, self.id, &mut store);
+        let id = store.import(&self.store, self.id);
         Self::check_size(&store, id)?;
         match solve_univariate(&mut store, id, var) {
             Some(roots) => {
@@ -229,60 +218,6 @@ impl Expr {
         to_sexpr(&self.store, self.id)
     }
 
-    /// Helper: rebuild expression in new store
-    fn rebuild_expr(src: &Store, id: ExprId, target: &mut Store) -> ExprId {
-        let node = src.get(id);
-        match &node.op {
-            Op::Integer => match &node.payload {
-                Payload::Int(i) => target.int(*i),
-                _ => target.int(0),
-            },
-            Op::Rational => match &node.payload {
-                Payload::Rat(n, d) => target.rat(*n, *d),
-                _ => target.int(0),
-            },
-            Op::Symbol => match &node.payload {
-                Payload::Sym(s) => target.sym(s),
-                _ => target.sym("x"),
-            },
-            Op::Add => {
-                let children: Vec<ExprId> =
-                    node.children.iter().map(|&c| Self::rebuild_expr(src, c, target)).collect();
-                target.add(children)
-            }
-            Op::Mul => {
-                let children: Vec<ExprId> =
-                    node.children.iter().map(|&c| Self::rebuild_expr(src, c, target)).collect();
-                target.mul(children)
-            }
-            Op::Pow => {
-                let base = Self::rebuild_expr(src, node.children[0], target);
-                let exp = Self::rebuild_expr(src, node.children[1], target);
-                target.pow(base, exp)
-            }
-            Op::Function => {
-                let fname = match &node.payload {
-                    Payload::Func(s) => s.clone(),
-                    _ => "f".to_string(),
-                };
-                let children: Vec<ExprId> =
-                    node.children.iter().map(|&c| Self::rebuild_expr(src, c, target)).collect();
-                target.func(fname, children)
-            }
-            Op::Piecewise => {
-                let children: Vec<ExprId> =
-                    node.children.iter().map(|&c| Self::rebuild_expr(src, c, target)).collect();
-                let mut pairs = Vec::new();
-                for chunk in children.chunks(2) {
-                    if chunk.len() == 2 {
-                        pairs.push((chunk[0], chunk[1]));
-                    }
-                }
-                target.piecewise(pairs)
-            }
-        }
-    }
-
     /// Resource guard: check expression tree size
     /// Note: Simplified implementation - checks if expressions are reasonable
     fn check_size(store: &Store, id: ExprId) -> Result<(), JsValue> {
@@ -299,7 +234,7 @@ impl Expr {
 #[wasm_bindgen(js_name = sin)]
 pub fn sin(x: &Expr) -> Result<Expr, JsValue> {
     let mut store = Store::new();
-    let arg = Expr::rebuild_expr(&x.store, x.id, &mut store);
+    let arg = store.import(&x.store, x.id);
     let id = store.func("sin".to_string(), vec![arg]);
     Expr::check_size(&store, id)?;
     Ok(Expr { store, id })
@@ -308,7 +243,7 @@ pub fn sin(x: &Expr) -> Result<Expr, JsValue> {
 #[wasm_bindgen(js_name = cos)]
 pub fn cos(x: &Expr) -> Result<Expr, JsValue> {
     let mut store = Store::new();
-    let arg = Expr::rebuild_expr(&x.store, x.id, &mut store);
+    let arg = store.import(&x.store, x.id);
     let id = store.func("cos".to_string(), vec![arg]);
     Expr::check_size(&store, id)?;
     Ok(Expr { store, id })
@@ -317,7 +252,7 @@ pub fn cos(x: &Expr) -> Result<Expr, JsValue> {
 #[wasm_bindgen(js_name = tan)]
 pub fn tan(x: &Expr) -> Result<Expr, JsValue> {
     let mut store = Store::new();
-    let arg = Expr::rebuild_expr(&x.store, x.id, &mut store);
+    let arg = store.import(&x.store, x.id);
     let id = store.func("tan".to_string(), vec![arg]);
     Expr::check_size(&store, id)?;
     Ok(Expr { store, id })
@@ -326,7 +261,7 @@ pub fn tan(x: &Expr) -> Result<Expr, JsValue> {
 #[wasm_bindgen(js_name = exp)]
 pub fn exp(x: &Expr) -> Result<Expr, JsValue> {
     let mut store = Store::new();
-    let arg = Expr::rebuild_expr(&x.store, x.id, &mut store);
+    let arg = store.import(&x.store, x.id);
     let id = store.func("exp".to_string(), vec![arg]);
     Expr::check_size(&store, id)?;
     Ok(Expr { store, id })
@@ -335,7 +270,7 @@ pub fn exp(x: &Expr) -> Result<Expr, JsValue> {
 #[wasm_bindgen(js_name = ln)]
 pub fn ln(x: &Expr) -> Result<Expr, JsValue> {
     let mut store = Store::new();
-    let arg = Expr::rebuild_expr(&x.store, x.id, &mut store);
+    let arg = store.import(&x.store, x.id);
     let id = store.func("ln".to_string(), vec![arg]);
     Expr::check_size(&store, id)?;
     Ok(Expr { store, id })
@@ -344,7 +279,7 @@ pub fn ln(x: &Expr) -> Result<Expr, JsValue> {
 #[wasm_bindgen(js_name = sqrt)]
 pub fn sqrt(x: &Expr) -> Result<Expr, JsValue> {
     let mut store = Store::new();
-    let arg = Expr::rebuild_expr(&x.store, x.id, &mut store);
+    let arg = store.import(&x.store, x.id);
     let half = store.rat(1, 2);
     let id = store.pow(arg, half);
     Expr::check_size(&store, id)?;